@@ -0,0 +1,171 @@
+//! 地址簿的型号间转换
+//!
+//! 更换 PLC 品牌时，已有的标签表需要改写为另一种地址写法。[`AddressBook`] 读取
+//! 每行 `名称,地址[,注释]` 的 CSV，并通过基恩士与三菱之间的地址转换层改写全部地址：
+//!
+//! ```text
+//! name,address,comment
+//! # 以 # 开头的行与空行被忽略
+//! spindle_speed,DM100,主轴转速
+//! door_open,R1005
+//! ```
+//!
+//! 首行为 `name,address` 开头的表头时跳过。注释为行内第二个逗号之后的全部内容，
+//! 可以包含逗号；不支持带引号的字段。
+
+use std::fmt::Write as _;
+
+use crate::{
+    frame::{
+        convert_keyence_to_mitsubishi_address, convert_mitsubishi_to_keyence_address, KVError,
+        Model, ProtocolError,
+    },
+    Error,
+};
+
+/// 将 `from` 写法的地址改写为 `to` 写法，型号相同时原样返回
+pub fn convert_address(address: &str, from: Model, to: Model) -> Result<String, KVError> {
+    match (from, to) {
+        (Model::Keyence, Model::Mitsubishi) => convert_keyence_to_mitsubishi_address(address),
+        (Model::Mitsubishi, Model::Keyence) => convert_mitsubishi_to_keyence_address(address),
+        _ => Ok(address.to_string()),
+    }
+}
+
+/// 地址簿中的一个标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub address: String,
+    /// 注释，可为空
+    pub comment: String,
+}
+
+/// 以某一型号写法记录的标签表，见[模块文档](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressBook {
+    /// 地址的写法
+    pub model: Model,
+    pub tags: Vec<Tag>,
+}
+
+impl AddressBook {
+    pub fn new(model: Model) -> Self {
+        Self {
+            model,
+            tags: Vec::new(),
+        }
+    }
+
+    /// 解析以 `model` 写法记录地址的 CSV
+    pub fn parse_csv(text: &str, model: Model) -> Result<Self, Error> {
+        let mut book = Self::new(model);
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.splitn(3, ',').map(str::trim);
+            let (name, address) = match (fields.next(), fields.next()) {
+                (Some(name), Some(address)) if !name.is_empty() && !address.is_empty() => {
+                    (name, address)
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "line {}: expected name,address[,comment]",
+                        index + 1
+                    )))
+                }
+            };
+            if book.tags.is_empty() && is_header(name, address) {
+                continue;
+            }
+            book.tags.push(Tag {
+                name: name.to_string(),
+                address: address.to_string(),
+                comment: fields.next().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(book)
+    }
+
+    /// 改写为 `to` 写法的地址簿，任一地址无法转换时返回错误并指明标签
+    pub fn convert(&self, to: Model) -> Result<Self, Error> {
+        let tags = self
+            .tags
+            .iter()
+            .map(|tag| {
+                let address = convert_address(&tag.address, self.model, to)
+                    .map_err(|err| invalid(format!("{} ({}): {err}", tag.name, tag.address)))?;
+                Ok(Tag {
+                    address,
+                    ..tag.clone()
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { model: to, tags })
+    }
+
+    /// 输出为带表头的 CSV，可由 [`Self::parse_csv`] 读回
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,address,comment\n");
+        for tag in &self.tags {
+            writeln!(csv, "{},{},{}", tag.name, tag.address, tag.comment)
+                .expect("writing to a String cannot fail");
+        }
+        csv
+    }
+}
+
+fn is_header(name: &str, address: &str) -> bool {
+    name.eq_ignore_ascii_case("name") && address.eq_ignore_ascii_case("address")
+}
+
+fn invalid(reason: String) -> Error {
+    Error::Protocol(ProtocolError::InvalidAddress(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_keyence_book_to_mitsubishi_and_back() {
+        let csv = "name,address,comment\n\
+                   # 主轴\n\
+                   spindle_speed, DM100 ,转速, rpm\n\
+                   \n\
+                   door_open,R1005\n\
+                   pieces,C5,计数\n";
+        let keyence = AddressBook::parse_csv(csv, Model::Keyence).unwrap();
+        assert_eq!(keyence.tags.len(), 3);
+        assert_eq!(keyence.tags[0].comment, "转速, rpm");
+
+        let mitsubishi = keyence.convert(Model::Mitsubishi).unwrap();
+        let addresses: Vec<&str> = mitsubishi
+            .tags
+            .iter()
+            .map(|tag| tag.address.as_str())
+            .collect();
+        assert_eq!(addresses, ["D100", "XA5", "CN5"]);
+        assert_eq!(
+            mitsubishi.to_csv(),
+            "name,address,comment\n\
+             spindle_speed,D100,转速, rpm\n\
+             door_open,XA5,\n\
+             pieces,CN5,计数\n"
+        );
+
+        let parsed = AddressBook::parse_csv(&mitsubishi.to_csv(), Model::Mitsubishi).unwrap();
+        assert_eq!(parsed, mitsubishi);
+        assert_eq!(parsed.convert(Model::Keyence).unwrap(), keyence);
+    }
+
+    #[test]
+    fn reports_the_failing_tag() {
+        let book = AddressBook::parse_csv("link,SW10", Model::Mitsubishi).unwrap();
+        let err = book.convert(Model::Keyence).unwrap_err();
+        assert!(err.to_string().contains("link (SW10)"), "{err}");
+        assert!(AddressBook::parse_csv("missing_address", Model::Keyence).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! MC 协议负载生成器
+//!
+//! 以 [`tokio_mc::client::loadgen`] 向 PLC 或模拟器持续发送读写请求，结束后输出吞吐量与耗时百分位：
+//!
+//! ```text
+//! mc-loadgen 127.0.0.1:5000 --duration 60 --rate 200 --read D0:10:4 --write D100:1
+//! ```
+
+use std::{env, process, time::Duration};
+
+use tokio_mc::{
+    client::{
+        loadgen::{self, LoadProfile, Operation},
+        tcp::connect,
+    },
+    frame::Model,
+};
+
+const USAGE: &str = "\
+Usage: mc-loadgen <HOST:PORT> [OPTIONS]
+
+Options:
+  --duration <SECS>               Test duration in seconds [default: 10]
+  --rate <PER_SEC>                Target requests per second, 0 for unlimited [default: 0]
+  --read <ADDR:COUNT[:WEIGHT]>    Add a word read to the mix, repeatable [default: D0:10]
+  --write <ADDR:COUNT[:WEIGHT]>   Add a word write to the mix, repeatable
+  --keyence                       Use Keyence device addresses
+  -h, --help                      Print this help";
+
+#[derive(Debug)]
+struct Options {
+    target: String,
+    profile: LoadProfile,
+    model: Model,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut target = None;
+    let mut duration = Duration::from_secs(10);
+    let mut rate = 0.0;
+    let mut operations = Vec::new();
+    let mut model = Model::Mitsubishi;
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--duration" => {
+                let value = value()?;
+                let secs: f64 = value
+                    .parse()
+                    .map_err(|_| format!("invalid duration {value:?}"))?;
+                duration = Duration::try_from_secs_f64(secs)
+                    .map_err(|_| format!("invalid duration {value:?}"))?;
+            }
+            "--rate" => {
+                let value = value()?;
+                rate = value
+                    .parse()
+                    .map_err(|_| format!("invalid rate {value:?}"))?;
+            }
+            "--read" | "--write" => {
+                let value = value()?;
+                operations.push(parse_operation(&arg, &value)?);
+            }
+            "--keyence" => model = Model::Keyence,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            other if other.starts_with('-') => {
+                return Err(format!("unexpected argument {other:?}"));
+            }
+            other if target.is_none() => target = Some(other.to_string()),
+            other => return Err(format!("unexpected argument {other:?}")),
+        }
+    }
+
+    if operations.is_empty() {
+        operations.push((
+            Operation::Read {
+                address: "D0".to_string(),
+                count: 10,
+            },
+            1,
+        ));
+    }
+    let profile = operations
+        .into_iter()
+        .fold(LoadProfile::new(duration).rate(rate), |profile, (op, w)| {
+            profile.operation(op, w)
+        });
+    Ok(Options {
+        target: target.ok_or("missing <HOST:PORT>")?,
+        profile,
+        model,
+    })
+}
+
+/// 解析 `ADDR:COUNT[:WEIGHT]`
+fn parse_operation(kind: &str, value: &str) -> Result<(Operation, u32), String> {
+    let invalid = || format!("invalid {kind} {value:?}, expected ADDR:COUNT[:WEIGHT]");
+    let mut parts = value.split(':');
+    let address = parts.next().filter(|a| !a.is_empty()).ok_or_else(invalid)?;
+    let count = parts
+        .next()
+        .and_then(|c| c.parse().ok())
+        .ok_or_else(invalid)?;
+    let weight = match parts.next() {
+        Some(w) => w.parse().map_err(|_| invalid())?,
+        None => 1,
+    };
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    let address = address.to_string();
+    let operation = if kind == "--read" {
+        Operation::Read { address, count }
+    } else {
+        Operation::Write { address, count }
+    };
+    Ok((operation, weight))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(2);
+    });
+    let addr = match tokio::net::lookup_host(&options.target)
+        .await
+        .map(|mut a| a.next())
+    {
+        Ok(Some(addr)) => addr,
+        _ => {
+            eprintln!("error: cannot resolve {}", options.target);
+            process::exit(2);
+        }
+    };
+
+    let mut context = match connect(addr).await {
+        Ok(context) => context,
+        Err(err) => {
+            eprintln!("error: cannot connect to {addr}: {err}");
+            process::exit(1);
+        }
+    };
+    context.set_plc_model(options.model);
+    let report = loadgen::run(&mut context, &options.profile).await;
+    let _ = context.disconnect().await;
+    print!("{report}");
+}
@@ -0,0 +1,163 @@
+//! 本机 MC 协议模拟器
+//!
+//! 以默认配置启动内置的 [`Simulator`]，供 QA 直接用作 PLC 替身：
+//!
+//! ```text
+//! mc-sim [--port 5000] [--bind 127.0.0.1] [--profile generic|q|iq-r|fx] [--seed dump.txt]
+//!        [--web 127.0.0.1:8080]
+//! ```
+//!
+//! `--seed` 读取初始数据：`.csv`、`.json` 为种子文件（见 `server::seed`），
+//! 其它文件为 `Context::dump_area` 导出的文本；`--web` 在指定地址提供查看、
+//! 修改软元件的网页。
+
+use std::{
+    env, fs, fs::File, io::BufReader, net::IpAddr, net::SocketAddr, path::Path, process, sync::Arc,
+};
+
+use tokio::net::TcpListener;
+use tokio_mc::{
+    frame::PlcProfile,
+    server::{accept_tcp_connection, serve_web, Seed, Server, Simulator},
+};
+
+const USAGE: &str = "\
+Usage: mc-sim [OPTIONS]
+
+Options:
+  --port <PORT>        TCP port to listen on [default: 5000]
+  --bind <ADDR>        Address to bind [default: 127.0.0.1]
+  --profile <NAME>     Device set: generic, q, iq-r or fx [default: generic]
+  --seed <FILE>        Initial device data: a .csv/.json seed file or a Context::dump_area export
+  --web <ADDR:PORT>    Serve a web page for viewing and editing device values
+  -h, --help           Print this help";
+
+#[derive(Debug)]
+struct Options {
+    addr: SocketAddr,
+    profile: PlcProfile,
+    seed: Option<String>,
+    web: Option<SocketAddr>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut ip: IpAddr = [127, 0, 0, 1].into();
+    let mut port = 5000;
+    let mut profile = PlcProfile::GENERIC;
+    let mut seed = None;
+    let mut web = None;
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--port" => {
+                let value = value()?;
+                port = value
+                    .parse()
+                    .map_err(|_| format!("invalid port {value:?}"))?;
+            }
+            "--bind" => {
+                let value = value()?;
+                ip = value
+                    .parse()
+                    .map_err(|_| format!("invalid address {value:?}"))?;
+            }
+            "--profile" => {
+                profile = match value()?.to_ascii_lowercase().as_str() {
+                    "generic" => PlcProfile::GENERIC,
+                    "q" => PlcProfile::Q_SERIES,
+                    "iq-r" | "iqr" => PlcProfile::IQ_R,
+                    "fx" => PlcProfile::FX_SERIES,
+                    other => return Err(format!("unknown profile {other:?}")),
+                };
+            }
+            "--seed" => seed = Some(value()?),
+            "--web" => {
+                let value = value()?;
+                web = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid web address {value:?}"))?,
+                );
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            other => return Err(format!("unexpected argument {other:?}")),
+        }
+    }
+
+    Ok(Options {
+        addr: SocketAddr::new(ip, port),
+        profile,
+        seed,
+        web,
+    })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(2);
+    });
+    if let Err(err) = run(options).await {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+async fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let simulator = Arc::new(Simulator::from_profile(&options.profile));
+    if let Some(seed) = &options.seed {
+        let extension = Path::new(seed).extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => {
+                let entries = simulator.load_seed(&Seed::parse_csv(&fs::read_to_string(seed)?)?)?;
+                eprintln!("Loaded {entries} entries from {seed}");
+            }
+            Some("json") => {
+                let entries =
+                    simulator.load_seed(&Seed::parse_json(&fs::read_to_string(seed)?)?)?;
+                eprintln!("Loaded {entries} entries from {seed}");
+            }
+            _ => {
+                let words = simulator.load_dump(BufReader::new(File::open(seed)?))?;
+                eprintln!("Loaded {words} words from {seed}");
+            }
+        }
+    }
+
+    if let Some(addr) = options.web {
+        let listener = TcpListener::bind(addr).await?;
+        eprintln!("Web page on http://{}/", listener.local_addr()?);
+        let simulator = Arc::clone(&simulator);
+        tokio::spawn(async move {
+            if let Err(err) = serve_web(listener, simulator).await {
+                eprintln!("web error: {err}");
+            }
+        });
+    }
+
+    let listener = TcpListener::bind(options.addr).await?;
+    eprintln!(
+        "mc-sim ({}) listening on {}",
+        options.profile.name,
+        listener.local_addr()?
+    );
+
+    let server = Server::new(listener);
+    let on_connected = |stream, peer| {
+        let simulator = Arc::clone(&simulator);
+        async move { accept_tcp_connection(stream, peer, move |_| Ok(Some(Arc::clone(&simulator)))) }
+    };
+    server
+        .serve(&on_connected, |err| eprintln!("connection error: {err}"))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,516 @@
+//! 软元件区域的导出与导入
+//!
+//! 导出格式为文本：每个区域以 `@<起始地址> <字数>` 开头，
+//! 随后每行最多 16 个以空格分隔的 4 位十六进制字。多个区域可以依次拼接。
+//!
+//! ```text
+//! @D0 20
+//! 0001 0002 0003 0004 0005 0006 0007 0008 0009 000A 000B 000C 000D 000E 000F 0010
+//! 0011 0012 0013 0014
+//! ```
+
+use std::{fmt::Write as _, io, ops::Range};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt as _, AsyncWrite, AsyncWriteExt as _};
+
+use crate::{
+    convert::{bytes_to_words, words_to_bytes},
+    frame::{
+        convert_to_base, device_kind, find_prefix_and_base_by_code, split_address, DeviceKind,
+        NumberBase, ProtocolError, Quantity, PLC_INSTRUCTIONS,
+    },
+    Error,
+};
+
+use super::{Client, Context, Request, Response};
+
+/// 单次读写的最大字数
+pub(crate) const BLOCK_WORDS: usize = 960;
+/// 合并为同一块读取时允许跳过的最大字数
+pub(crate) const MAX_GAP_WORDS: u32 = 16;
+const WORDS_PER_LINE: usize = 16;
+
+/// 软元件前缀及其编号规则
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Device {
+    prefix: &'static str,
+    number_base: NumberBase,
+    /// 每个字对应的软元件点数
+    step: u32,
+}
+
+impl Device {
+    fn new(prefix: &str) -> Result<Self, Error> {
+        let &(prefix, _, number_base) = PLC_INSTRUCTIONS
+            .iter()
+            .find(|(p, _, _)| *p == prefix)
+            .ok_or_else(|| {
+                ProtocolError::InvalidAddress(format!("unknown device prefix {prefix:?}"))
+            })?;
+        Ok(Self {
+            prefix,
+            number_base,
+            step: match device_kind(prefix) {
+                Some(DeviceKind::Bit) => 16,
+                _ => 1,
+            },
+        })
+    }
+
+    /// 按 MC 协议的软元件代码查找
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        let (prefix, _) = find_prefix_and_base_by_code(code)?;
+        Self::new(prefix).ok()
+    }
+
+    pub(crate) fn address(&self, number: u32) -> String {
+        match self.number_base {
+            NumberBase::Decimal => format!("{}{number}", self.prefix),
+            NumberBase::Hexadecimal => format!("{}{number:X}", self.prefix),
+        }
+    }
+
+    pub(crate) fn prefix(&self) -> &'static str {
+        self.prefix
+    }
+
+    /// 按字访问时每个字对应的软元件点数
+    pub(crate) fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// 是否为位软元件
+    pub(crate) fn is_bit(&self) -> bool {
+        self.step == 16
+    }
+
+    pub(crate) fn parse(address: &str) -> Result<(Self, u32), Error> {
+        let invalid = || Error::Protocol(ProtocolError::InvalidAddress(address.to_string()));
+        let (prefix, number) = split_address(address).ok_or_else(invalid)?;
+        let device = Self::new(prefix)?;
+        let number = convert_to_base(number, device.number_base).ok_or_else(invalid)?;
+        Ok((device, number))
+    }
+}
+
+/// 导出计划：区域头与各读取块
+pub(crate) struct DumpPlan {
+    device: Device,
+    start: u32,
+    words: usize,
+}
+
+impl DumpPlan {
+    /// `range` 为软元件编号范围；位软元件的范围长度须为 16 的倍数
+    pub(crate) fn new(prefix: &str, range: Range<u32>) -> Result<Self, Error> {
+        let device = Device::new(prefix)?;
+        let len = range.end.saturating_sub(range.start);
+        if len == 0 || !len.is_multiple_of(device.step) {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        Ok(Self {
+            device,
+            start: range.start,
+            words: (len / device.step) as usize,
+        })
+    }
+
+    /// 从起始地址与字数构造，与区域头的内容对应
+    pub(crate) fn at(address: &str, words: usize) -> Result<Self, Error> {
+        let (device, start) = Device::parse(address)?;
+        if words == 0 {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        Ok(Self {
+            device,
+            start,
+            words,
+        })
+    }
+
+    pub(crate) fn words(&self) -> usize {
+        self.words
+    }
+
+    /// 起始地址
+    pub(crate) fn address(&self) -> String {
+        self.device.address(self.start)
+    }
+
+    pub(crate) fn header(&self) -> String {
+        format!("@{} {}\n", self.address(), self.words)
+    }
+
+    /// 各读取块的起始地址与字数
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = (String, Quantity)> + '_ {
+        (0..self.words).step_by(BLOCK_WORDS).map(|offset| {
+            let count = (self.words - offset).min(BLOCK_WORDS);
+            let number = self.start + offset as u32 * self.device.step;
+            (self.device.address(number), count as Quantity)
+        })
+    }
+}
+
+/// 一次块读取覆盖的字范围
+#[derive(Debug)]
+pub(crate) struct ReadBlock {
+    pub(crate) device: Device,
+    pub(crate) start: u32,
+    pub(crate) words: u32,
+    /// 范围在输入中的序号及其在块内的字偏移
+    pub(crate) spans: Vec<(usize, u32)>,
+}
+
+/// 按软元件与编号排序后，把间隔不超过 [`MAX_GAP_WORDS`] 的 `(软元件, 起始编号, 字数)` 范围合并成块
+///
+/// 位软元件的范围只与编号相差 16 的倍数的块合并，使块内偏移总是整字。
+pub(crate) fn plan_reads(spans: &[(Device, u32, u32)]) -> Vec<ReadBlock> {
+    let mut order: Vec<usize> = (0..spans.len()).collect();
+    order.sort_by_key(|&index| (spans[index].0.prefix, spans[index].1));
+
+    let mut blocks: Vec<ReadBlock> = Vec::new();
+    for index in order {
+        let (device, number, words) = spans[index];
+        if let Some(block) = blocks.last_mut().filter(|block| {
+            block.device.prefix == device.prefix
+                && (number - block.start).is_multiple_of(device.step)
+        }) {
+            let offset = (number - block.start) / device.step;
+            let end = (offset + words).max(block.words);
+            if end as usize <= BLOCK_WORDS && offset <= block.words + MAX_GAP_WORDS {
+                block.words = end;
+                block.spans.push((index, offset));
+                continue;
+            }
+        }
+        blocks.push(ReadBlock {
+            device,
+            start: number,
+            words,
+            spans: vec![(index, 0)],
+        });
+    }
+    blocks
+}
+
+/// 将字格式化为数据行
+pub(crate) fn format_words(words: &[u16]) -> String {
+    let mut out = String::with_capacity(words.len() * 5);
+    for line in words.chunks(WORDS_PER_LINE) {
+        for (i, word) in line.iter().enumerate() {
+            let sep = if i == 0 { "" } else { " " };
+            let _ = write!(out, "{sep}{word:04X}");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 逐行解析导出文件，攒满一块即交给调用方写入
+#[derive(Default)]
+pub(crate) struct AreaLoader {
+    section: Option<(Device, u32, usize)>,
+    buffer: Vec<u16>,
+    written: usize,
+}
+
+impl AreaLoader {
+    /// 处理一行，返回可以写入的块
+    pub(crate) fn feed_line(&mut self, line: &str) -> Result<Vec<(String, Vec<u16>)>, Error> {
+        let line = line.trim();
+        let mut blocks = Vec::new();
+        if line.is_empty() {
+            return Ok(blocks);
+        }
+
+        if let Some(header) = line.strip_prefix('@') {
+            blocks.extend(self.finish_section()?);
+            let (address, count) = header
+                .split_once(' ')
+                .ok_or_else(|| invalid_data(format!("invalid area header {line:?}")))?;
+            let (device, number) = Device::parse(address)?;
+            let count = count
+                .trim()
+                .parse()
+                .map_err(|_| invalid_data(format!("invalid word count in {line:?}")))?;
+            self.section = Some((device, number, count));
+            return Ok(blocks);
+        }
+
+        let Some((device, number, remaining)) = &mut self.section else {
+            return Err(invalid_data("data line before area header".to_string()));
+        };
+        for word in line.split_whitespace() {
+            let word = u16::from_str_radix(word, 16)
+                .map_err(|_| invalid_data(format!("invalid word {word:?}")))?;
+            if *remaining == 0 {
+                return Err(invalid_data(
+                    "more words than declared in header".to_string(),
+                ));
+            }
+            *remaining -= 1;
+            self.buffer.push(word);
+            if self.buffer.len() == BLOCK_WORDS {
+                let words = std::mem::take(&mut self.buffer);
+                blocks.push((device.address(*number), words));
+                *number += BLOCK_WORDS as u32 * device.step;
+            }
+        }
+        self.written += blocks.iter().map(|(_, words)| words.len()).sum::<usize>();
+        Ok(blocks)
+    }
+
+    /// 结束解析，返回最后一块
+    pub(crate) fn finish(&mut self) -> Result<Option<(String, Vec<u16>)>, Error> {
+        self.finish_section()
+    }
+
+    /// 已交给调用方写入的字数
+    pub(crate) fn written(&self) -> usize {
+        self.written
+    }
+
+    fn finish_section(&mut self) -> Result<Option<(String, Vec<u16>)>, Error> {
+        let Some((device, number, remaining)) = self.section.take() else {
+            return Ok(None);
+        };
+        if remaining != 0 {
+            return Err(invalid_data(format!("area is missing {remaining} words")));
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let words = std::mem::take(&mut self.buffer);
+        self.written += words.len();
+        Ok(Some((device.address(number), words)))
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::Transport(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+pub(crate) fn read_words(response: Response) -> Vec<u16> {
+    match response {
+        Response::ReadU8s(bytes) => bytes_to_words(&bytes),
+        _ => unreachable!("Unexpected response type, expected ReadU8s"),
+    }
+}
+
+pub(crate) fn write_request(address: String, words: &[u16]) -> Request<'static> {
+    Request::WriteU8s(address.into(), words_to_bytes(words).into())
+}
+
+impl<T: Client> Context<T> {
+    /// 以最大块读取导出 `prefix` 软元件 `range` 范围的数据，返回导出的字数
+    ///
+    /// 地址使用 MC 软元件名称，不经过 PLC 型号的地址转换。
+    /// 位软元件按字读取，每个字覆盖 16 点。
+    pub async fn dump_area<W>(
+        &mut self,
+        prefix: &str,
+        range: Range<u32>,
+        writer: &mut W,
+    ) -> Result<usize, Error>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        let plan = DumpPlan::new(prefix, range)?;
+        writer.write_all(plan.header().as_bytes()).await?;
+        for (address, count) in plan.blocks() {
+            let response = self
+                .client
+                .call(Request::ReadU8s(address.into(), count))
+                .await?;
+            writer
+                .write_all(format_words(&read_words(response)).as_bytes())
+                .await?;
+        }
+        writer.flush().await?;
+        Ok(plan.words())
+    }
+
+    /// 读取 [`Self::dump_area`] 导出的数据并以最大块写回，返回写入的字数
+    pub async fn load_area<R>(&mut self, reader: R) -> Result<usize, Error>
+    where
+        R: AsyncBufRead + Unpin + Send,
+    {
+        let mut loader = AreaLoader::default();
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            for (address, words) in loader.feed_line(&line)? {
+                self.load_block(address, &words).await?;
+            }
+        }
+        if let Some((address, words)) = loader.finish()? {
+            self.load_block(address, &words).await?;
+        }
+        Ok(loader.written())
+    }
+
+    /// 写入导入的一块，并使读取缓存中对应的字失效
+    async fn load_block(&mut self, address: String, words: &[u16]) -> Result<(), Error> {
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(&address, words.len());
+        }
+        self.send(write_request(address, words)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::BTreeMap;
+
+    /// 以字地址为键的内存 PLC，记录每次请求的地址与字数
+    #[derive(Debug, Default)]
+    struct Memory {
+        words: BTreeMap<String, Vec<u16>>,
+        calls: Vec<(String, usize)>,
+    }
+
+    #[async_trait]
+    impl Client for Memory {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(addr, cnt) => {
+                    self.calls.push((addr.to_string(), cnt as usize));
+                    let (device, start) = Device::parse(&addr).unwrap();
+                    let words: Vec<u16> =
+                        (0..cnt).map(|i| (start + i * device.step) as u16).collect();
+                    Ok(Response::ReadU8s(words_to_bytes(&words)))
+                }
+                Request::WriteU8s(addr, bytes) => {
+                    self.calls.push((addr.to_string(), bytes.len() / 2));
+                    self.words.insert(addr.to_string(), bytes_to_words(&bytes));
+                    Ok(Response::WriteU8s())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn dump_and_load_round_trip() {
+        let mut context = Context::new(Memory::default());
+        let mut dump = Vec::new();
+        assert_eq!(
+            context.dump_area("D", 100..1100, &mut dump).await.unwrap(),
+            1000
+        );
+        assert_eq!(context.dump_area("M", 0..32, &mut dump).await.unwrap(), 2);
+        assert_eq!(
+            context.client.calls,
+            [
+                ("D100".to_string(), 960),
+                ("D1060".to_string(), 40),
+                ("M0".to_string(), 2)
+            ]
+        );
+
+        let text = String::from_utf8(dump.clone()).unwrap();
+        assert!(text.starts_with("@D100 1000\n0064 0065"));
+        assert!(text.ends_with("@M0 2\n0000 0010\n"));
+
+        let mut target = Context::new(Memory::default());
+        assert_eq!(target.load_area(&dump[..]).await.unwrap(), 1002);
+        assert_eq!(target.client.words["D1060"][0], 1060);
+        assert_eq!(target.client.words["M0"], [0x0000, 0x0010]);
+        assert_eq!(target.client.calls.len(), 3);
+
+        // 位软元件范围须按字对齐，字数须与区域头一致
+        assert!(context
+            .dump_area("M", 0..10, &mut Vec::new())
+            .await
+            .is_err());
+        assert!(target.load_area(&b"@D0 3\n0001 0002\n"[..]).await.is_err());
+        assert!(target.load_area(&b"0001\n"[..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn load_invalidates_cached_words() {
+        use crate::client::Reader as _;
+
+        let mut context = Context::new(Memory::default());
+        context.set_read_cache(Some(std::time::Duration::from_secs(3600)));
+        context.read_u16s("D0", 2).await.unwrap();
+        context.read_u16s("D0", 2).await.unwrap();
+        assert_eq!(context.client.calls.len(), 1);
+
+        // 不足一个最大块的区域只由末尾的一块写入
+        context.load_area(&b"@D0 2\n0007 0008\n"[..]).await.unwrap();
+        context.read_u16s("D0", 2).await.unwrap();
+        assert_eq!(
+            context.client.calls,
+            [
+                ("D0".to_string(), 2),
+                ("D0".to_string(), 2),
+                ("D0".to_string(), 2)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn link_devices_use_hex_numbers() {
+        let mut context = Context::new(Memory::default());
+        let mut dump = Vec::new();
+        context
+            .dump_area("W", 0x3C0..0x7C0, &mut dump)
+            .await
+            .unwrap();
+        context
+            .dump_area("SB", 0x10..0x30, &mut dump)
+            .await
+            .unwrap();
+        assert_eq!(
+            context.client.calls,
+            [
+                ("W3C0".to_string(), 960),
+                ("W780".to_string(), 64),
+                ("SB10".to_string(), 2)
+            ]
+        );
+        let text = String::from_utf8(dump.clone()).unwrap();
+        assert!(text.starts_with("@W3C0 1024\n03C0 03C1"));
+        assert!(text.ends_with("@SB10 2\n0010 0020\n"));
+
+        let mut target = Context::new(Memory::default());
+        assert_eq!(target.load_area(&dump[..]).await.unwrap(), 1026);
+        assert_eq!(target.client.words["W780"][0], 0x780);
+        assert_eq!(target.client.words["SB10"], [0x10, 0x20]);
+        assert!(context
+            .dump_area("SW", 0..0x10, &mut Vec::new())
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn plans_reads_across_gaps_and_bit_offsets() {
+        let spans: Vec<(Device, u32, u32)> = [("W1FE", 1), ("W200", 1), ("SB30", 1), ("SB10", 1)]
+            .into_iter()
+            .chain([("M0", 2), ("M40", 1), ("M8", 1), ("D100", 4), ("D130", 1)])
+            .map(|(addr, words)| {
+                let (device, number) = Device::parse(addr).unwrap();
+                (device, number, words)
+            })
+            .collect();
+        let blocks: Vec<_> = plan_reads(&spans)
+            .into_iter()
+            .map(|block| (block.device.address(block.start), block.words, block.spans))
+            .collect();
+        // M8 与 M0 相差不足一字，另起一块；D130 距 D103 超过 MAX_GAP_WORDS
+        assert_eq!(
+            blocks,
+            [
+                ("D100".to_string(), 4, vec![(7, 0)]),
+                ("D130".into(), 1, vec![(8, 0)]),
+                ("M0".into(), 2, vec![(4, 0)]),
+                ("M8".into(), 3, vec![(6, 0), (5, 2)]),
+                ("SB10".into(), 3, vec![(3, 0), (2, 2)]),
+                ("W1FE".into(), 3, vec![(0, 0), (1, 2)]),
+            ]
+        );
+    }
+}
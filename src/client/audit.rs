@@ -0,0 +1,244 @@
+//! 写入审计日志
+//!
+//! [`WriteAudit`] 包装任意 [`Client`]，每次写请求（`WriteU8s`、`WriteBits` 以及
+//! [`READ_ONLY_COMMANDS`] 以外的命令，与只读模式的判断一致）完成后向输出追加一行，
+//! 以制表符分隔时间戳、请求类型、地址、写入值与结果（下例以空格代替制表符）；
+//! 读请求原样转发，不做记录：
+//!
+//! ```text
+//! 2024-05-01T08:00:00.123Z  words  D100  1234 0001  ok
+//! 2024-05-01T08:00:00.456Z  bits  M10  1 0 1  error: Protocol error occurred: OutOfRange
+//! 2024-05-01T08:00:01.000Z  command  1001/0000  01 00 00 00  ok
+//! ```
+//!
+//! 写入的字节按小端还原为字后以 4 位十六进制表示，奇数个字节时最后一个字节以 2 位十六进制表示。
+//! 写入输出失败只记录警告，不影响请求结果。
+
+use std::{
+    fmt::{self, Write as _},
+    fs::{File, OpenOptions},
+    io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    frame::{Request, Response},
+    trace, Error,
+};
+
+use super::{Client, READ_ONLY_COMMANDS};
+
+/// 将每次写请求及其结果追加到 `W` 的 [`Client`] 包装，见[模块文档](self)
+pub struct WriteAudit<T, W> {
+    inner: T,
+    output: W,
+}
+
+impl<T: Client> WriteAudit<T, File> {
+    /// 以追加方式打开（不存在时创建）`path` 作为审计日志
+    pub fn open(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(inner, file))
+    }
+}
+
+impl<T: Client, W: io::Write + Send> WriteAudit<T, W> {
+    pub fn new(inner: T, output: W) -> Self {
+        Self { inner, output }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// 拆分为内部客户端与输出
+    pub fn into_parts(self) -> (T, W) {
+        (self.inner, self.output)
+    }
+
+    fn record(&mut self, line: &str) -> io::Result<()> {
+        self.output.write_all(line.as_bytes())?;
+        self.output.flush()
+    }
+}
+
+impl<T: fmt::Debug, W> fmt::Debug for WriteAudit<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteAudit")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<T: Client, W: io::Write + Send> Client for WriteAudit<T, W> {
+    async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        let Some((kind, target, value)) = describe(&request) else {
+            return self.inner.call(request).await;
+        };
+        let timestamp = SystemTime::now();
+        let result = self.inner.call(request).await;
+        let outcome = match &result {
+            Ok(_) => "ok".to_string(),
+            Err(err) => format!("error: {err}"),
+        };
+        let line = format!(
+            "{}\t{kind}\t{target}\t{value}\t{outcome}\n",
+            rfc3339(timestamp)
+        );
+        if let Err(err) = self.record(&line) {
+            trace::warning!("Failed to write audit entry: {err}");
+        }
+        result
+    }
+
+    async fn disconnect(&mut self) -> io::Result<()> {
+        self.inner.disconnect().await
+    }
+}
+
+/// 写请求的类型、目标与写入值，读请求返回 `None`
+fn describe(request: &Request<'_>) -> Option<(&'static str, String, String)> {
+    match request {
+        Request::WriteU8s(address, u8s) => {
+            let mut value = String::new();
+            for chunk in u8s.chunks(2) {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                match chunk {
+                    [low, high] => write!(value, "{:04X}", u16::from_le_bytes([*low, *high])),
+                    [byte] => write!(value, "{byte:02X}"),
+                    _ => unreachable!(),
+                }
+                .expect("writing to a String cannot fail");
+            }
+            Some(("words", address.to_string(), value))
+        }
+        Request::WriteBits(address, bits) => {
+            let value: Vec<&str> = bits
+                .iter()
+                .map(|&bit| if bit { "1" } else { "0" })
+                .collect();
+            Some(("bits", address.to_string(), value.join(" ")))
+        }
+        Request::Command(command, subcommand, data) if !READ_ONLY_COMMANDS.contains(command) => {
+            let value: Vec<String> = data.iter().map(|byte| format!("{byte:02X}")).collect();
+            Some((
+                "command",
+                format!("{command:04X}/{subcommand:04X}"),
+                value.join(" "),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// 格式化为 UTC 的 RFC 3339 时间，精确到毫秒
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // 由 1970-01-01 起的天数换算公历日期
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ProtocolError;
+    use std::time::Duration;
+
+    #[derive(Debug)]
+    struct Plc;
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(_, _) => Ok(Response::ReadU8s(vec![1, 0])),
+                Request::WriteU8s(_, _) => Ok(Response::WriteU8s()),
+                Request::Command(command, subcommand, _) => {
+                    Ok(Response::Command(command, subcommand, Vec::new()))
+                }
+                _ => Err(Error::Protocol(ProtocolError::OutOfRange)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn records_writes_only() {
+        let mut audit = WriteAudit::new(Plc, Vec::new());
+        audit.call(Request::ReadU8s("D0".into(), 1)).await.unwrap();
+        audit
+            .call(Request::WriteU8s(
+                "D100".into(),
+                vec![0x34, 0x12, 0x01].into(),
+            ))
+            .await
+            .unwrap();
+        assert!(audit
+            .call(Request::WriteBits("M10".into(), vec![true, false].into()))
+            .await
+            .is_err());
+        audit
+            .call(Request::Command(0x0101, 0, Vec::new().into()))
+            .await
+            .unwrap();
+        audit
+            .call(Request::Command(0x1001, 0, vec![1, 0, 0, 0].into()))
+            .await
+            .unwrap();
+
+        let (_, output) = audit.into_parts();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<Vec<&str>> = output
+            .lines()
+            .map(|line| line.split('\t').skip(1).collect())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                vec!["words", "D100", "1234 01", "ok"],
+                vec![
+                    "bits",
+                    "M10",
+                    "1 0",
+                    "error: Protocol error occurred: OutOfRange"
+                ],
+                vec!["command", "1001/0000", "01 00 00 00", "ok"],
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_utc_timestamps() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_251_199_123);
+        assert_eq!(rfc3339(time), "2024-02-29T23:59:59.123Z");
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+}
@@ -0,0 +1,210 @@
+//! 块读取结果的按地址取值
+//!
+//! 一次读取一大段字后，[`BlockView`] 按软元件地址从中取出各种类型的值，无需手工计算偏移：
+//!
+//! ```text
+//! let view = context.read_block("D100", 32).await?;
+//! let speed = view.u16_at("D104")?;
+//! let temperature = view.f32_at("D110")?;
+//! let alarm = view.bit_at("D120.3")?;
+//! ```
+//!
+//! 双字与浮点数按低位字在前解释，与 `read_u32s`、`read_f32s` 一致。
+//! 字软元件的位以 `D120.3` 表示，位号为十六进制 `0`-`F`；位软元件直接写点号，如 `M35`。
+
+use crate::{
+    frame::{convert_keyence_to_mitsubishi_address, Model, ProtocolError, Quantity},
+    Error,
+};
+
+use super::{area::Device, Client, Context, LeBytes, Reader as _};
+
+/// 块读取的原始数据及其起始地址，见[模块文档](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockView {
+    device: &'static str,
+    start: u32,
+    /// 每个字对应的软元件点数
+    step: u32,
+    model: Model,
+    bytes: Vec<u8>,
+}
+
+impl BlockView {
+    /// 以 MC 地址 `base` 为起始地址的原始数据，`bytes` 按字小端排列
+    pub fn new(base: &str, bytes: Vec<u8>) -> Result<Self, Error> {
+        Self::with_model(base, bytes, Model::Mitsubishi)
+    }
+
+    /// 同 [`Self::new`]，但 `base` 与各取值方法的地址按 `model` 转换
+    pub fn with_model(base: &str, bytes: Vec<u8>, model: Model) -> Result<Self, Error> {
+        let (device, start) = Device::parse(&translate(base, model)?)?;
+        Ok(Self {
+            device: device.prefix(),
+            start,
+            step: device.step(),
+            model,
+            bytes,
+        })
+    }
+
+    /// 原始数据
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// 覆盖的字数
+    pub fn words(&self) -> usize {
+        self.bytes.len() / 2
+    }
+
+    pub fn u16_at(&self, addr: &str) -> Result<u16, Error> {
+        self.at(addr)
+    }
+
+    pub fn i16_at(&self, addr: &str) -> Result<i16, Error> {
+        self.at(addr)
+    }
+
+    pub fn u32_at(&self, addr: &str) -> Result<u32, Error> {
+        self.at(addr)
+    }
+
+    pub fn i32_at(&self, addr: &str) -> Result<i32, Error> {
+        self.at(addr)
+    }
+
+    pub fn f32_at(&self, addr: &str) -> Result<f32, Error> {
+        self.at(addr)
+    }
+
+    pub fn f64_at(&self, addr: &str) -> Result<f64, Error> {
+        self.at(addr)
+    }
+
+    /// 取一位：字软元件写作 `D120.3`，位软元件直接写点号
+    pub fn bit_at(&self, addr: &str) -> Result<bool, Error> {
+        let (word, bit) = match addr.split_once('.') {
+            Some((word, bit)) => {
+                let bit = match bit.as_bytes() {
+                    [digit] => (*digit as char).to_digit(16),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid(format!("invalid bit number in {addr}")))?;
+                if self.step != 1 {
+                    return Err(invalid(format!("{addr}: bit devices take no bit number")));
+                }
+                (self.word_offset(word)?, bit)
+            }
+            None if self.step == 1 => {
+                return Err(invalid(format!("{addr}: word devices need a bit number")));
+            }
+            None => {
+                let offset = self.offset(addr)?;
+                (offset / self.step, offset % self.step)
+            }
+        };
+        let value = u16::from_le_bytes(self.slice(word as usize, 2)?.try_into().unwrap());
+        Ok(value >> bit & 1 != 0)
+    }
+
+    fn at<V: LeBytes>(&self, addr: &str) -> Result<V, Error> {
+        let offset = self.word_offset(addr)? as usize;
+        let size = std::mem::size_of::<V>();
+        Ok(V::from_le_chunk(self.slice(offset, size)?))
+    }
+
+    /// `addr` 相对起始地址的点数
+    fn offset(&self, addr: &str) -> Result<u32, Error> {
+        let (device, number) = Device::parse(&translate(addr, self.model)?)?;
+        if device.prefix() != self.device {
+            return Err(invalid(format!(
+                "{addr} is outside the {} block",
+                self.device
+            )));
+        }
+        number
+            .checked_sub(self.start)
+            .ok_or(Error::Protocol(ProtocolError::OutOfRange))
+    }
+
+    /// `addr` 相对起始地址的字数，位软元件须按 16 点对齐
+    fn word_offset(&self, addr: &str) -> Result<u32, Error> {
+        let offset = self.offset(addr)?;
+        if !offset.is_multiple_of(self.step) {
+            return Err(invalid(format!(
+                "{addr} is not aligned to a word of the block"
+            )));
+        }
+        Ok(offset / self.step)
+    }
+
+    fn slice(&self, word: usize, size: usize) -> Result<&[u8], Error> {
+        let start = word * 2;
+        self.bytes
+            .get(start..start + size)
+            .ok_or(Error::Protocol(ProtocolError::OutOfRange))
+    }
+}
+
+fn translate(addr: &str, model: Model) -> Result<String, Error> {
+    match model {
+        Model::Keyence => convert_keyence_to_mitsubishi_address(addr).map_err(Error::KV),
+        Model::Mitsubishi => Ok(addr.to_string()),
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::Protocol(ProtocolError::InvalidAddress(reason))
+}
+
+impl<T: Client> Context<T> {
+    /// 从 `addr` 读取 `cnt` 个字，返回可按地址取值的 [`BlockView`]
+    ///
+    /// 取值方法的地址与 `addr` 一样按 PLC 型号转换。
+    pub async fn read_block<A>(&mut self, addr: &A, cnt: Quantity) -> Result<BlockView, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let bytes = self.read_u8s(addr, cnt).await?;
+        BlockView::with_model(addr.as_ref(), bytes, self.model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_typed_values() {
+        let mut bytes = vec![0; 32];
+        bytes[8..10].copy_from_slice(&0x1234u16.to_le_bytes());
+        bytes[10..14].copy_from_slice(&(-2i32).to_le_bytes());
+        bytes[20..24].copy_from_slice(&1.5f32.to_le_bytes());
+        bytes[30..32].copy_from_slice(&0x0008u16.to_le_bytes());
+        let view = BlockView::new("D100", bytes).unwrap();
+
+        assert_eq!(view.words(), 16);
+        assert_eq!(view.u16_at("D104").unwrap(), 0x1234);
+        assert_eq!(view.i32_at("D105").unwrap(), -2);
+        assert_eq!(view.f32_at("D110").unwrap(), 1.5);
+        assert!(view.bit_at("D115.3").unwrap());
+        assert!(!view.bit_at("D115.4").unwrap());
+
+        assert!(view.u16_at("D99").is_err());
+        assert!(view.u32_at("D115").is_err());
+        assert!(view.u16_at("W104").is_err());
+        assert!(view.bit_at("D115").is_err());
+        assert!(view.bit_at("D115.G").is_err());
+    }
+
+    #[test]
+    fn bit_device_blocks() {
+        let view = BlockView::new("M0", vec![0x00, 0x00, 0x08, 0x00]).unwrap();
+        assert!(view.bit_at("M19").unwrap());
+        assert!(!view.bit_at("M18").unwrap());
+        assert_eq!(view.u16_at("M16").unwrap(), 0x0008);
+        assert!(view.u16_at("M8").is_err());
+        assert!(view.bit_at("M32").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! 读取缓存
+//!
+//! 开启后，`read_*` 按字读取的结果以软元件字为单位保存在本地；
+//! 之后的读取若覆盖的每个字都在最大缓存时间内，则直接由缓存返回，否则发出实际请求并刷新缓存。
+//! 多个界面元素读取相互重叠的地址时可显著减少请求数。
+//!
+//! 经由 `Context` 的写入会使对应的缓存失效，位软元件被写入时清除该软元件的全部缓存。
+//! 直接调用 [`Client::call`](super::Client::call) 的请求不经过缓存；事务记录原值时也直接读取 PLC。
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{convert::words_to_bytes, frame::Quantity, Error};
+
+use super::{area::Device, from_le_bytes, Client, Context};
+
+/// 以软元件前缀与编号为键的字缓存
+#[derive(Debug)]
+pub(crate) struct ReadCache {
+    max_age: Duration,
+    words: HashMap<(&'static str, u32), (u16, Instant)>,
+}
+
+impl ReadCache {
+    pub(crate) fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            words: HashMap::new(),
+        }
+    }
+
+    /// `address` 开始的 `cnt` 个字均未过期时返回其字节
+    pub(crate) fn lookup(&self, address: &str, cnt: Quantity) -> Option<Vec<u8>> {
+        let (device, number) = Device::parse(address).ok()?;
+        let now = Instant::now();
+        let words = word_numbers(device, number, cnt)
+            .map(|number| {
+                let &(value, updated) = self.words.get(&(device.prefix(), number))?;
+                (now.duration_since(updated) <= self.max_age).then_some(value)
+            })
+            .collect::<Option<Vec<u16>>>()?;
+        Some(words_to_bytes(&words))
+    }
+
+    /// 保存 `address` 开始读取到的字节
+    pub(crate) fn store(&mut self, address: &str, bytes: &[u8]) {
+        let Ok((device, number)) = Device::parse(address) else {
+            return;
+        };
+        let now = Instant::now();
+        let values = bytes
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]));
+        for (number, value) in word_numbers(device, number, Quantity::MAX).zip(values) {
+            self.words.insert((device.prefix(), number), (value, now));
+        }
+    }
+
+    /// 使 `address` 开始 `words` 个字的缓存失效
+    pub(crate) fn invalidate(&mut self, address: &str, words: usize) {
+        let Ok((device, number)) = Device::parse(address) else {
+            // 无法解析的地址无从判断影响范围
+            self.words.clear();
+            return;
+        };
+        if device.is_bit() {
+            // 缓存的字未必按 16 点对齐，任何写入都可能与之重叠
+            self.words
+                .retain(|(prefix, _), _| *prefix != device.prefix());
+            return;
+        }
+        for number in word_numbers(device, number, words as Quantity) {
+            self.words.remove(&(device.prefix(), number));
+        }
+    }
+}
+
+/// 按字访问时各字的起始编号，位软元件每字 16 点
+fn word_numbers(device: Device, number: u32, cnt: Quantity) -> impl Iterator<Item = u32> {
+    (0..cnt).map_while(move |i| number.checked_add(i.checked_mul(device.step())?))
+}
+
+impl<T: Client> Context<T> {
+    /// 开启（`Some`）或关闭（`None`）读取缓存，`max_age` 为缓存值可被复用的最长时间
+    ///
+    /// 每次调用都会清空已有的缓存。
+    pub fn set_read_cache(&mut self, max_age: Option<Duration>) {
+        self.cache = max_age.map(ReadCache::new);
+    }
+
+    /// 清空读取缓存，下一次读取一定发出实际请求
+    pub fn clear_read_cache(&mut self) {
+        if let Some(cache) = &mut self.cache {
+            cache.words.clear();
+        }
+    }
+
+    /// 不经缓存直接从 PLC 读取字，用于需要 PLC 当前值的场合，读到的值刷新缓存
+    pub(super) async fn read_u16s_uncached(
+        &mut self,
+        addr: &str,
+        cnt: Quantity,
+    ) -> Result<Vec<u16>, Error> {
+        let address = self.process_address(addr)?;
+        Ok(from_le_bytes(&self.fetch_u8s(address, cnt).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::{Reader as _, Writer as _},
+        frame::{Request, Response},
+        Error,
+    };
+    use async_trait::async_trait;
+
+    /// 读取返回字序号，记录读取请求数
+    #[derive(Debug, Default)]
+    struct Plc {
+        reads: usize,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(addr, cnt) => {
+                    self.reads += 1;
+                    let (_, start) = Device::parse(&addr)?;
+                    let words: Vec<u16> = (0..cnt).map(|i| (start + i) as u16).collect();
+                    Ok(Response::ReadU8s(words_to_bytes(&words)))
+                }
+                Request::WriteU8s(_, _) => Ok(Response::WriteU8s()),
+                Request::WriteBits(_, _) => Ok(Response::WriteBits()),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn overlapping_reads_are_served_from_cache() {
+        let mut context = Context::new(Plc::default());
+        context.set_read_cache(Some(Duration::from_secs(3600)));
+
+        assert_eq!(context.read_u16s("D0", 4).await.unwrap(), [0, 1, 2, 3]);
+        assert_eq!(context.read_u16s("D1", 2).await.unwrap(), [1, 2]);
+        assert_eq!(context.client.reads, 1);
+
+        // 部分未缓存时发出实际请求
+        assert_eq!(context.read_u16s("D2", 4).await.unwrap(), [2, 3, 4, 5]);
+        assert_eq!(context.client.reads, 2);
+
+        // 写入使对应的字失效
+        context.write_u16s("D3", &[9]).await.unwrap();
+        context.read_u16s("D0", 2).await.unwrap();
+        assert_eq!(context.client.reads, 2);
+        context.read_u16s("D3", 1).await.unwrap();
+        assert_eq!(context.client.reads, 3);
+
+        context.read_u16s("M0", 2).await.unwrap();
+        context.write_bools("M100", &[true]).await.unwrap();
+        context.read_u16s("M0", 1).await.unwrap();
+        assert_eq!(context.client.reads, 5);
+
+        // 过期的值不再使用
+        context.set_read_cache(Some(Duration::ZERO));
+        context.read_u16s("D0", 1).await.unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        context.read_u16s("D0", 1).await.unwrap();
+        assert_eq!(context.client.reads, 7);
+    }
+}
@@ -0,0 +1,391 @@
+//! 命令支持探测
+//!
+//! [`Context::probe_capabilities`] 依次发出各命令不改变 PLC 状态的变体（只读 SD0、SM0 等），
+//! 按应答判断目标是否支持：正常应答为支持，结束代码为不支持，其它错误（超时、断线等）无法判断。
+//! 监视登记（0801）会覆盖本连接上已有的登记，正在使用 [`Collector`](super::Collector) 的连接
+//! 探测后需重新登记。
+//!
+//! 随机读取、多块批量读取、监视登记等高层接口会参考上下文中已知不支持的命令：
+//! 探测中或运行中 PLC 以「不支持」应答（结束代码 C059、C05F，或 1E 帧无法发出）的命令，
+//! 以及以 [`Context::mark_unsupported`] 配置的命令；以其它结束代码拒绝的命令仍会发出。
+//! 已知不支持时不再发出该命令，改为逐段的批量读取，同一套代码可用于 FX、Q 与 iQ-R。
+
+use std::fmt;
+
+use crate::{
+    frame::{ProtocolError, Request, Response},
+    trace, Error,
+};
+
+use super::{Client, Context};
+
+/// SD0 的软元件编号（3 字节）与软元件代码
+const SD0: [u8; 4] = [0x00, 0x00, 0x00, 0xA9];
+
+/// 探测项：命令、子命令、名称以及请求数据
+const PROBES: &[(u16, u16, &str, &[u8])] = &[
+    (0x0101, 0x0000, "CPU model read", &[]),
+    // 1 个字符的回送数据
+    (0x0619, 0x0000, "loopback test", &[0x01, 0x00, b'0']),
+    // 以下请求数据由 `probe_request` 按软元件地址生成
+    (0x0401, 0x0000, "batch read (words)", &[]),
+    (0x0401, 0x0001, "batch read (bits)", &[]),
+    // iQ-R 扩展软元件指定：4 字节编号 + 2 字节代码 + 点数
+    (
+        0x0401,
+        0x0002,
+        "batch read (iQ-R device spec)",
+        &[0x00, 0x00, 0x00, 0x00, 0xA9, 0x00, 0x01, 0x00],
+    ),
+    // 字访问 1 点、双字访问 0 点
+    (
+        0x0403,
+        0x0000,
+        "random read",
+        &[0x01, 0x00, SD0[0], SD0[1], SD0[2], SD0[3]],
+    ),
+    // 字块 1 个、位块 0 个，块内 1 点
+    (
+        0x0406,
+        0x0000,
+        "multiple block read",
+        &[0x01, 0x00, SD0[0], SD0[1], SD0[2], SD0[3], 0x01, 0x00],
+    ),
+    (
+        0x0801,
+        0x0000,
+        "monitor registration",
+        &[0x01, 0x00, SD0[0], SD0[1], SD0[2], SD0[3]],
+    ),
+];
+
+/// 单个命令的探测结果
+#[derive(Debug)]
+pub enum Support {
+    /// 正常应答
+    Supported,
+    /// 以结束代码拒绝
+    Rejected(u16),
+    /// 未得到应答，无法判断
+    Unknown(Error),
+}
+
+/// 单个命令的探测记录
+#[derive(Debug)]
+pub struct Capability {
+    pub command: u16,
+    pub subcommand: u16,
+    /// 命令名称，如 `random read`
+    pub name: &'static str,
+    pub support: Support,
+}
+
+/// [`Context::probe_capabilities`] 返回的探测报告
+#[derive(Debug)]
+pub struct Capabilities {
+    /// 按探测顺序排列的各项结果
+    pub entries: Vec<Capability>,
+}
+
+impl Capabilities {
+    /// 是否确认支持该命令与子命令；未探测或无法判断时为 `false`
+    pub fn supports(&self, command: u16, subcommand: u16) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.command == command
+                && entry.subcommand == subcommand
+                && matches!(entry.support, Support::Supported)
+        })
+    }
+}
+
+/// 每项一行：命令/子命令、名称与结果
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(
+                f,
+                "{:04X}/{:04X} {:<30} ",
+                entry.command, entry.subcommand, entry.name
+            )?;
+            match &entry.support {
+                Support::Supported => writeln!(f, "supported")?,
+                Support::Rejected(code) => writeln!(f, "rejected (end code {code:04X})")?,
+                Support::Unknown(err) => writeln!(f, "unknown ({err})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn probe_request(command: u16, subcommand: u16, data: &'static [u8]) -> Request<'static> {
+    match (command, subcommand) {
+        (0x0401, 0x0000) => Request::ReadU8s("SD0".into(), 1),
+        (0x0401, 0x0001) => Request::ReadBits("SM0".into(), 1),
+        _ => Request::Command(command, subcommand, data.into()),
+    }
+}
+
+/// 表明目标不支持所发命令的结束代码，探测与运行中的判断共用
+fn is_unsupported_code(code: u16) -> bool {
+    matches!(code, 0xC059 | 0xC05F)
+}
+
+/// 表明目标不支持所发命令的错误
+pub(super) fn is_unsupported(err: &Error) -> bool {
+    match err {
+        Error::Protocol(ProtocolError::EndCode(end_code)) => is_unsupported_code(end_code.code()),
+        Error::Protocol(ProtocolError::NotImplemented) => true,
+        _ => false,
+    }
+}
+
+impl<T: Client> Context<T> {
+    /// 探测 PLC 支持的命令，见[模块文档](super::capability)
+    ///
+    /// 各项相互独立，单项失败不影响其他项；探测地址不经过 PLC 型号的地址转换。
+    /// 以「不支持」的结束代码拒绝的命令记为不支持，高层接口随后改用批量读取。
+    pub async fn probe_capabilities(&mut self) -> Capabilities {
+        let mut entries = Vec::with_capacity(PROBES.len());
+        for &(command, subcommand, name, data) in PROBES {
+            let support = match self
+                .client
+                .call(probe_request(command, subcommand, data))
+                .await
+            {
+                Ok(_) => Support::Supported,
+                Err(Error::Protocol(ProtocolError::EndCode(end_code))) => {
+                    Support::Rejected(end_code.code())
+                }
+                Err(err) => Support::Unknown(err),
+            };
+            entries.push(Capability {
+                command,
+                subcommand,
+                name,
+                support,
+            });
+        }
+        let capabilities = Capabilities { entries };
+        self.set_capabilities(&capabilities);
+        capabilities
+    }
+
+    /// 把 `capabilities` 中以「不支持」的结束代码拒绝的命令记为不支持，用于沿用之前的探测结果
+    ///
+    /// 以其它结束代码拒绝（如软元件保护、点数超出范围）的命令不受影响。
+    pub fn set_capabilities(&mut self, capabilities: &Capabilities) {
+        for entry in &capabilities.entries {
+            if matches!(entry.support, Support::Rejected(code) if is_unsupported_code(code)) {
+                self.mark_unsupported(entry.command, entry.subcommand);
+            }
+        }
+    }
+
+    /// 把命令记为不支持，高层接口不再发出该命令，见[模块文档](super::capability)
+    pub fn mark_unsupported(&mut self, command: u16, subcommand: u16) {
+        if !self.unsupported.contains(&(command, subcommand)) {
+            self.unsupported.push((command, subcommand));
+        }
+    }
+
+    /// 是否可以发出该命令，未记为不支持的命令（包括未探测的）均视为支持
+    pub fn supports_command(&self, command: u16, subcommand: u16) -> bool {
+        !self.unsupported.contains(&(command, subcommand))
+    }
+
+    /// 发出命令，已知不支持或 PLC 以「不支持」应答时返回 `None`，由调用方改用批量读取
+    pub(super) async fn try_command(
+        &mut self,
+        command: u16,
+        subcommand: u16,
+        data: Vec<u8>,
+    ) -> Result<Option<Response>, Error> {
+        if !self.supports_command(command, subcommand) {
+            return Ok(None);
+        }
+        match self
+            .send(Request::Command(command, subcommand, data.into()))
+            .await
+        {
+            Ok(response) => Ok(Some(response)),
+            Err(err) if is_unsupported(&err) => {
+                trace::debug!("Command {command:04X}/{subcommand:04X} is not supported, falling back to batch reads: {err}");
+                self.mark_unsupported(command, subcommand);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Reader as _,
+        frame::{EndCode, Response},
+    };
+    use async_trait::async_trait;
+    use std::io;
+
+    /// 只支持批量读取的 PLC，回送测试超时
+    #[derive(Debug)]
+    struct Plc;
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(_, _) => Ok(Response::ReadU8s(vec![0, 0])),
+                Request::ReadBits(_, _) => Ok(Response::ReadBits(vec![false])),
+                Request::Command(0x0619, _, _) => Err(Error::Transport(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "timed out",
+                ))),
+                Request::Command(0x0101, _, _) => Ok(Response::Command(0x0101, 0, Vec::new())),
+                Request::Command(0x0801, _, _) => Err(Error::Protocol(ProtocolError::EndCode(
+                    EndCode::new(0xC051, 0x00, 0xFF, 0x03FF, 0x00),
+                ))),
+                Request::Command(_, _, _) => Err(Error::Protocol(ProtocolError::EndCode(
+                    EndCode::new(0xC059, 0x00, 0xFF, 0x03FF, 0x00),
+                ))),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn classifies_probe_results() {
+        let mut context = Context::new(Plc);
+        let capabilities = context.probe_capabilities().await;
+        assert_eq!(capabilities.entries.len(), PROBES.len());
+        assert!(capabilities.supports(0x0101, 0x0000));
+        assert!(capabilities.supports(0x0401, 0x0001));
+        assert!(!capabilities.supports(0x0403, 0x0000));
+        assert!(!capabilities.supports(0x0619, 0x0000));
+        assert!(!capabilities.supports(0x1401, 0x0000));
+        // 只有「不支持」的结束代码使高层接口不再发出该命令
+        assert!(!context.supports_command(0x0403, 0x0000));
+        assert!(!capabilities.supports(0x0801, 0x0000));
+        assert!(context.supports_command(0x0801, 0x0000));
+
+        let report = capabilities.to_string();
+        assert!(report.contains("0401/0000 batch read (words)"));
+        assert!(report.contains("random read                    rejected (end code C059)"));
+        assert!(report.contains("loopback test                  unknown ("));
+    }
+
+    /// 各系列的命令支持情况：每个字的值为其编号，记录每次请求的命令
+    #[derive(Debug)]
+    struct Target {
+        commands: &'static [u16],
+        /// 不支持的命令以 1E 帧的方式无法发出，而不是以结束代码拒绝
+        frame_1e: bool,
+        /// 登记的监视点
+        monitor: Vec<u8>,
+        sent: Vec<u16>,
+    }
+
+    impl Target {
+        fn new(commands: &'static [u16], frame_1e: bool) -> Self {
+            Self {
+                commands,
+                frame_1e,
+                monitor: Vec::new(),
+                sent: Vec::new(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Client for Target {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let words = |entries: &[u8], size: usize| -> Vec<u8> {
+                entries
+                    .chunks(size)
+                    .flat_map(|entry| {
+                        let number = u16::from_le_bytes([entry[0], entry[1]]);
+                        let count = if size == 6 { entry[4] as u16 } else { 1 };
+                        (number..number + count).flat_map(u16::to_le_bytes)
+                    })
+                    .collect()
+            };
+            match request {
+                Request::ReadU8s(address, count) => {
+                    self.sent.push(0x0401);
+                    let number: u16 = address[1..].parse().unwrap();
+                    Ok(Response::ReadU8s(
+                        (number..number + count as u16)
+                            .flat_map(u16::to_le_bytes)
+                            .collect(),
+                    ))
+                }
+                Request::Command(command, _, _) if !self.commands.contains(&command) => {
+                    if self.frame_1e {
+                        return Err(Error::Protocol(ProtocolError::NotImplemented));
+                    }
+                    self.sent.push(command);
+                    Err(Error::Protocol(ProtocolError::EndCode(EndCode::new(
+                        0xC059, 0x00, 0xFF, 0x03FF, 0x00,
+                    ))))
+                }
+                Request::Command(command, subcommand, data) => {
+                    self.sent.push(command);
+                    let data = match command {
+                        0x0403 => words(&data[2..], 4),
+                        0x0406 => words(&data[2..], 6),
+                        0x0801 => {
+                            self.monitor = data[2..].to_vec();
+                            Vec::new()
+                        }
+                        0x0802 => words(&self.monitor, 4),
+                        _ => unreachable!(),
+                    };
+                    Ok(Response::Command(command, subcommand, data))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn helpers_degrade_to_batch_reads() {
+        // FX（1E 帧）、Q（不支持监视）、iQ-R（全部支持）：每轮依次随机读取 2 点、多块读取、登记并监视，
+        // 以结束代码拒绝的命令只在第一轮发出一次
+        let targets: [(Target, [&[u16]; 2]); 3] = [
+            (Target::new(&[], true), [&[0x0401; 4], &[0x0401; 4]]),
+            (
+                Target::new(&[0x0403, 0x0406], false),
+                [&[0x0403, 0x0406, 0x0801, 0x0403], &[0x0403, 0x0406, 0x0403]],
+            ),
+            (
+                Target::new(&[0x0403, 0x0406, 0x0801, 0x0802], false),
+                [&[0x0403, 0x0406, 0x0801, 0x0802]; 2],
+            ),
+        ];
+        for (target, rounds) in targets {
+            let mut context = Context::new(target);
+            for expected in rounds {
+                context.client.sent.clear();
+                assert_eq!(
+                    context.read_random(&["D7", "D300"]).await.unwrap(),
+                    [7, 300]
+                );
+                assert_eq!(
+                    context.read_blocks(&[("D10", 2)]).await.unwrap(),
+                    [vec![10, 11]]
+                );
+                let monitor = context.register_monitor(&["D5"]).await.unwrap();
+                assert_eq!(monitor.read(&mut context).await.unwrap(), [5]);
+                assert_eq!(context.client.sent, expected);
+            }
+        }
+
+        // 已配置为不支持的命令不发出
+        let mut context = Context::new(Target::new(&[], false));
+        context.mark_unsupported(0x0403, 0x0000);
+        assert!(!context.supports_command(0x0403, 0x0000));
+        context.read_random(&["D1"]).await.unwrap();
+        assert_eq!(context.client.sent, [0x0401]);
+    }
+}
@@ -0,0 +1,210 @@
+//! 软元件区域的校验和
+//!
+//! 部署工具确认配方区域是否与下发时一致时，不必每次导出整个区域再逐字比较：
+//! [`Context::area_crc`] 以最大块读取区域并在本地计算 CRC-32，只需保存一行基准，
+//! 之后由 [`Context::verify_area`] 重新计算并与基准比较。
+//!
+//! 基准的文本格式与导出文件的区域头一致，末尾追加 8 位十六进制的 CRC：
+//!
+//! ```text
+//! @D100 1000 1A2B3C4D
+//! ```
+//!
+//! CRC 为 CRC-32（IEEE 802.3），按小端字节序依次计算每个字。
+
+use std::{fmt, ops::Range, str::FromStr};
+
+use crate::{
+    frame::{ProtocolError, Request},
+    Error,
+};
+
+use super::{
+    area::{read_words, DumpPlan},
+    Client, Context,
+};
+
+/// CRC-32 查找表，多项式 0xEDB88320（反射）
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 逐块累计的 CRC-32
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        Self(!0)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC_TABLE[((self.0 ^ byte as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    pub(crate) fn update_words(&mut self, words: &[u16]) {
+        for word in words {
+            self.update(&word.to_le_bytes());
+        }
+    }
+
+    /// 结束计算，得到 `plan` 覆盖区域的校验和
+    pub(crate) fn finish(self, plan: &DumpPlan) -> AreaChecksum {
+        AreaChecksum {
+            address: plan.address(),
+            words: plan.words(),
+            crc: !self.0,
+        }
+    }
+}
+
+/// 区域的校验和，可以保存为基准，见[模块文档](self)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AreaChecksum {
+    /// 起始地址
+    pub address: String,
+    /// 字数
+    pub words: usize,
+    pub crc: u32,
+}
+
+impl fmt::Display for AreaChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{} {} {:08X}", self.address, self.words, self.crc)
+    }
+}
+
+impl FromStr for AreaChecksum {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || {
+            Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "invalid area checksum {s:?}"
+            )))
+        };
+        let mut fields = s.trim().strip_prefix('@').ok_or_else(invalid)?.split(' ');
+        let (Some(address), Some(words), Some(crc), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        let plan = DumpPlan::at(address, words.parse().map_err(|_| invalid())?)?;
+        Ok(Self {
+            address: plan.address(),
+            words: plan.words(),
+            crc: u32::from_str_radix(crc, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl<T: Client> Context<T> {
+    /// 以最大块读取 `prefix` 软元件 `range` 范围并计算校验和
+    ///
+    /// 地址规则与 [`Self::dump_area`] 相同。
+    pub async fn area_crc(
+        &mut self,
+        prefix: &str,
+        range: Range<u32>,
+    ) -> Result<AreaChecksum, Error> {
+        self.checksum(DumpPlan::new(prefix, range)?).await
+    }
+
+    /// 重新计算 `baseline` 覆盖区域的校验和，返回是否与基准一致
+    pub async fn verify_area(&mut self, baseline: &AreaChecksum) -> Result<bool, Error> {
+        let plan = DumpPlan::at(&baseline.address, baseline.words)?;
+        Ok(self.checksum(plan).await?.crc == baseline.crc)
+    }
+
+    async fn checksum(&mut self, plan: DumpPlan) -> Result<AreaChecksum, Error> {
+        let mut crc = Crc32::new();
+        for (address, count) in plan.blocks() {
+            let response = self
+                .client
+                .call(Request::ReadU8s(address.into(), count))
+                .await?;
+            crc.update_words(&read_words(response));
+        }
+        Ok(crc.finish(&plan))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convert::words_to_bytes, frame::Response};
+    use async_trait::async_trait;
+
+    /// 每个字的值为其编号加上 `offset`，记录每次读取
+    #[derive(Debug, Default)]
+    struct Plc {
+        offset: u16,
+        calls: Vec<(String, u32)>,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let Request::ReadU8s(address, count) = request else {
+                unreachable!()
+            };
+            self.calls.push((address.to_string(), count));
+            let start: u16 = address[1..].parse().unwrap();
+            let words: Vec<u16> = (0..count as u16).map(|i| start + i + self.offset).collect();
+            Ok(Response::ReadU8s(words_to_bytes(&words)))
+        }
+    }
+
+    #[test]
+    fn computes_crc32() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        let plan = DumpPlan::new("D", 0..1).unwrap();
+        assert_eq!(crc.finish(&plan).crc, 0xCBF4_3926);
+    }
+
+    #[tokio::test]
+    async fn verifies_against_a_stored_baseline() {
+        let mut context = Context::new(Plc::default());
+        let baseline = context.area_crc("D", 100..1100).await.unwrap();
+        assert_eq!(
+            context.client.calls,
+            [("D100".to_string(), 960), ("D1060".to_string(), 40)]
+        );
+
+        let mut expected = Crc32::new();
+        expected.update_words(&(100..1100).collect::<Vec<u16>>());
+        let plan = DumpPlan::new("D", 100..1100).unwrap();
+        assert_eq!(baseline, expected.finish(&plan));
+
+        // 基准以文本保存后读回
+        let text = baseline.to_string();
+        assert!(text.starts_with("@D100 1000 "));
+        let stored: AreaChecksum = text.parse().unwrap();
+        assert_eq!(stored, baseline);
+        assert!(context.verify_area(&stored).await.unwrap());
+
+        context.client.offset = 1;
+        assert!(!context.verify_area(&stored).await.unwrap());
+        assert!("@D100 1000".parse::<AreaChecksum>().is_err());
+        assert!("@D100 0 00000000".parse::<AreaChecksum>().is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! 周期采集与本地影子数据
+//!
+//! [`Collector`] 维护一组登记的软元件（按字访问，位软元件每个字覆盖 16 点），
+//! 每次 [`Collector::refresh`] 时优先使用监视登记（0801）与监视（0802）命令一次取回全部数据；
+//! PLC 不支持监视命令或登记点数超出上限时，改为把相邻编号合并成块逐块读取。
+//! 读取结果保存在本地，[`Collector::get`] 返回最近一次的值及其采集时间。
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    convert::bytes_to_words,
+    frame::{find_instruction_code, ProtocolError, Quantity, Request},
+    trace, Error,
+};
+
+use super::{
+    area::{plan_reads, Device, ReadBlock},
+    capability::is_unsupported,
+    diagnostics::command_data,
+    monitor::{MONITOR, MONITOR_MAX_POINTS, MONITOR_REGISTER},
+    scatter::encode_word_entries,
+    view::response_words,
+    Client, Context,
+};
+
+/// 采集方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectMode {
+    /// 监视登记后以监视命令一次读取
+    Monitor,
+    /// 按块读取
+    BlockRead,
+}
+
+/// 一个软元件字的采集结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub value: u16,
+    /// 采集时间
+    pub updated: Instant,
+}
+
+impl Sample {
+    /// 距采集时刻经过的时间
+    pub fn age(&self) -> Duration {
+        self.updated.elapsed()
+    }
+}
+
+/// 登记的一个字：软元件与起始编号
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    device: Device,
+    number: u32,
+}
+
+impl Point {
+    fn key(&self) -> (&'static str, u32) {
+        (self.device.prefix(), self.number)
+    }
+}
+
+/// 周期采集客户端，见[模块文档](self)
+#[derive(Debug)]
+pub struct Collector<T: Client> {
+    context: Context<T>,
+    points: Vec<Point>,
+    mode: Option<CollectMode>,
+    blocks: Vec<ReadBlock>,
+    shadow: HashMap<(&'static str, u32), Sample>,
+}
+
+impl<T: Client> Collector<T> {
+    pub fn new(context: Context<T>) -> Self {
+        Self {
+            context,
+            points: Vec::new(),
+            mode: None,
+            blocks: Vec::new(),
+            shadow: HashMap::new(),
+        }
+    }
+
+    /// 登记一个字，地址按 PLC 型号转换；位软元件的编号须为 16 的倍数
+    ///
+    /// 登记后下一次 [`Self::refresh`] 会重新选择采集方式。
+    pub fn register<A>(&mut self, addr: &A) -> Result<(), Error>
+    where
+        A: AsRef<str> + ?Sized,
+    {
+        let point = self.point(addr)?;
+        if point.device.is_bit() && !point.number.is_multiple_of(16) {
+            return Err(Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "{} must start at a multiple of 16 points",
+                addr.as_ref()
+            ))));
+        }
+        if !self.points.iter().any(|p| p.key() == point.key()) {
+            self.points.push(point);
+            self.mode = None;
+        }
+        Ok(())
+    }
+
+    /// 当前的采集方式，首次 [`Self::refresh`] 之前为 `None`
+    pub fn mode(&self) -> Option<CollectMode> {
+        self.mode
+    }
+
+    /// 最近一次采集到的值，未登记或尚未采集成功时为 `None`
+    pub fn get<A>(&self, addr: &A) -> Option<Sample>
+    where
+        A: AsRef<str> + ?Sized,
+    {
+        let point = self.point(addr).ok()?;
+        self.shadow.get(&point.key()).copied()
+    }
+
+    /// 采集一次全部登记的软元件
+    ///
+    /// 块读取时某一块失败会继续读取其余的块，并返回第一个错误；
+    /// 失败的软元件保留上次的值，其 [`Sample::age`] 随之增长。
+    pub async fn refresh(&mut self) -> Result<(), Error> {
+        if self.points.is_empty() {
+            return Ok(());
+        }
+        if self.mode.is_none() {
+            self.select_mode().await?;
+        }
+        match self.mode {
+            Some(CollectMode::Monitor) => self.refresh_monitor().await,
+            _ => self.refresh_blocks().await,
+        }
+    }
+
+    pub fn context(&self) -> &Context<T> {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context<T> {
+        &mut self.context
+    }
+
+    pub fn into_inner(self) -> Context<T> {
+        self.context
+    }
+
+    fn point<A>(&self, addr: &A) -> Result<Point, Error>
+    where
+        A: AsRef<str> + ?Sized,
+    {
+        let (device, number) = Device::parse(&self.context.process_address(addr)?)?;
+        Ok(Point { device, number })
+    }
+
+    /// 尝试监视登记，PLC 以结束代码拒绝或已知不支持时改用块读取
+    async fn select_mode(&mut self) -> Result<(), Error> {
+        if self.points.len() <= MONITOR_MAX_POINTS
+            && self.context.supports_command(MONITOR_REGISTER, 0x0000)
+        {
+            match self
+                .context
+                .client
+                .call(Request::Command(
+                    MONITOR_REGISTER,
+                    0x0000,
+                    monitor_entries(&self.points).into(),
+                ))
+                .await
+            {
+                Ok(_) => {
+                    self.mode = Some(CollectMode::Monitor);
+                    return Ok(());
+                }
+                Err(err @ Error::Protocol(_)) => {
+                    if is_unsupported(&err) {
+                        self.context.mark_unsupported(MONITOR_REGISTER, 0x0000);
+                    }
+                    trace::debug!("Monitor registration rejected, using block reads: {err}");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        let spans: Vec<_> = self
+            .points
+            .iter()
+            .map(|point| (point.device, point.number, 1))
+            .collect();
+        self.blocks = plan_reads(&spans);
+        self.mode = Some(CollectMode::BlockRead);
+        Ok(())
+    }
+
+    async fn refresh_monitor(&mut self) -> Result<(), Error> {
+        let response = match self
+            .context
+            .client
+            .call(Request::Command(MONITOR, 0x0000, Vec::new().into()))
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                // PLC 重启等情况下登记会丢失，下次重新登记
+                if let Error::Protocol(_) = err {
+                    self.mode = None;
+                }
+                return Err(err);
+            }
+        };
+        let words = bytes_to_words(&command_data(response));
+        if words.len() < self.points.len() {
+            return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: self.points.len() * 2,
+                actual: words.len() * 2,
+            }));
+        }
+        let updated = Instant::now();
+        for (point, value) in self.points.iter().zip(words) {
+            self.shadow.insert(point.key(), Sample { value, updated });
+        }
+        Ok(())
+    }
+
+    async fn refresh_blocks(&mut self) -> Result<(), Error> {
+        let mut first_error = None;
+        for block in &self.blocks {
+            let request = Request::ReadU8s(
+                block.device.address(block.start).into(),
+                block.words as Quantity,
+            );
+            match self.context.client.call(request).await {
+                Ok(response) => {
+                    let words = response_words(response);
+                    let updated = Instant::now();
+                    for &(index, offset) in &block.spans {
+                        if let Some(&value) = words.get(offset as usize) {
+                            let key = self.points[index].key();
+                            self.shadow.insert(key, Sample { value, updated });
+                        }
+                    }
+                }
+                Err(err) => {
+                    trace::warning!(
+                        address = block.device.address(block.start);
+                        "Block read failed: {err}"
+                    );
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+/// 监视登记的数据，格式同随机读取
+fn monitor_entries(points: &[Point]) -> Vec<u8> {
+    let entries: Vec<(u32, u8)> = points
+        .iter()
+        .map(|point| {
+            let (code, _) = find_instruction_code(point.device.prefix())
+                .expect("registered devices have a device code");
+            (point.number, code)
+        })
+        .collect();
+    encode_word_entries(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{EndCode, Response};
+    use async_trait::async_trait;
+
+    /// 记录收到的请求，`monitor` 为假时以结束代码拒绝监视登记
+    #[derive(Debug)]
+    struct Plc {
+        monitor: bool,
+        requests: Vec<String>,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.requests.push(format!("{request:?}"));
+            match request {
+                Request::Command(MONITOR_REGISTER, _, data) if self.monitor => {
+                    assert_eq!(data[..2], [3, 0]);
+                    Ok(Response::Command(MONITOR_REGISTER, 0, Vec::new()))
+                }
+                Request::Command(MONITOR_REGISTER, _, _) => Err(Error::Protocol(
+                    ProtocolError::EndCode(EndCode::new(0xC059, 0x00, 0xFF, 0x03FF, 0x00)),
+                )),
+                Request::Command(MONITOR, _, _) => {
+                    Ok(Response::Command(MONITOR, 0, vec![1, 0, 2, 0, 3, 0]))
+                }
+                Request::ReadU8s(addr, cnt) => {
+                    let base: u16 = if addr.starts_with('D') { 100 } else { 0xF0 };
+                    Ok(Response::ReadU8s(
+                        (0..cnt as u16)
+                            .flat_map(|i| (base + i).to_le_bytes())
+                            .collect(),
+                    ))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn collector(monitor: bool) -> Collector<Plc> {
+        let mut collector = Collector::new(Context::new(Plc {
+            monitor,
+            requests: Vec::new(),
+        }));
+        for addr in ["D100", "D103", "M16"] {
+            collector.register(addr).unwrap();
+        }
+        collector
+    }
+
+    #[tokio::test]
+    async fn collects_with_monitor_commands() {
+        let mut collector = collector(true);
+        assert!(collector.get("D100").is_none());
+        collector.refresh().await.unwrap();
+        collector.refresh().await.unwrap();
+
+        assert_eq!(collector.mode(), Some(CollectMode::Monitor));
+        assert_eq!(collector.get("D103").unwrap().value, 2);
+        assert_eq!(collector.get("M16").unwrap().value, 3);
+        assert!(collector.get("D101").is_none());
+        // 登记一次，之后每次一个监视命令
+        assert_eq!(collector.context().client.requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_block_reads() {
+        let mut collector = collector(false);
+        assert!(collector.register("M3").is_err());
+        collector.refresh().await.unwrap();
+
+        assert_eq!(collector.mode(), Some(CollectMode::BlockRead));
+        let sample = collector.get("D103").unwrap();
+        assert_eq!(sample.value, 103);
+        assert!(sample.age() < Duration::from_secs(60));
+        assert_eq!(collector.get("M16").unwrap().value, 0xF0);
+        // D100 与 D103 合并为一块
+        let requests = &collector.context().client.requests;
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains("\"D100\", 4"));
+    }
+}
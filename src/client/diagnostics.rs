@@ -0,0 +1,215 @@
+//! PLC 诊断信息汇总
+//!
+//! 将 CPU 型号读取（0101）、回送测试（0619）以及特殊寄存器 SD0（最新自诊断错误代码）、
+//! SD203（CPU 运行状态）的读取合并为一次调用。各项相互独立，单项失败不影响其他项。
+//!
+//! 错误履历并非所有系列都能通过 MC 协议命令读取，因此不在汇总范围内；
+//! 需要时可按所用系列的手册读取对应的 SD 寄存器。
+
+use crate::{
+    convert::bytes_to_words,
+    frame::{ProtocolError, Request, Response},
+    Error,
+};
+
+use super::{Client, Context};
+
+/// CPU 型号读取命令
+const READ_CPU_MODEL: u16 = 0x0101;
+/// 回送测试命令
+const LOOPBACK_TEST: u16 = 0x0619;
+/// 型号名称的 ASCII 字符数
+const MODEL_NAME_LEN: usize = 16;
+/// 回送数据的最大字符数
+const LOOPBACK_MAX: usize = 960;
+/// 诊断时使用的回送数据
+pub(crate) const LOOPBACK_DATA: &str = "0123456789ABCDEF";
+
+/// CPU 型号读取（0101）的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuModel {
+    /// 型号名称，已去除末尾空格，如 `Q03UDVCPU`
+    pub name: String,
+    /// 型号代码
+    pub code: u16,
+}
+
+impl CpuModel {
+    /// 解析 0101 命令的响应数据：16 字节 ASCII 型号名称 + 2 字节型号代码
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < MODEL_NAME_LEN + 2 {
+            return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: MODEL_NAME_LEN + 2,
+                actual: data.len(),
+            }));
+        }
+        let name = String::from_utf8_lossy(&data[..MODEL_NAME_LEN])
+            .trim_end_matches([' ', '\0'])
+            .to_string();
+        let code = u16::from_le_bytes([data[MODEL_NAME_LEN], data[MODEL_NAME_LEN + 1]]);
+        Ok(Self { name, code })
+    }
+}
+
+/// [`Context::diagnostics`] 返回的诊断报告，每一项单独记录读取结果
+#[derive(Debug)]
+pub struct PlcHealth {
+    /// CPU 型号
+    pub model: Result<CpuModel, Error>,
+    /// SD203 的原始值，低 4 位为运行状态，编码因系列而异：
+    /// Q/L 系列 0=RUN、1=STOP、2=PAUSE，iQ-R 系列 0=RUN、2=STOP、3=PAUSE
+    pub operating_status: Result<u16, Error>,
+    /// SD0 中的最新自诊断错误代码，0 表示无错误
+    pub error_code: Result<u16, Error>,
+    /// 回送测试的数据是否原样返回
+    pub loopback: Result<bool, Error>,
+}
+
+impl PlcHealth {
+    /// 所有项均读取成功、无自诊断错误且回送测试通过
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.model.is_ok()
+            && self.operating_status.is_ok()
+            && matches!(self.error_code, Ok(0))
+            && matches!(self.loopback, Ok(true))
+    }
+}
+
+pub(crate) fn cpu_model_request() -> Request<'static> {
+    Request::Command(READ_CPU_MODEL, 0x0000, Vec::new().into())
+}
+
+/// 构造回送测试请求：2 字节字符数 + `0`-`9`、`A`-`F` 组成的 ASCII 数据
+pub(crate) fn loopback_request(data: &str) -> Result<Request<'static>, Error> {
+    let valid = data
+        .bytes()
+        .all(|b| b.is_ascii_digit() || (b'A'..=b'F').contains(&b));
+    if data.is_empty() || data.len() > LOOPBACK_MAX || !valid {
+        return Err(Error::Protocol(ProtocolError::OutOfRange));
+    }
+    let mut payload = (data.len() as u16).to_le_bytes().to_vec();
+    payload.extend_from_slice(data.as_bytes());
+    Ok(Request::Command(LOOPBACK_TEST, 0x0000, payload.into()))
+}
+
+pub(crate) fn command_data(response: Response) -> Vec<u8> {
+    match response {
+        Response::Command(_, _, data) => data,
+        _ => unreachable!("Unexpected response type, expected Command"),
+    }
+}
+
+/// 回送测试的响应是否与请求数据一致
+pub(crate) fn loopback_matches(data: &str, response: &[u8]) -> bool {
+    response.len() >= 2 && &response[2..] == data.as_bytes()
+}
+
+pub(crate) fn single_word(response: Response) -> Result<u16, Error> {
+    match response {
+        Response::ReadU8s(bytes) => bytes_to_words(&bytes)
+            .first()
+            .copied()
+            .ok_or(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: 2,
+                actual: bytes.len(),
+            })),
+        _ => unreachable!("Unexpected response type, expected ReadU8s"),
+    }
+}
+
+impl<T: Client> Context<T> {
+    /// 读取 CPU 型号（0101 命令）
+    pub async fn read_cpu_model(&mut self) -> Result<CpuModel, Error> {
+        let response = self.client.call(cpu_model_request()).await?;
+        CpuModel::decode(&command_data(response))
+    }
+
+    /// 回送测试（0619 命令），返回 PLC 是否原样返回 `data`
+    ///
+    /// `data` 须由 1 到 960 个 `0`-`9`、`A`-`F` 字符组成。
+    pub async fn loopback_test(&mut self, data: &str) -> Result<bool, Error> {
+        let response = self.client.call(loopback_request(data)?).await?;
+        Ok(loopback_matches(data, &command_data(response)))
+    }
+
+    /// 依次读取 CPU 型号、运行状态、自诊断错误代码并进行回送测试
+    ///
+    /// SD 寄存器地址不经过 PLC 型号的地址转换。
+    pub async fn diagnostics(&mut self) -> PlcHealth {
+        let model = self.read_cpu_model().await;
+        let operating_status = self.read_special_register("SD203").await;
+        let error_code = self.read_special_register("SD0").await;
+        let loopback = self.loopback_test(LOOPBACK_DATA).await;
+        PlcHealth {
+            model,
+            operating_status,
+            error_code,
+            loopback,
+        }
+    }
+
+    async fn read_special_register(&mut self, address: &'static str) -> Result<u16, Error> {
+        let response = self
+            .client
+            .call(Request::ReadU8s(address.into(), 1))
+            .await?;
+        single_word(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// 模拟一台 STOP 状态、SD0 为 0x1234 的 PLC，不支持回送测试
+    #[derive(Debug)]
+    struct Plc;
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::Command(READ_CPU_MODEL, 0, _) => {
+                    let mut data = b"Q03UDVCPU       ".to_vec();
+                    data.extend_from_slice(&0x0366u16.to_le_bytes());
+                    Ok(Response::Command(READ_CPU_MODEL, 0, data))
+                }
+                Request::Command(LOOPBACK_TEST, _, _) => {
+                    Err(Error::Protocol(ProtocolError::NotImplemented))
+                }
+                Request::ReadU8s(addr, 1) => match addr.as_ref() {
+                    "SD203" => Ok(Response::ReadU8s(vec![0x01, 0x00])),
+                    "SD0" => Ok(Response::ReadU8s(vec![0x34, 0x12])),
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn diagnostics_report() {
+        let mut context = Context::new(Plc);
+        let health = context.diagnostics().await;
+        assert_eq!(
+            health.model.as_ref().unwrap(),
+            &CpuModel {
+                name: "Q03UDVCPU".to_string(),
+                code: 0x0366
+            }
+        );
+        assert_eq!(health.operating_status.as_ref().unwrap(), &1);
+        assert_eq!(health.error_code.as_ref().unwrap(), &0x1234);
+        assert!(health.loopback.is_err());
+        assert!(!health.is_healthy());
+
+        assert!(loopback_request("0123abc").is_err());
+        let Request::Command(_, _, payload) = loopback_request("09AF").unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(payload.as_ref(), b"\x04\x0009AF");
+        assert!(loopback_matches("09AF", b"\x04\x0009AF"));
+    }
+}
@@ -0,0 +1,238 @@
+//! 写入整形与去重
+//!
+//! [`WriteGate`] 包装任意 [`Client`]，只处理写请求（`WriteU8s`、`WriteBits`），其他请求原样转发：
+//!
+//! - 去重窗口：同一地址在窗口内再次写入相同的值时直接返回成功，不发出请求；
+//! - 最小间隔：同一地址两次实际写入之间至少间隔指定时间，过早的写入会等待到期后再发出。
+//!
+//! 地址按请求中的起始地址区分，起始地址不同但范围重叠的写入互不影响。
+//! 写入失败时清除该地址的记录，重试不会被当作重复写入丢弃。
+
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use crate::{
+    frame::{Request, Response},
+    trace, Error,
+};
+
+use super::Client;
+
+/// 上一次实际发出的写入
+#[derive(Debug)]
+struct LastWrite {
+    value: Value,
+    sent: Instant,
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Bytes(Vec<u8>),
+    Bits(Vec<bool>),
+}
+
+/// 写入整形与去重的 [`Client`] 包装，见[模块文档](self)
+#[derive(Debug)]
+pub struct WriteGate<T> {
+    inner: T,
+    dedup_window: Option<Duration>,
+    min_interval: Option<Duration>,
+    last: HashMap<String, LastWrite>,
+    dropped: u64,
+}
+
+impl<T: Client> WriteGate<T> {
+    /// 不去重、不限速，需通过 [`Self::dedup_window`]、[`Self::min_interval`] 开启
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            dedup_window: None,
+            min_interval: None,
+            last: HashMap::new(),
+            dropped: 0,
+        }
+    }
+
+    /// 在 `window` 内丢弃同一地址相同值的重复写入
+    pub fn dedup_window(mut self, window: Duration) -> Self {
+        self.dedup_window = Some(window);
+        self
+    }
+
+    /// 同一地址两次写入之间的最小间隔
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = Some(interval);
+        self
+    }
+
+    /// 被去重丢弃的写入次数
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// 清除所有地址的写入记录
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    async fn write(
+        &mut self,
+        address: &str,
+        value: Value,
+        request: Request<'_>,
+    ) -> Result<Response, Error> {
+        if let Some(last) = self.last.get(address) {
+            let now = Instant::now();
+            if let Some(window) = self.dedup_window {
+                if last.value == value && now.duration_since(last.sent) < window {
+                    trace::debug!(address = address; "Dropping duplicate write");
+                    self.dropped += 1;
+                    return Ok(match request {
+                        Request::WriteBits(_, _) => Response::WriteBits(),
+                        _ => Response::WriteU8s(),
+                    });
+                }
+            }
+            if let Some(interval) = self.min_interval {
+                let earliest = last.sent + interval;
+                if earliest > now {
+                    trace::debug!(
+                        address = address,
+                        delay = earliest - now;
+                        "Delaying write to respect the minimum interval"
+                    );
+                    tokio::time::sleep_until(earliest).await;
+                }
+            }
+        }
+
+        match self.inner.call(request).await {
+            Ok(response) => {
+                let last = LastWrite {
+                    value,
+                    sent: Instant::now(),
+                };
+                self.last.insert(address.to_string(), last);
+                Ok(response)
+            }
+            Err(err) => {
+                self.last.remove(address);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Client> Client for WriteGate<T> {
+    async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        match &request {
+            Request::WriteU8s(address, bytes) => {
+                let address = address.to_string();
+                let value = Value::Bytes(bytes.to_vec());
+                self.write(&address, value, request).await
+            }
+            Request::WriteBits(address, bits) => {
+                let address = address.to_string();
+                let value = Value::Bits(bits.to_vec());
+                self.write(&address, value, request).await
+            }
+            _ => self.inner.call(request).await,
+        }
+    }
+
+    async fn disconnect(&mut self) -> std::io::Result<()> {
+        self.inner.disconnect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录每次写入的地址与发出时间
+    #[derive(Debug, Default)]
+    struct Plc {
+        writes: Vec<(String, Instant)>,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::WriteU8s(addr, _) => {
+                    self.writes.push((addr.to_string(), Instant::now()));
+                    Ok(Response::WriteU8s())
+                }
+                Request::WriteBits(addr, _) => {
+                    self.writes.push((addr.to_string(), Instant::now()));
+                    Ok(Response::WriteBits())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn write(address: &str, value: u8) -> Request<'static> {
+        Request::WriteU8s(address.to_string().into(), vec![value, 0].into())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn duplicates_are_dropped_within_window() {
+        let mut gate = WriteGate::new(Plc::default()).dedup_window(Duration::from_secs(1));
+        gate.call(write("D0", 1)).await.unwrap();
+        gate.call(write("D0", 1)).await.unwrap();
+        gate.call(write("D1", 1)).await.unwrap();
+        gate.call(write("D0", 2)).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        gate.call(write("D0", 2)).await.unwrap();
+        let bits = Request::WriteBits("M0".into(), vec![true].into());
+        assert_eq!(
+            gate.call(bits.clone()).await.unwrap(),
+            Response::WriteBits()
+        );
+        assert_eq!(gate.call(bits).await.unwrap(), Response::WriteBits());
+
+        assert_eq!(gate.dropped(), 2);
+        let addresses: Vec<_> = gate
+            .get_ref()
+            .writes
+            .iter()
+            .map(|(a, _)| a.as_str())
+            .collect();
+        assert_eq!(addresses, ["D0", "D1", "D0", "D0", "M0"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn writes_to_same_address_are_spaced() {
+        let mut gate = WriteGate::new(Plc::default()).min_interval(Duration::from_millis(100));
+        let start = Instant::now();
+        for value in 0..3 {
+            gate.call(write("D0", value)).await.unwrap();
+        }
+        gate.call(write("D1", 0)).await.unwrap();
+
+        let sent: Vec<_> = gate
+            .into_inner()
+            .writes
+            .into_iter()
+            .map(|(_, at)| at - start)
+            .collect();
+        let ms = Duration::from_millis;
+        assert_eq!(sent, [ms(0), ms(100), ms(200), ms(200)]);
+    }
+}
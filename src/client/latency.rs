@@ -0,0 +1,194 @@
+//! 请求耗时统计
+
+use std::{fmt, time::Duration};
+
+/// 每个二进制数量级内的线性子桶位数（8 个子桶，相对误差约 12.5%）
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+const BUCKETS: usize = SUB_BUCKETS + (64 - SUB_BUCKET_BITS as usize) * SUB_BUCKETS;
+
+/// HDR 风格的耗时直方图
+///
+/// 以微秒为单位记录，桶宽随数量级按 2 的幂增长，
+/// 因此在很大的取值范围内保持固定的相对精度，内存占用也固定。
+#[derive(Clone)]
+pub struct LatencyHistogram {
+    counts: Box<[u64; BUCKETS]>,
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: Box::new([0; BUCKETS]),
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// 记录一次耗时
+    pub fn record(&mut self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.counts[bucket_index(micros)] += 1;
+        self.count += 1;
+        self.sum += u128::from(micros);
+        self.min = self.min.min(micros);
+        self.max = self.max.max(micros);
+    }
+
+    /// 已记录的次数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 最小耗时，未记录时为 `None`
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.min))
+    }
+
+    /// 最大耗时，未记录时为 `None`
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.max))
+    }
+
+    /// 平均耗时，未记录时为 `None`
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| {
+            let mean = self.sum / u128::from(self.count);
+            Duration::from_micros(u64::try_from(mean).unwrap_or(u64::MAX))
+        })
+    }
+
+    /// 百分位耗时（`percentile` 取 0.0 ~ 100.0），返回所在桶的上界
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64;
+        let rank = rank.max(1);
+
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper = bucket_upper_bound(index).min(self.max);
+                return Some(Duration::from_micros(upper));
+            }
+        }
+        self.max()
+    }
+
+    /// 清空所有记录
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatencyHistogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("mean", &self.mean())
+            .field("p99", &self.percentile(99.0))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+/// 按阶段统计的请求耗时（需启用 `profiling` feature）
+///
+/// 每次逻辑操作在各阶段各记录一次：`io` 较大说明耗时在网络或 PLC，
+/// `encode`/`decode` 较大则说明耗时在本库。
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default)]
+pub struct PhaseLatency {
+    /// 请求编码（含分帧）
+    pub encode: LatencyHistogram,
+    /// 发送所有分帧并接收响应，包括网络传输与 PLC 处理时间
+    pub io: LatencyHistogram,
+    /// 响应合并与解码
+    pub decode: LatencyHistogram,
+}
+
+#[cfg(feature = "profiling")]
+impl PhaseLatency {
+    /// 清空所有阶段的记录
+    pub fn reset(&mut self) {
+        self.encode.reset();
+        self.io.reset();
+        self.decode.reset();
+    }
+}
+
+fn bucket_index(micros: u64) -> usize {
+    if micros < SUB_BUCKETS as u64 {
+        return micros as usize;
+    }
+    let magnitude = 63 - micros.leading_zeros();
+    let shift = magnitude - SUB_BUCKET_BITS;
+    let sub_bucket = ((micros >> shift) as usize) & (SUB_BUCKETS - 1);
+    SUB_BUCKETS + shift as usize * SUB_BUCKETS + sub_bucket
+}
+
+fn bucket_upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+    let shift = (index - SUB_BUCKETS) / SUB_BUCKETS;
+    let sub_bucket = ((index - SUB_BUCKETS) % SUB_BUCKETS) as u64;
+    let lower = (SUB_BUCKETS as u64 + sub_bucket) << shift;
+    lower + ((1u64 << shift) - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentile(50.0), None);
+
+        for millis in 1..=100 {
+            histogram.record(Duration::from_millis(millis));
+        }
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.min(), Some(Duration::from_millis(1)));
+        assert_eq!(histogram.max(), Some(Duration::from_millis(100)));
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(50_500)));
+
+        // 桶的相对误差不超过 1/8
+        let p50 = histogram.percentile(50.0).unwrap().as_micros() as f64;
+        assert!((50_000.0..=50_000.0 * 1.125).contains(&p50));
+        assert_eq!(
+            histogram.percentile(100.0),
+            Some(Duration::from_millis(100))
+        );
+
+        histogram.reset();
+        assert_eq!(histogram.count(), 0);
+    }
+
+    #[test]
+    fn bucket_bounds_cover_values() {
+        for micros in [0, 7, 8, 9, 15, 16, 1_000, 123_456, u64::MAX] {
+            let index = bucket_index(micros);
+            assert!(index < BUCKETS);
+            assert!(bucket_upper_bound(index) >= micros);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! 负载生成
+//!
+//! 按 [`LoadProfile`] 配置的读写组合与目标速率持续发送请求，统计吞吐量与读、写各自的耗时分布，
+//! 用于对本库的服务端或实际 PLC 做长时间压力测试。各操作按权重以平滑加权轮询交错发出，
+//! 结果可重复，不依赖随机数。
+
+use std::{fmt, time::Duration};
+
+use tokio::time::{interval, Instant, MissedTickBehavior};
+
+use crate::{frame::Quantity, trace};
+
+use super::{Client, Context, LatencyHistogram, Reader as _, Writer as _};
+
+/// 一种负载操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// 从 `address` 读取 `count` 个字
+    Read { address: String, count: Quantity },
+    /// 向 `address` 写入 `count` 个字，值为当前的请求序号
+    Write { address: String, count: Quantity },
+}
+
+/// 负载配置
+#[derive(Debug, Clone)]
+pub struct LoadProfile {
+    operations: Vec<(Operation, u32)>,
+    rate: Option<f64>,
+    duration: Duration,
+}
+
+impl LoadProfile {
+    /// 持续 `duration` 的负载，默认不限速率
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            operations: Vec::new(),
+            rate: None,
+            duration,
+        }
+    }
+
+    /// 目标速率（每秒请求数）；连接跟不上时不补发，实际速率见 [`LoadReport::throughput`]
+    #[must_use]
+    pub fn rate(mut self, per_second: f64) -> Self {
+        self.rate = (per_second > 0.0).then_some(per_second);
+        self
+    }
+
+    /// 添加权重为 `weight` 的读取操作
+    #[must_use]
+    pub fn read(self, address: impl Into<String>, count: Quantity, weight: u32) -> Self {
+        self.operation(
+            Operation::Read {
+                address: address.into(),
+                count,
+            },
+            weight,
+        )
+    }
+
+    /// 添加权重为 `weight` 的写入操作
+    #[must_use]
+    pub fn write(self, address: impl Into<String>, count: Quantity, weight: u32) -> Self {
+        self.operation(
+            Operation::Write {
+                address: address.into(),
+                count,
+            },
+            weight,
+        )
+    }
+
+    /// 添加权重为 `weight` 的操作，权重为 0 的操作被忽略
+    #[must_use]
+    pub fn operation(mut self, operation: Operation, weight: u32) -> Self {
+        if weight > 0 {
+            self.operations.push((operation, weight));
+        }
+        self
+    }
+}
+
+/// 负载测试结果
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// 成功的读取耗时
+    pub reads: LatencyHistogram,
+    /// 成功的写入耗时
+    pub writes: LatencyHistogram,
+    /// 失败的请求数
+    pub errors: u64,
+    /// 实际运行时间
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    /// 发出的请求总数，含失败的请求
+    pub fn requests(&self) -> u64 {
+        self.reads.count() + self.writes.count() + self.errors
+    }
+
+    /// 每秒完成的请求数
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.requests() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 一行汇总与读、写各一行的耗时百分位
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "requests {} in {:.1?} ({:.1}/s), errors {}",
+            self.requests(),
+            self.elapsed,
+            self.throughput(),
+            self.errors
+        )?;
+        for (name, histogram) in [("reads", &self.reads), ("writes", &self.writes)] {
+            write!(f, "{name:<6} {:>8}", histogram.count())?;
+            if histogram.count() > 0 {
+                for percentile in [50.0, 90.0, 99.0] {
+                    let latency = histogram.percentile(percentile).unwrap_or_default();
+                    write!(f, "  p{percentile}={latency:.1?}")?;
+                }
+                write!(f, "  max={:.1?}", histogram.max().unwrap_or_default())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// 按 `profile` 向 `context` 发送负载，直到运行时间用完
+///
+/// 单个请求失败只计入 [`LoadReport::errors`]，不会中止测试。
+pub async fn run<T: Client>(context: &mut Context<T>, profile: &LoadProfile) -> LoadReport {
+    let mut report = LoadReport::default();
+    if profile.operations.is_empty() {
+        return report;
+    }
+
+    let mut ticker = profile.rate.map(|rate| {
+        let mut ticker = interval(Duration::from_secs_f64(1.0 / rate));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker
+    });
+    let mut schedule = Schedule::new(&profile.operations);
+    let started = Instant::now();
+    let mut sequence: u16 = 0;
+
+    loop {
+        if let Some(ticker) = &mut ticker {
+            ticker.tick().await;
+        }
+        if started.elapsed() >= profile.duration {
+            break;
+        }
+        let operation = &profile.operations[schedule.next()].0;
+        sequence = sequence.wrapping_add(1);
+
+        let start = Instant::now();
+        let (result, histogram) = match operation {
+            Operation::Read { address, count } => (
+                context.read_u16s(address, *count).await.map(drop),
+                &mut report.reads,
+            ),
+            Operation::Write { address, count } => (
+                context
+                    .write_u16s(address, &vec![sequence; *count as usize])
+                    .await,
+                &mut report.writes,
+            ),
+        };
+        match result {
+            Ok(()) => histogram.record(start.elapsed()),
+            Err(err) => {
+                trace::debug!("Load request {operation:?} failed: {err}");
+                report.errors += 1;
+            }
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    report
+}
+
+/// 平滑加权轮询：权重 5:1 时依次为 A A A B A A 这样的交错序列
+struct Schedule {
+    weights: Vec<i64>,
+    current: Vec<i64>,
+    total: i64,
+}
+
+impl Schedule {
+    fn new(operations: &[(Operation, u32)]) -> Self {
+        let weights: Vec<i64> = operations.iter().map(|&(_, w)| i64::from(w)).collect();
+        Self {
+            total: weights.iter().sum(),
+            current: vec![0; weights.len()],
+            weights,
+        }
+    }
+
+    fn next(&mut self) -> usize {
+        for (current, weight) in self.current.iter_mut().zip(&self.weights) {
+            *current += weight;
+        }
+        let (index, _) = self
+            .current
+            .iter()
+            .enumerate()
+            .max_by_key(|&(index, &current)| (current, std::cmp::Reverse(index)))
+            .expect("schedule has at least one operation");
+        self.current[index] -= self.total;
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        frame::{Request, Response},
+        Error,
+    };
+    use async_trait::async_trait;
+
+    /// 统计读写次数，写入 D9 时失败
+    #[derive(Debug, Default)]
+    struct Counter {
+        reads: usize,
+        writes: usize,
+    }
+
+    #[async_trait]
+    impl Client for Counter {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(_, count) => {
+                    self.reads += 1;
+                    Ok(Response::ReadU8s(vec![0; count as usize * 2]))
+                }
+                Request::WriteU8s(address, _) if address == "D9" => {
+                    Err(Error::Protocol(crate::frame::ProtocolError::OutOfRange))
+                }
+                Request::WriteU8s(_, _) => {
+                    self.writes += 1;
+                    Ok(Response::WriteU8s())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn schedule_interleaves_by_weight() {
+        let profile = LoadProfile::new(Duration::ZERO)
+            .read("D0", 1, 5)
+            .write("D1", 1, 1)
+            .read("D2", 1, 0);
+        let mut schedule = Schedule::new(&profile.operations);
+        let order: Vec<usize> = (0..12).map(|_| schedule.next()).collect();
+        assert_eq!(order, [0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_at_target_rate() {
+        let profile = LoadProfile::new(Duration::from_millis(100))
+            .rate(100.0)
+            .read("D0", 4, 2)
+            .write("D1", 2, 1)
+            .write("D9", 1, 1);
+        let mut context = Context::new(Counter::default());
+        let report = run(&mut context, &profile).await;
+
+        // 暂停的时钟下每 10 毫秒一个请求
+        assert_eq!(report.requests(), 10);
+        assert_eq!(report.reads.count(), context.client.reads as u64);
+        assert_eq!(report.writes.count(), context.client.writes as u64);
+        assert_eq!(report.reads.count(), 5);
+        assert!(report.errors > 0);
+        assert!(report.to_string().starts_with("requests 10 in"));
+    }
+}
@@ -1,13 +1,203 @@
+mod area;
+pub mod audit;
+pub mod block;
+mod cache;
+pub mod capability;
+pub mod checksum;
+pub mod collector;
+mod diagnostics;
+pub mod gate;
+mod latency;
+pub mod loadgen;
+pub mod monitor;
+mod packed;
+pub mod policy;
+mod remote;
+#[cfg(feature = "tcp")]
+pub mod scan;
+pub mod scatter;
+pub mod sequence;
+pub mod shared;
+pub mod snapshot;
 #[cfg(feature = "sync")]
 pub mod sync;
 #[cfg(feature = "tcp")]
 pub mod tcp;
+mod timer;
+pub mod transaction;
+#[cfg(feature = "transcript")]
+pub mod transcript;
+mod url;
+mod view;
+
+pub use self::{
+    audit::WriteAudit,
+    block::BlockView,
+    capability::{Capabilities, Capability, Support},
+    checksum::AreaChecksum,
+    collector::{CollectMode, Collector, Sample},
+    diagnostics::{CpuModel, PlcHealth},
+    gate::WriteGate,
+    latency::LatencyHistogram,
+    monitor::Monitor,
+    policy::{AccessGuard, AccessPolicy, DeviceRange, READ_ONLY_COMMANDS},
+    remote::ClearMode,
+    sequence::{Sequence, Step},
+    shared::SharedClient,
+    snapshot::{Snapshot, SnapshotItem},
+    timer::{Timer, TokioTimer},
+    transaction::Transaction,
+    url::ConnectOptions,
+};
+
+#[cfg(feature = "profiling")]
+pub use self::latency::PhaseLatency;
+#[cfg(feature = "transcript")]
+pub use self::transcript::Transcript;
 
 use async_trait::async_trait;
-use std::{borrow::Cow, fmt::Debug};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug},
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::frame::*;
-use crate::Error;
+use crate::{trace, Error};
+
+/// 逻辑操作 ID
+///
+/// 一次用户级调用（如 `read_u16s`）可能被拆分为多个请求帧，
+/// 这些帧产生的日志事件都携带同一个 ID，便于关联追踪。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperationId(u64);
+
+impl OperationId {
+    /// 生成一个进程内唯一的新 ID
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// 获取 ID 的数值
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for OperationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08X}", self.0)
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+use bytemuck::Pod;
+
+#[cfg(not(feature = "bytemuck"))]
+trait Pod {}
+
+#[cfg(not(feature = "bytemuck"))]
+impl<T> Pod for T {}
+
+/// 可由小端字节序数据直接构造的数值类型
+trait LeBytes: Pod + Sized {
+    #[cfg_attr(
+        all(feature = "bytemuck", target_endian = "little", not(test)),
+        allow(dead_code)
+    )]
+    fn from_le_chunk(chunk: &[u8]) -> Self;
+}
+
+macro_rules! impl_le_bytes {
+    ($($ty:ty),+) => {
+        $(
+            impl LeBytes for $ty {
+                fn from_le_chunk(chunk: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(chunk.try_into().expect("chunk size"))
+                }
+            }
+        )+
+    };
+}
+
+impl_le_bytes!(u16, i16, u32, i32, f32, u64, i64, f64);
+
+/// 将小端字节序数据转换为数值序列，末尾不足一个元素的字节被忽略
+fn from_le_bytes<T: LeBytes>(bytes: &[u8]) -> Vec<T> {
+    let size = std::mem::size_of::<T>();
+    let bytes = &bytes[..bytes.len() - bytes.len() % size];
+
+    // 小端平台上字节布局与目标类型一致，整体拷贝即可
+    #[cfg(all(feature = "bytemuck", target_endian = "little"))]
+    {
+        bytemuck::allocation::pod_collect_to_vec(bytes)
+    }
+
+    #[cfg(not(all(feature = "bytemuck", target_endian = "little")))]
+    {
+        from_le_chunks(bytes)
+    }
+}
+
+/// 逐个元素按小端字节序转换，结果与主机字节序无关，大端平台（如 PowerPC 网关）使用此路径
+#[cfg_attr(
+    all(feature = "bytemuck", target_endian = "little", not(test)),
+    allow(dead_code)
+)]
+fn from_le_chunks<T: LeBytes>(bytes: &[u8]) -> Vec<T> {
+    bytes
+        .chunks_exact(std::mem::size_of::<T>())
+        .map(T::from_le_chunk)
+        .collect()
+}
+
+/// 解码 UTF-16 字序列：识别并去除字节序标记，遇到 0x0000 结束
+fn decode_wstring(words: &[u16]) -> Result<String, Error> {
+    let (words, swapped) = match words.first() {
+        Some(0xFEFF) => (&words[1..], false),
+        Some(0xFFFE) => (&words[1..], true),
+        _ => (words, false),
+    };
+    let units = words
+        .iter()
+        .map(|&word| if swapped { word.swap_bytes() } else { word })
+        .take_while(|&word| word != 0);
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| Error::Utf8Error(e.to_string()))
+}
+
+/// 按固定字长拆分字节数据为字符串：每个字存放 2 个字符（低字节在前），
+/// 截断至首个 NUL 并去除两端空白
+fn split_strings(bytes: &[u8], string_len_words: Quantity) -> Result<Vec<String>, Error> {
+    bytes
+        .chunks(string_len_words as usize * 2)
+        .map(|entry| {
+            let end = entry.iter().position(|&b| b == 0).unwrap_or(entry.len());
+            std::str::from_utf8(&entry[..end])
+                .map(|s| s.trim().to_string())
+                .map_err(|e| Error::Utf8Error(e.to_string()))
+        })
+        .collect()
+}
+
+/// 取出单元素读取的结果，返回的元素数不为 1 时报错
+fn single<T>(values: Vec<T>) -> Result<T, Error> {
+    let size = std::mem::size_of::<T>();
+    match <[T; 1]>::try_from(values) {
+        Ok([value]) => Ok(value),
+        Err(values) => Err(Error::Protocol(ProtocolError::LengthMismatch {
+            expected: size,
+            actual: values.len() * size,
+        })),
+    }
+}
+
+/// 编码为 UTF-16 字序列（不含字节序标记），末尾追加 0x0000 结束符
+fn encode_wstring(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
 
 #[async_trait]
 pub trait Client: Send + Debug {
@@ -61,6 +251,108 @@ pub trait Reader: Client {
     async fn read_bools<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<bool>, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取 `cnt` 个字并按 UTF-16LE 解码为字符串
+    async fn read_wstring<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取 `count` 个连续存放、每个占 `string_len_words` 字的字符串
+    async fn read_strings<A>(
+        &mut self,
+        addr: &A,
+        string_len_words: Quantity,
+        count: Quantity,
+    ) -> Result<Vec<String>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取从 `addr` 起第 `bit_offset` 位开始、宽 `width`（1..=32）位的无符号整数
+    async fn read_packed<A>(&mut self, addr: &A, bit_offset: u32, width: u32) -> Result<u32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 以字单位随机读取（0403）一次读取分散的字，按 `addrs` 的顺序返回
+    ///
+    /// 位软元件按字读取，每个字覆盖 16 点；超过 192 点时拆分为多个请求依次发出。
+    async fn read_random(&mut self, addrs: &[&str]) -> Result<Vec<u16>, Error>;
+
+    /// 以多块批量读取（0406）一次读取多段连续的字，每段为 `(起始地址, 字数)`，按 `blocks` 的顺序返回
+    ///
+    /// 位软元件的每个字覆盖 16 点；超过 120 块或总计 960 字时拆分为多个请求依次发出。
+    async fn read_blocks(&mut self, blocks: &[(&str, Quantity)]) -> Result<Vec<Vec<u16>>, Error>;
+
+    /// 读取单个位
+    async fn read_bool<A>(&mut self, addr: &A) -> Result<bool, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_bools(addr, 1).await?)
+    }
+
+    /// 读取单个字
+    async fn read_u16<A>(&mut self, addr: &A) -> Result<u16, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u16s(addr, 1).await?)
+    }
+
+    /// 读取单个有符号字
+    async fn read_i16<A>(&mut self, addr: &A) -> Result<i16, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i16s(addr, 1).await?)
+    }
+
+    /// 读取单个双字
+    async fn read_u32<A>(&mut self, addr: &A) -> Result<u32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u32s(addr, 1).await?)
+    }
+
+    /// 读取单个有符号双字
+    async fn read_i32<A>(&mut self, addr: &A) -> Result<i32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i32s(addr, 1).await?)
+    }
+
+    /// 读取单个单精度浮点数
+    async fn read_f32<A>(&mut self, addr: &A) -> Result<f32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_f32s(addr, 1).await?)
+    }
+
+    /// 读取单个四字
+    async fn read_u64<A>(&mut self, addr: &A) -> Result<u64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u64s(addr, 1).await?)
+    }
+
+    /// 读取单个有符号四字
+    async fn read_i64<A>(&mut self, addr: &A) -> Result<i64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i64s(addr, 1).await?)
+    }
+
+    /// 读取单个双精度浮点数
+    async fn read_f64<A>(&mut self, addr: &A) -> Result<f64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_f64s(addr, 1).await?)
+    }
 }
 
 #[async_trait]
@@ -104,6 +396,103 @@ pub trait Writer: Client {
     async fn write_f64s<A>(&mut self, addr: &A, f64s: &[f64]) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 以 UTF-16LE 写入字符串，末尾附带 0x0000 结束符
+    async fn write_wstring<A>(&mut self, addr: &A, s: &str) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 将 `value` 写入从 `addr` 起第 `bit_offset` 位开始、宽 `width` 位的字段
+    ///
+    /// 先读取所在的字，只修改字段对应的位后写回；读写之间不加锁，
+    /// 其他站点同时修改同一字时可能被覆盖。
+    async fn write_packed<A>(
+        &mut self,
+        addr: &A,
+        bit_offset: u32,
+        width: u32,
+        value: u32,
+    ) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 以多块批量写入（1406）一次写入多段连续的字，每段为 `(起始地址, 数据)`
+    ///
+    /// 位软元件的每个字写入 16 点；超过 120 块或总计 960 点（每块另计 4 点）时拆分为多个请求依次发出，
+    /// 中途失败时之前的请求已经生效。
+    async fn write_blocks(&mut self, blocks: &[(&str, &[u16])]) -> Result<(), Error>;
+
+    /// 写入单个位
+    async fn write_bool<A>(&mut self, addr: &A, value: bool) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_bools(addr, &[value]).await
+    }
+
+    /// 写入单个字
+    async fn write_u16<A>(&mut self, addr: &A, value: u16) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_u16s(addr, &[value]).await
+    }
+
+    /// 写入单个有符号字
+    async fn write_i16<A>(&mut self, addr: &A, value: i16) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_i16s(addr, &[value]).await
+    }
+
+    /// 写入单个双字
+    async fn write_u32<A>(&mut self, addr: &A, value: u32) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_u32s(addr, &[value]).await
+    }
+
+    /// 写入单个有符号双字
+    async fn write_i32<A>(&mut self, addr: &A, value: i32) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_i32s(addr, &[value]).await
+    }
+
+    /// 写入单个单精度浮点数
+    async fn write_f32<A>(&mut self, addr: &A, value: f32) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_f32s(addr, &[value]).await
+    }
+
+    /// 写入单个四字
+    async fn write_u64<A>(&mut self, addr: &A, value: u64) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_u64s(addr, &[value]).await
+    }
+
+    /// 写入单个有符号四字
+    async fn write_i64<A>(&mut self, addr: &A, value: i64) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_i64s(addr, &[value]).await
+    }
+
+    /// 写入单个双精度浮点数
+    async fn write_f64<A>(&mut self, addr: &A, value: f64) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_f64s(addr, &[value]).await
+    }
 }
 
 /// Asynchronous Modbus client context with generic transport
@@ -111,6 +500,11 @@ pub trait Writer: Client {
 pub struct Context<T: Client> {
     client: T,
     model: Model, // 新增字段
+    odd_length: OddLengthPolicy,
+    cache: Option<cache::ReadCache>,
+    dry_run: bool,
+    /// 已知不支持的命令与子命令，见 [`capability`] 模块
+    unsupported: Vec<(u16, u16)>,
 }
 
 impl<T: Client> Context<T> {
@@ -118,6 +512,10 @@ impl<T: Client> Context<T> {
         Self {
             client,
             model: Model::default(), // 使用默认值
+            odd_length: OddLengthPolicy::default(),
+            cache: None,
+            dry_run: false,
+            unsupported: Vec::new(),
         }
     }
 
@@ -126,11 +524,92 @@ impl<T: Client> Context<T> {
         self.model = model;
     }
 
+    /// 当前的 PLC 型号
+    pub fn plc_model(&self) -> Model {
+        self.model
+    }
+
+    /// 临时按 `model` 转换地址，返回的守卫释放时恢复原型号
+    ///
+    /// 守卫独占借用上下文，可直接在其上调用读写方法，不必来回调用 [`Self::set_plc_model`]：
+    /// `context.with_model(Model::Mitsubishi).read_u16s("D100", 1).await`
+    pub fn with_model(&mut self, model: Model) -> ModelOverride<'_, T> {
+        let previous = std::mem::replace(&mut self.model, model);
+        ModelOverride {
+            context: self,
+            previous,
+        }
+    }
+
+    /// 设置 `write_u8s` 收到奇数字节时的处理方式，默认拒绝
+    pub fn set_odd_length_policy(&mut self, policy: OddLengthPolicy) {
+        self.odd_length = policy;
+    }
+
+    /// 以 `f` 包装内部客户端（如 [`WriteGate`]、[`AccessGuard`]），保留型号等设置
+    pub fn map_client<U: Client>(self, f: impl FnOnce(T) -> U) -> Context<U> {
+        Context {
+            client: f(self.client),
+            model: self.model,
+            odd_length: self.odd_length,
+            cache: self.cache,
+            dry_run: self.dry_run,
+            unsupported: self.unsupported,
+        }
+    }
+
+    /// 开启或关闭演练模式
+    ///
+    /// 演练模式下写请求（含 [`READ_ONLY_COMMANDS`] 以外的命令）照常转换地址并编码校验，
+    /// 记录日志后在本地应答成功，不发送给 PLC；读请求不受影响。
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// 是否处于演练模式
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     /// Disconnect the client connection
     pub async fn disconnect(&mut self) -> std::io::Result<()> {
         self.client.disconnect().await
     }
 
+    /// 发送请求，演练模式下的写请求在本地应答
+    async fn send(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        match self.rehearse(&request) {
+            Some(result) => result,
+            None => self.client.call(request).await,
+        }
+    }
+
+    /// 演练模式下校验写请求并返回本地应答，其它请求返回 `None`
+    fn rehearse(&self, request: &Request<'_>) -> Option<Result<Response, Error>> {
+        if !self.dry_run {
+            return None;
+        }
+        let response = match request {
+            Request::WriteU8s(_, _) => Response::WriteU8s(),
+            Request::WriteBits(_, _) => Response::WriteBits(),
+            Request::Command(command, subcommand, _) if !READ_ONLY_COMMANDS.contains(command) => {
+                Response::Command(*command, *subcommand, Vec::new())
+            }
+            _ => return None,
+        };
+        Some(
+            crate::codec::ClientEncoder::encode(request.clone()).map(|frames| {
+                trace::warning!(
+                    address = request.address(),
+                    bytes = frames.iter().map(|frame| frame.len()).sum::<usize>();
+                    "Dry run: {:?} acknowledged without sending",
+                    request.function_code()
+                );
+                response
+            }),
+        )
+    }
+
     fn process_address<A>(&self, addr: &A) -> Result<String, Error>
     where
         A: AsRef<str> + ?Sized,
@@ -149,12 +628,71 @@ impl<T: Client> Context<T> {
             }
         }
     }
+
+    /// 从 PLC 读取已转换的地址，不查找读取缓存，读到的值存入缓存
+    async fn fetch_u8s(&mut self, address: String, cnt: Quantity) -> Result<Vec<u8>, Error> {
+        let u8s = self
+            .client
+            .call(Request::ReadU8s(address.as_str().into(), cnt))
+            .await
+            .map(|response| match response {
+                Response::ReadU8s(u8s) => Ok(u8s),
+                _ => {
+                    unreachable!("Unexpected response type, expected ReadU8s")
+                }
+            })
+            .and_then(|result| result)?;
+        if let Some(cache) = &mut self.cache {
+            cache.store(&address, &u8s);
+        }
+        Ok(u8s)
+    }
+
+    /// 位字段所在首个字的地址（已按 PLC 型号转换）及字段在该字内的位偏移
+    fn packed_start<A>(&self, addr: &A, bit_offset: u32) -> Result<(String, u32), Error>
+    where
+        A: AsRef<str> + ?Sized,
+    {
+        let (device, number) = area::Device::parse(&self.process_address(addr)?)?;
+        let number = (bit_offset / 16)
+            .checked_mul(device.step())
+            .and_then(|offset| number.checked_add(offset))
+            .ok_or(Error::Protocol(ProtocolError::OutOfRange))?;
+        Ok((device.address(number), bit_offset % 16))
+    }
+}
+
+/// [`Context::with_model`] 返回的守卫，释放时恢复原型号
+#[derive(Debug)]
+pub struct ModelOverride<'a, T: Client> {
+    context: &'a mut Context<T>,
+    previous: Model,
+}
+
+impl<T: Client> std::ops::Deref for ModelOverride<'_, T> {
+    type Target = Context<T>;
+
+    fn deref(&self) -> &Context<T> {
+        self.context
+    }
+}
+
+impl<T: Client> std::ops::DerefMut for ModelOverride<'_, T> {
+    fn deref_mut(&mut self) -> &mut Context<T> {
+        self.context
+    }
+}
+
+impl<T: Client> Drop for ModelOverride<'_, T> {
+    fn drop(&mut self) {
+        self.context.model = self.previous;
+    }
 }
 
 #[async_trait]
 impl<T: Client> Client for Context<T> {
     async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
-        self.client.call(request).await
+        self.send(request).await
     }
 }
 
@@ -164,16 +702,12 @@ impl<T: Client> Reader for Context<T> {
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        self.client
-            .call(Request::ReadU8s(self.process_address(addr)?.into(), cnt))
-            .await
-            .map(|response| match response {
-                Response::ReadU8s(u8s) => Ok(u8s),
-                _ => {
-                    unreachable!("Unexpected response type, expected ReadU8s")
-                }
-            })
-            .and_then(|result| result)
+        let address = self.process_address(addr)?;
+        if let Some(u8s) = self.cache.as_ref().and_then(|c| c.lookup(&address, cnt)) {
+            trace::debug!(address = address, count = cnt; "Read served from cache");
+            return Ok(u8s);
+        }
+        self.fetch_u8s(address, cnt).await
     }
 
     async fn read_u16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u16>, Error>
@@ -184,13 +718,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt).await?;
 
         // 将u8数据转换为小端字节序的u16
-        let mut u16_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(2) {
-            let value = u16::from_le_bytes([chunk[0], chunk[1]]);
-            u16_data.push(value);
-        }
-
-        Ok(u16_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_i16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i16>, Error>
@@ -201,13 +729,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt).await?;
 
         // 将u8数据转换为小端字节序的i16
-        let mut i16_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(2) {
-            let value = i16::from_le_bytes([chunk[0], chunk[1]]);
-            i16_data.push(value);
-        }
-
-        Ok(i16_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_u32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u32>, Error>
@@ -218,13 +740,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 2).await?;
 
         // 将u8数据转换为小端字节序的u32
-        let mut u32_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(4) {
-            let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-            u32_data.push(value);
-        }
-
-        Ok(u32_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_i32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i32>, Error>
@@ -235,13 +751,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 2).await?;
 
         // 将u8数据转换为小端字节序的i32
-        let mut i32_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(4) {
-            let value = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-            i32_data.push(value);
-        }
-
-        Ok(i32_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_f32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f32>, Error>
@@ -252,13 +762,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 2).await?;
 
         // 将u8数据转换为小端字节序的f32
-        let mut f32_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(4) {
-            let value = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-            f32_data.push(value);
-        }
-
-        Ok(f32_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_u64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u64>, Error>
@@ -269,15 +773,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 4).await?;
 
         // 将u8数据转换为小端字节序的u64
-        let mut u64_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(8) {
-            let value = u64::from_le_bytes([
-                chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
-            ]);
-            u64_data.push(value);
-        }
-
-        Ok(u64_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_i64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i64>, Error>
@@ -288,15 +784,7 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 4).await?;
 
         // 将u8数据转换为小端字节序的i64
-        let mut i64_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(8) {
-            let value = i64::from_le_bytes([
-                chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
-            ]);
-            i64_data.push(value);
-        }
-
-        Ok(i64_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_f64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f64>, Error>
@@ -307,23 +795,17 @@ impl<T: Client> Reader for Context<T> {
         let u8_data = self.read_u8s(addr, cnt * 4).await?;
 
         // 将u8数据转换为小端字节序的f64
-        let mut f64_data = Vec::with_capacity(cnt as usize);
-        for chunk in u8_data.chunks_exact(8) {
-            let value = f64::from_le_bytes([
-                chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
-            ]);
-            f64_data.push(value);
-        }
-
-        Ok(f64_data)
+        Ok(from_le_bytes(&u8_data))
     }
 
     async fn read_bools<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<bool>, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
+        let address = self.process_address(addr)?;
+        view::check_bit_access(&address)?;
         self.client
-            .call(Request::ReadBits(self.process_address(addr)?.into(), cnt))
+            .call(Request::ReadBits(address.into(), cnt))
             .await
             .map(|response| match response {
                 Response::ReadBits(u8s) => Ok(u8s),
@@ -333,6 +815,54 @@ impl<T: Client> Reader for Context<T> {
             })
             .and_then(|result| result)
     }
+
+    async fn read_wstring<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let words = self.read_u16s(addr, cnt).await?;
+        decode_wstring(&words)
+    }
+
+    async fn read_strings<A>(
+        &mut self,
+        addr: &A,
+        string_len_words: Quantity,
+        count: Quantity,
+    ) -> Result<Vec<String>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        if string_len_words == 0 || count == 0 {
+            return Ok(Vec::new());
+        }
+        let cnt = string_len_words
+            .checked_mul(count)
+            .ok_or(Error::Protocol(ProtocolError::OutOfRange))?;
+        let bytes = self.read_u8s(addr, cnt).await?;
+        split_strings(&bytes, string_len_words)
+    }
+
+    async fn read_packed<A>(&mut self, addr: &A, bit_offset: u32, width: u32) -> Result<u32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let (start, bit_offset) = self.packed_start(addr, bit_offset)?;
+        let cnt = packed::words_spanned(bit_offset, width)?;
+        let words = self
+            .with_model(Model::Mitsubishi)
+            .read_u16s(&start, cnt)
+            .await?;
+        Ok(packed::extract_bits(&words, bit_offset, width))
+    }
+
+    async fn read_random(&mut self, addrs: &[&str]) -> Result<Vec<u16>, Error> {
+        self.read_random_words(addrs).await
+    }
+
+    async fn read_blocks(&mut self, blocks: &[(&str, Quantity)]) -> Result<Vec<Vec<u16>>, Error> {
+        self.read_word_blocks(blocks).await
+    }
 }
 
 #[async_trait]
@@ -341,11 +871,27 @@ impl<T: Client> Writer for Context<T> {
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        self.client
-            .call(Request::WriteU8s(
-                self.process_address(addr)?.into(),
-                Cow::Borrowed(u8s),
-            ))
+        let mut u8s = Cow::Borrowed(u8s);
+        if u8s.len() % 2 != 0 {
+            match self.odd_length {
+                OddLengthPolicy::Reject => {
+                    return Err(Error::Protocol(ProtocolError::OddByteCount(u8s.len())));
+                }
+                OddLengthPolicy::ZeroPad => {
+                    trace::warning!(
+                        "Odd byte count {} written to {}, padding with zero",
+                        u8s.len(),
+                        addr.as_ref()
+                    );
+                    u8s.to_mut().push(0);
+                }
+            }
+        }
+        let address = self.process_address(addr)?;
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(&address, u8s.len() / 2);
+        }
+        self.send(Request::WriteU8s(address.into(), u8s))
             .await
             .map(|response| match response {
                 Response::WriteU8s() => Ok(()),
@@ -358,11 +904,12 @@ impl<T: Client> Writer for Context<T> {
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        self.client
-            .call(Request::WriteBits(
-                self.process_address(addr)?.into(),
-                Cow::Borrowed(bools),
-            ))
+        let address = self.process_address(addr)?;
+        view::check_bit_access(&address)?;
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(&address, bools.len().div_ceil(16));
+        }
+        self.send(Request::WriteBits(address.into(), Cow::Borrowed(bools)))
             .await
             .map(|response| match response {
                 Response::WriteBits() => Ok(()),
@@ -466,7 +1013,239 @@ impl<T: Client> Writer for Context<T> {
         }
         self.write_u8s(addr, &u8s).await
     }
+
+    async fn write_wstring<A>(&mut self, addr: &A, s: &str) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.write_u16s(addr, &encode_wstring(s)).await
+    }
+
+    async fn write_packed<A>(
+        &mut self,
+        addr: &A,
+        bit_offset: u32,
+        width: u32,
+        value: u32,
+    ) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let (start, bit_offset) = self.packed_start(addr, bit_offset)?;
+        let cnt = packed::words_spanned(bit_offset, width)?;
+        let mut context = self.with_model(Model::Mitsubishi);
+        let mut words = context.read_u16s(&start, cnt).await?;
+        packed::insert_bits(&mut words, bit_offset, width, value)?;
+        context.write_u16s(&start, &words).await
+    }
+
+    async fn write_blocks(&mut self, blocks: &[(&str, &[u16])]) -> Result<(), Error> {
+        self.write_word_blocks(blocks).await
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::convert::{bytes_to_words, words_to_bytes};
+
+    #[test]
+    fn le_conversion_does_not_depend_on_host_order() {
+        let bytes = [0x34, 0x12, 0x78, 0x56, 0x00, 0x00, 0xC0, 0x3F, 0xFF];
+        // 大端平台使用的逐元素路径与各平台实际使用的路径结果相同
+        assert_eq!(
+            from_le_bytes::<u16>(&bytes),
+            [0x1234, 0x5678, 0x0000, 0x3FC0]
+        );
+        assert_eq!(from_le_bytes::<u32>(&bytes), [0x5678_1234, 0x3FC0_0000]);
+        assert_eq!(from_le_bytes::<f32>(&bytes[4..]), [1.5]);
+        assert_eq!(
+            from_le_bytes::<i64>(&bytes),
+            from_le_chunks::<i64>(&bytes[..8])
+        );
+        assert_eq!(
+            from_le_bytes::<f64>(&bytes[1..]),
+            from_le_chunks::<f64>(&bytes[1..])
+        );
+    }
+
+    #[test]
+    fn operation_ids_are_unique() {
+        let first = OperationId::next();
+        let second = OperationId::next();
+        assert_ne!(first, second);
+        assert!(second.get() > first.get());
+        assert_eq!(OperationId(0x2A).to_string(), "0000002A");
+    }
+
+    #[test]
+    fn from_le_bytes_decodes_words() {
+        let bytes = [0x34, 0x12, 0xFF, 0xFF, 0x00, 0x00, 0x80, 0x3F, 0xAA];
+        assert_eq!(
+            from_le_bytes::<u16>(&bytes),
+            [0x1234, 0xFFFF, 0x0000, 0x3F80]
+        );
+        assert_eq!(from_le_bytes::<i16>(&bytes[2..4]), [-1]);
+        assert_eq!(from_le_bytes::<f32>(&bytes[4..8]), [1.0]);
+        assert_eq!(from_le_bytes::<u64>(&bytes[..8]), [0x3F80_0000_FFFF_1234]);
+    }
+
+    #[test]
+    fn single_requires_exactly_one_value() {
+        assert_eq!(single(vec![1.5f32]).unwrap(), 1.5);
+        assert!(matches!(
+            single(Vec::<u32>::new()),
+            Err(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: 4,
+                actual: 0
+            }))
+        ));
+        assert!(single(vec![true, false]).is_err());
+    }
+
+    /// 记录最后一次写入的数据
+    #[derive(Debug, Default)]
+    struct LastWrite(Vec<u8>);
+
+    #[async_trait]
+    impl Client for LastWrite {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let Request::WriteU8s(_, u8s) = request else {
+                unreachable!()
+            };
+            self.0 = u8s.into_owned();
+            Ok(Response::WriteU8s())
+        }
+    }
+
+    #[tokio::test]
+    async fn odd_length_policy() {
+        let mut context = Context::new(LastWrite::default());
+        assert!(matches!(
+            context.write_u8s("D0", &[1, 2, 3]).await,
+            Err(Error::Protocol(ProtocolError::OddByteCount(3)))
+        ));
+        assert!(context.client.0.is_empty());
+
+        context.set_odd_length_policy(OddLengthPolicy::ZeroPad);
+        context.write_u8s("D0", &[1, 2, 3]).await.unwrap();
+        assert_eq!(context.client.0, [1, 2, 3, 0]);
+        context.write_u8s("D0", &[4, 5]).await.unwrap();
+        assert_eq!(context.client.0, [4, 5]);
+    }
+
+    #[tokio::test]
+    async fn dry_run_acknowledges_writes_locally() {
+        let mut context = Context::new(LastWrite::default());
+        context.set_dry_run(true);
+        context.write_u16s("D0", &[0x1234]).await.unwrap();
+        context.write_bools("M0", &[true]).await.unwrap();
+        assert!(context.client.0.is_empty());
+
+        // 仍然校验地址
+        assert!(context.write_u16s("Q0", &[1]).await.is_err());
+
+        context.set_dry_run(false);
+        context.write_u16s("D0", &[0x1234]).await.unwrap();
+        assert_eq!(context.client.0, [0x34, 0x12]);
+    }
+
+    /// 记录最后一次请求的地址
+    #[derive(Debug, Default)]
+    struct LastAddress(String);
+
+    #[async_trait]
+    impl Client for LastAddress {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.0 = request.address().to_string();
+            Ok(Response::ReadU8s(vec![0; 2]))
+        }
+    }
+
+    #[tokio::test]
+    async fn with_model_overrides_one_call() {
+        let mut context = Context::new(LastAddress::default());
+        context.set_plc_model(Model::Keyence);
+        context.read_u16s("DM10", 1).await.unwrap();
+        assert_eq!(context.client.0, "D10");
+
+        context
+            .with_model(Model::Mitsubishi)
+            .read_u16s("DM10", 1)
+            .await
+            .unwrap();
+        assert_eq!(context.client.0, "DM10");
+        assert_eq!(context.plc_model(), Model::Keyence);
+    }
+
+    /// 以 D0 起的 16 个字应答读取，记录每个请求的地址与点数
+    #[derive(Debug)]
+    struct Words {
+        words: [u16; 16],
+        requests: Vec<(String, Quantity)>,
+    }
+
+    #[async_trait]
+    impl Client for Words {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let address = request.address().to_string();
+            let start: usize = address["D".len()..].parse().unwrap();
+            match request {
+                Request::ReadU8s(_, cnt) => {
+                    self.requests.push((address, cnt));
+                    let words = &self.words[start..start + cnt as usize];
+                    Ok(Response::ReadU8s(words_to_bytes(words)))
+                }
+                Request::WriteU8s(_, u8s) => {
+                    let words = bytes_to_words(&u8s);
+                    self.requests.push((address, words.len() as Quantity));
+                    self.words[start..start + words.len()].copy_from_slice(&words);
+                    Ok(Response::WriteU8s())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn packed_fields_touch_only_the_covered_words() {
+        let mut context = Context::new(Words {
+            words: [0xFFFF; 16],
+            requests: Vec::new(),
+        });
+        context.write_packed("D0", 130, 4, 0x5).await.unwrap();
+        assert_eq!(
+            context.client.requests,
+            [("D8".into(), 1), ("D8".into(), 1)]
+        );
+        assert_eq!(context.client.words[8], 0xFFD7);
+        assert!(context.client.words[..8].iter().all(|&word| word == 0xFFFF));
+
+        // 跨越字边界的字段读取两个字
+        context.client.requests.clear();
+        assert_eq!(context.read_packed("D0", 30, 4).await.unwrap(), 0xF);
+        assert_eq!(context.client.requests, [("D1".into(), 2)]);
+    }
+
+    #[test]
+    fn split_strings_trims_entries() {
+        let bytes = b"AB-1 \0\0\0  C2\0\0\0\0\0\0\0\0\0\0";
+        assert_eq!(split_strings(bytes, 4).unwrap(), ["AB-1", "C2", ""]);
+        assert!(split_strings(&[0xFF, 0x00], 1).is_err());
+    }
+
+    #[test]
+    fn wstring_round_trip() {
+        let words = encode_wstring("温度😀");
+        assert_eq!(words, [0x6E29, 0x5EA6, 0xD83D, 0xDE00, 0x0000]);
+        assert_eq!(decode_wstring(&words).unwrap(), "温度😀");
+
+        // 字节序标记与结束符之后的数据
+        assert_eq!(
+            decode_wstring(&[0xFEFF, 0x0041, 0x0000, 0x0042]).unwrap(),
+            "A"
+        );
+        assert_eq!(decode_wstring(&[0xFFFE, 0x4100, 0x4200]).unwrap(), "AB");
+        assert!(decode_wstring(&[0xD83D, 0x0041]).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! 监视登记与监视
+//!
+//! HMI 式的周期轮询每次都读取同一组地址。监视登记（0801）把地址列表登记在 PLC 一侧，
+//! 之后每次只需发送不带数据的监视命令（0802），请求帧的大小与登记点数无关：
+//!
+//! ```text
+//! let monitor = context.register_monitor(&["D100", "D2000", "M16"]).await?;
+//! loop {
+//!     let words = monitor.read(&mut context).await?;
+//!     // words[0] 为 D100，words[2] 为 M16 起的 16 点
+//! }
+//! ```
+//!
+//! PLC 为每个连接只保存一份登记，再次登记（包括 [`Collector`](super::Collector) 的登记）
+//! 会替换之前的登记；PLC 重启后登记丢失，监视命令以错误结束代码应答，需要重新登记。
+//!
+//! PLC 不支持监视登记时（见 [`capability`](super::capability) 模块），登记不发出请求，
+//! 每次监视改为随机读取，仍不支持时逐个地址批量读取。
+
+use crate::{
+    convert::bytes_to_words,
+    frame::{ProtocolError, Request},
+    Error,
+};
+
+use super::{diagnostics::command_data, scatter::encode_word_entries, Client, Context};
+
+/// 监视登记命令
+pub(super) const MONITOR_REGISTER: u16 = 0x0801;
+/// 监视命令
+pub(super) const MONITOR: u16 = 0x0802;
+/// 监视登记的字访问点数上限
+pub(super) const MONITOR_MAX_POINTS: usize = 192;
+
+/// 已登记的监视点，见[模块文档](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    addresses: Vec<String>,
+    registered: bool,
+}
+
+impl Monitor {
+    /// 登记的地址，即 [`Self::read`] 返回值的顺序
+    pub fn addresses(&self) -> &[String] {
+        &self.addresses
+    }
+
+    /// 是否已在 PLC 一侧登记，PLC 不支持监视登记时为 false
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+
+    /// 执行一次监视，按登记顺序返回各点的字
+    pub async fn read<T: Client>(&self, context: &mut Context<T>) -> Result<Vec<u16>, Error> {
+        if !self.registered {
+            let addresses: Vec<&str> = self.addresses.iter().map(String::as_str).collect();
+            return context.read_random_words(&addresses).await;
+        }
+        let response = context
+            .send(Request::Command(MONITOR, 0x0000, Vec::new().into()))
+            .await?;
+        let data = command_data(response);
+        if data.len() != self.addresses.len() * 2 {
+            return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: self.addresses.len() * 2,
+                actual: data.len(),
+            }));
+        }
+        Ok(bytes_to_words(&data))
+    }
+}
+
+impl<T: Client> Context<T> {
+    /// 监视登记 `addresses`，按字访问，位软元件每个字覆盖 16 点，见[模块文档](super::monitor)
+    ///
+    /// 最多 192 点，为空或超出时返回 [`ProtocolError::OutOfRange`]，不发送请求。
+    pub async fn register_monitor(&mut self, addresses: &[&str]) -> Result<Monitor, Error> {
+        if addresses.is_empty() || addresses.len() > MONITOR_MAX_POINTS {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        let entries = self.word_entries(addresses)?;
+        let registered = self
+            .try_command(MONITOR_REGISTER, 0x0000, encode_word_entries(&entries))
+            .await?
+            .is_some();
+        Ok(Monitor {
+            addresses: addresses.iter().map(|addr| addr.to_string()).collect(),
+            registered,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Response;
+    use async_trait::async_trait;
+
+    /// 保存登记数据，监视时返回各点编号的低 16 位
+    #[derive(Debug, Default)]
+    struct Plc {
+        registered: Option<Vec<u8>>,
+        requests: usize,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.requests += 1;
+            let Request::Command(command, 0x0000, data) = request else {
+                unreachable!()
+            };
+            let data = match command {
+                MONITOR_REGISTER => {
+                    self.registered = Some(data.into_owned());
+                    Vec::new()
+                }
+                MONITOR => {
+                    assert!(data.is_empty());
+                    self.registered.as_ref().unwrap()[2..]
+                        .chunks(4)
+                        .flat_map(|entry| [entry[0], entry[1]])
+                        .collect()
+                }
+                _ => unreachable!(),
+            };
+            Ok(Response::Command(command, 0x0000, data))
+        }
+    }
+
+    #[tokio::test]
+    async fn registers_once_and_reads_repeatedly() {
+        let mut context = Context::new(Plc::default());
+        let monitor = context
+            .register_monitor(&["D300", "M16", "D5"])
+            .await
+            .unwrap();
+        assert_eq!(
+            context.client.registered.as_deref().unwrap(),
+            [3, 0, 0x2C, 0x01, 0, 0xA8, 0x10, 0, 0, 0x90, 5, 0, 0, 0xA8]
+        );
+        for _ in 0..2 {
+            assert_eq!(monitor.read(&mut context).await.unwrap(), [300, 16, 5]);
+        }
+        assert_eq!(context.client.requests, 3);
+
+        let addresses = vec!["D0"; MONITOR_MAX_POINTS + 1];
+        assert!(context.register_monitor(&addresses).await.is_err());
+        assert!(context.register_monitor(&[]).await.is_err());
+        assert_eq!(context.client.requests, 3);
+    }
+}
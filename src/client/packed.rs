@@ -0,0 +1,73 @@
+//! 字内任意位偏移、位宽的打包整数
+
+use crate::{frame::ProtocolError, Error};
+
+/// 校验位宽并返回从起始字开始覆盖 `bit_offset..bit_offset + width` 所需的字数
+pub(crate) fn words_spanned(bit_offset: u32, width: u32) -> Result<u32, Error> {
+    if width == 0 || width > 32 {
+        return Err(Error::Protocol(ProtocolError::OutOfRange));
+    }
+    bit_offset
+        .checked_add(width)
+        .map(|end| end.div_ceil(16))
+        .ok_or(Error::Protocol(ProtocolError::OutOfRange))
+}
+
+/// 从字序列中提取起始于 `bit_offset`、宽 `width` 位的无符号整数（低位在前）
+pub(crate) fn extract_bits(words: &[u16], bit_offset: u32, width: u32) -> u32 {
+    (0..width).fold(0, |value, i| {
+        let bit = bit_offset + i;
+        let set = words[(bit / 16) as usize] >> (bit % 16) & 1;
+        value | u32::from(set) << i
+    })
+}
+
+/// 将 `value` 写入字序列的对应位，其余位保持不变
+pub(crate) fn insert_bits(
+    words: &mut [u16],
+    bit_offset: u32,
+    width: u32,
+    value: u32,
+) -> Result<(), Error> {
+    if width < 32 && value >> width != 0 {
+        return Err(Error::Protocol(ProtocolError::OutOfRange));
+    }
+    for i in 0..width {
+        let bit = bit_offset + i;
+        let word = &mut words[(bit / 16) as usize];
+        let mask = 1 << (bit % 16);
+        if value >> i & 1 == 1 {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_fields_within_and_across_words() {
+        // D100 的 0..11 位为 12 位计数值，12..15 位为状态
+        let mut words = [0xA123, 0x0000];
+        assert_eq!(words_spanned(0, 12).unwrap(), 1);
+        assert_eq!(extract_bits(&words, 0, 12), 0x123);
+        assert_eq!(extract_bits(&words, 12, 4), 0xA);
+
+        insert_bits(&mut words, 0, 12, 0xFFF).unwrap();
+        assert_eq!(words, [0xAFFF, 0x0000]);
+
+        // 跨越字边界的字段
+        assert_eq!(words_spanned(12, 8).unwrap(), 2);
+        insert_bits(&mut words, 12, 8, 0x5C).unwrap();
+        assert_eq!(words, [0xCFFF, 0x0005]);
+        assert_eq!(extract_bits(&words, 12, 8), 0x5C);
+
+        assert!(insert_bits(&mut words, 0, 4, 0x10).is_err());
+        assert!(words_spanned(0, 0).is_err());
+        assert!(words_spanned(0, 33).is_err());
+    }
+}
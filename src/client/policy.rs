@@ -0,0 +1,491 @@
+//! 客户端访问策略
+//!
+//! [`AccessGuard`] 包装任意 [`Client`]，在发出请求前按 [`AccessPolicy`] 检查访问的软元件范围，
+//! 被拒绝的请求返回 `ProtocolError::AccessDenied`，不会发出任何帧。
+//! 检查使用 MC 软元件名称，即经过 PLC 型号地址转换之后的地址。
+//! 随机读写、监视登记等命令按请求数据中的每个软元件分别检查。
+//!
+//! - 允许列表：非空时，每次访问的全部点须落在同一个允许范围内；
+//! - 拒绝列表：与任一拒绝范围重叠的访问均被拒绝；
+//! - 只读模式：拒绝所有写入，以及 [`READ_ONLY_COMMANDS`] 以外的命令。
+
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use async_trait::async_trait;
+
+use crate::{
+    frame::{find_instruction_code, ProtocolError, Quantity, Request, Response},
+    Error,
+};
+
+use super::{area::Device, Client};
+
+/// 只读模式下仍允许的命令：CPU 型号读取、回送测试、监视登记与监视
+pub const READ_ONLY_COMMANDS: &[u16] = &[0x0101, 0x0403, 0x0406, 0x0619, 0x0801, 0x0802];
+
+/// 一种软元件的编号范围，如 `D100-D199`、`Y0-Y1F`、`M10`，或仅前缀 `Y` 表示全部编号
+#[derive(Debug, Clone)]
+pub struct DeviceRange {
+    device: Device,
+    /// `None` 表示全部编号
+    numbers: Option<RangeInclusive<u32>>,
+}
+
+impl DeviceRange {
+    fn numbers(&self) -> RangeInclusive<u32> {
+        self.numbers.clone().unwrap_or(0..=u32::MAX)
+    }
+
+    fn overlaps(&self, prefix: &str, numbers: &RangeInclusive<u32>) -> bool {
+        let own = self.numbers();
+        self.device.prefix() == prefix
+            && own.start() <= numbers.end()
+            && numbers.start() <= own.end()
+    }
+
+    fn contains(&self, prefix: &str, numbers: &RangeInclusive<u32>) -> bool {
+        let own = self.numbers();
+        self.device.prefix() == prefix
+            && own.contains(numbers.start())
+            && own.contains(numbers.end())
+    }
+}
+
+impl FromStr for DeviceRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let s = s.trim();
+        if find_instruction_code(s).is_some() {
+            let (device, _) = Device::parse(&format!("{s}0"))?;
+            return Ok(Self {
+                device,
+                numbers: None,
+            });
+        }
+        let (first, last) = s.split_once('-').unwrap_or((s, s));
+        let (device, start) = Device::parse(first)?;
+        let (last_device, end) = Device::parse(last)?;
+        if device.prefix() != last_device.prefix() || end < start {
+            return Err(Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "invalid device range {s:?}"
+            ))));
+        }
+        Ok(Self {
+            device,
+            numbers: Some(start..=end),
+        })
+    }
+}
+
+impl fmt::Display for DeviceRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.numbers {
+            Some(numbers) => write!(
+                f,
+                "{}-{}",
+                self.device.address(*numbers.start()),
+                self.device.address(*numbers.end())
+            ),
+            None => f.write_str(self.device.prefix()),
+        }
+    }
+}
+
+/// 访问策略，默认允许一切访问
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    read_only: bool,
+    allow: Vec<DeviceRange>,
+    deny: Vec<DeviceRange>,
+}
+
+impl AccessPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只读模式
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// 添加允许范围
+    pub fn allow(mut self, range: DeviceRange) -> Self {
+        self.allow.push(range);
+        self
+    }
+
+    /// 添加拒绝范围
+    pub fn deny(mut self, range: DeviceRange) -> Self {
+        self.deny.push(range);
+        self
+    }
+
+    /// 检查请求，拒绝时返回原因
+    ///
+    /// 批量读写、随机读写、多块批量读写与监视登记的命令按请求数据中的各软元件逐一检查，
+    /// 设置了允许或拒绝范围时，无法解析的请求数据也被拒绝。
+    pub fn check(&self, request: &Request<'_>) -> Result<(), Error> {
+        let (address, points, write) = match request {
+            Request::Command(command, subcommand, data) => {
+                if self.read_only && !READ_ONLY_COMMANDS.contains(command) {
+                    return Err(denied(format!("command {command:04X} in read-only mode")));
+                }
+                if self.allow.is_empty() && self.deny.is_empty() {
+                    return Ok(());
+                }
+                let entries = command_points(*command, *subcommand, data).ok_or_else(|| {
+                    denied(format!(
+                        "cannot check devices of command {command:04X}/{subcommand:04X}"
+                    ))
+                })?;
+                for (device, start, points) in entries {
+                    self.check_range(&device.address(start), device, start, points)?;
+                }
+                return Ok(());
+            }
+            Request::ReadU8s(address, cnt) => (address, Points::Words(*cnt), false),
+            Request::WriteU8s(address, bytes) => {
+                let words = bytes.len().div_ceil(2) as Quantity;
+                (address, Points::Words(words), true)
+            }
+            Request::ReadBits(address, cnt) => (address, Points::Bits(*cnt), false),
+            Request::WriteBits(address, bits) => {
+                (address, Points::Bits(bits.len() as Quantity), true)
+            }
+        };
+        if write && self.read_only {
+            return Err(denied(format!("write to {address} in read-only mode")));
+        }
+
+        let (device, start) = Device::parse(address)?;
+        self.check_range(address, device, start, points)
+    }
+
+    fn check_range(
+        &self,
+        address: &str,
+        device: Device,
+        start: u32,
+        points: Points,
+    ) -> Result<(), Error> {
+        let count = match points {
+            Points::Words(words) => words.saturating_mul(device.step()),
+            Points::Bits(bits) => bits,
+        };
+        let numbers = start..=start.saturating_add(count.max(1) - 1);
+        if let Some(range) = self
+            .deny
+            .iter()
+            .find(|range| range.overlaps(device.prefix(), &numbers))
+        {
+            return Err(denied(format!("{address} overlaps denied range {range}")));
+        }
+        if !self.allow.is_empty()
+            && !self
+                .allow
+                .iter()
+                .any(|range| range.contains(device.prefix(), &numbers))
+        {
+            return Err(denied(format!("{address} is outside the allowed ranges")));
+        }
+        Ok(())
+    }
+}
+
+/// 命令请求数据中访问的各软元件：软元件、起始编号与点数，数据无法解析时返回 `None`
+///
+/// 子命令 0002/0003 为 iQ-R 的扩展指定，编号 4 字节、软元件代码 2 字节；
+/// 不访问软元件的命令返回空列表。
+fn command_points(
+    command: u16,
+    subcommand: u16,
+    data: &[u8],
+) -> Option<Vec<(Device, u32, Points)>> {
+    let bits = subcommand & 0x0001 != 0;
+    let wide = subcommand & 0x0002 != 0;
+    let mut data = CommandData { rest: data, wide };
+    let mut points = Vec::new();
+    match command {
+        // 批量读写：起始软元件与点数
+        0x0401 | 0x1401 => {
+            let (device, start) = data.entry()?;
+            let count = data.u16()? as Quantity;
+            let count = if bits {
+                Points::Bits(count)
+            } else {
+                Points::Words(count)
+            };
+            points.push((device, start, count));
+        }
+        // 随机读取与监视登记：字访问点数、双字访问点数
+        0x0403 | 0x0801 => {
+            let counts = data.take(2)?;
+            let (words, dwords) = (counts[0], counts[1]);
+            for i in 0..words as usize + dwords as usize {
+                let (device, start) = data.entry()?;
+                let words = if i < words as usize { 1 } else { 2 };
+                points.push((device, start, Points::Words(words)));
+            }
+        }
+        // 随机写入：位单位为点数与各点的 ON/OFF，字单位与随机读取相同并附带写入值
+        0x1402 if bits => {
+            for _ in 0..data.take(1)?[0] {
+                let (device, start) = data.entry()?;
+                data.take(if wide { 2 } else { 1 })?;
+                points.push((device, start, Points::Bits(1)));
+            }
+        }
+        0x1402 => {
+            let counts = data.take(2)?;
+            let (words, dwords) = (counts[0], counts[1]);
+            for i in 0..words as usize + dwords as usize {
+                let (device, start) = data.entry()?;
+                let words = if i < words as usize { 1 } else { 2 };
+                data.take(words as usize * 2)?;
+                points.push((device, start, Points::Words(words)));
+            }
+        }
+        // 多块批量读写：字软元件块数、位软元件块数，每块以字为单位，写入时附带数据
+        0x0406 | 0x1406 => {
+            let counts = data.take(2)?;
+            for _ in 0..counts[0] as usize + counts[1] as usize {
+                let (device, start) = data.entry()?;
+                let words = data.u16()?;
+                if command == 0x1406 {
+                    data.take(words as usize * 2)?;
+                }
+                points.push((device, start, Points::Words(words as Quantity)));
+            }
+        }
+        _ => {}
+    }
+    Some(points)
+}
+
+/// 按顺序读取命令请求数据
+struct CommandData<'a> {
+    rest: &'a [u8],
+    /// iQ-R 扩展指定：编号 4 字节、软元件代码 2 字节
+    wide: bool,
+}
+
+impl<'a> CommandData<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (head, tail) = self.rest.split_at_checked(len)?;
+        self.rest = tail;
+        Some(head)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// 软元件编号与软元件代码
+    fn entry(&mut self) -> Option<(Device, u32)> {
+        let (number_len, code_len) = if self.wide { (4, 2) } else { (3, 1) };
+        let mut number = [0; 4];
+        number[..number_len].copy_from_slice(self.take(number_len)?);
+        let device = Device::from_code(self.take(code_len)?[0])?;
+        Some((device, u32::from_le_bytes(number)))
+    }
+}
+
+enum Points {
+    Words(Quantity),
+    Bits(Quantity),
+}
+
+fn denied(reason: String) -> Error {
+    Error::Protocol(ProtocolError::AccessDenied(reason))
+}
+
+/// 按 [`AccessPolicy`] 拦截请求的 [`Client`] 包装，见[模块文档](self)
+#[derive(Debug)]
+pub struct AccessGuard<T> {
+    inner: T,
+    policy: AccessPolicy,
+}
+
+impl<T: Client> AccessGuard<T> {
+    pub fn new(inner: T, policy: AccessPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn policy(&self) -> &AccessPolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: AccessPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<T: Client> Client for AccessGuard<T> {
+    async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        self.policy.check(&request)?;
+        self.inner.call(request).await
+    }
+
+    async fn disconnect(&mut self) -> std::io::Result<()> {
+        self.inner.disconnect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> DeviceRange {
+        s.parse().unwrap()
+    }
+
+    fn is_denied(result: Result<(), Error>) -> bool {
+        matches!(result, Err(Error::Protocol(ProtocolError::AccessDenied(_))))
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(range("D100-D199").to_string(), "D100-D199");
+        assert_eq!(range("Y0-Y1F").to_string(), "Y0-Y1F");
+        assert_eq!(range("M10").to_string(), "M10-M10");
+        assert_eq!(range("Y").to_string(), "Y");
+        assert_eq!(range("W100-W1FF").to_string(), "W100-W1FF");
+        assert_eq!(range("SB0-SBFF").to_string(), "SB0-SBFF");
+        assert_eq!(range("SW1A").to_string(), "SW1A-SW1A");
+        assert!("W1FF-W100".parse::<DeviceRange>().is_err());
+        assert!("D10-M20".parse::<DeviceRange>().is_err());
+        assert!("D20-D10".parse::<DeviceRange>().is_err());
+    }
+
+    #[test]
+    fn policy_checks_requests() {
+        let policy = AccessPolicy::new()
+            .allow(range("D0-D999"))
+            .allow(range("M"))
+            .allow(range("Y"))
+            .deny(range("Y20-Y2F"));
+
+        assert!(policy.check(&Request::ReadU8s("D990".into(), 10)).is_ok());
+        assert!(is_denied(
+            policy.check(&Request::ReadU8s("D995".into(), 10))
+        ));
+        assert!(is_denied(policy.check(&Request::ReadU8s("R0".into(), 1))));
+        // Y10 开始的一个字覆盖 Y10-Y1F，下一个字与拒绝范围重叠
+        assert!(policy.check(&Request::ReadU8s("Y10".into(), 1)).is_ok());
+        assert!(is_denied(policy.check(&Request::ReadU8s("Y10".into(), 2))));
+        let write = Request::WriteBits("Y2F".into(), vec![true].into());
+        assert!(is_denied(policy.check(&write)));
+
+        let policy = policy.read_only(true);
+        assert!(is_denied(
+            policy.check(&Request::WriteU8s("D0".into(), vec![0, 0].into()))
+        ));
+        assert!(policy
+            .check(&Request::Command(0x0101, 0, Vec::new().into()))
+            .is_ok());
+        assert!(is_denied(policy.check(&Request::Command(
+            0x1001,
+            0,
+            Vec::new().into()
+        ))));
+    }
+
+    #[test]
+    fn policy_checks_command_devices() {
+        let policy = AccessPolicy::new().allow(range("D0-D99")).allow(range("Y"));
+        // 字访问 D10 与 D99 起的双字
+        let random = [1, 1, 10, 0, 0, 0xA8, 99, 0, 0, 0xA8];
+        assert!(is_denied(policy.check(&Request::Command(
+            0x0403,
+            0,
+            random[..].into()
+        ))));
+        let monitor = [1, 0, 10, 0, 0, 0xA8];
+        assert!(policy
+            .check(&Request::Command(0x0801, 0, monitor[..].into()))
+            .is_ok());
+        // iQ-R 扩展指定的批量读取 D100
+        let batch = [100, 0, 0, 0, 0xA8, 0, 1, 0];
+        assert!(is_denied(policy.check(&Request::Command(
+            0x0401,
+            2,
+            batch[..].into()
+        ))));
+        // 多块批量读取 D90 起 20 字越出允许范围，多块批量写入 Y0 起 1 字
+        let blocks = [1, 0, 90, 0, 0, 0xA8, 20, 0];
+        assert!(is_denied(policy.check(&Request::Command(
+            0x0406,
+            0,
+            blocks[..].into()
+        ))));
+        let blocks = [1, 1, 0, 0, 0, 0xA8, 1, 0, 7, 0, 0, 0, 0, 0x9D, 1, 0, 1, 0];
+        assert!(policy
+            .check(&Request::Command(0x1406, 0, blocks[..].into()))
+            .is_ok());
+        assert!(is_denied(
+            AccessPolicy::new()
+                .deny(range("Y0-Y1F"))
+                .check(&Request::Command(0x1406, 0, blocks[..].into()))
+        ));
+
+        // 截断的请求数据无法检查
+        assert!(is_denied(policy.check(&Request::Command(
+            0x0403,
+            0,
+            random[..5].into()
+        ))));
+    }
+
+    #[derive(Debug)]
+    struct Unreachable;
+
+    #[async_trait]
+    impl Client for Unreachable {
+        async fn call(&mut self, _: Request<'_>) -> Result<Response, Error> {
+            unreachable!("denied requests must not be sent")
+        }
+    }
+
+    #[tokio::test]
+    async fn guard_blocks_before_sending() {
+        use crate::client::{Context, Writer as _};
+
+        let policy = AccessPolicy::new().read_only(true);
+        let mut context =
+            Context::new(Unreachable).map_client(|client| AccessGuard::new(client, policy));
+        let result = context.write_bools("Y0", &[true]).await;
+        assert!(matches!(
+            result,
+            Err(Error::Protocol(ProtocolError::AccessDenied(_)))
+        ));
+
+        // 位单位随机写入按其中的每个软元件检查
+        let policy = AccessPolicy::new().deny(range("Y0-Y1F"));
+        let mut context =
+            Context::new(Unreachable).map_client(|client| AccessGuard::new(client, policy));
+        let result = context
+            .write_scattered_bools(&[("M0", true), ("Y10", true)])
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::Protocol(ProtocolError::AccessDenied(_)))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+//! 远程 RUN/STOP/PAUSE
+//!
+//! 以远程 RUN（1001）、远程 STOP（1002）、远程 PAUSE（1003）命令切换 CPU 的运行状态，
+//! 用于程序下载前后的停机与恢复。CPU 的 RUN/STOP 开关须处于 RUN 位置，
+//! 其他设备已执行远程 STOP/PAUSE 时，只有指定强制执行才能远程 RUN 或 PAUSE。
+//!
+//! 这些命令都不是只读命令，演练模式下不会发出，只读的 [`AccessPolicy`](super::AccessPolicy) 会拒绝它们。
+
+use crate::{frame::Request, Error};
+
+use super::{Client, Context};
+
+/// 远程 RUN 命令
+const REMOTE_RUN: u16 = 0x1001;
+/// 远程 STOP 命令
+const REMOTE_STOP: u16 = 0x1002;
+/// 远程 PAUSE 命令
+const REMOTE_PAUSE: u16 = 0x1003;
+/// 不强制执行
+const NORMAL: u16 = 0x0001;
+/// 强制执行
+const FORCED: u16 = 0x0003;
+
+/// 远程 RUN 时的软元件清除方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClearMode {
+    /// 不清除
+    #[default]
+    None = 0x00,
+    /// 清除停电保持范围以外的软元件
+    ExceptLatch = 0x01,
+    /// 清除包括停电保持范围在内的全部软元件
+    All = 0x02,
+}
+
+fn mode(force: bool) -> [u8; 2] {
+    if force { FORCED } else { NORMAL }.to_le_bytes()
+}
+
+impl<T: Client> Context<T> {
+    /// 远程 RUN（1001），`force` 为真时即使其他设备已远程 STOP/PAUSE 也执行
+    pub async fn remote_run(&mut self, force: bool, clear: ClearMode) -> Result<(), Error> {
+        let [low, high] = mode(force);
+        let data = vec![low, high, clear as u8, 0x00];
+        self.send(Request::Command(REMOTE_RUN, 0x0000, data.into()))
+            .await?;
+        Ok(())
+    }
+
+    /// 远程 STOP（1002）
+    pub async fn remote_stop(&mut self) -> Result<(), Error> {
+        let data = NORMAL.to_le_bytes().to_vec();
+        self.send(Request::Command(REMOTE_STOP, 0x0000, data.into()))
+            .await?;
+        Ok(())
+    }
+
+    /// 远程 PAUSE（1003），`force` 的含义同 [`Self::remote_run`]
+    pub async fn remote_pause(&mut self, force: bool) -> Result<(), Error> {
+        let data = mode(force).to_vec();
+        self.send(Request::Command(REMOTE_PAUSE, 0x0000, data.into()))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Response;
+    use async_trait::async_trait;
+
+    /// 记录每个命令及其数据
+    #[derive(Debug, Default)]
+    struct Plc(Vec<(u16, Vec<u8>)>);
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let Request::Command(command, 0x0000, data) = request else {
+                unreachable!()
+            };
+            self.0.push((command, data.into_owned()));
+            Ok(Response::Command(command, 0x0000, Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn encodes_remote_operations() {
+        let mut context = Context::new(Plc::default());
+        context.remote_stop().await.unwrap();
+        context.remote_pause(true).await.unwrap();
+        context
+            .remote_run(false, ClearMode::ExceptLatch)
+            .await
+            .unwrap();
+        assert_eq!(
+            context.client.0,
+            [
+                (REMOTE_STOP, vec![0x01, 0x00]),
+                (REMOTE_PAUSE, vec![0x03, 0x00]),
+                (REMOTE_RUN, vec![0x01, 0x00, 0x01, 0x00]),
+            ]
+        );
+
+        // 演练模式下不发出
+        context.set_dry_run(true);
+        context.remote_run(true, ClearMode::All).await.unwrap();
+        assert_eq!(context.client.0.len(), 3);
+    }
+}
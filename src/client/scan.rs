@@ -0,0 +1,166 @@
+//! 网段扫描
+//!
+//! 向一组 IP 地址与端口发起 TCP 连接，并以 CPU 型号读取（0101）确认对端是否为 MC 协议设备，
+//! 返回有应答的端点及其型号与往返时间。与 SLMP 节点搜索不同，扫描只需要 TCP 可达，
+//! 也适用于不支持节点搜索的设备。
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use futures_util::{stream, StreamExt as _};
+
+use crate::{frame::ProtocolError, trace, Error};
+
+use super::{tcp::connect_with_timeout, CpuModel};
+
+/// 有应答的端点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub addr: SocketAddr,
+    /// CPU 型号，设备以结束代码拒绝 0101 命令时为 `None`
+    pub model: Option<CpuModel>,
+    /// CPU 型号读取的往返时间，不含建立连接的时间
+    pub rtt: Duration,
+}
+
+/// 扫描参数
+#[derive(Debug, Clone, Copy)]
+pub struct ScanOptions {
+    /// 每个端点建立连接与等待应答各自的超时，默认 500 毫秒
+    pub timeout: Duration,
+    /// 同时探测的端点数，默认 32
+    pub concurrency: usize,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(500),
+            concurrency: 32,
+        }
+    }
+}
+
+/// 由 IP 地址范围与端口列表组合出所有端点
+pub fn targets(range: RangeInclusive<Ipv4Addr>, ports: &[u16]) -> Vec<SocketAddr> {
+    let (start, end) = (u32::from(*range.start()), u32::from(*range.end()));
+    (start..=end)
+        .flat_map(|ip| {
+            ports
+                .iter()
+                .map(move |&port| SocketAddr::from((Ipv4Addr::from(ip), port)))
+        })
+        .collect()
+}
+
+/// 探测单个端点，连接失败、超时或应答无法识别时返回 `None`
+pub async fn probe(addr: SocketAddr, timeout: Duration) -> Option<Endpoint> {
+    let mut context = connect_with_timeout(addr, timeout).await.ok()?;
+    let start = Instant::now();
+    let result = tokio::time::timeout(timeout, context.read_cpu_model()).await;
+    let rtt = start.elapsed();
+    let _ = context.disconnect().await;
+
+    let model = match result {
+        Ok(Ok(model)) => Some(model),
+        // 设备以结束代码应答，说明对端是 MC 协议设备
+        Ok(Err(Error::Protocol(ProtocolError::EndCode(_)))) => None,
+        Ok(Err(err)) => {
+            trace::debug!(peer = addr; "Probe failed: {err}");
+            return None;
+        }
+        Err(_) => {
+            trace::debug!(peer = addr; "Probe timed out");
+            return None;
+        }
+    };
+    Some(Endpoint { addr, model, rtt })
+}
+
+/// 并发探测所有端点，按地址顺序返回有应答的端点
+pub async fn scan<I>(targets: I, options: ScanOptions) -> Vec<Endpoint>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let mut endpoints: Vec<Endpoint> = stream::iter(targets)
+        .map(|addr| probe(addr, options.timeout))
+        .buffer_unordered(options.concurrency.max(1))
+        .filter_map(|endpoint| async move { endpoint })
+        .collect()
+        .await;
+    endpoints.sort_by_key(|endpoint| endpoint.addr);
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt as _, AsyncWriteExt as _},
+        net::TcpListener,
+    };
+
+    /// 应答一次 0101 命令，`end_code` 非零时以结束代码拒绝
+    async fn plc(end_code: u16) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 15];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[11..13], [0x01, 0x01]);
+
+            let mut data = end_code.to_le_bytes().to_vec();
+            if end_code == 0 {
+                data.extend_from_slice(b"R04CPU          ");
+                data.extend_from_slice(&0x4800u16.to_le_bytes());
+            } else {
+                data.extend_from_slice(&[0x00, 0xFF, 0xFF, 0x03, 0x00, 0x01, 0x01, 0x00, 0x00]);
+            }
+            let mut frame = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+            frame.extend_from_slice(&(data.len() as u16).to_le_bytes());
+            frame.extend_from_slice(&data);
+            stream.write_all(&frame).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn scan_reports_responding_endpoints() {
+        let plc_addr = plc(0).await;
+        let rejecting = plc(0xC059).await;
+        // 已关闭的端口
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let options = ScanOptions {
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let endpoints = scan([closed, rejecting, plc_addr], options).await;
+        let found: Vec<_> = endpoints.iter().map(|e| e.addr).collect();
+        let mut expected = vec![plc_addr, rejecting];
+        expected.sort();
+        assert_eq!(found, expected);
+
+        let plc = endpoints.iter().find(|e| e.addr == plc_addr).unwrap();
+        assert_eq!(plc.model.as_ref().unwrap().name, "R04CPU");
+        assert!(endpoints
+            .iter()
+            .any(|e| e.addr == rejecting && e.model.is_none()));
+    }
+
+    #[test]
+    fn targets_cover_range_and_ports() {
+        let range = Ipv4Addr::new(192, 168, 0, 255)..=Ipv4Addr::new(192, 168, 1, 0);
+        let targets = targets(range, &[5000, 5001]);
+        assert_eq!(targets.len(), 4);
+        assert_eq!(targets[3], "192.168.1.0:5001".parse().unwrap());
+    }
+}
@@ -0,0 +1,426 @@
+//! 分散软元件的随机读写
+//!
+//! 以字单位随机读取命令（0403/0000）一次读取多个不连续的字，以多块批量读取命令（0406/0000）
+//! 与多块批量写入命令（1406/0000）一次读写多段连续的字，以位单位随机写入命令（1402/0001）
+//! 一次写入多个不连续的位软元件，代替逐个地址发出的批量读写。
+//!
+//! PLC 不支持随机读取或多块批量读取时（见 [`capability`](super::capability) 模块），
+//! 改为逐个地址或逐块的批量读取，结果相同，只是请求数更多。
+
+use crate::{
+    convert::bytes_to_words,
+    frame::{find_instruction_code, ProtocolError, Quantity, Request},
+    Error,
+};
+
+use super::{area::Device, diagnostics::command_data, Client, Context, Reader as _};
+
+/// 随机读取命令
+const RANDOM_READ: u16 = 0x0403;
+/// 多块批量读取命令
+const BLOCK_READ: u16 = 0x0406;
+/// 随机写入命令
+const RANDOM_WRITE: u16 = 0x1402;
+/// 多块批量写入命令
+const BLOCK_WRITE: u16 = 0x1406;
+/// 字单位子命令
+const WORD_UNITS: u16 = 0x0000;
+/// 位单位子命令
+const BIT_UNITS: u16 = 0x0001;
+/// 单次字单位随机读取的最大点数
+const MAX_READ_POINTS: usize = 192;
+/// 单次多块批量读取的最大块数
+const MAX_BLOCKS: usize = 120;
+/// 单次多块批量读写的最大总字数
+const MAX_BLOCK_WORDS: Quantity = 960;
+/// 多块批量写入时每块额外计入总字数的点数
+const BLOCK_WRITE_OVERHEAD: Quantity = 4;
+/// 单次位单位随机写入的最大点数
+const MAX_POINTS: usize = 188;
+
+/// 随机读取与监视登记的数据：字访问点数、双字访问点数，随后每点 3 字节编号与 1 字节软元件代码
+pub(super) fn encode_word_entries(entries: &[(u32, u8)]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(2 + entries.len() * 4);
+    data.extend_from_slice(&[entries.len() as u8, 0]);
+    for &(number, code) in entries {
+        data.extend_from_slice(&number.to_le_bytes()[..3]);
+        data.push(code);
+    }
+    data
+}
+
+/// 多块批量读写中的一块，超出单次字数的块拆分为多块
+struct Piece {
+    /// 所属的请求块
+    block: usize,
+    /// 在所属请求块内的字偏移
+    offset: Quantity,
+    bit: bool,
+    number: u32,
+    code: u8,
+    words: Quantity,
+}
+
+impl<T: Client> Context<T> {
+    /// 以字单位随机读取分散的字，按 `addresses` 的顺序返回，见 [`Reader::read_random`](super::Reader::read_random)
+    pub(super) async fn read_random_words(
+        &mut self,
+        addresses: &[&str],
+    ) -> Result<Vec<u16>, Error> {
+        let entries = self.word_entries(addresses)?;
+        let mut words = Vec::with_capacity(entries.len());
+        for chunk in entries.chunks(MAX_READ_POINTS) {
+            let data = encode_word_entries(chunk);
+            let Some(response) = self.try_command(RANDOM_READ, WORD_UNITS, data).await? else {
+                return self.read_each_word(addresses).await;
+            };
+            let data = command_data(response);
+            if data.len() != chunk.len() * 2 {
+                return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                    expected: chunk.len() * 2,
+                    actual: data.len(),
+                }));
+            }
+            words.extend(bytes_to_words(&data));
+        }
+        Ok(words)
+    }
+
+    /// 不支持随机读取时逐个地址批量读取 1 字
+    async fn read_each_word(&mut self, addresses: &[&str]) -> Result<Vec<u16>, Error> {
+        let mut words = Vec::with_capacity(addresses.len());
+        for &addr in addresses {
+            words.extend(self.read_u16s(addr, 1).await?);
+        }
+        Ok(words)
+    }
+
+    /// 按 PLC 型号转换各地址，返回软元件编号与代码
+    pub(super) fn word_entries(&self, addresses: &[&str]) -> Result<Vec<(u32, u8)>, Error> {
+        addresses
+            .iter()
+            .map(|addr| {
+                let (device, number) = Device::parse(&self.process_address(addr)?)?;
+                let (code, _) = find_instruction_code(device.prefix())
+                    .expect("parsed devices have a device code");
+                Ok((number, code))
+            })
+            .collect()
+    }
+
+    /// 以多块批量读取一次读取多段连续的字，按 `blocks` 的顺序返回，见 [`Reader::read_blocks`](super::Reader::read_blocks)
+    pub(super) async fn read_word_blocks(
+        &mut self,
+        blocks: &[(&str, Quantity)],
+    ) -> Result<Vec<Vec<u16>>, Error> {
+        let pieces = self.block_pieces(blocks.iter().copied(), MAX_BLOCK_WORDS)?;
+        let mut values: Vec<Vec<u16>> = blocks
+            .iter()
+            .map(|&(_, count)| Vec::with_capacity(count as usize))
+            .collect();
+        let mut rest = &pieces[..];
+        while !rest.is_empty() {
+            let mut len = 0;
+            let mut total = 0;
+            while len < rest.len().min(MAX_BLOCKS) && total + rest[len].words <= MAX_BLOCK_WORDS {
+                total += rest[len].words;
+                len += 1;
+            }
+            let (request, remaining) = rest.split_at(len);
+            rest = remaining;
+
+            // 字软元件块数与位软元件块数，随后每块 3 字节编号、1 字节软元件代码与 2 字节字数，
+            // 字软元件块在前；应答数据的顺序与之相同
+            let (word_blocks, bit_blocks): (Vec<&Piece>, Vec<&Piece>) =
+                request.iter().partition(|piece| !piece.bit);
+            let mut data = Vec::with_capacity(2 + request.len() * 6);
+            data.extend_from_slice(&[word_blocks.len() as u8, bit_blocks.len() as u8]);
+            for piece in word_blocks.iter().chain(&bit_blocks) {
+                data.extend_from_slice(&piece.number.to_le_bytes()[..3]);
+                data.push(piece.code);
+                data.extend_from_slice(&(piece.words as u16).to_le_bytes());
+            }
+            let Some(response) = self.try_command(BLOCK_READ, WORD_UNITS, data).await? else {
+                let mut values = Vec::with_capacity(blocks.len());
+                for &(addr, count) in blocks {
+                    values.push(self.read_u16s(addr, count).await?);
+                }
+                return Ok(values);
+            };
+            let data = command_data(response);
+            if data.len() != total as usize * 2 {
+                return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                    expected: total as usize * 2,
+                    actual: data.len(),
+                }));
+            }
+            let mut words = bytes_to_words(&data).into_iter();
+            for piece in word_blocks.iter().chain(&bit_blocks) {
+                values[piece.block].extend(words.by_ref().take(piece.words as usize));
+            }
+        }
+        Ok(values)
+    }
+
+    /// 以多块批量写入一次写入多段连续的字，见 [`Writer::write_blocks`](super::Writer::write_blocks)
+    pub(super) async fn write_word_blocks(
+        &mut self,
+        blocks: &[(&str, &[u16])],
+    ) -> Result<(), Error> {
+        let counts = blocks
+            .iter()
+            .map(|&(addr, words)| (addr, words.len() as Quantity));
+        let pieces = self.block_pieces(counts, MAX_BLOCK_WORDS - BLOCK_WRITE_OVERHEAD)?;
+        for &(addr, words) in blocks {
+            let address = self.process_address(addr)?;
+            if let Some(cache) = &mut self.cache {
+                cache.invalidate(&address, words.len());
+            }
+        }
+
+        let mut rest = &pieces[..];
+        while !rest.is_empty() {
+            let mut len = 0;
+            let mut total = 0;
+            while len < rest.len().min(MAX_BLOCKS)
+                && total + BLOCK_WRITE_OVERHEAD + rest[len].words <= MAX_BLOCK_WORDS
+            {
+                total += BLOCK_WRITE_OVERHEAD + rest[len].words;
+                len += 1;
+            }
+            let (request, remaining) = rest.split_at(len);
+            rest = remaining;
+
+            // 与多块批量读取相同，每块的 6 字节之后紧跟该块的写入数据
+            let (word_blocks, bit_blocks): (Vec<&Piece>, Vec<&Piece>) =
+                request.iter().partition(|piece| !piece.bit);
+            let mut data = Vec::with_capacity(2 + total as usize * 2);
+            data.extend_from_slice(&[word_blocks.len() as u8, bit_blocks.len() as u8]);
+            for piece in word_blocks.iter().chain(&bit_blocks) {
+                data.extend_from_slice(&piece.number.to_le_bytes()[..3]);
+                data.push(piece.code);
+                data.extend_from_slice(&(piece.words as u16).to_le_bytes());
+                let words = &blocks[piece.block].1[piece.offset as usize..];
+                for word in &words[..piece.words as usize] {
+                    data.extend_from_slice(&word.to_le_bytes());
+                }
+            }
+            self.send(Request::Command(BLOCK_WRITE, WORD_UNITS, data.into()))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 按 PLC 型号转换各块的起始地址，超出 `max_words` 的块拆分为多块
+    fn block_pieces<'a>(
+        &self,
+        blocks: impl Iterator<Item = (&'a str, Quantity)>,
+        max_words: Quantity,
+    ) -> Result<Vec<Piece>, Error> {
+        let mut pieces = Vec::new();
+        for (block, (addr, count)) in blocks.enumerate() {
+            let address = self.process_address(addr)?;
+            let (device, number) = Device::parse(&address)?;
+            let (code, _) =
+                find_instruction_code(device.prefix()).expect("parsed devices have a device code");
+            let mut offset = 0;
+            while offset < count {
+                let words = (count - offset).min(max_words);
+                pieces.push(Piece {
+                    block,
+                    offset,
+                    bit: device.is_bit(),
+                    number: number + offset * device.step(),
+                    code,
+                    words,
+                });
+                offset += words;
+            }
+        }
+        Ok(pieces)
+    }
+
+    /// 以位单位随机写入一次写入分散的位软元件，见[模块文档](super::scatter)
+    ///
+    /// 各地址按 PLC 型号转换后须为位软元件；超过 188 点时拆分为多个请求依次发出，
+    /// 中途失败时之前的请求已经生效。
+    pub async fn write_scattered_bools(&mut self, points: &[(&str, bool)]) -> Result<(), Error> {
+        let mut entries = Vec::with_capacity(points.len());
+        for &(addr, value) in points {
+            let address = self.process_address(addr)?;
+            let (device, number) = Device::parse(&address)?;
+            if !device.is_bit() {
+                return Err(Error::Protocol(ProtocolError::InvalidAddress(format!(
+                    "{address} is not a bit device"
+                ))));
+            }
+            let (code, _) =
+                find_instruction_code(device.prefix()).expect("parsed devices have a device code");
+            if let Some(cache) = &mut self.cache {
+                cache.invalidate(&address, 1);
+            }
+            entries.push((number, code, value));
+        }
+
+        for chunk in entries.chunks(MAX_POINTS) {
+            let mut data = Vec::with_capacity(1 + chunk.len() * 5);
+            data.push(chunk.len() as u8);
+            for &(number, code, value) in chunk {
+                data.extend_from_slice(&number.to_le_bytes()[..3]);
+                data.push(code);
+                data.push(u8::from(value));
+            }
+            self.send(Request::Command(RANDOM_WRITE, BIT_UNITS, data.into()))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::Writer as _, frame::Response};
+    use async_trait::async_trait;
+
+    /// 记录每个随机读写请求的数据
+    #[derive(Debug, Default)]
+    struct Plc(Vec<Vec<u8>>);
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::Command(command @ (RANDOM_WRITE | BLOCK_WRITE), subcommand, data) => {
+                    self.0.push(data.into_owned());
+                    Ok(Response::Command(command, subcommand, Vec::new()))
+                }
+                // 每点返回其编号
+                Request::Command(RANDOM_READ, WORD_UNITS, data) => {
+                    let words: Vec<u8> = data[2..]
+                        .chunks(4)
+                        .flat_map(|entry| [entry[0], entry[1]])
+                        .collect();
+                    self.0.push(data.into_owned());
+                    Ok(Response::Command(RANDOM_READ, WORD_UNITS, words))
+                }
+                // 每块返回其编号起的连续整数
+                Request::Command(BLOCK_READ, WORD_UNITS, data) => {
+                    let words: Vec<u8> = data[2..]
+                        .chunks(6)
+                        .flat_map(|entry| {
+                            let number = u16::from_le_bytes([entry[0], entry[1]]);
+                            let count = u16::from_le_bytes([entry[4], entry[5]]);
+                            (number..number + count).flat_map(u16::to_le_bytes)
+                        })
+                        .collect();
+                    self.0.push(data.into_owned());
+                    Ok(Response::Command(BLOCK_READ, WORD_UNITS, words))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_scattered_bits() {
+        let mut context = Context::new(Plc::default());
+        context
+            .write_scattered_bools(&[("M100", true), ("X1F", false)])
+            .await
+            .unwrap();
+        assert_eq!(
+            context.client.0,
+            [[2, 0x64, 0, 0, 0x90, 1, 0x1F, 0, 0, 0x9C, 0]]
+        );
+
+        let addresses: Vec<String> = (0..200).map(|i| format!("M{}", i * 3)).collect();
+        let points: Vec<(&str, bool)> = addresses.iter().map(|a| (a.as_str(), true)).collect();
+        context.client.0.clear();
+        context.write_scattered_bools(&points).await.unwrap();
+        let counts: Vec<u8> = context.client.0.iter().map(|data| data[0]).collect();
+        assert_eq!(counts, [188, 12]);
+
+        context.client.0.clear();
+        assert!(context
+            .write_scattered_bools(&[("M0", true), ("D0", true)])
+            .await
+            .is_err());
+        assert!(context.write_scattered_bools(&[]).await.is_ok());
+        assert!(context.client.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reads_scattered_words_in_request_order() {
+        let mut context = Context::new(Plc::default());
+        let words = context.read_random(&["D300", "M16", "D5"]).await.unwrap();
+        assert_eq!(words, [300, 16, 5]);
+        assert_eq!(
+            context.client.0[0],
+            [3, 0, 0x2C, 0x01, 0, 0xA8, 0x10, 0, 0, 0x90, 5, 0, 0, 0xA8]
+        );
+
+        let addresses: Vec<String> = (0..200).map(|i| format!("D{i}")).collect();
+        let addresses: Vec<&str> = addresses.iter().map(String::as_str).collect();
+        context.client.0.clear();
+        let words = context.read_random(&addresses).await.unwrap();
+        assert_eq!(words, (0..200).collect::<Vec<u16>>());
+        let counts: Vec<u8> = context.client.0.iter().map(|data| data[0]).collect();
+        assert_eq!(counts, [192, 8]);
+    }
+
+    #[tokio::test]
+    async fn reads_blocks_in_request_order() {
+        let mut context = Context::new(Plc::default());
+        let blocks = context
+            .read_blocks(&[("M32", 2), ("D100", 3)])
+            .await
+            .unwrap();
+        assert_eq!(blocks, [vec![32, 33], vec![100, 101, 102]]);
+        // 字软元件块在前
+        assert_eq!(
+            context.client.0[0],
+            [1, 1, 100, 0, 0, 0xA8, 3, 0, 32, 0, 0, 0x90, 2, 0]
+        );
+
+        // 超出 960 字的块拆分，位软元件按每字 16 点推进编号
+        context.client.0.clear();
+        let blocks = context
+            .read_blocks(&[("D0", 1000), ("M0", 10)])
+            .await
+            .unwrap();
+        assert_eq!(blocks[0], (0..1000).collect::<Vec<u16>>());
+        assert_eq!(context.client.0.len(), 2);
+        assert_eq!(
+            context.client.0[1],
+            [1, 1, 0xC0, 3, 0, 0xA8, 40, 0, 0, 0, 0, 0x90, 10, 0]
+        );
+    }
+    #[tokio::test]
+    async fn writes_blocks_with_their_data() {
+        let mut context = Context::new(Plc::default());
+        context
+            .write_blocks(&[("M16", &[0x0003][..]), ("D100", &[1, 2])])
+            .await
+            .unwrap();
+        // 字软元件块在前，每块的数据紧跟其后
+        assert_eq!(
+            context.client.0,
+            [[1, 1, 100, 0, 0, 0xA8, 2, 0, 1, 0, 2, 0, 16, 0, 0, 0x90, 1, 0, 3, 0]]
+        );
+
+        // 每块计入 4 点：956 字的块单独成帧
+        context.client.0.clear();
+        let words: Vec<u16> = (0..1000).collect();
+        context
+            .write_blocks(&[("D0", &words[..]), ("W10", &[7])])
+            .await
+            .unwrap();
+        assert_eq!(context.client.0.len(), 2);
+        assert_eq!(context.client.0[0][6..8], 956u16.to_le_bytes());
+        assert_eq!(
+            context.client.0[1][..10],
+            [2, 0, 0xBC, 3, 0, 0xA8, 44, 0, 0xBC, 3]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! 握手写入序列
+//!
+//! 与 PLC 交换数据的常见方式是握手：写入命令字、等待 PLC 置位应答位、再写入数据块……
+//! [`Sequence`] 按顺序记录写入与等待条件，[`Sequence::run`] 依次执行：
+//!
+//! ```text
+//! Sequence::new()
+//!     .write_u16("D100", 1)                               // 命令字
+//!     .wait_bool("M200", true, Duration::from_secs(2))    // 等待应答位
+//!     .write_u16s("D110", &recipe)                        // 数据块
+//!     .write_bool("M201", true)                           // 完成标志
+//!     .run(&mut context)
+//!     .await?;
+//! ```
+//!
+//! 等待步骤每隔 [`Sequence::poll_interval`] 读取一次，超时返回 `TimedOut` 传输错误并指明步骤；
+//! 任一步骤失败时序列立即停止，之前的写入已经生效。
+
+use std::{fmt, io, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{trace, Error};
+
+use super::{Client, Context, Reader as _, Writer as _};
+
+/// 默认的轮询间隔
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 序列中的一步
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// 写入连续的字
+    WriteWords(String, Vec<u16>),
+    /// 写入连续的位
+    WriteBits(String, Vec<bool>),
+    /// 等待位变为指定值
+    WaitBit {
+        address: String,
+        value: bool,
+        timeout: Duration,
+    },
+    /// 等待字变为指定值
+    WaitWord {
+        address: String,
+        value: u16,
+        timeout: Duration,
+    },
+    /// 固定等待
+    Delay(Duration),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::WriteWords(address, words) => write!(f, "write {address} {words:?}"),
+            Step::WriteBits(address, bits) => write!(f, "write {address} {bits:?}"),
+            Step::WaitBit { address, value, .. } => write!(f, "wait for {address} = {value}"),
+            Step::WaitWord { address, value, .. } => write!(f, "wait for {address} = {value}"),
+            Step::Delay(duration) => write!(f, "delay {duration:?}"),
+        }
+    }
+}
+
+/// 按顺序执行的写入与等待，见[模块文档](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    steps: Vec<Step>,
+    poll_interval: Duration,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// 等待步骤的轮询间隔，默认 20ms
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn write_u16s(self, address: impl Into<String>, words: &[u16]) -> Self {
+        self.step(Step::WriteWords(address.into(), words.to_vec()))
+    }
+
+    pub fn write_u16(self, address: impl Into<String>, word: u16) -> Self {
+        self.write_u16s(address, &[word])
+    }
+
+    pub fn write_bools(self, address: impl Into<String>, bits: &[bool]) -> Self {
+        self.step(Step::WriteBits(address.into(), bits.to_vec()))
+    }
+
+    pub fn write_bool(self, address: impl Into<String>, bit: bool) -> Self {
+        self.write_bools(address, &[bit])
+    }
+
+    /// 等待 `address` 的位变为 `value`，超过 `timeout` 时序列失败
+    pub fn wait_bool(self, address: impl Into<String>, value: bool, timeout: Duration) -> Self {
+        self.step(Step::WaitBit {
+            address: address.into(),
+            value,
+            timeout,
+        })
+    }
+
+    /// 等待 `address` 的字变为 `value`，超过 `timeout` 时序列失败
+    pub fn wait_u16(self, address: impl Into<String>, value: u16, timeout: Duration) -> Self {
+        self.step(Step::WaitWord {
+            address: address.into(),
+            value,
+            timeout,
+        })
+    }
+
+    pub fn delay(self, duration: Duration) -> Self {
+        self.step(Step::Delay(duration))
+    }
+
+    /// 追加任意步骤
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// 依次执行全部步骤，任一步骤失败时停止并返回其错误
+    pub async fn run<T: Client>(&self, context: &mut Context<T>) -> Result<(), Error> {
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Err(err) = self.run_step(context, step).await {
+                trace::warning!("Sequence step {} ({step}) failed: {err}", index + 1);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_step<T: Client>(
+        &self,
+        context: &mut Context<T>,
+        step: &Step,
+    ) -> Result<(), Error> {
+        match step {
+            Step::WriteWords(address, words) => context.write_u16s(address, words).await,
+            Step::WriteBits(address, bits) => context.write_bools(address, bits).await,
+            Step::WaitBit {
+                address,
+                value,
+                timeout,
+            } => {
+                let deadline = Instant::now() + *timeout;
+                while context.read_bool(address).await? != *value {
+                    self.pause(deadline, step).await?;
+                }
+                Ok(())
+            }
+            Step::WaitWord {
+                address,
+                value,
+                timeout,
+            } => {
+                let deadline = Instant::now() + *timeout;
+                // 轮询须读取 PLC 的当前值，不使用读取缓存
+                while context.read_u16s_uncached(address, 1).await?[0] != *value {
+                    self.pause(deadline, step).await?;
+                }
+                Ok(())
+            }
+            Step::Delay(duration) => {
+                tokio::time::sleep(*duration).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// 等待下一次轮询，已到期时返回超时错误
+    async fn pause(&self, deadline: Instant, step: &Step) -> Result<(), Error> {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Error::Transport(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out: {step}"),
+            )));
+        }
+        tokio::time::sleep(self.poll_interval.min(deadline - now)).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codec::{bools_to_bytes, ClientDecoder},
+        frame::{Request, Response},
+    };
+    use async_trait::async_trait;
+
+    /// 写入 D100 后经过 `delay` 次读取才置位 M200，记录每次写入
+    #[derive(Debug, Default)]
+    struct Plc {
+        delay: usize,
+        armed: bool,
+        writes: Vec<String>,
+    }
+
+    impl Plc {
+        /// 握手信号的当前值，每次读取使剩余的延迟减一
+        fn poll(&mut self) -> bool {
+            let set = self.armed && self.delay == 0;
+            if self.armed {
+                self.delay = self.delay.saturating_sub(1);
+            }
+            set
+        }
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::WriteU8s(address, data) => {
+                    self.armed |= address == "D100";
+                    self.writes.push(format!("{address} {data:?}"));
+                    Ok(Response::WriteU8s())
+                }
+                Request::WriteBits(address, bits) => {
+                    self.writes.push(format!("{address} {bits:?}"));
+                    Ok(Response::WriteBits())
+                }
+                Request::ReadBits(_, _) => {
+                    let set = self.poll();
+                    // 与真实 PLC 一样以半字节打包应答，经客户端解码器解码
+                    let payload = [&[0x00, 0x00][..], &bools_to_bytes(&[set])].concat();
+                    ClientDecoder::decode(vec![payload.into()], request)
+                }
+                Request::ReadU8s(_, 1) => {
+                    let word = u16::from(self.poll());
+                    Ok(Response::ReadU8s(word.to_le_bytes().to_vec()))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn handshake() -> Sequence {
+        Sequence::new()
+            .write_u16("D100", 1)
+            .wait_bool("M200", true, Duration::from_secs(1))
+            .write_u16s("D110", &[7, 8])
+            .write_bool("M201", true)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_handshake_between_writes() {
+        let mut context = Context::new(Plc {
+            delay: 3,
+            ..Plc::default()
+        });
+        let started = Instant::now();
+        handshake().run(&mut context).await.unwrap();
+        assert_eq!(started.elapsed(), DEFAULT_POLL_INTERVAL * 3);
+        assert_eq!(
+            context.client.writes,
+            ["D100 [1, 0]", "D110 [7, 0, 8, 0]", "M201 [true]"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn word_waits_poll_the_plc_past_the_read_cache() {
+        let mut context = Context::new(Plc {
+            delay: 2,
+            ..Plc::default()
+        });
+        context.set_read_cache(Some(Duration::from_secs(3600)));
+        assert_eq!(context.read_u16("D200").await.unwrap(), 0);
+
+        Sequence::new()
+            .write_u16("D100", 1)
+            .wait_u16("D200", 1, Duration::from_secs(1))
+            .run(&mut context)
+            .await
+            .unwrap();
+        assert_eq!(context.client.delay, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_when_a_wait_times_out() {
+        let mut context = Context::new(Plc {
+            delay: usize::MAX,
+            ..Plc::default()
+        });
+        let Err(Error::Transport(err)) = handshake().run(&mut context).await else {
+            panic!("expected a timeout");
+        };
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(err.to_string(), "timed out: wait for M200 = true");
+        assert_eq!(context.client.writes, ["D100 [1, 0]"]);
+    }
+}
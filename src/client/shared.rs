@@ -0,0 +1,105 @@
+//! 共享连接
+//!
+//! [`SharedClient`] 以异步锁包装一个客户端，克隆出的句柄共用同一条连接，请求依次发出。
+//! 经 [`Context::into_shared`] 转换后，可用 [`Context::view`] 派生使用不同 [`Model`] 的上下文，
+//! 供经由同一链路转发 Keyence 与三菱两种地址写法的网关使用。
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{
+    frame::{Model, Request, Response},
+    Error,
+};
+
+use super::{Client, Context};
+
+/// 可克隆、共用同一内部客户端的 [`Client`]，见[模块文档](self)
+#[derive(Debug)]
+pub struct SharedClient<T>(Arc<Mutex<T>>);
+
+impl<T: Client> SharedClient<T> {
+    pub fn new(inner: T) -> Self {
+        Self(Arc::new(Mutex::new(inner)))
+    }
+}
+
+impl<T> Clone for SharedClient<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+#[async_trait]
+impl<T: Client> Client for SharedClient<T> {
+    async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        self.0.lock().await.call(request).await
+    }
+
+    /// 断开共用的连接，所有句柄随之失效
+    async fn disconnect(&mut self) -> std::io::Result<()> {
+        self.0.lock().await.disconnect().await
+    }
+}
+
+impl<T: Client> Context<T> {
+    /// 将内部客户端转为 [`SharedClient`]，型号等设置不变
+    pub fn into_shared(self) -> Context<SharedClient<T>> {
+        self.map_client(SharedClient::new)
+    }
+}
+
+impl<T: Client> Context<SharedClient<T>> {
+    /// 派生一个共用同一连接、按 `model` 转换地址的上下文
+    ///
+    /// 奇数字节策略、演练模式与已知不支持的命令沿用当前上下文，之后各自设置互不影响。
+    /// 派生的上下文不启用读缓存：一方的写入不会使另一方缓存的数据失效。
+    pub fn view(&self, model: Model) -> Self {
+        Self {
+            client: self.client.clone(),
+            model,
+            odd_length: self.odd_length,
+            cache: None,
+            dry_run: self.dry_run,
+            unsupported: self.unsupported.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Reader as _, Writer as _};
+
+    /// 记录收到的请求地址
+    #[derive(Debug, Default)]
+    struct Plc(Vec<String>);
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.0.push(request.address().to_string());
+            Ok(match request {
+                Request::ReadU8s(_, cnt) => Response::ReadU8s(vec![0; cnt as usize * 2]),
+                Request::WriteU8s(_, _) => Response::WriteU8s(),
+                _ => unreachable!(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn views_share_the_client_with_their_own_model() {
+        let mut keyence = Context::new(Plc::default()).into_shared();
+        keyence.set_plc_model(Model::Keyence);
+        let mut mitsubishi = keyence.view(Model::Mitsubishi);
+
+        keyence.read_u16s("DM100", 1).await.unwrap();
+        mitsubishi.write_u16s("D200", &[1]).await.unwrap();
+        // 三菱视图不转换地址
+        mitsubishi.read_u16s("DM100", 1).await.unwrap();
+
+        assert_eq!(keyence.client.0.lock().await.0, ["D100", "D200", "DM100"]);
+    }
+}
@@ -0,0 +1,259 @@
+//! 启动快照
+//!
+//! 应用启动时通常需要一次读取大量配方、参数与状态。[`Context::snapshot`] 接收一组带名称的地址范围
+//! （或地址簿中的标签），把同一软元件上相距不远的范围合并为块读取，以尽量少的请求取回全部数据，
+//! 结果按名称返回。合并的块读取失败时改为逐项读取，因此一项地址无效不会连累相邻的项；
+//! 各项的失败单独记录在 [`Snapshot::failures`] 中，不中断整个快照。
+
+use std::collections::HashMap;
+
+use crate::{
+    address_book::Tag,
+    frame::{ProtocolError, Quantity, Request},
+    trace, Error,
+};
+
+use super::{
+    area::{plan_reads, read_words, Device, BLOCK_WORDS},
+    Client, Context,
+};
+
+/// 快照中的一项：名称、起始地址与字数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotItem {
+    pub name: String,
+    /// 起始地址，按 PLC 型号转换；位软元件按字读取，每个字覆盖 16 点
+    pub address: String,
+    pub words: Quantity,
+}
+
+impl SnapshotItem {
+    pub fn new(name: impl Into<String>, address: impl Into<String>, words: Quantity) -> Self {
+        Self {
+            name: name.into(),
+            address: address.into(),
+            words,
+        }
+    }
+}
+
+impl From<&Tag> for SnapshotItem {
+    /// 标签读取一个字
+    fn from(tag: &Tag) -> Self {
+        Self::new(&tag.name, &tag.address, 1)
+    }
+}
+
+/// [`Context::snapshot`] 的结果
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    /// 读取成功的项
+    pub values: HashMap<String, Vec<u16>>,
+    /// 读取失败的项及其错误
+    pub failures: HashMap<String, Error>,
+}
+
+impl Snapshot {
+    /// 名为 `name` 的项读取到的字
+    pub fn get(&self, name: &str) -> Option<&[u16]> {
+        self.values.get(name).map(Vec::as_slice)
+    }
+
+    /// 名为 `name` 的项的第一个字
+    pub fn word(&self, name: &str) -> Option<u16> {
+        self.get(name)?.first().copied()
+    }
+
+    /// 是否全部读取成功
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 已解析的一项
+#[derive(Debug)]
+struct Entry {
+    index: usize,
+    device: Device,
+    number: u32,
+    words: u32,
+}
+
+impl<T: Client> Context<T> {
+    /// 读取启动所需的全部数据，见[模块文档](super::snapshot)
+    ///
+    /// 名称重复时后一项覆盖前一项。
+    pub async fn snapshot<I>(&mut self, items: I) -> Snapshot
+    where
+        I: IntoIterator,
+        I::Item: Into<SnapshotItem>,
+    {
+        let items: Vec<SnapshotItem> = items.into_iter().map(Into::into).collect();
+        let mut snapshot = Snapshot::default();
+        let mut entries = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            match self.snapshot_entry(index, item) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => {
+                    snapshot.failures.insert(item.name.clone(), err);
+                }
+            }
+        }
+
+        let spans: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.device, entry.number, entry.words))
+            .collect();
+        for block in plan_reads(&spans) {
+            let request = Request::ReadU8s(
+                block.device.address(block.start).into(),
+                block.words as Quantity,
+            );
+            match self.client.call(request).await {
+                Ok(response) => {
+                    let words = read_words(response);
+                    for &(span, offset) in &block.spans {
+                        let entry = &entries[span];
+                        let (offset, len) = (offset as usize, entry.words as usize);
+                        let name = items[entry.index].name.clone();
+                        match words.get(offset..offset + len) {
+                            Some(values) => {
+                                snapshot.values.insert(name, values.to_vec());
+                            }
+                            None => {
+                                let err = ProtocolError::LengthMismatch {
+                                    expected: (offset + len) * 2,
+                                    actual: words.len() * 2,
+                                };
+                                snapshot.failures.insert(name, Error::Protocol(err));
+                            }
+                        }
+                    }
+                }
+                Err(err) if block.spans.len() == 1 => {
+                    let name = items[entries[block.spans[0].0].index].name.clone();
+                    snapshot.failures.insert(name, err);
+                }
+                Err(err) => {
+                    trace::debug!(
+                        address = block.device.address(block.start);
+                        "Snapshot block read failed, reading items one by one: {err}"
+                    );
+                    for &(span, _) in &block.spans {
+                        let entry = &entries[span];
+                        let address = entry.device.address(entry.number);
+                        let name = items[entry.index].name.clone();
+                        match self
+                            .client
+                            .call(Request::ReadU8s(address.into(), entry.words as Quantity))
+                            .await
+                        {
+                            Ok(response) => {
+                                snapshot.values.insert(name, read_words(response));
+                            }
+                            Err(err) => {
+                                snapshot.failures.insert(name, err);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        snapshot
+    }
+
+    fn snapshot_entry(&self, index: usize, item: &SnapshotItem) -> Result<Entry, Error> {
+        let (device, number) = Device::parse(&self.process_address(&item.address)?)?;
+        if item.words == 0 || item.words as usize > BLOCK_WORDS {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        Ok(Entry {
+            index,
+            device,
+            number,
+            words: item.words,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{EndCode, Response};
+    use async_trait::async_trait;
+
+    /// 每个字的值为其编号；D9000 起的地址以结束代码拒绝，记录每次读取
+    #[derive(Debug, Default)]
+    struct Plc(Vec<(String, Quantity)>);
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let Request::ReadU8s(address, count) = request else {
+                unreachable!()
+            };
+            self.0.push((address.to_string(), count));
+            let (device, start) = Device::parse(&address)?;
+            if device.prefix() == "D" && start + count > 9000 {
+                return Err(Error::Protocol(ProtocolError::EndCode(EndCode::new(
+                    0xC056, 0x00, 0xFF, 0x03FF, 0x00,
+                ))));
+            }
+            let words = (start..start + count).map(|number| number as u16);
+            Ok(Response::ReadU8s(
+                words.flat_map(u16::to_le_bytes).collect(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_reads_and_reports_failures_per_item() {
+        let mut context = Context::new(Plc::default());
+        let snapshot = context
+            .snapshot([
+                SnapshotItem::new("recipe", "D100", 4),
+                SnapshotItem::new("speed", "D110", 1),
+                SnapshotItem::new("limit", "D8999", 1),
+                SnapshotItem::new("broken", "D9000", 2),
+                SnapshotItem::new("flags", "M32", 2),
+                SnapshotItem::new("typo", "QQ1", 1),
+            ])
+            .await;
+
+        assert_eq!(snapshot.get("recipe"), Some(&[100, 101, 102, 103][..]));
+        assert_eq!(snapshot.word("speed"), Some(110));
+        assert_eq!(snapshot.word("limit"), Some(8999));
+        assert_eq!(snapshot.get("flags"), Some(&[32, 33][..]));
+        assert!(!snapshot.is_complete());
+        assert!(matches!(
+            snapshot.failures["broken"],
+            Error::Protocol(ProtocolError::EndCode(_))
+        ));
+        assert!(snapshot.failures.contains_key("typo"));
+        assert_eq!(snapshot.failures.len(), 2);
+
+        // D100 与 D110 合并为一块；D8999 与 D9000 的块失败后逐项重读
+        assert_eq!(
+            context.client.0,
+            [
+                ("D100".to_string(), 11),
+                ("D8999".to_string(), 3),
+                ("D8999".to_string(), 1),
+                ("D9000".to_string(), 2),
+                ("M32".to_string(), 2),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_address_book_tags() {
+        let book = crate::address_book::AddressBook::parse_csv(
+            "speed,D5\ncount,D6",
+            crate::frame::Model::Mitsubishi,
+        )
+        .unwrap();
+        let snapshot = Context::new(Plc::default()).snapshot(&book.tags).await;
+        assert_eq!(snapshot.word("speed"), Some(5));
+        assert_eq!(snapshot.word("count"), Some(6));
+    }
+}
@@ -0,0 +1,71 @@
+//! 同步客户端的区域导出与导入
+
+use std::{
+    io::{BufRead, Write},
+    ops::Range,
+};
+
+use crate::{
+    client::{
+        area::{format_words, read_words, write_request, AreaLoader, DumpPlan},
+        checksum::Crc32,
+        AreaChecksum,
+    },
+    frame::Request,
+    Error,
+};
+
+use super::{AsyncClient, Client as _, Context};
+
+impl<T: AsyncClient> Context<T> {
+    /// 导出 `prefix` 软元件 `range` 范围的数据，见异步版本的 `dump_area`
+    pub fn dump_area<W: Write>(
+        &mut self,
+        prefix: &str,
+        range: Range<u32>,
+        writer: &mut W,
+    ) -> Result<usize, Error> {
+        let plan = DumpPlan::new(prefix, range)?;
+        writer.write_all(plan.header().as_bytes())?;
+        for (address, count) in plan.blocks() {
+            let response = self.call(Request::ReadU8s(address.into(), count))?;
+            writer.write_all(format_words(&read_words(response)).as_bytes())?;
+        }
+        writer.flush()?;
+        Ok(plan.words())
+    }
+
+    /// 读取 `dump_area` 导出的数据并写回，返回写入的字数
+    pub fn load_area<R: BufRead>(&mut self, reader: R) -> Result<usize, Error> {
+        let mut loader = AreaLoader::default();
+        for line in reader.lines() {
+            for (address, words) in loader.feed_line(&line?)? {
+                self.call(write_request(address, &words))?;
+            }
+        }
+        if let Some((address, words)) = loader.finish()? {
+            self.call(write_request(address, &words))?;
+        }
+        Ok(loader.written())
+    }
+
+    /// 读取 `prefix` 软元件 `range` 范围并计算校验和，见异步版本的 `area_crc`
+    pub fn area_crc(&mut self, prefix: &str, range: Range<u32>) -> Result<AreaChecksum, Error> {
+        self.checksum(DumpPlan::new(prefix, range)?)
+    }
+
+    /// 重新计算 `baseline` 覆盖区域的校验和，返回是否与基准一致
+    pub fn verify_area(&mut self, baseline: &AreaChecksum) -> Result<bool, Error> {
+        let plan = DumpPlan::at(&baseline.address, baseline.words)?;
+        Ok(self.checksum(plan)?.crc == baseline.crc)
+    }
+
+    fn checksum(&mut self, plan: DumpPlan) -> Result<AreaChecksum, Error> {
+        let mut crc = Crc32::new();
+        for (address, count) in plan.blocks() {
+            let response = self.call(Request::ReadU8s(address.into(), count))?;
+            crc.update_words(&read_words(response));
+        }
+        Ok(crc.finish(&plan))
+    }
+}
@@ -0,0 +1,130 @@
+//! 同步客户端的批量读写、握手序列、事务与监视
+
+use crate::{
+    client::{Monitor, Sequence, Snapshot, SnapshotItem, Transaction},
+    Error,
+};
+
+use super::{block_on_with_timeout, AsyncClient, Context};
+
+impl<T: AsyncClient> Context<T> {
+    forward_async! {
+        /// 以位单位随机写入分散的位软元件，见异步版本的 `write_scattered_bools`
+        pub fn write_scattered_bools(&mut self, points: &[(&str, bool)]) -> Result<(), Error>;
+
+        /// 监视登记 `addresses`，见异步版本的 `register_monitor`
+        pub fn register_monitor(&mut self, addresses: &[&str]) -> Result<Monitor, Error>;
+    }
+
+    /// 读取启动所需的全部数据，见异步版本的 `snapshot`
+    ///
+    /// 整个快照共用一次操作超时，超时时返回错误而不是部分结果。
+    pub fn snapshot<I>(&mut self, items: I) -> Result<Snapshot, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<SnapshotItem>,
+    {
+        let async_ctx = &mut self.async_ctx;
+        block_on_with_timeout(&self.runtime, &*self.timer, self.timeout, async move {
+            Ok(async_ctx.snapshot(items).await)
+        })
+    }
+
+    /// 依次执行 `sequence` 的全部步骤，见 [`Sequence::run`]
+    ///
+    /// 整个序列共用一次操作超时，等待步骤较长时需相应调大。
+    pub fn run_sequence(&mut self, sequence: &Sequence) -> Result<(), Error> {
+        block_on_with_timeout(
+            &self.runtime,
+            &*self.timer,
+            self.timeout,
+            sequence.run(&mut self.async_ctx),
+        )
+    }
+
+    /// 记录原值后执行 `transaction` 的全部写入，见 [`Transaction::commit`]
+    pub fn commit_transaction(&mut self, transaction: &mut Transaction) -> Result<(), Error> {
+        block_on_with_timeout(
+            &self.runtime,
+            &*self.timer,
+            self.timeout,
+            transaction.commit(&mut self.async_ctx),
+        )
+    }
+
+    /// 把 `transaction` 已尝试写入的目标写回原值，见 [`Transaction::rollback`]
+    pub fn rollback_transaction(&mut self, transaction: &mut Transaction) -> Result<(), Error> {
+        block_on_with_timeout(
+            &self.runtime,
+            &*self.timer,
+            self.timeout,
+            transaction.rollback(&mut self.async_ctx),
+        )
+    }
+
+    /// 执行一次监视，按登记顺序返回各点的字，见 [`Monitor::read`]
+    pub fn read_monitor(&mut self, monitor: &Monitor) -> Result<Vec<u16>, Error> {
+        block_on_with_timeout(
+            &self.runtime,
+            &*self.timer,
+            self.timeout,
+            monitor.read(&mut self.async_ctx),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::sync::Reader as _,
+        frame::{Request, Response},
+    };
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// 读取时每个字的值为其编号，写入只做记录
+    #[derive(Debug, Default)]
+    struct Plc(Vec<String>);
+
+    #[async_trait]
+    impl crate::client::Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.0.push(format!("{request:?}"));
+            match request {
+                Request::ReadU8s(address, count) => {
+                    let start: u16 = address[1..].parse().unwrap();
+                    let words = (start..start + count as u16).flat_map(u16::to_le_bytes);
+                    Ok(Response::ReadU8s(words.collect()))
+                }
+                Request::ReadBits(_, _) => Ok(Response::ReadBits(vec![true])),
+                Request::WriteU8s(_, _) => Ok(Response::WriteU8s()),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn mirrors_async_helpers() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let mut context = Context::new(Plc::default(), runtime, Some(Duration::from_secs(1)));
+
+        let snapshot = context
+            .snapshot([SnapshotItem::new("speed", "D5", 2)])
+            .unwrap();
+        assert_eq!(snapshot.get("speed"), Some(&[5, 6][..]));
+
+        let sequence =
+            Sequence::new()
+                .write_u16("D100", 1)
+                .wait_bool("M0", true, Duration::from_secs(1));
+        context.run_sequence(&sequence).unwrap();
+
+        let block = context.read_block("D10", 2).unwrap();
+        assert_eq!(block.u16_at("D11").unwrap(), 11);
+        assert_eq!(context.read_u16("D7").unwrap(), 7);
+    }
+}
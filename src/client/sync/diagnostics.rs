@@ -0,0 +1,76 @@
+//! 同步客户端的诊断信息汇总与远程操作
+
+use crate::{
+    client::{
+        diagnostics::{
+            command_data, cpu_model_request, loopback_matches, loopback_request, single_word,
+            LOOPBACK_DATA,
+        },
+        Capabilities, ClearMode, CpuModel, PlcHealth,
+    },
+    frame::Request,
+    Error,
+};
+
+use super::{block_on_with_timeout, AsyncClient, Client as _, Context};
+
+impl<T: AsyncClient> Context<T> {
+    /// 读取 CPU 型号（0101 命令）
+    pub fn read_cpu_model(&mut self) -> Result<CpuModel, Error> {
+        let response = self.call(cpu_model_request())?;
+        CpuModel::decode(&command_data(response))
+    }
+
+    /// 回送测试（0619 命令），见异步版本的 `loopback_test`
+    pub fn loopback_test(&mut self, data: &str) -> Result<bool, Error> {
+        let response = self.call(loopback_request(data)?)?;
+        Ok(loopback_matches(data, &command_data(response)))
+    }
+
+    /// 汇总诊断信息，见异步版本的 `diagnostics`
+    ///
+    /// 每一项单独应用操作超时。
+    pub fn diagnostics(&mut self) -> PlcHealth {
+        PlcHealth {
+            model: self.read_cpu_model(),
+            operating_status: self.read_special_register("SD203"),
+            error_code: self.read_special_register("SD0"),
+            loopback: self.loopback_test(LOOPBACK_DATA),
+        }
+    }
+
+    forward_async! {
+        /// 探测 PLC 支持的命令，见异步版本的 `probe_capabilities`
+        ///
+        /// 全部探测共用一次操作超时。
+        pub infallible fn probe_capabilities(&mut self) -> Result<Capabilities, Error>;
+
+        /// 远程 RUN（1001），见异步版本的 `remote_run`
+        pub fn remote_run(&mut self, force: bool, clear: ClearMode) -> Result<(), Error>;
+
+        /// 远程 STOP（1002）
+        pub fn remote_stop(&mut self) -> Result<(), Error>;
+
+        /// 远程 PAUSE（1003），见异步版本的 `remote_pause`
+        pub fn remote_pause(&mut self, force: bool) -> Result<(), Error>;
+    }
+
+    /// 把 `capabilities` 中以「不支持」的结束代码拒绝的命令记为不支持，见异步版本的 `set_capabilities`
+    pub fn set_capabilities(&mut self, capabilities: &Capabilities) {
+        self.async_ctx.set_capabilities(capabilities);
+    }
+
+    /// 把命令记为不支持，见异步版本的 `mark_unsupported`
+    pub fn mark_unsupported(&mut self, command: u16, subcommand: u16) {
+        self.async_ctx.mark_unsupported(command, subcommand);
+    }
+
+    /// 是否可以发出该命令，见异步版本的 `supports_command`
+    pub fn supports_command(&self, command: u16, subcommand: u16) -> bool {
+        self.async_ctx.supports_command(command, subcommand)
+    }
+
+    fn read_special_register(&mut self, address: &'static str) -> Result<u16, Error> {
+        single_word(self.call(Request::ReadU8s(address.into(), 1))?)
+    }
+}
@@ -1,15 +1,88 @@
-use futures_util::future::Either;
-use std::{future::Future, io, time::Duration};
+use futures_util::future::{self, Either};
+use std::{future::Future, io, pin::pin, sync::Arc, time::Duration};
 use tokio::runtime::Runtime;
 
 use crate::{frame::*, Error};
 
-use super::{Client as AsyncClient, Context as AsyncContext, Reader as _, Writer as _};
-#[cfg(feature = "sync")]
+use super::{
+    single, Client as AsyncClient, Context as AsyncContext, Reader as _, Timer, TokioTimer,
+    Writer as _,
+};
+
+/// 生成转发到异步 `Context` 同名方法的同步方法，使两套接口不会各自演变
+///
+/// `fn name<A>(&mut self, addr: &A, ...)` 中的 `A` 为地址参数，附带与 `Reader` 相同的约束。
+/// 生成的方法以 `block_on_with_timeout` 等待并应用操作超时；异步版本不返回 `Result` 的方法
+/// 以 `infallible` 标记，其结果包装为 `Ok`，超时时同样返回 `TimedOut` 错误。
+macro_rules! forward_async {
+    () => {};
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident<A>(&mut self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis fn $name<A>(&mut self $(, $arg: $ty)*) -> $ret
+        where
+            A: AsRef<str> + Send + Sync + ?Sized,
+        {
+            block_on_with_timeout(
+                &self.runtime,
+                &*self.timer,
+                self.timeout,
+                self.async_ctx.$name($($arg),*),
+            )
+        }
+        forward_async!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident(&mut self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+            block_on_with_timeout(
+                &self.runtime,
+                &*self.timer,
+                self.timeout,
+                self.async_ctx.$name($($arg),*),
+            )
+        }
+        forward_async!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis infallible fn $name:ident(&mut self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+            let async_ctx = &mut self.async_ctx;
+            block_on_with_timeout(
+                &self.runtime,
+                &*self.timer,
+                self.timeout,
+                async move { Ok(async_ctx.$name($($arg),*).await) },
+            )
+        }
+        forward_async!($($rest)*);
+    };
+}
+
+mod area;
+mod batch;
+mod diagnostics;
+mod poll;
+#[cfg(feature = "tcp")]
 pub mod tcp;
+mod view;
+
+pub use self::poll::{GroupStats, PollSchedule, Priority, StopToken};
 
 fn block_on_with_timeout<T, E>(
     runtime: &tokio::runtime::Runtime, // 传入一个 Tokio 运行时
+    timer: &dyn Timer,                 // 用于等待超时的计时器
     timeout: Option<Duration>,         // 可选的超时时间
     task: impl Future<Output = std::result::Result<T, E>>, // 异步任务，返回 `Result<T, E>`
 ) -> std::result::Result<T, E>
@@ -20,14 +93,15 @@ where
     // 根据是否设置了超时决定处理的方式
     let task = if let Some(duration) = timeout {
         // 如果 `timeout` 是 `Some`，即设置了超时
+        let sleep = timer.sleep(duration);
         Either::Left(async move {
-            // 使用 `tokio::time::timeout` 包装任务，超时后会返回错误
-            tokio::time::timeout(duration, task)
-                .await
-                .unwrap_or_else(|elapsed| {
-                    // 如果超时发生，返回一个 `TimedOut` 错误，并转换为 `E` 类型
-                    Err(io::Error::new(io::ErrorKind::TimedOut, elapsed).into())
-                })
+            // 任务与计时器竞争，计时器先完成时返回 `TimedOut` 错误，并转换为 `E` 类型
+            match future::select(pin!(task), sleep).await {
+                Either::Left((result, _)) => result,
+                Either::Right(((), _)) => {
+                    Err(io::Error::new(io::ErrorKind::TimedOut, "operation timed out").into())
+                }
+            }
         })
     } else {
         // 如果 `timeout` 为 `None`，直接执行任务
@@ -82,11 +156,11 @@ pub trait Reader: Client {
     where
         A: AsRef<str> + Send + Sync + ?Sized;
 
-    fn read_reconver_string<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
+    fn read_reconver_string<A>(&mut self, _addr: &A, _cnt: Quantity) -> Result<String, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
 
-    fn read_string<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
+    fn read_string<A>(&mut self, _addr: &A, _cnt: Quantity) -> Result<String, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
 
@@ -97,6 +171,104 @@ pub trait Reader: Client {
     ) -> Result<(Vec<u8>, Vec<bool>), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取 `cnt` 个字并按 UTF-16LE 解码为字符串
+    fn read_wstring<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取 `count` 个连续存放、每个占 `string_len_words` 字的字符串
+    fn read_strings<A>(
+        &mut self,
+        addr: &A,
+        string_len_words: Quantity,
+        count: Quantity,
+    ) -> Result<Vec<String>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 读取从 `addr` 起第 `bit_offset` 位开始、宽 `width`（1..=32）位的无符号整数
+    fn read_packed<A>(&mut self, addr: &A, bit_offset: u32, width: u32) -> Result<u32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
+
+    /// 以字单位随机读取（0403）一次读取分散的字，按 `addrs` 的顺序返回
+    fn read_random(&mut self, addrs: &[&str]) -> Result<Vec<u16>, Error>;
+
+    /// 以多块批量读取（0406）一次读取多段连续的字，按 `blocks` 的顺序返回
+    fn read_blocks(&mut self, blocks: &[(&str, Quantity)]) -> Result<Vec<Vec<u16>>, Error>;
+
+    /// 读取单个位
+    fn read_bool<A>(&mut self, addr: &A) -> Result<bool, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_bools(addr, 1)?)
+    }
+
+    /// 读取单个字
+    fn read_u16<A>(&mut self, addr: &A) -> Result<u16, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u16s(addr, 1)?)
+    }
+
+    /// 读取单个有符号字
+    fn read_i16<A>(&mut self, addr: &A) -> Result<i16, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i16s(addr, 1)?)
+    }
+
+    /// 读取单个双字
+    fn read_u32<A>(&mut self, addr: &A) -> Result<u32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u32s(addr, 1)?)
+    }
+
+    /// 读取单个有符号双字
+    fn read_i32<A>(&mut self, addr: &A) -> Result<i32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i32s(addr, 1)?)
+    }
+
+    /// 读取单个单精度浮点数
+    fn read_f32<A>(&mut self, addr: &A) -> Result<f32, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_f32s(addr, 1)?)
+    }
+
+    /// 读取单个四字
+    fn read_u64<A>(&mut self, addr: &A) -> Result<u64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_u64s(addr, 1)?)
+    }
+
+    /// 读取单个有符号四字
+    fn read_i64<A>(&mut self, addr: &A) -> Result<i64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_i64s(addr, 1)?)
+    }
+
+    /// 读取单个双精度浮点数
+    fn read_f64<A>(&mut self, addr: &A) -> Result<f64, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        single(self.read_f64s(addr, 1)?)
+    }
 }
 
 pub trait Writer: Client {
@@ -140,288 +312,211 @@ pub trait Writer: Client {
     where
         A: AsRef<str> + Send + Sync + ?Sized;
 
-    fn write_string<A>(&mut self, addr: &A, s: &A) -> Result<(), Error>
+    fn write_string<A>(&mut self, _addr: &A, _s: &A) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
 
-    fn write_reconver_string<A>(&mut self, addr: &A, s: &A) -> Result<(), Error>
+    fn write_reconver_string<A>(&mut self, _addr: &A, _s: &A) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized;
-}
-
-#[derive(Debug)]
-pub struct Context<T: AsyncClient> {
-    runtime: tokio::runtime::Runtime,
-    async_ctx: AsyncContext<T>,
-    timeout: Option<Duration>,
-}
-
-impl<T: AsyncClient> Context<T> {
-    /// 构造函数，初始化 `Context`，包含 `runtime` 和 `async_ctx`
-    pub fn new(async_ctx: T, runtime: Runtime, timeout: Option<Duration>) -> Self {
-        // 将传入的 `async_ctx` 包装为 `AsyncContext`
-        let async_ctx = AsyncContext::new(async_ctx); // 假设 `AsyncContext` 有 `new` 构造函数
 
-        Self {
-            async_ctx,
-            runtime,
-            timeout,
-        }
-    }
+    /// 以 UTF-16LE 写入字符串，末尾附带 0x0000 结束符
+    fn write_wstring<A>(&mut self, addr: &A, s: &str) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
 
-    pub fn set_plc_model(&mut self, model: Model) {
-        // 将模型传递给异步上下文
-        self.async_ctx.set_plc_model(model);
-    }
-}
+    /// 读-改-写方式更新从 `addr` 起第 `bit_offset` 位开始、宽 `width` 位的字段
+    fn write_packed<A>(
+        &mut self,
+        addr: &A,
+        bit_offset: u32,
+        width: u32,
+        value: u32,
+    ) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized;
 
-impl<T: AsyncClient> Client for Context<T> {
-    fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
-        block_on_with_timeout(&self.runtime, self.timeout, self.async_ctx.call(request))
-    }
-}
+    /// 以多块批量写入（1406）一次写入多段连续的字，每段为 `(起始地址, 数据)`
+    fn write_blocks(&mut self, blocks: &[(&str, &[u16])]) -> Result<(), Error>;
 
-impl<T: AsyncClient> Reader for Context<T> {
-    fn read_bools<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<bool>, Error>
+    /// 写入单个位
+    fn write_bool<A>(&mut self, addr: &A, value: bool) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_bools(addr, cnt),
-        )
+        self.write_bools(addr, &[value])
     }
 
-    fn read_u16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u16>, Error>
+    /// 写入单个字
+    fn write_u16<A>(&mut self, addr: &A, value: u16) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_u16s(addr, cnt),
-        )
+        self.write_u16s(addr, &[value])
     }
 
-    fn read_i16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i16>, Error>
+    /// 写入单个有符号字
+    fn write_i16<A>(&mut self, addr: &A, value: i16) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_i16s(addr, cnt),
-        )
+        self.write_i16s(addr, &[value])
     }
 
-    fn read_u32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u32>, Error>
+    /// 写入单个双字
+    fn write_u32<A>(&mut self, addr: &A, value: u32) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_u32s(addr, cnt),
-        )
+        self.write_u32s(addr, &[value])
     }
 
-    fn read_i32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i32>, Error>
+    /// 写入单个有符号双字
+    fn write_i32<A>(&mut self, addr: &A, value: i32) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_i32s(addr, cnt),
-        )
+        self.write_i32s(addr, &[value])
     }
 
-    fn read_f32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f32>, Error>
+    /// 写入单个单精度浮点数
+    fn write_f32<A>(&mut self, addr: &A, value: f32) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_f32s(addr, cnt),
-        )
+        self.write_f32s(addr, &[value])
     }
 
-    fn read_f64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f64>, Error>
+    /// 写入单个四字
+    fn write_u64<A>(&mut self, addr: &A, value: u64) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_f64s(addr, cnt),
-        )
+        self.write_u64s(addr, &[value])
     }
 
-    fn read_u64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u64>, Error>
+    /// 写入单个有符号四字
+    fn write_i64<A>(&mut self, addr: &A, value: i64) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_u64s(addr, cnt),
-        )
+        self.write_i64s(addr, &[value])
     }
 
-    fn read_i64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i64>, Error>
+    /// 写入单个双精度浮点数
+    fn write_f64<A>(&mut self, addr: &A, value: f64) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_i64s(addr, cnt),
-        )
+        self.write_f64s(addr, &[value])
     }
+}
 
-    fn read_reconver_string<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        // 目前未实现，返回错误
-        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
+#[derive(Debug)]
+pub struct Context<T: AsyncClient> {
+    runtime: tokio::runtime::Runtime,
+    async_ctx: AsyncContext<T>,
+    timeout: Option<Duration>,
+    timer: Arc<dyn Timer>,
+}
+
+impl<T: AsyncClient> Context<T> {
+    /// 构造函数，初始化 `Context`，包含 `runtime` 和 `async_ctx`
+    pub fn new(async_ctx: T, runtime: Runtime, timeout: Option<Duration>) -> Self {
+        // 将传入的 `async_ctx` 包装为 `AsyncContext`
+        let async_ctx = AsyncContext::new(async_ctx); // 假设 `AsyncContext` 有 `new` 构造函数
+
+        Self {
+            async_ctx,
+            runtime,
+            timeout,
+            timer: Arc::new(TokioTimer),
+        }
     }
 
-    fn read_string<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        // 目前未实现，返回错误
-        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
+    /// 替换超时使用的计时器，默认为 `TokioTimer`
+    pub fn set_timer(&mut self, timer: impl Timer + 'static) {
+        self.timer = Arc::new(timer);
     }
 
-    fn read_u8s_and_bools<A>(
-        &mut self,
-        addr: &A,
-        cnt: Quantity,
-    ) -> Result<(Vec<u8>, Vec<bool>), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        // 目前未实现，返回错误
-        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
+    pub fn set_plc_model(&mut self, model: Model) {
+        // 将模型传递给异步上下文
+        self.async_ctx.set_plc_model(model);
     }
 
-    fn read_u8s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u8>, Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.read_u8s(addr, cnt),
-        )
+    /// 当前的 PLC 型号
+    pub fn plc_model(&self) -> Model {
+        self.async_ctx.plc_model()
     }
-}
 
-impl<T: AsyncClient> Writer for Context<T> {
-    fn write_bools<A>(&mut self, addr: &A, bools: &'_ [bool]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_bools(addr, bools),
-        )
+    /// 在 `f` 执行期间按 `model` 转换地址，结束后恢复原型号，见异步 `Context::with_model`
+    pub fn with_model<R>(&mut self, model: Model, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.plc_model();
+        self.set_plc_model(model);
+        let result = f(self);
+        self.set_plc_model(previous);
+        result
     }
 
-    fn write_u16s<A>(&mut self, addr: &A, u16s: &'_ [u16]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_u16s(addr, u16s),
-        )
+    /// 设置 `write_u8s` 收到奇数字节时的处理方式，默认拒绝
+    pub fn set_odd_length_policy(&mut self, policy: OddLengthPolicy) {
+        self.async_ctx.set_odd_length_policy(policy);
     }
 
-    fn write_i16s<A>(&mut self, addr: &A, i16s: &'_ [i16]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_i16s(addr, i16s),
-        )
+    /// 开启或关闭读取缓存，见异步 `Context::set_read_cache`
+    pub fn set_read_cache(&mut self, max_age: Option<Duration>) {
+        self.async_ctx.set_read_cache(max_age);
     }
 
-    fn write_u32s<A>(&mut self, addr: &A, u32s: &[u32]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_u32s(addr, u32s),
-        )
+    /// 清空读取缓存
+    pub fn clear_read_cache(&mut self) {
+        self.async_ctx.clear_read_cache();
     }
 
-    fn write_i32s<A>(&mut self, addr: &A, i32s: &[i32]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_i32s(addr, i32s),
-        )
+    /// 开启或关闭演练模式，见异步 `Context::set_dry_run`
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.async_ctx.set_dry_run(dry_run);
     }
 
-    fn write_f32s<A>(&mut self, addr: &A, f32s: &[f32]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_f32s(addr, f32s),
-        )
+    /// 是否处于演练模式
+    pub fn is_dry_run(&self) -> bool {
+        self.async_ctx.is_dry_run()
     }
+}
 
-    fn write_u64s<A>(&mut self, addr: &A, u64s: &[u64]) -> Result<(), Error>
-    where
-        A: AsRef<str> + Send + Sync + ?Sized,
-    {
+impl<T: AsyncClient> Client for Context<T> {
+    fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
         block_on_with_timeout(
             &self.runtime,
+            &*self.timer,
             self.timeout,
-            self.async_ctx.write_u64s(addr, u64s),
+            self.async_ctx.call(request),
         )
     }
+}
 
-    fn write_i64s<A>(&mut self, addr: &A, i64s: &[i64]) -> Result<(), Error>
+impl<T: AsyncClient> Reader for Context<T> {
+    fn read_reconver_string<A>(&mut self, _addr: &A, _cnt: Quantity) -> Result<String, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_i64s(addr, i64s),
-        )
+        // 目前未实现，返回错误
+        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
     }
 
-    fn write_f64s<A>(&mut self, addr: &A, f64s: &[f64]) -> Result<(), Error>
+    fn read_string<A>(&mut self, _addr: &A, _cnt: Quantity) -> Result<String, Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_f64s(addr, f64s),
-        )
+        // 目前未实现，返回错误
+        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
     }
 
-    fn write_string<A>(&mut self, addr: &A, s: &A) -> Result<(), Error>
+    fn read_u8s_and_bools<A>(
+        &mut self,
+        _addr: &A,
+        _cnt: Quantity,
+    ) -> Result<(Vec<u8>, Vec<bool>), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
@@ -429,7 +524,46 @@ impl<T: AsyncClient> Writer for Context<T> {
         Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
     }
 
-    fn write_reconver_string<A>(&mut self, addr: &A, s: &A) -> Result<(), Error>
+    forward_async! {
+        fn read_bools<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<bool>, Error>;
+
+        fn read_u16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u16>, Error>;
+
+        fn read_i16s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i16>, Error>;
+
+        fn read_u32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u32>, Error>;
+
+        fn read_i32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i32>, Error>;
+
+        fn read_f32s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f32>, Error>;
+
+        fn read_f64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<f64>, Error>;
+
+        fn read_u64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u64>, Error>;
+
+        fn read_i64s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<i64>, Error>;
+
+        fn read_u8s<A>(&mut self, addr: &A, cnt: Quantity) -> Result<Vec<u8>, Error>;
+
+        fn read_wstring<A>(&mut self, addr: &A, cnt: Quantity) -> Result<String, Error>;
+
+        fn read_strings<A>(
+            &mut self,
+            addr: &A,
+            string_len_words: Quantity,
+            count: Quantity,
+        ) -> Result<Vec<String>, Error>;
+
+        fn read_packed<A>(&mut self, addr: &A, bit_offset: u32, width: u32) -> Result<u32, Error>;
+
+        fn read_random(&mut self, addrs: &[&str]) -> Result<Vec<u16>, Error>;
+
+        fn read_blocks(&mut self, blocks: &[(&str, Quantity)]) -> Result<Vec<Vec<u16>>, Error>;
+    }
+}
+
+impl<T: AsyncClient> Writer for Context<T> {
+    fn write_string<A>(&mut self, _addr: &A, _s: &A) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
@@ -437,14 +571,78 @@ impl<T: AsyncClient> Writer for Context<T> {
         Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
     }
 
-    fn write_u8s<A>(&mut self, addr: &A, u8s: &[u8]) -> Result<(), Error>
+    fn write_reconver_string<A>(&mut self, _addr: &A, _s: &A) -> Result<(), Error>
     where
         A: AsRef<str> + Send + Sync + ?Sized,
     {
-        block_on_with_timeout(
-            &self.runtime,
-            self.timeout,
-            self.async_ctx.write_u8s(addr, u8s),
-        )
+        // 目前未实现，返回错误
+        Err(Error::Protocol(crate::frame::ProtocolError::NotImplemented))
+    }
+
+    forward_async! {
+        fn write_bools<A>(&mut self, addr: &A, bools: &'_ [bool]) -> Result<(), Error>;
+
+        fn write_u16s<A>(&mut self, addr: &A, u16s: &'_ [u16]) -> Result<(), Error>;
+
+        fn write_i16s<A>(&mut self, addr: &A, i16s: &'_ [i16]) -> Result<(), Error>;
+
+        fn write_u32s<A>(&mut self, addr: &A, u32s: &[u32]) -> Result<(), Error>;
+
+        fn write_i32s<A>(&mut self, addr: &A, i32s: &[i32]) -> Result<(), Error>;
+
+        fn write_f32s<A>(&mut self, addr: &A, f32s: &[f32]) -> Result<(), Error>;
+
+        fn write_u64s<A>(&mut self, addr: &A, u64s: &[u64]) -> Result<(), Error>;
+
+        fn write_i64s<A>(&mut self, addr: &A, i64s: &[i64]) -> Result<(), Error>;
+
+        fn write_f64s<A>(&mut self, addr: &A, f64s: &[f64]) -> Result<(), Error>;
+
+        fn write_u8s<A>(&mut self, addr: &A, u8s: &[u8]) -> Result<(), Error>;
+
+        fn write_wstring<A>(&mut self, addr: &A, s: &str) -> Result<(), Error>;
+
+        fn write_packed<A>(
+            &mut self,
+            addr: &A,
+            bit_offset: u32,
+            width: u32,
+            value: u32,
+        ) -> Result<(), Error>;
+
+        fn write_blocks(&mut self, blocks: &[(&str, &[u16])]) -> Result<(), Error>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    #[derive(Debug)]
+    struct Unresponsive;
+
+    #[async_trait]
+    impl AsyncClient for Unresponsive {
+        async fn call(&mut self, _: Request<'_>) -> Result<Response, Error> {
+            std::future::pending().await
+        }
+    }
+
+    #[test]
+    fn timeout_uses_paused_tokio_time() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        let mut context = Context::new(Unresponsive, runtime, Some(Duration::from_secs(3600)));
+
+        let started = std::time::Instant::now();
+        let Err(Error::Transport(e)) = context.read_u16s("D0", 1) else {
+            panic!("expected a timeout");
+        };
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(1));
     }
 }
@@ -0,0 +1,341 @@
+//! 同步客户端的定时轮询
+
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{frame::Quantity, trace, Error};
+
+use super::{AsyncClient, Context, Reader as _};
+
+/// 轮询停止令牌
+///
+/// 克隆后的令牌共享同一状态，可在其他线程或回调中调用 `stop` 结束轮询。
+#[derive(Debug, Clone, Default)]
+pub struct StopToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl StopToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求停止，正在等待下一周期的轮询会立即返回
+    pub fn stop(&self) {
+        let (stopped, condvar) = &*self.inner;
+        *stopped.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        condvar.notify_all();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.inner.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 等待至 `deadline` 或被停止，返回是否已停止
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let (stopped, condvar) = &*self.inner;
+        let mut guard = stopped.lock().unwrap_or_else(|e| e.into_inner());
+        while !*guard {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            guard = condvar
+                .wait_timeout(guard, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        *guard
+    }
+}
+
+impl<T: AsyncClient> Context<T> {
+    /// 以固定周期读取 `cnt` 个字，直到 `stop` 被触发
+    ///
+    /// 周期以开始时刻为基准计算，不会因每次读取的耗时而累积漂移；
+    /// 若某次读取超过一个或多个周期，则跳过错过的周期。
+    /// 读取错误会交给回调处理，轮询继续进行。
+    pub fn poll_every<A, F>(
+        &mut self,
+        addr: &A,
+        cnt: Quantity,
+        interval: Duration,
+        stop: &StopToken,
+        mut callback: F,
+    ) where
+        A: AsRef<str> + Send + Sync + ?Sized,
+        F: FnMut(Result<Vec<u16>, Error>),
+    {
+        let interval = interval.max(Duration::from_millis(1));
+        let mut deadline = Instant::now();
+
+        while !stop.is_stopped() {
+            callback(self.read_u16s(addr, cnt));
+
+            deadline += interval;
+            let now = Instant::now();
+            if deadline < now {
+                let behind = (now - deadline).as_nanos() / interval.as_nanos();
+                let missed = u32::try_from(behind + 1).unwrap_or(u32::MAX);
+                trace::debug!(
+                    address = addr.as_ref(),
+                    missed = missed;
+                    "Polling fell behind, skipping ticks"
+                );
+                deadline += interval * missed;
+            }
+
+            if stop.wait_until(deadline) {
+                break;
+            }
+        }
+    }
+}
+
+/// 轮询组的优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// 每个周期都读取，不受预算限制
+    High,
+    Normal,
+    Low,
+}
+
+/// 单个轮询组的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupStats {
+    /// 已完成的读取次数，含失败的读取
+    pub reads: u64,
+    /// 因周期预算用尽而跳过的次数
+    pub skipped: u64,
+    /// 因读取耗时过长而错过的周期数
+    pub missed: u64,
+}
+
+#[derive(Debug)]
+struct PollGroup {
+    name: String,
+    address: String,
+    count: Quantity,
+    interval: Duration,
+    priority: Priority,
+    due: Option<Instant>,
+    stats: GroupStats,
+}
+
+/// 按周期分组、带预算的轮询计划
+///
+/// 每组以各自的周期（如 100ms、1s、10s）读取一段连续的字。每次唤醒时到期的组按优先级依次读取；
+/// 本次唤醒的读取耗时超过预算后，剩余的非 [`Priority::High`] 组跳过至下一周期，
+/// 以便 PLC 跟不上时保住高优先级的数据。跳过与超时情况计入 [`Self::stats`] 和 [`Self::overruns`]。
+#[derive(Debug)]
+pub struct PollSchedule {
+    groups: Vec<PollGroup>,
+    budget: Duration,
+    overruns: u64,
+}
+
+impl PollSchedule {
+    /// 每次唤醒的读取预算为 `budget`
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            groups: Vec::new(),
+            budget,
+            overruns: 0,
+        }
+    }
+
+    /// 添加一组：每隔 `interval` 从 `address` 读取 `count` 个字
+    #[must_use]
+    pub fn group(
+        mut self,
+        name: impl Into<String>,
+        address: impl Into<String>,
+        count: Quantity,
+        interval: Duration,
+        priority: Priority,
+    ) -> Self {
+        self.groups.push(PollGroup {
+            name: name.into(),
+            address: address.into(),
+            count,
+            interval: interval.max(Duration::from_millis(1)),
+            priority,
+            due: None,
+            stats: GroupStats::default(),
+        });
+        self
+    }
+
+    /// 各组名称及其统计，按添加顺序排列
+    pub fn stats(&self) -> impl Iterator<Item = (&str, GroupStats)> {
+        self.groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.stats))
+    }
+
+    /// 读取耗时超过预算的唤醒次数
+    pub fn overruns(&self) -> u64 {
+        self.overruns
+    }
+
+    /// 本次唤醒应处理的组，按优先级与到期时间排序
+    fn due(&self, now: Instant) -> Vec<usize> {
+        let mut due: Vec<usize> = (0..self.groups.len())
+            .filter(|&i| self.groups[i].due.is_none_or(|due| due <= now))
+            .collect();
+        due.sort_by_key(|&i| (self.groups[i].priority, self.groups[i].due));
+        due
+    }
+
+    /// 将组的下一次到期时间推进一个周期，跳过已错过的周期
+    fn advance(&mut self, index: usize, start: Instant) {
+        let group = &mut self.groups[index];
+        let mut due = group.due.unwrap_or(start) + group.interval;
+        let now = Instant::now();
+        if due < now {
+            let behind = (now - due).as_nanos() / group.interval.as_nanos();
+            let missed = u32::try_from(behind + 1).unwrap_or(u32::MAX);
+            group.stats.missed += u64::from(missed);
+            due += group.interval * missed;
+        }
+        group.due = Some(due);
+    }
+
+    fn next_wakeup(&self) -> Option<Instant> {
+        self.groups.iter().filter_map(|group| group.due).min()
+    }
+}
+
+impl<T: AsyncClient> Context<T> {
+    /// 按 `schedule` 轮询，直到 `stop` 被触发，见 [`PollSchedule`]
+    ///
+    /// 每次读取的结果连同组名交给回调，读取错误不会中止轮询。
+    pub fn poll_schedule<F>(
+        &mut self,
+        schedule: &mut PollSchedule,
+        stop: &StopToken,
+        mut callback: F,
+    ) where
+        F: FnMut(&str, Result<Vec<u16>, Error>),
+    {
+        while !stop.is_stopped() {
+            let start = Instant::now();
+            for index in schedule.due(start) {
+                let group = &schedule.groups[index];
+                if group.priority != Priority::High && start.elapsed() > schedule.budget {
+                    trace::debug!(group = group.name; "Poll budget exhausted, skipping group");
+                    schedule.groups[index].stats.skipped += 1;
+                } else {
+                    let result = self.read_u16s(&group.address, group.count);
+                    callback(&group.name, result);
+                    schedule.groups[index].stats.reads += 1;
+                }
+                schedule.advance(index, start);
+                if stop.is_stopped() {
+                    return;
+                }
+            }
+            if start.elapsed() > schedule.budget {
+                schedule.overruns += 1;
+            }
+
+            match schedule.next_wakeup() {
+                Some(deadline) => {
+                    if stop.wait_until(deadline) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Client,
+        frame::{Request, Response},
+    };
+    use async_trait::async_trait;
+
+    #[derive(Debug)]
+    struct Counter(u8);
+
+    #[async_trait]
+    impl Client for Counter {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.0 += 1;
+            match request {
+                Request::ReadU8s(_, _) if self.0 == 2 => Err(Error::Transport(
+                    std::io::Error::new(std::io::ErrorKind::TimedOut, "timeout"),
+                )),
+                Request::ReadU8s(_, cnt) => Ok(Response::ReadU8s(vec![self.0; cnt as usize * 2])),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn poll_every_continues_after_errors_until_stopped() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut context = Context::new(Counter(0), runtime, None);
+        let stop = StopToken::new();
+
+        let mut results = Vec::new();
+        context.poll_every("D0", 1, Duration::from_millis(1), &stop, |result| {
+            results.push(result.ok());
+            if results.len() == 3 {
+                stop.stop();
+            }
+        });
+
+        assert_eq!(results, [Some(vec![0x0101]), None, Some(vec![0x0303])]);
+    }
+
+    /// 每次读取耗时 2ms
+    #[derive(Debug)]
+    struct Slow;
+
+    #[async_trait]
+    impl Client for Slow {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            std::thread::sleep(Duration::from_millis(2));
+            let Request::ReadU8s(_, cnt) = request else {
+                unreachable!()
+            };
+            Ok(Response::ReadU8s(vec![0; cnt as usize * 2]))
+        }
+    }
+
+    #[test]
+    fn poll_schedule_skips_low_priority_groups_over_budget() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut context = Context::new(Slow, runtime, None);
+        let stop = StopToken::new();
+        let mut schedule = PollSchedule::new(Duration::from_millis(1))
+            .group("slow", "D100", 10, Duration::from_millis(1), Priority::Low)
+            .group("fast", "D0", 1, Duration::from_millis(1), Priority::High);
+
+        let mut reads = Vec::new();
+        context.poll_schedule(&mut schedule, &stop, |name, result| {
+            assert!(result.is_ok());
+            reads.push(name.to_string());
+            if reads.len() == 3 {
+                stop.stop();
+            }
+        });
+
+        // 高优先级组的读取就已用尽预算，低优先级组始终被跳过
+        assert_eq!(reads, ["fast", "fast", "fast"]);
+        let stats: Vec<(&str, GroupStats)> = schedule.stats().collect();
+        assert_eq!(stats[0].1.reads, 0);
+        assert!(stats[0].1.skipped >= 2);
+        assert_eq!(stats[1].1.reads, 3);
+        assert!(schedule.overruns() >= 2);
+    }
+}
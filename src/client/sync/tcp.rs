@@ -1,18 +1,28 @@
-use std::{io, net::SocketAddr, time::Duration};
-use tokio::net::TcpStream;
+use std::{io, time::Duration};
+use tokio::net::ToSocketAddrs;
 
-use crate::client::tcp::TcpClient;
+use crate::{
+    client::{
+        tcp::{connect_stream, TcpClient},
+        ConnectOptions, LatencyHistogram,
+    },
+    codec::FrameTransform,
+    frame::{FrameType, HeaderConfig, PlcProfile, Quantity, ResponseInfo},
+    stats::Traffic,
+};
 
-use super::Context;
+use super::{Context, Reader as _, Writer as _};
 use crate::Error;
 
-pub fn connect(socket_addr: SocketAddr) -> Result<Context<TcpClient>, Error> {
+/// `addr` may be a socket address or a `"host:port"` string, resolved
+/// asynchronously inside the client runtime with fallback across all records.
+pub fn connect(addr: impl ToSocketAddrs) -> Result<Context<TcpClient>, Error> {
     // Create a new Tokio runtime
     let runtime = tokio::runtime::Runtime::new()?;
 
     // Connect to TCP server through runtime and create TcpClient
     let tcp_client = runtime.block_on(async {
-        let stream = TcpStream::connect(socket_addr).await?;
+        let stream = connect_stream(addr).await?;
         Ok::<TcpClient, Error>(TcpClient::new(stream))
     })?;
 
@@ -24,7 +34,7 @@ pub fn connect(socket_addr: SocketAddr) -> Result<Context<TcpClient>, Error> {
 
 /// Connect with custom timeouts
 pub fn connect_with_timeout(
-    socket_addr: SocketAddr,
+    addr: impl ToSocketAddrs,
     connect_timeout: Duration,
     operation_timeout: Option<Duration>,
 ) -> Result<Context<TcpClient>, Error> {
@@ -33,9 +43,14 @@ pub fn connect_with_timeout(
 
     // Connect to TCP server through runtime and create TcpClient with connection timeout
     let tcp_client = runtime.block_on(async {
-        let stream = tokio::time::timeout(connect_timeout, TcpStream::connect(socket_addr))
+        let stream = tokio::time::timeout(connect_timeout, connect_stream(addr))
             .await
-            .map_err(|_| Error::Transport(io::Error::new(io::ErrorKind::TimedOut, "Connection timeout")))?
+            .map_err(|_| {
+                Error::Transport(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Connection timeout",
+                ))
+            })?
             .map_err(Error::Transport)?;
         Ok::<TcpClient, Error>(TcpClient::new(stream))
     })?;
@@ -45,3 +60,130 @@ pub fn connect_with_timeout(
 
     Ok(context)
 }
+
+/// Connect using a URL such as `mc://10.0.0.5:5007?model=keyence&timeout=2s`
+///
+/// The `timeout` parameter is used both as connect timeout and operation timeout,
+/// otherwise the defaults of [`connect`] apply.
+pub fn connect_url(url: &str) -> Result<Context<TcpClient>, Error> {
+    connect_with_options(url.parse()?)
+}
+
+/// Connect with parsed [`ConnectOptions`]
+pub fn connect_with_options(options: ConnectOptions) -> Result<Context<TcpClient>, Error> {
+    let mut context = match options.timeout {
+        Some(timeout) => connect_with_timeout(options.addr.as_str(), timeout, Some(timeout))?,
+        None => connect(options.addr.as_str())?,
+    };
+    context.set_plc_model(options.model);
+    context.set_profile(options.profile);
+    context.set_frame_type(options.frame_type);
+    Ok(context)
+}
+
+impl Context<TcpClient> {
+    /// 请求耗时直方图
+    pub fn latency(&self) -> &LatencyHistogram {
+        self.async_ctx.latency()
+    }
+
+    /// 清空耗时统计
+    pub fn reset_latency(&mut self) {
+        self.async_ctx.reset_latency();
+    }
+
+    /// 流量统计，见 [`TcpClient::traffic`]
+    pub fn traffic(&self) -> &Traffic {
+        self.async_ctx.traffic()
+    }
+
+    /// 最近一帧应答的帧头，见 [`TcpClient::last_response`]
+    pub fn last_response(&self) -> Option<ResponseInfo> {
+        self.async_ctx.last_response()
+    }
+
+    /// 按编码、收发、解码阶段统计的耗时
+    #[cfg(feature = "profiling")]
+    pub fn phases(&self) -> &crate::client::PhaseLatency {
+        self.async_ctx.phases()
+    }
+
+    /// 设置慢请求阈值，`None` 表示不记录慢请求
+    pub fn set_slow_request_threshold(&mut self, threshold: Option<Duration>) {
+        self.async_ctx.set_slow_request_threshold(threshold);
+    }
+
+    /// 当前使用的 PLC 系列参数
+    pub fn profile(&self) -> &PlcProfile {
+        self.async_ctx.profile()
+    }
+
+    /// 设置 PLC 系列参数
+    pub fn set_profile(&mut self, profile: PlcProfile) {
+        self.async_ctx.set_profile(profile);
+    }
+
+    /// 当前使用的帧格式
+    pub fn frame_type(&self) -> FrameType {
+        self.async_ctx.frame_type()
+    }
+
+    /// 设置帧格式，默认为 3E 帧
+    pub fn set_frame_type(&mut self, frame_type: FrameType) {
+        self.async_ctx.set_frame_type(frame_type);
+    }
+
+    /// 请求帧头中的访问路径
+    pub fn header_config(&self) -> HeaderConfig {
+        self.async_ctx.header_config()
+    }
+
+    /// 设置请求帧头中的访问路径，见 [`TcpClient::set_header_config`]
+    pub fn set_header_config(&mut self, header: HeaderConfig) {
+        self.async_ctx.set_header_config(header);
+    }
+
+    /// 经 `route` 读取字，不改变之后请求的访问路径
+    pub fn read_u16s_via<A>(
+        &mut self,
+        route: HeaderConfig,
+        addr: &A,
+        cnt: Quantity,
+    ) -> Result<Vec<u16>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let previous = self.header_config();
+        self.set_header_config(route);
+        let result = self.read_u16s(addr, cnt);
+        self.set_header_config(previous);
+        result
+    }
+
+    /// 经 `route` 写入字，不改变之后请求的访问路径
+    pub fn write_u16s_via<A>(
+        &mut self,
+        route: HeaderConfig,
+        addr: &A,
+        data: &[u16],
+    ) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let previous = self.header_config();
+        self.set_header_config(route);
+        let result = self.write_u16s(addr, data);
+        self.set_header_config(previous);
+        result
+    }
+
+    /// 设置收发帧的改写钩子，见 [`TcpClient::set_frame_transform`]
+    pub fn set_frame_transform(&mut self, transform: impl FrameTransform + 'static) {
+        self.async_ctx.set_frame_transform(transform);
+    }
+
+    /// 移除收发帧的改写钩子
+    pub fn clear_frame_transform(&mut self) {
+        self.async_ctx.clear_frame_transform();
+    }
+}
@@ -0,0 +1,26 @@
+//! 同步客户端的字视图与位视图
+
+use crate::{client::BlockView, frame::Quantity, Error};
+
+use super::{block_on_with_timeout, AsyncClient, Context};
+
+impl<T: AsyncClient> Context<T> {
+    forward_async! {
+        /// 以字为单位读取位软元件，见异步版本的 `read_bit_device_as_words`
+        pub fn read_bit_device_as_words<A>(
+            &mut self,
+            addr: &A,
+            cnt: Quantity,
+        ) -> Result<Vec<u16>, Error>;
+
+        /// 读取字软元件的各个位，见异步版本的 `read_word_device_bits`
+        pub fn read_word_device_bits<A>(
+            &mut self,
+            addr: &A,
+            bit_count: Quantity,
+        ) -> Result<Vec<bool>, Error>;
+
+        /// 读取连续的字并按地址取值，见异步版本的 `read_block`
+        pub fn read_block<A>(&mut self, addr: &A, cnt: Quantity) -> Result<BlockView, Error>;
+    }
+}
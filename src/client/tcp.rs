@@ -1,40 +1,110 @@
-use std::{fmt, io, net::SocketAddr, time::Duration};
+use std::{
+    fmt, io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use tokio::{
-    io::{AsyncRead, AsyncWrite},
-    net::TcpStream,
+    io::{split, AsyncRead, AsyncWrite, ReadHalf, WriteHalf},
+    net::{lookup_host, TcpStream, ToSocketAddrs},
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::{
+    bytes::{Bytes, BytesMut},
+    codec::{
+        ascii::{self, DataUnit},
+        batch_points, frame_1e,
+        hexdump::HexDump,
+        tcp::{McClientCodec, McClientDecoder, ResponseFormat},
+        FrameTransform, Splitter,
+    },
+    frame::{FrameType, HeaderConfig, PlcProfile, ProtocolError, Quantity, ResponseInfo},
+    stats::Traffic,
+    trace, Error,
 };
-use tokio_util::codec::Framed;
 
-use crate::{codec::tcp::McClientCodec, Error};
+use super::{ConnectOptions, LatencyHistogram, OperationId};
 
-use super::{Client, Context, Request, Response};
+use super::{Client, Context, Reader as _, Request, Response, Writer as _};
 
 /// Establish a direct connection to a MC TCP device
-pub async fn connect(socket_addr: SocketAddr) -> Result<Context<TcpClient>, Error> {
-    let transport = TcpStream::connect(socket_addr).await?;
+///
+/// `addr` may be a socket address or a `"host:port"` string. Every resolved address is tried
+/// in turn until one connects; if all fail, the last error is returned.
+pub async fn connect(addr: impl ToSocketAddrs) -> Result<Context<TcpClient>, Error> {
+    let transport = connect_stream(addr).await?;
     let client = TcpClient::new(transport);
     let context = Context::<TcpClient>::new(client);
     Ok(context)
 }
 
 /// Establish a direct connection to a MC TCP device with timeout
+///
+/// The timeout covers name resolution and all connection attempts.
 pub async fn connect_with_timeout(
-    socket_addr: SocketAddr,
+    addr: impl ToSocketAddrs,
     timeout: Duration,
 ) -> Result<Context<TcpClient>, Error> {
-    let transport = tokio::time::timeout(timeout, TcpStream::connect(socket_addr))
+    let transport = tokio::time::timeout(timeout, connect_stream(addr))
         .await
-        .map_err(|_| Error::Transport(io::Error::new(io::ErrorKind::TimedOut, "Connection timeout")))?
+        .map_err(|_| {
+            Error::Transport(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Connection timeout",
+            ))
+        })?
         .map_err(Error::Transport)?;
-    
+
     let client = TcpClient::new(transport);
     let context = Context::<TcpClient>::new(client);
     Ok(context)
 }
 
+/// 解析地址并依次尝试每个结果，返回第一个成功的连接
+///
+/// 主机名通过异步解析器查询，存在多条 A/AAAA 记录时，
+/// 前面的地址连接失败会继续尝试后面的地址；全部失败时返回最后一个错误。
+pub(crate) async fn connect_stream(addr: impl ToSocketAddrs) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for socket_addr in lookup_host(addr).await? {
+        match TcpStream::connect(socket_addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => {
+                trace::debug!(peer = socket_addr, error = err; "Connection attempt failed");
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    }))
+}
+
+/// Establish a connection described by a URL such as `mc://10.0.0.5:5007?model=keyence&timeout=2s`
+///
+/// See [`ConnectOptions`] for the supported parameters.
+pub async fn connect_url(url: &str) -> Result<Context<TcpClient>, Error> {
+    connect_with_options(url.parse()?).await
+}
+
+/// Establish a connection with parsed [`ConnectOptions`]
+pub async fn connect_with_options(options: ConnectOptions) -> Result<Context<TcpClient>, Error> {
+    let mut context = match options.timeout {
+        Some(timeout) => connect_with_timeout(options.addr.as_str(), timeout).await?,
+        None => connect(options.addr.as_str()).await?,
+    };
+    context.set_plc_model(options.model);
+    context.set_profile(options.profile);
+    context.set_frame_type(options.frame_type);
+    Ok(context)
+}
+
 /// Attach a new client context to a transport connection
 pub fn attach<T>(transport: T) -> Context<TcpClient<T>>
 where
@@ -44,9 +114,42 @@ where
     Context::<TcpClient<T>>::new(client)
 }
 
+/// 读写分离的连接：发送与接收各自持有一半，可以同时进行
+#[derive(Debug)]
+struct Transport<T> {
+    writer: FramedWrite<WriteHalf<T>, McClientCodec>,
+    reader: FramedRead<ReadHalf<T>, McClientDecoder>,
+}
+
+impl<T> Transport<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn new(transport: T) -> Self {
+        let (reader, writer) = split(transport);
+        Self {
+            writer: FramedWrite::new(writer, McClientCodec::new()),
+            reader: FramedRead::new(reader, McClientDecoder::default()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TcpClient<T = TcpStream> {
-    framed: Option<Framed<T, McClientCodec>>,
+    transport: Option<Transport<T>>,
+    latency: LatencyHistogram,
+    #[cfg(feature = "profiling")]
+    phases: super::PhaseLatency,
+    slow_request_threshold: Option<Duration>,
+    profile: PlcProfile,
+    splitter: Splitter,
+    frame_type: FrameType,
+    header: HeaderConfig,
+    transform: Option<Arc<dyn FrameTransform>>,
+    traffic: Arc<Traffic>,
+    last_response: Option<ResponseInfo>,
+    /// 自适应拆分时正在进行的批量请求，调用被取消时保留到下一次调用
+    in_flight: Option<(bool, Quantity)>,
 }
 
 impl<T> TcpClient<T>
@@ -55,23 +158,136 @@ where
 {
     /// Create a new TcpClient with the given transport
     pub fn new(transport: T) -> Self {
-        let framed = Framed::new(transport, McClientCodec::new());
         Self {
-            framed: Some(framed),
+            transport: Some(Transport::new(transport)),
+            latency: LatencyHistogram::new(),
+            #[cfg(feature = "profiling")]
+            phases: super::PhaseLatency::default(),
+            slow_request_threshold: None,
+            profile: PlcProfile::default(),
+            splitter: Splitter::new(),
+            frame_type: FrameType::default(),
+            header: HeaderConfig::default(),
+            transform: None,
+            traffic: {
+                let traffic = Traffic::new();
+                traffic.record_connection(false);
+                Arc::new(traffic)
+            },
+            last_response: None,
+            in_flight: None,
         }
     }
 
-    fn framed(&mut self) -> io::Result<&mut Framed<T, McClientCodec>> {
-        let Some(framed) = &mut self.framed else {
-            return Err(io::Error::new(io::ErrorKind::NotConnected, "disconnected"));
-        };
-        Ok(framed)
+    /// 以新的连接替换当前连接，保留全部设置与统计，计入重连次数
+    ///
+    /// 供检测到连接断开后自行重新建立连接的长时间运行的应用使用。
+    pub fn reconnect(&mut self, transport: T) {
+        self.transport = Some(Transport::new(transport));
+        self.traffic.record_connection(true);
+    }
+
+    /// 当前使用的 PLC 系列参数
+    pub fn profile(&self) -> &PlcProfile {
+        &self.profile
+    }
+
+    /// 设置 PLC 系列参数，决定请求拆分的点数与可用软元件
+    pub fn set_profile(&mut self, profile: PlcProfile) {
+        self.profile = profile;
+    }
+
+    /// 当前使用的请求拆分策略
+    pub fn splitter(&self) -> &Splitter {
+        &self.splitter
+    }
+
+    /// 设置请求拆分策略，默认仅按 PLC 系列的点数上限拆分
+    ///
+    /// 自适应拆分减小后的点数记录在当前策略中，设置新的策略会重新从上限开始。
+    pub fn set_splitter(&mut self, splitter: Splitter) {
+        self.splitter = splitter;
+    }
+
+    /// 当前使用的帧格式
+    pub fn frame_type(&self) -> FrameType {
+        self.frame_type
+    }
+
+    /// 设置帧格式，默认为 3E 帧
+    ///
+    /// 1E 帧按其固定的单帧点数拆分请求，不使用 [`Self::splitter`]；
+    /// 1E 帧与 ASCII 码的 3E 帧都不支持批量读写以外的命令。
+    pub fn set_frame_type(&mut self, frame_type: FrameType) {
+        self.frame_type = frame_type;
+    }
+
+    /// 请求帧头中的访问路径
+    pub fn header_config(&self) -> HeaderConfig {
+        self.header
+    }
+
+    /// 设置请求帧头中的访问路径，默认访问直接连接的本站 CPU
+    ///
+    /// 用于 3E 帧（含 ASCII 码），1E 帧没有访问路径。
+    pub fn set_header_config(&mut self, header: HeaderConfig) {
+        self.header = header;
+    }
+
+    /// 设置收发帧的改写钩子，替换已设置的钩子
+    ///
+    /// 改写在编码之后、发送之前以及解码之前进行，慢请求日志记录的是改写前的请求帧。
+    pub fn set_frame_transform(&mut self, transform: impl FrameTransform + 'static) {
+        self.transform = Some(Arc::new(transform));
+    }
+
+    /// 移除收发帧的改写钩子
+    pub fn clear_frame_transform(&mut self) {
+        self.transform = None;
+    }
+
+    /// 请求耗时直方图，每次逻辑操作（含所有分帧）记录一次
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+
+    /// 收发的帧数、重连次数等流量统计，见 [`stats`](crate::stats)
+    pub fn traffic(&self) -> &Traffic {
+        &self.traffic
+    }
+
+    /// 最近收到的一帧应答的帧头，包括以错误结束代码应答的帧，尚未收到应答时为 `None`
+    ///
+    /// 经由中继访问其它站时，可据此确认实际应答的站与请求的访问路径一致。
+    pub fn last_response(&self) -> Option<ResponseInfo> {
+        self.last_response
+    }
+
+    /// 清空耗时统计
+    pub fn reset_latency(&mut self) {
+        self.latency.reset();
+        #[cfg(feature = "profiling")]
+        self.phases.reset();
+    }
+
+    /// 按编码、收发、解码阶段统计的耗时
+    #[cfg(feature = "profiling")]
+    pub fn phases(&self) -> &super::PhaseLatency {
+        &self.phases
+    }
+
+    /// 设置慢请求阈值，耗时达到阈值的请求会连同完整帧内容记录到日志
+    pub fn set_slow_request_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_request_threshold = threshold;
     }
 
     async fn disconnect(&mut self) -> io::Result<()> {
-        if let Some(framed) = self.framed.take() {
+        if let Some(transport) = self.transport.take() {
             // Proper cleanup of the connection
-            let transport = framed.into_inner();
+            let transport = transport
+                .reader
+                .into_inner()
+                .unsplit(transport.writer.into_inner());
             drop(transport);
         }
         Ok(())
@@ -84,28 +300,705 @@ where
     T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
 {
     async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
-        let framed = self.framed()?;
+        let op = OperationId::next();
+        let call = self.call_frames(op, request);
+        // 同一逻辑操作的所有事件（包括各个分帧）都挂在同一个 span 下
+        #[cfg(feature = "tracing")]
+        let call =
+            tracing::Instrument::instrument(call, tracing::debug_span!("mc_operation", op = %op));
+        call.await
+    }
+
+    async fn disconnect(&mut self) -> io::Result<()> {
+        self.disconnect().await
+    }
+}
+
+impl<T> TcpClient<T>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    /// 发送请求，自适应拆分时按 PLC 的表现减小单帧点数，见 [`Splitter::adaptive`]
+    async fn call_frames(
+        &mut self,
+        op: OperationId,
+        request: Request<'_>,
+    ) -> Result<Response, Error> {
+        if let Some((bits, quantity)) = self.in_flight.take() {
+            // 上一次调用未完成就被取消，通常是调用方的超时
+            if self.splitter.shrink(bits, quantity, &self.profile) {
+                trace::debug!(op = op, points = self.splitter.learned_points(bits); "Reduced frame size after a cancelled request");
+            }
+        }
+        let adaptive = self.splitter.is_adaptive() && self.frame_type != FrameType::Binary1E;
+        let points = batch_points(&request).filter(|_| adaptive);
+        loop {
+            self.in_flight = points;
+            let result = self.call_split(op, request.clone()).await;
+            self.in_flight = None;
+            match (&result, points) {
+                (
+                    Err(Error::Protocol(ProtocolError::EndCode(end_code))),
+                    Some((bits, quantity)),
+                ) if (0xC051..=0xC054).contains(&end_code.code())
+                    && self.splitter.shrink(bits, quantity, &self.profile) =>
+                {
+                    trace::debug!(op = op, points = self.splitter.learned_points(bits); "Retrying with a smaller frame size");
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// 发送请求拆分出的每一帧并逐帧接收响应
+    async fn call_split(
+        &mut self,
+        op: OperationId,
+        request: Request<'_>,
+    ) -> Result<Response, Error> {
+        #[cfg(feature = "profiling")]
+        let encode_started = Instant::now();
+        let frames = match self.frame_type {
+            FrameType::Binary3E => crate::codec::ClientEncoder::encode_routed(
+                request.clone(),
+                &self.profile,
+                &self.splitter,
+                self.header,
+            )?,
+            FrameType::Binary1E => frame_1e::encode_request(&request)?,
+            FrameType::Ascii3E => crate::codec::ClientEncoder::encode_routed(
+                request.clone(),
+                &self.profile,
+                &self.splitter,
+                self.header,
+            )?
+            .iter()
+            .map(|frame| ascii::binary_to_ascii(frame, DataUnit::Words).map(Bytes::from))
+            .collect::<Result<_, _>>()?,
+        };
+        #[cfg(feature = "profiling")]
+        self.phases.encode.record(encode_started.elapsed());
+        let chunks = frames.len();
+
+        trace::debug!(
+            op = op,
+            function = request.function_code(),
+            address = request.address(),
+            chunks = chunks;
+            "Sending request"
+        );
+
+        let started = Instant::now();
+        let payloads = self.exchange(op, &frames).await;
+        let elapsed = started.elapsed();
+        self.latency.record(elapsed);
+        #[cfg(feature = "profiling")]
+        self.phases.io.record(elapsed);
+
+        if self
+            .slow_request_threshold
+            .is_some_and(|threshold| elapsed >= threshold)
+        {
+            let responses = payloads.as_deref().unwrap_or_default();
+            trace::warning!(
+                op = op,
+                function = request.function_code(),
+                address = request.address(),
+                elapsed = elapsed,
+                requests = frames.iter().map(|frame| trace::Hex(frame)).collect::<Vec<_>>(),
+                responses = responses.iter().map(|payload| trace::Hex(payload)).collect::<Vec<_>>();
+                "Slow request"
+            );
+        }
+
+        // Use ClientDecoder to merge and parse the payloads of all frames
+        #[cfg(feature = "profiling")]
+        let decode_started = Instant::now();
+        let response = crate::codec::ClientDecoder::decode(payloads?, request);
+        #[cfg(feature = "profiling")]
+        self.phases.decode.record(decode_started.elapsed());
+        let response = response?;
+
+        Ok(response)
+    }
+
+    /// 逐帧发送并接收响应，返回各帧以结束代码开头的 payload
+    async fn exchange(&mut self, op: OperationId, frames: &[Bytes]) -> Result<Vec<Bytes>, Error> {
+        let frame_type = self.frame_type;
+        let transform = self.transform.clone();
+        let traffic = Arc::clone(&self.traffic);
+        let Some(Transport { writer, reader }) = &mut self.transport else {
+            return Err(io::Error::new(io::ErrorKind::NotConnected, "disconnected").into());
+        };
+        reader.decoder_mut().transform = transform.clone();
+        reader.decoder_mut().traffic = Some(Arc::clone(&traffic));
 
         // Clear any existing data in the read buffer
-        framed.read_buffer_mut().clear();
+        reader.read_buffer_mut().clear();
+
+        let mut payloads = Vec::with_capacity(frames.len());
+        for (chunk, frame) in frames.iter().enumerate() {
+            trace::debug!(op = op, chunk = chunk, bytes = trace::Hex(frame); "Sending frame");
+            trace::event!(trace, "Request frame\n{}", HexDump(frame));
+            let frame_len = match &transform {
+                Some(transform) => {
+                    let mut frame = BytesMut::from(&frame[..]);
+                    transform.outgoing(&mut frame);
+                    let frame_len = frame.len();
+                    writer.send(frame.freeze()).await?;
+                    frame_len
+                }
+                None => {
+                    writer.send(frame.clone()).await?;
+                    frame.len()
+                }
+            };
+            traffic.record_sent(frame_len);
+            reader.decoder_mut().format = match frame_type {
+                FrameType::Binary3E => ResponseFormat::Binary3E,
+                FrameType::Binary1E => {
+                    let (subheader, data_len) = frame_1e::expected_response(frame);
+                    ResponseFormat::Binary1E {
+                        subheader,
+                        data_len,
+                    }
+                }
+                FrameType::Ascii3E => ResponseFormat::Ascii3E(ascii::response_unit(frame)),
+            };
+
+            // Receive the raw response frame
+            let response_frame = reader.next().await.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed")
+            })??;
+            self.last_response = Some(response_frame.info());
+            if let Some(end_code) = response_frame.end_code() {
+                trace::debug!(op = op, chunk = chunk, code = end_code.code(); "PLC returned error end code");
+                return Err(ProtocolError::EndCode(end_code).into());
+            }
+            payloads.push(response_frame.payload);
+        }
+
+        Ok(payloads)
+    }
+}
+
+impl<T> Context<TcpClient<T>>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    /// 请求耗时直方图
+    pub fn latency(&self) -> &LatencyHistogram {
+        self.client.latency()
+    }
+
+    /// 清空耗时统计
+    pub fn reset_latency(&mut self) {
+        self.client.reset_latency();
+    }
+
+    /// 流量统计，见 [`TcpClient::traffic`]
+    pub fn traffic(&self) -> &Traffic {
+        self.client.traffic()
+    }
+
+    /// 最近一帧应答的帧头，见 [`TcpClient::last_response`]
+    pub fn last_response(&self) -> Option<ResponseInfo> {
+        self.client.last_response()
+    }
+
+    /// 以新的连接替换当前连接，见 [`TcpClient::reconnect`]
+    pub fn reconnect(&mut self, transport: T) {
+        self.client.reconnect(transport);
+    }
+
+    /// 按编码、收发、解码阶段统计的耗时
+    #[cfg(feature = "profiling")]
+    pub fn phases(&self) -> &super::PhaseLatency {
+        self.client.phases()
+    }
+
+    /// 设置慢请求阈值，`None` 表示不记录慢请求
+    pub fn set_slow_request_threshold(&mut self, threshold: Option<Duration>) {
+        self.client.set_slow_request_threshold(threshold);
+    }
+
+    /// 当前使用的 PLC 系列参数
+    pub fn profile(&self) -> &PlcProfile {
+        self.client.profile()
+    }
 
-        // Send the request
-        framed.send(request.clone()).await?;
+    /// 设置 PLC 系列参数
+    pub fn set_profile(&mut self, profile: PlcProfile) {
+        self.client.set_profile(profile);
+    }
+
+    /// 当前使用的帧格式
+    pub fn frame_type(&self) -> FrameType {
+        self.client.frame_type()
+    }
+
+    /// 设置帧格式，见 [`TcpClient::set_frame_type`]
+    pub fn set_frame_type(&mut self, frame_type: FrameType) {
+        self.client.set_frame_type(frame_type);
+    }
+
+    /// 请求帧头中的访问路径
+    pub fn header_config(&self) -> HeaderConfig {
+        self.client.header_config()
+    }
+
+    /// 设置请求帧头中的访问路径，见 [`TcpClient::set_header_config`]
+    ///
+    /// 访问路径改变时清空读取缓存，缓存的值属于原来访问的站。
+    pub fn set_header_config(&mut self, header: HeaderConfig) {
+        if header != self.header_config() {
+            self.clear_read_cache();
+        }
+        self.client.set_header_config(header);
+    }
+
+    /// 临时经 `route` 访问，返回的守卫释放时恢复原访问路径
+    ///
+    /// 同一连接上混合访问本站与经中继的其他站时使用，守卫上可直接调用读写方法：
+    /// `context.via(route).read_u16s("D100", 1).await`
+    pub fn via(&mut self, route: HeaderConfig) -> RouteOverride<'_, T> {
+        let previous = self.header_config();
+        self.set_header_config(route);
+        RouteOverride {
+            context: self,
+            previous,
+        }
+    }
+
+    /// 经 `route` 读取字，不改变之后请求的访问路径，见 [`Self::via`]
+    pub async fn read_u16s_via<A>(
+        &mut self,
+        route: HeaderConfig,
+        addr: &A,
+        cnt: Quantity,
+    ) -> Result<Vec<u16>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.via(route).read_u16s(addr, cnt).await
+    }
+
+    /// 经 `route` 写入字，不改变之后请求的访问路径，见 [`Self::via`]
+    pub async fn write_u16s_via<A>(
+        &mut self,
+        route: HeaderConfig,
+        addr: &A,
+        data: &[u16],
+    ) -> Result<(), Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        self.via(route).write_u16s(addr, data).await
+    }
+
+    /// 设置收发帧的改写钩子，见 [`TcpClient::set_frame_transform`]
+    pub fn set_frame_transform(&mut self, transform: impl FrameTransform + 'static) {
+        self.client.set_frame_transform(transform);
+    }
+
+    /// 移除收发帧的改写钩子
+    pub fn clear_frame_transform(&mut self) {
+        self.client.clear_frame_transform();
+    }
+
+    /// 当前使用的请求拆分策略
+    pub fn splitter(&self) -> &Splitter {
+        self.client.splitter()
+    }
+
+    /// 设置请求拆分策略
+    pub fn set_splitter(&mut self, splitter: Splitter) {
+        self.client.set_splitter(splitter);
+    }
+}
+
+/// [`Context::via`] 返回的守卫，释放时恢复原访问路径
+#[derive(Debug)]
+pub struct RouteOverride<'a, T>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    context: &'a mut Context<TcpClient<T>>,
+    previous: HeaderConfig,
+}
+
+impl<T> std::ops::Deref for RouteOverride<'_, T>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    type Target = Context<TcpClient<T>>;
 
-        // Receive the raw response bytes
-        let raw_response = framed
-            .next()
+    fn deref(&self) -> &Context<TcpClient<T>> {
+        self.context
+    }
+}
+
+impl<T> std::ops::DerefMut for RouteOverride<'_, T>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    fn deref_mut(&mut self) -> &mut Context<TcpClient<T>> {
+        self.context
+    }
+}
+
+impl<T> Drop for RouteOverride<'_, T>
+where
+    T: fmt::Debug + AsyncRead + AsyncWrite + Send + Unpin,
+{
+    fn drop(&mut self) {
+        self.context.set_header_config(self.previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn connect_falls_back_to_next_resolved_address() {
+        // 先占用再释放一个端口，得到一个拒绝连接的地址
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed_addr = closed.local_addr().unwrap();
+        drop(closed);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open_addr = listener.local_addr().unwrap();
+
+        let stream = connect_stream(&[closed_addr, open_addr][..]).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), open_addr);
+        assert!(connect_stream(&[closed_addr][..]).await.is_err());
+        assert!(connect(format!("localhost:{}", open_addr.port()))
             .await
-            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed"))??;
+            .is_ok());
+    }
 
-        // Convert raw bytes to Vec<Bytes> and use ClientDecoder for parsing
-        let bytes_vec = vec![raw_response];
-        let response = crate::codec::ClientDecoder::decode(bytes_vec, request)?;
+    #[tokio::test]
+    async fn split_request_receives_one_response_per_frame() {
+        let (client, mut plc) = duplex(16 * 1024);
 
-        Ok(response)
+        // 模拟 PLC：每收到一个读请求帧就返回对应点数的数据
+        let plc_task = tokio::spawn(async move {
+            let mut frames = 0;
+            let mut header = [0u8; 9];
+            while plc.read_exact(&mut header).await.is_ok() {
+                let len = u16::from_le_bytes([header[7], header[8]]) as usize;
+                let mut body = vec![0u8; len];
+                plc.read_exact(&mut body).await.unwrap();
+                let points = u16::from_le_bytes([body[10], body[11]]) as usize;
+
+                let mut response = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+                response.extend_from_slice(&((points * 2 + 2) as u16).to_le_bytes());
+                response.extend_from_slice(&[0x00, 0x00]);
+                response.extend(std::iter::repeat_n(frames as u8, points * 2));
+                plc.write_all(&response).await.unwrap();
+                frames += 1;
+            }
+            frames
+        });
+
+        let mut context = attach(client);
+        let words = context.read_u16s("D0", 2000).await.unwrap();
+        assert_eq!(words.len(), 2000);
+        assert_eq!(words[0], 0x0000);
+        assert_eq!(words[1999], 0x0202);
+
+        assert_eq!(context.latency().count(), 1);
+        let traffic = context.traffic().snapshot();
+        assert_eq!((traffic.frames_sent, traffic.frames_received), (3, 3));
+        assert_eq!(traffic.bytes_received, 3 * 11 + 2000 * 2);
+        assert_eq!((traffic.connections, traffic.reconnects), (1, 0));
+        #[cfg(feature = "profiling")]
+        {
+            let phases = context.phases();
+            assert_eq!(phases.encode.count(), 1);
+            assert_eq!(phases.io.count(), 1);
+            assert_eq!(phases.decode.count(), 1);
+        }
+
+        context.disconnect().await.unwrap();
+        assert_eq!(plc_task.await.unwrap(), 3);
     }
 
-    async fn disconnect(&mut self) -> io::Result<()> {
-        self.disconnect().await
+    #[tokio::test]
+    async fn odd_bit_reads_drop_the_padding_nibble() {
+        let (client, mut plc) = duplex(1024);
+
+        // 模拟 PLC：按请求点数返回半字节打包的位，填充的半字节也置位
+        tokio::spawn(async move {
+            let mut header = [0u8; 9];
+            while plc.read_exact(&mut header).await.is_ok() {
+                let len = u16::from_le_bytes([header[7], header[8]]) as usize;
+                let mut body = vec![0u8; len];
+                plc.read_exact(&mut body).await.unwrap();
+                let points = u16::from_le_bytes([body[10], body[11]]) as usize;
+
+                let bytes = points.div_ceil(2);
+                let mut response = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+                response.extend_from_slice(&((bytes + 2) as u16).to_le_bytes());
+                response.extend_from_slice(&[0x00, 0x00]);
+                response.extend(std::iter::repeat_n(0x11, bytes));
+                plc.write_all(&response).await.unwrap();
+            }
+        });
+
+        let mut context = attach(client);
+        assert!(context.read_bool("M0").await.unwrap());
+        assert_eq!(context.read_bools("M0", 3).await.unwrap(), [true; 3]);
+    }
+
+    #[tokio::test]
+    async fn adaptive_splitter_learns_the_frame_size() {
+        let (client, mut plc) = duplex(16 * 1024);
+
+        // 模拟小型 CPU：单帧超过 4 字时以 C051 拒绝
+        tokio::spawn(async move {
+            let mut header = [0u8; 9];
+            while plc.read_exact(&mut header).await.is_ok() {
+                let len = u16::from_le_bytes([header[7], header[8]]) as usize;
+                let mut body = vec![0u8; len];
+                plc.read_exact(&mut body).await.unwrap();
+                let points = u16::from_le_bytes([body[10], body[11]]) as usize;
+
+                let mut response = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+                if points > 4 {
+                    response.extend_from_slice(&[0x02, 0x00, 0x51, 0xC0]);
+                } else {
+                    response.extend_from_slice(&((points * 2 + 2) as u16).to_le_bytes());
+                    response.extend(std::iter::repeat_n(0x00, points * 2 + 2));
+                }
+                plc.write_all(&response).await.unwrap();
+            }
+        });
+
+        let mut context = attach(client);
+        context.set_splitter(Splitter::new().adaptive(true));
+        assert_eq!(context.read_u16s("D0", 10).await.unwrap(), [0; 10]);
+        assert_eq!(context.splitter().learned_points(false), Some(2));
+        let frames = context.traffic().snapshot().frames_sent;
+        context.read_u16s("D0", 4).await.unwrap();
+        assert_eq!(context.traffic().snapshot().frames_sent, frames + 2);
+
+        // 不应答的 PLC：调用方超时取消后，下一次调用前减半
+        let (client, _silent) = duplex(1024);
+        let mut context = attach(client);
+        context.set_splitter(Splitter::new().adaptive(true));
+        for _ in 0..2 {
+            let read = context.read_u16s("D0", 100);
+            assert!(tokio::time::timeout(Duration::from_millis(20), read)
+                .await
+                .is_err());
+        }
+        assert_eq!(context.splitter().learned_points(false), Some(50));
+    }
+
+    #[tokio::test]
+    async fn frame_1e_responses_are_sized_by_the_request() {
+        let (client, mut plc) = duplex(1024);
+
+        // 模拟 FX3U：D0 读取 2 字后应答异常代码 0x10
+        tokio::spawn(async move {
+            let mut request = [0u8; 11];
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[..4], [0x01, 0xFF, 0x0A, 0x00]);
+            plc.write_all(&[0x81, 0x00, 0x34, 0x12, 0x78, 0x56])
+                .await
+                .unwrap();
+            plc.read_exact(&mut request).await.unwrap();
+            plc.write_all(&[0x81, 0x5B, 0x10]).await.unwrap();
+        });
+
+        let mut context = attach(client);
+        context.set_frame_type(FrameType::Binary1E);
+        assert_eq!(context.read_u16s("D0", 2).await.unwrap(), [0x1234, 0x5678]);
+        let Err(Error::Protocol(ProtocolError::EndCode(end_code))) =
+            context.read_u16s("D0", 2).await
+        else {
+            panic!("expected an end code error");
+        };
+        assert_eq!(end_code.code(), 0x5B10);
+    }
+
+    #[tokio::test]
+    async fn ascii_frames_match_captures() {
+        let (client, mut plc) = duplex(1024);
+
+        // 抓取的 ASCII 码报文：D100 起读取 3 字、M0 起读取 4 点
+        tokio::spawn(async move {
+            let mut request = [0u8; 42];
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request, b"500000FF03FF000018001004010000D*0001000003");
+            plc.write_all(b"D00000FF03FF000010000000010002000A")
+                .await
+                .unwrap();
+            plc.write_all(b"00").await.unwrap();
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request, b"500000FF03FF000018001004010001M*0000000004");
+            plc.write_all(b"D00000FF03FF00000800001011").await.unwrap();
+        });
+
+        let mut context = attach(client);
+        context.set_frame_type(FrameType::Ascii3E);
+        assert_eq!(
+            context.read_u16s("D100", 3).await.unwrap(),
+            [0x0001, 0x0002, 0x000A]
+        );
+        assert_eq!(
+            context.read_bools("M0", 4).await.unwrap(),
+            [true, false, true, true]
+        );
+    }
+
+    #[tokio::test]
+    async fn header_config_routes_requests_through_relays() {
+        let (client, mut plc) = duplex(1024);
+
+        // 经网络 01 访问 PLC 编号 02 的多 CPU 系统 2 号机
+        let plc_task = tokio::spawn(async move {
+            let mut request = [0u8; 21];
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[2..7], [0x01, 0x02, 0xE1, 0x03, 0x00]);
+            let response = [
+                0xD0, 0x00, 0x01, 0x02, 0xE1, 0x03, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00,
+            ];
+            plc.write_all(&response).await.unwrap();
+            // 单次经本站访问
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[2..7], [0x00, 0xFF, 0xFF, 0x03, 0x00]);
+            let response = [
+                0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00,
+            ];
+            plc.write_all(&response).await.unwrap();
+            let mut request = [0u8; 42];
+            plc.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request[4..14], b"010203E100");
+        });
+
+        let mut context = attach(client);
+        let header = HeaderConfig {
+            network_no: 0x01,
+            pc_no: 0x02,
+            ..HeaderConfig::multi_cpu(2)
+        };
+        context.set_header_config(header);
+        assert_eq!(context.header_config(), header);
+        assert_eq!(context.read_u16("D0").await.unwrap(), 1);
+        assert_eq!(
+            context
+                .read_u16s_via(HeaderConfig::LOCAL, "D0", 1)
+                .await
+                .unwrap(),
+            [2]
+        );
+        assert_eq!(context.header_config(), header);
+        // ASCII 码帧头的访问路径同样来自设置
+        context.set_frame_type(FrameType::Ascii3E);
+        assert!(context.read_u16("D0").await.is_err());
+        plc_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn routed_reads_do_not_share_the_read_cache() {
+        let (client, mut plc) = duplex(1024);
+
+        // 模拟 PLC：单字读取返回请求帧头中的网络编号
+        let plc_task = tokio::spawn(async move {
+            let mut requests = 0;
+            let mut request = [0u8; 21];
+            while plc.read_exact(&mut request).await.is_ok() {
+                let mut response = vec![0xD0, 0x00];
+                response.extend_from_slice(&request[2..7]);
+                response.extend_from_slice(&[0x04, 0x00, 0x00, 0x00, request[2], 0x00]);
+                plc.write_all(&response).await.unwrap();
+                requests += 1;
+            }
+            requests
+        });
+
+        let mut context = attach(client);
+        context.set_read_cache(Some(Duration::from_secs(3600)));
+        let remote = HeaderConfig {
+            network_no: 0x01,
+            ..HeaderConfig::LOCAL
+        };
+        assert_eq!(context.read_u16("D100").await.unwrap(), 0);
+        assert_eq!(context.read_u16("D100").await.unwrap(), 0);
+        assert_eq!(context.read_u16s_via(remote, "D100", 1).await.unwrap(), [1]);
+        assert_eq!(context.read_u16("D100").await.unwrap(), 0);
+
+        context.disconnect().await.unwrap();
+        assert_eq!(plc_task.await.unwrap(), 3);
+    }
+
+    /// 固定监视定时器并修正应答子头部的改写钩子
+    #[derive(Debug)]
+    struct Quirks;
+
+    impl FrameTransform for Quirks {
+        fn outgoing(&self, frame: &mut BytesMut) {
+            frame[9..11].copy_from_slice(&[0x04, 0x00]);
+        }
+
+        fn incoming(&self, frame: &mut BytesMut) {
+            frame[0] = 0xD0;
+        }
+    }
+
+    #[tokio::test]
+    async fn frame_transform_rewrites_both_directions() {
+        let (client, mut plc) = duplex(1024);
+
+        // 模拟应答子头部为 D4 00 的第三方实现
+        tokio::spawn(async move {
+            let mut request = [0u8; 21];
+            for _ in 0..2 {
+                plc.read_exact(&mut request).await.unwrap();
+                assert_eq!(request[9..11], [0x04, 0x00]);
+                let response = [
+                    0xD4, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x04, 0x00, 0x00, 0x00, 0x34, 0x12,
+                ];
+                plc.write_all(&response).await.unwrap();
+            }
+        });
+
+        let mut context = attach(client);
+        context.set_frame_transform(Quirks);
+        assert_eq!(context.read_u16("D0").await.unwrap(), 0x1234);
+        context.clear_frame_transform();
+        assert!(context.read_u16("D0").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn error_end_code_is_reported_with_responder() {
+        let (client, mut plc) = duplex(1024);
+
+        // 模拟 PLC：返回结束代码 0xC059 以及网络 01、站号 05 的应答帧头
+        tokio::spawn(async move {
+            let mut request = [0u8; 21];
+            plc.read_exact(&mut request).await.unwrap();
+            let response = [
+                0xD0, 0x00, 0x01, 0xFF, 0xFF, 0x03, 0x05, 0x02, 0x00, 0x59, 0xC0,
+            ];
+            plc.write_all(&response).await.unwrap();
+        });
+
+        let mut context = attach(client);
+        let Err(Error::Protocol(ProtocolError::EndCode(end_code))) =
+            context.read_u16s("D0", 1).await
+        else {
+            panic!("expected an end code error");
+        };
+        assert_eq!(end_code.code(), 0xC059);
+        assert_eq!(end_code.network_no(), 0x01);
+        assert_eq!(end_code.dest_station(), 0x05);
+        let info = context.last_response().unwrap();
+        assert_eq!(info.route, end_code.route());
+        assert_eq!(info.end_code, 0xC059);
+        assert!(end_code.to_string().contains("0xC059"));
     }
 }
@@ -0,0 +1,25 @@
+//! 超时计时抽象
+
+use std::{fmt, future::Future, pin::Pin, time::Duration};
+
+/// 超时计时器
+///
+/// 客户端的超时逻辑通过此 trait 等待，而不是直接调用系统时钟，
+/// 测试中可注入自定义实现或使用暂停时间的 tokio 运行时。
+pub trait Timer: fmt::Debug + Send + Sync {
+    /// 返回在 `duration` 后完成的 future
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// 基于 `tokio::time` 的计时器（默认）
+///
+/// 在 `start_paused` 的运行时中，时间由 tokio 自动推进，无需真实等待。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTimer;
+
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        // 延迟到首次 poll 时创建，使其绑定到执行该 future 的运行时
+        Box::pin(async move { tokio::time::sleep(duration).await })
+    }
+}
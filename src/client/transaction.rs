@@ -0,0 +1,223 @@
+//! 带回滚提示的多项写入
+//!
+//! 多个寄存器组成的参数组需要一起修改，中途失败会让 PLC 处于新旧参数混合的状态。
+//! [`Transaction`] 记录一组写入，[`Transaction::commit`] 先读取全部目标的原值，再依次写入：
+//!
+//! ```text
+//! let mut transaction = Transaction::new()
+//!     .write_u16s("D500", &speeds)
+//!     .write_u16s("D600", &positions)
+//!     .write_bool("M500", true);
+//! if let Err(err) = transaction.commit(&mut context).await {
+//!     log::warn!("parameter change failed: {err}, restoring {:?}", transaction.prior());
+//!     transaction.rollback(&mut context).await?;
+//! }
+//! ```
+//!
+//! MC 协议没有事务，回滚只是尽力而为：把已尝试写入的目标按相反顺序写回原值，
+//! 读取原值之后 PLC 程序自身的修改会被覆盖。原值直接从 PLC 读取，不使用读取缓存；
+//! 读取原值失败时不进行任何写入。
+
+use crate::{trace, Error};
+
+use super::{Client, Context, Reader as _, Step, Writer as _};
+
+/// 记录原值并可回滚的一组写入，见[模块文档](self)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transaction {
+    writes: Vec<Step>,
+    /// 各写入目标的原值
+    prior: Vec<Step>,
+    /// 已尝试的写入数，失败的写入可能已部分生效，同样计入
+    attempted: usize,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u16s(mut self, address: impl Into<String>, words: &[u16]) -> Self {
+        self.writes
+            .push(Step::WriteWords(address.into(), words.to_vec()));
+        self
+    }
+
+    pub fn write_u16(self, address: impl Into<String>, word: u16) -> Self {
+        self.write_u16s(address, &[word])
+    }
+
+    pub fn write_bools(mut self, address: impl Into<String>, bits: &[bool]) -> Self {
+        self.writes
+            .push(Step::WriteBits(address.into(), bits.to_vec()));
+        self
+    }
+
+    pub fn write_bool(self, address: impl Into<String>, bit: bool) -> Self {
+        self.write_bools(address, &[bit])
+    }
+
+    /// 登记的写入，均为 [`Step::WriteWords`] 或 [`Step::WriteBits`]
+    pub fn writes(&self) -> &[Step] {
+        &self.writes
+    }
+
+    /// 回滚时写回的原值，按写入顺序排列，只包含已尝试写入的目标
+    pub fn prior(&self) -> &[Step] {
+        &self.prior[..self.attempted]
+    }
+
+    /// 读取全部目标的原值后依次写入，任一写入失败时停止并返回其错误
+    pub async fn commit<T: Client>(&mut self, context: &mut Context<T>) -> Result<(), Error> {
+        self.prior.clear();
+        self.attempted = 0;
+        let mut prior = Vec::with_capacity(self.writes.len());
+        for write in &self.writes {
+            prior.push(match write {
+                Step::WriteWords(address, words) => Step::WriteWords(
+                    address.clone(),
+                    context
+                        .read_u16s_uncached(address, words.len() as u32)
+                        .await?,
+                ),
+                Step::WriteBits(address, bits) => {
+                    let mut values = context.read_bools(address, bits.len() as u32).await?;
+                    values.truncate(bits.len());
+                    Step::WriteBits(address.clone(), values)
+                }
+                step => unreachable!("transactions only contain writes, found {step}"),
+            });
+        }
+        self.prior = prior;
+
+        for (index, write) in self.writes.iter().enumerate() {
+            self.attempted = index + 1;
+            if let Err(err) = apply(context, write).await {
+                trace::warning!("Transaction write {} ({write}) failed: {err}", index + 1);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// 按相反顺序把已尝试写入的目标写回原值
+    ///
+    /// 某个目标写回失败时继续写回其余目标，最后返回第一个错误。
+    pub async fn rollback<T: Client>(&mut self, context: &mut Context<T>) -> Result<(), Error> {
+        let mut result = Ok(());
+        for prior in self.prior[..self.attempted].iter().rev() {
+            if let Err(err) = apply(context, prior).await {
+                trace::warning!("Transaction rollback ({prior}) failed: {err}");
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        self.attempted = 0;
+        result
+    }
+}
+
+async fn apply<T: Client>(context: &mut Context<T>, write: &Step) -> Result<(), Error> {
+    match write {
+        Step::WriteWords(address, words) => context.write_u16s(address, words).await,
+        Step::WriteBits(address, bits) => context.write_bools(address, bits).await,
+        step => unreachable!("transactions only contain writes, found {step}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        convert::{bytes_to_words, words_to_bytes},
+        frame::{ProtocolError, Request, Response},
+    };
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+
+    /// 按地址保存写入的字，写入 `broken` 地址时失败
+    #[derive(Debug, Default)]
+    struct Plc {
+        words: HashMap<String, Vec<u16>>,
+        broken: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(address, count) => {
+                    let mut words = self.words.get(&*address).cloned().unwrap_or_default();
+                    words.resize(count as usize, 0);
+                    Ok(Response::ReadU8s(words_to_bytes(&words)))
+                }
+                Request::WriteU8s(address, _) if self.broken == Some(&*address) => {
+                    Err(Error::Protocol(ProtocolError::NotImplemented))
+                }
+                Request::WriteU8s(address, data) => {
+                    self.words
+                        .insert(address.to_string(), bytes_to_words(&data));
+                    Ok(Response::WriteU8s())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn parameters() -> Transaction {
+        Transaction::new()
+            .write_u16s("D500", &[10, 20])
+            .write_u16s("D600", &[30])
+            .write_u16("D700", 1)
+    }
+
+    #[tokio::test]
+    async fn rolls_back_attempted_writes_in_reverse() {
+        let mut plc = Plc::default();
+        plc.words.insert("D500".to_string(), vec![1, 2]);
+        plc.words.insert("D600".to_string(), vec![3]);
+        plc.broken = Some("D600");
+        let mut context = Context::new(plc);
+
+        let mut transaction = parameters();
+        assert!(transaction.commit(&mut context).await.is_err());
+        assert_eq!(context.client.words["D500"], [10, 20]);
+        assert_eq!(
+            transaction.prior(),
+            [
+                Step::WriteWords("D500".to_string(), vec![1, 2]),
+                Step::WriteWords("D600".to_string(), vec![3]),
+            ]
+        );
+
+        // D600 仍无法写回，其余目标照常恢复
+        assert!(transaction.rollback(&mut context).await.is_err());
+        assert_eq!(context.client.words["D500"], [1, 2]);
+        assert!(!context.client.words.contains_key("D700"));
+        assert!(transaction.prior().is_empty());
+
+        context.client.broken = None;
+        transaction.commit(&mut context).await.unwrap();
+        assert_eq!(context.client.words["D700"], [1]);
+        assert_eq!(transaction.prior().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn records_prior_values_from_the_plc() {
+        let mut context = Context::new(Plc::default());
+        context.set_read_cache(Some(std::time::Duration::from_secs(3600)));
+        assert_eq!(context.read_u16s("D600", 1).await.unwrap(), [0]);
+        // PLC 程序在缓存有效期内修改了 D600
+        context.client.words.insert("D600".to_string(), vec![5]);
+
+        let mut transaction = Transaction::new().write_u16("D600", 30);
+        transaction.commit(&mut context).await.unwrap();
+        assert_eq!(
+            transaction.prior(),
+            [Step::WriteWords("D600".to_string(), vec![5])]
+        );
+        transaction.rollback(&mut context).await.unwrap();
+        assert_eq!(context.client.words["D600"], [5]);
+    }
+}
@@ -0,0 +1,154 @@
+//! 请求与响应的 JSON 记录
+//!
+//! [`Transcript`] 包装任意 [`Client`]，每次调用后向输出写入一行 JSON（NDJSON），
+//! 内容为解码后的请求与响应（而非原始字节），便于导入 ELK 等日志系统：
+//!
+//! ```text
+//! {"@timestamp":"2024-05-01T08:00:00.123Z","operation":7,"duration_us":850,"request":{"ReadU8s":["D100",2]},"response":{"ReadU8s":[1,0,2,0]}}
+//! ```
+//!
+//! 请求失败时以 `error` 字段代替 `response`。写入输出失败只记录警告，不影响请求结果。
+
+use std::{
+    fmt, io,
+    time::{Instant, SystemTime},
+};
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{
+    frame::{Request, Response},
+    trace, Error,
+};
+
+use super::{audit::rfc3339, Client, OperationId};
+
+/// 一条记录
+#[derive(Debug, Serialize)]
+struct Entry<'a, 'r> {
+    #[serde(rename = "@timestamp")]
+    timestamp: String,
+    operation: u64,
+    duration_us: u64,
+    request: &'a Request<'r>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<&'a Response>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// 将每次请求与响应以 NDJSON 写入 `W` 的 [`Client`] 包装，见[模块文档](self)
+pub struct Transcript<T, W> {
+    inner: T,
+    output: W,
+}
+
+impl<T: Client, W: io::Write + Send> Transcript<T, W> {
+    pub fn new(inner: T, output: W) -> Self {
+        Self { inner, output }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// 拆分为内部客户端与输出
+    pub fn into_parts(self) -> (T, W) {
+        (self.inner, self.output)
+    }
+
+    fn record(&mut self, entry: &Entry<'_, '_>) -> io::Result<()> {
+        serde_json::to_writer(&mut self.output, entry)?;
+        self.output.write_all(b"\n")?;
+        self.output.flush()
+    }
+}
+
+impl<T: fmt::Debug, W> fmt::Debug for Transcript<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcript")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<T: Client, W: io::Write + Send> Client for Transcript<T, W> {
+    async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+        let timestamp = SystemTime::now();
+        let start = Instant::now();
+        let result = self.inner.call(request.clone()).await;
+        let entry = Entry {
+            timestamp: rfc3339(timestamp),
+            operation: OperationId::next().get(),
+            duration_us: u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX),
+            request: &request,
+            response: result.as_ref().ok(),
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        if let Err(err) = self.record(&entry) {
+            trace::warning!("Failed to write transcript entry: {err}");
+        }
+        result
+    }
+
+    async fn disconnect(&mut self) -> io::Result<()> {
+        self.inner.disconnect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ProtocolError;
+
+    #[derive(Debug)]
+    struct Plc;
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            match request {
+                Request::ReadU8s(_, _) => Ok(Response::ReadU8s(vec![1, 0])),
+                _ => Err(Error::Protocol(ProtocolError::OutOfRange)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn records_requests_as_ndjson() {
+        let mut transcript = Transcript::new(Plc, Vec::new());
+        transcript
+            .call(Request::ReadU8s("D100".into(), 1))
+            .await
+            .unwrap();
+        assert!(transcript
+            .call(Request::WriteBits("M0".into(), vec![true].into()))
+            .await
+            .is_err());
+
+        let (_, output) = transcript.into_parts();
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0]["request"]["ReadU8s"],
+            serde_json::json!(["D100", 1])
+        );
+        assert_eq!(lines[0]["response"]["ReadU8s"], serde_json::json!([1, 0]));
+        assert!(lines[0].get("error").is_none());
+        assert_eq!(
+            lines[1]["request"]["WriteBits"],
+            serde_json::json!(["M0", [true]])
+        );
+        assert!(lines[1]["error"].as_str().unwrap().contains("OutOfRange"));
+    }
+}
@@ -0,0 +1,146 @@
+//! 连接 URL 解析
+
+use std::{str::FromStr, time::Duration};
+
+use crate::frame::{FrameType, Model, PlcProfile, ProtocolError};
+
+/// 由连接 URL 解析出的连接参数
+///
+/// 格式为 `mc://host:port?key=value&...`，支持的参数：
+///
+/// - `frame`：帧类型，`3e`（默认）、`3e-ascii` 或 `1e`
+/// - `udp`：是否使用 UDP，目前仅支持 `false`
+/// - `model`：`mitsubishi` 或 `keyence`
+/// - `profile`：`generic`、`q`、`iqr` 或 `fx`
+/// - `timeout`：连接超时，同步客户端同时用作操作超时，如 `2s`、`500ms`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectOptions {
+    /// `host:port` 形式的地址，主机名在连接时解析
+    pub addr: String,
+    pub model: Model,
+    pub profile: PlcProfile,
+    pub frame_type: FrameType,
+    pub timeout: Option<Duration>,
+}
+
+impl ConnectOptions {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            model: Model::default(),
+            profile: PlcProfile::default(),
+            frame_type: FrameType::default(),
+            timeout: None,
+        }
+    }
+}
+
+impl FromStr for ConnectOptions {
+    type Err = ProtocolError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| ProtocolError::InvalidUrl(reason);
+
+        let rest = url
+            .strip_prefix("mc://")
+            .ok_or_else(|| invalid(format!("unsupported scheme in {url:?}")))?;
+        let (authority, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let authority = authority.trim_end_matches('/');
+        match authority.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {}
+            _ => return Err(invalid(format!("expected host:port, got {authority:?}"))),
+        }
+
+        let mut options = Self::new(authority);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("missing value for {pair:?}")))?;
+            let unsupported = || invalid(format!("unsupported {key} {value:?}"));
+            match key {
+                "frame" => {
+                    options.frame_type = match value.to_ascii_lowercase().as_str() {
+                        "3e" => FrameType::Binary3E,
+                        "3e-ascii" => FrameType::Ascii3E,
+                        "1e" => FrameType::Binary1E,
+                        _ => return Err(unsupported()),
+                    }
+                }
+                "udp" => {
+                    if value != "false" {
+                        return Err(unsupported());
+                    }
+                }
+                "model" => {
+                    options.model = match value.to_ascii_lowercase().as_str() {
+                        "mitsubishi" => Model::Mitsubishi,
+                        "keyence" => Model::Keyence,
+                        _ => return Err(unsupported()),
+                    }
+                }
+                "profile" => {
+                    options.profile = match value.to_ascii_lowercase().as_str() {
+                        "generic" => PlcProfile::GENERIC,
+                        "q" => PlcProfile::Q_SERIES,
+                        "iqr" => PlcProfile::IQ_R,
+                        "fx" => PlcProfile::FX_SERIES,
+                        _ => return Err(unsupported()),
+                    }
+                }
+                "timeout" => options.timeout = Some(parse_duration(value).ok_or_else(unsupported)?),
+                _ => return Err(invalid(format!("unknown parameter {key:?}"))),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// 解析 `500ms`、`2s`、`1m` 形式的时长
+fn parse_duration(value: &str) -> Option<Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(number)),
+        "s" => Some(Duration::from_secs(number)),
+        "m" => Some(Duration::from_secs(number.checked_mul(60)?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_connect_url() {
+        let options: ConnectOptions =
+            "mc://10.0.0.5:5007?frame=3e&model=keyence&profile=fx&timeout=2s&udp=false"
+                .parse()
+                .unwrap();
+        assert_eq!(options.addr, "10.0.0.5:5007");
+        assert_eq!(options.model, Model::Keyence);
+        assert_eq!(options.profile, PlcProfile::FX_SERIES);
+        assert_eq!(options.timeout, Some(Duration::from_secs(2)));
+        let options: ConnectOptions = "mc://10.0.0.5:5000?frame=1E".parse().unwrap();
+        assert_eq!(options.frame_type, FrameType::Binary1E);
+
+        let options: ConnectOptions = "mc://[::1]:5000/".parse().unwrap();
+        assert_eq!(options, ConnectOptions::new("[::1]:5000"));
+        let options: ConnectOptions = "mc://plc-line1.local:5000".parse().unwrap();
+        assert_eq!(options.addr, "plc-line1.local:5000");
+
+        for url in [
+            "tcp://10.0.0.5:5007",
+            "mc://10.0.0.5",
+            "mc://:5007",
+            "mc://10.0.0.5:http",
+            "mc://10.0.0.5:5007?frame=4e",
+            "mc://10.0.0.5:5007?udp=true",
+            "mc://10.0.0.5:5007?timeout=2",
+            "mc://10.0.0.5:5007?retries=3",
+        ] {
+            assert!(url.parse::<ConnectOptions>().is_err(), "{url}");
+        }
+    }
+}
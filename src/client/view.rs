@@ -0,0 +1,170 @@
+//! 位软元件的字视图与字软元件的位视图
+//!
+//! 位软元件按字读取时每个字覆盖 16 点，低位对应编号最小的点；
+//! 字软元件只能按字读取，位视图由读取到的字按低位在前展开。
+
+use crate::{
+    convert::bytes_to_words,
+    frame::{device_kind, split_address, DeviceKind, ProtocolError, Quantity, Request, Response},
+    Error,
+};
+
+use super::{area::Device, Client, Context};
+
+/// 校验 `address` 为起始编号按 16 点对齐的位软元件
+pub(crate) fn check_bit_device(address: &str) -> Result<(), Error> {
+    let (device, number) = Device::parse(address)?;
+    if !device.is_bit() {
+        return Err(invalid(format!("{address} is not a bit device")));
+    }
+    if !number.is_multiple_of(16) {
+        return Err(invalid(format!(
+            "{address} must start at a multiple of 16 points"
+        )));
+    }
+    Ok(())
+}
+
+/// 校验 `address` 为字软元件
+pub(crate) fn check_word_device(address: &str) -> Result<(), Error> {
+    let (device, _) = Device::parse(address)?;
+    if device.is_bit() {
+        return Err(invalid(format!("{address} is not a word device")));
+    }
+    Ok(())
+}
+
+/// 校验 `address` 可按位访问
+///
+/// 内置的字软元件（D、W、R 等）按位访问会被 PLC 以错误代码拒绝，这里提前报告；
+/// 未知前缀（如 PLC 系列参数中的自定义软元件）不做判断。
+pub(crate) fn check_bit_access(address: &str) -> Result<(), Error> {
+    match split_address(address).and_then(|(prefix, _)| device_kind(prefix)) {
+        Some(DeviceKind::Word) => Err(invalid(format!(
+            "{address} is a word device and cannot be accessed by bit"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::Protocol(ProtocolError::InvalidAddress(reason))
+}
+
+pub(crate) fn response_words(response: Response) -> Vec<u16> {
+    match response {
+        Response::ReadU8s(bytes) => bytes_to_words(&bytes),
+        _ => unreachable!("Unexpected response type, expected ReadU8s"),
+    }
+}
+
+/// 将字按低位在前展开为 `bit_count` 点
+pub(crate) fn unpack_bits(words: &[u16], bit_count: usize) -> Vec<bool> {
+    (0..bit_count)
+        .map(|i| words[i / 16] >> (i % 16) & 1 != 0)
+        .collect()
+}
+
+impl<T: Client> Context<T> {
+    /// 以字为单位读取位软元件，每个字覆盖 16 点
+    ///
+    /// 起始编号须为 16 的倍数（X、Y 等十六进制软元件即 `X0`、`X10`、`X20`…）。
+    pub async fn read_bit_device_as_words<A>(
+        &mut self,
+        addr: &A,
+        cnt: Quantity,
+    ) -> Result<Vec<u16>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let address = self.process_address(addr)?;
+        check_bit_device(&address)?;
+        let response = self
+            .client
+            .call(Request::ReadU8s(address.into(), cnt))
+            .await?;
+        Ok(response_words(response))
+    }
+
+    /// 读取字软元件中从起始字第 0 位开始的 `bit_count` 位
+    pub async fn read_word_device_bits<A>(
+        &mut self,
+        addr: &A,
+        bit_count: Quantity,
+    ) -> Result<Vec<bool>, Error>
+    where
+        A: AsRef<str> + Send + Sync + ?Sized,
+    {
+        let address = self.process_address(addr)?;
+        check_word_device(&address)?;
+        let response = self
+            .client
+            .call(Request::ReadU8s(address.into(), bit_count.div_ceil(16)))
+            .await?;
+        Ok(unpack_bits(&response_words(response), bit_count as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Reader as _, Writer as _};
+    use async_trait::async_trait;
+
+    /// 每个字的值等于其相对起始地址的序号加 0x8001
+    #[derive(Debug)]
+    struct Plc;
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            let Request::ReadU8s(_, cnt) = request else {
+                unreachable!()
+            };
+            let words: Vec<u16> = (0..cnt as u16).map(|i| 0x8001 + i).collect();
+            Ok(Response::ReadU8s(
+                words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn word_and_bit_views() {
+        let mut context = Context::new(Plc);
+        assert_eq!(
+            context.read_bit_device_as_words("X10", 2).await.unwrap(),
+            [0x8001, 0x8002]
+        );
+        assert_eq!(
+            context.read_bit_device_as_words("M32", 1).await.unwrap(),
+            [0x8001]
+        );
+        assert!(context.read_bit_device_as_words("M8", 1).await.is_err());
+        assert!(context.read_bit_device_as_words("D0", 1).await.is_err());
+
+        let bits = context.read_word_device_bits("D100", 18).await.unwrap();
+        assert_eq!(bits.len(), 18);
+        assert!(bits[0] && !bits[1] && bits[15]);
+        assert!(!bits[16] && bits[17]);
+        assert!(context.read_word_device_bits("M0", 16).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn bit_access_rejects_word_devices() {
+        // 被拒绝的请求不会发出，否则 Plc 会因非 ReadU8s 请求 panic
+        let mut context = Context::new(Plc);
+        for result in [
+            context.read_bools("D100", 1).await.map(drop),
+            context.write_bools("W1F", &[true]).await,
+            context.read_bools("ZR0", 8).await.map(drop),
+        ] {
+            assert!(matches!(
+                result,
+                Err(Error::Protocol(ProtocolError::InvalidAddress(reason)))
+                    if reason.contains("is a word device")
+            ));
+        }
+        assert!(check_bit_access("M100").is_ok());
+        assert!(check_bit_access("X1F").is_ok());
+    }
+}
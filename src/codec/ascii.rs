@@ -0,0 +1,467 @@
+//! 3E 帧 ASCII 码与二进制码之间的转换
+//!
+//! 用于比对不同通信代码设置下抓取的报文，客户端以
+//! [`FrameType::Ascii3E`](crate::frame::FrameType::Ascii3E) 通信时也经由这里转换。两种格式字段相同，
+//! 二进制码的多字节数值为小端序，ASCII 码则以大端序十六进制字符表示；
+//! 软元件代码在 ASCII 码中为 2 个字符（如 `D*`），编号为 6 个字符，
+//! 位单位数据在二进制码中每字节 2 点，在 ASCII 码中每点 1 个字符。
+//!
+//! 目前支持批量读取（0401）、批量写入（1401）的请求以及所有应答帧，
+//! 其它命令的请求返回 [`ProtocolError::NotImplemented`]。
+
+use std::io;
+
+use crate::{
+    frame::{find_instruction_code, find_prefix_and_base_by_code, NumberBase, ProtocolError},
+    Error,
+};
+
+use super::{bools_to_bytes, bytes_to_bools};
+
+const BATCH_READ: u16 = 0x0401;
+const BATCH_WRITE: u16 = 0x1401;
+const REQUEST_SUBHEADER: u8 = 0x50;
+const RESPONSE_SUBHEADER: u8 = 0xD0;
+
+/// 应答数据的单位
+///
+/// 应答帧本身不区分字单位与位单位，需要由调用方按对应的请求指定；
+/// 请求帧的数据单位由子指令决定，不使用该参数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataUnit {
+    #[default]
+    Words,
+    Bits,
+}
+
+/// 将 ASCII 码的 3E 帧转换为二进制码
+pub fn ascii_to_binary(frame: &[u8], unit: DataUnit) -> Result<Vec<u8>, Error> {
+    let mut sink = BinarySink::default();
+    convert(&mut AsciiSource { frame, pos: 0 }, &mut sink, unit)?;
+    Ok(sink.buf)
+}
+
+/// 将二进制码的 3E 帧转换为 ASCII 码
+pub fn binary_to_ascii(frame: &[u8], unit: DataUnit) -> Result<Vec<u8>, Error> {
+    let mut sink = AsciiSink::default();
+    convert(&mut BinarySource { frame, pos: 0 }, &mut sink, unit)?;
+    Ok(sink.buf)
+}
+
+/// ASCII 码请求帧对应应答的数据单位：位单位的批量读取为 [`DataUnit::Bits`]，其它为字单位
+pub fn response_unit(request: &[u8]) -> DataUnit {
+    // 子头部与访问路径 14 个字符，数据长度与监视定时器各 4 个字符，随后为指令与子指令
+    match request.get(22..30) {
+        Some(b"04010001") => DataUnit::Bits,
+        _ => DataUnit::Words,
+    }
+}
+
+fn convert(src: &mut impl Source, dst: &mut impl Sink, unit: DataUnit) -> Result<(), Error> {
+    let subheader = src.u8()?;
+    dst.u8(subheader);
+    dst.u8(src.u8()?);
+    // 网络编号、PC 编号、请求目标模块 I/O 编号、请求目标模块站号
+    dst.u8(src.u8()?);
+    dst.u8(src.u8()?);
+    dst.u16(src.u16()?);
+    dst.u8(src.u8()?);
+
+    let length = src.u16()? as usize;
+    if src.remaining() != length {
+        return Err(invalid_data(format!(
+            "data length field is {length}, but {} follow",
+            src.remaining()
+        )));
+    }
+    dst.begin_length();
+
+    match subheader {
+        REQUEST_SUBHEADER => {
+            let timer = src.u16()?;
+            let command = src.u16()?;
+            let subcommand = src.u16()?;
+            if !matches!(command, BATCH_READ | BATCH_WRITE) || subcommand > 1 {
+                return Err(Error::Protocol(ProtocolError::NotImplemented));
+            }
+            dst.u16(timer);
+            dst.u16(command);
+            dst.u16(subcommand);
+            let (code, number) = src.device()?;
+            dst.device(code, number)?;
+            let points = src.u16()?;
+            dst.u16(points);
+            if command == BATCH_WRITE {
+                if subcommand == 0 {
+                    dst.words(&src.words()?);
+                } else {
+                    dst.bits(&src.bits(Some(points as usize))?);
+                }
+            }
+        }
+        RESPONSE_SUBHEADER => {
+            let end_code = src.u16()?;
+            dst.u16(end_code);
+            if end_code == 0 {
+                match unit {
+                    DataUnit::Words => dst.words(&src.words()?),
+                    DataUnit::Bits => dst.bits(&src.bits(None)?),
+                }
+            } else {
+                // 异常应答：应答站的访问路径以及出错的指令与子指令
+                dst.u8(src.u8()?);
+                dst.u8(src.u8()?);
+                dst.u16(src.u16()?);
+                dst.u8(src.u8()?);
+                dst.u16(src.u16()?);
+                dst.u16(src.u16()?);
+            }
+        }
+        other => return Err(invalid_data(format!("unknown subheader {other:02X}"))),
+    }
+
+    if src.remaining() != 0 {
+        return Err(invalid_data(format!(
+            "{} unexpected trailing bytes",
+            src.remaining()
+        )));
+    }
+    dst.end_length();
+    Ok(())
+}
+
+trait Source {
+    fn u8(&mut self) -> Result<u8, Error>;
+    fn u16(&mut self) -> Result<u16, Error>;
+    /// 软元件代码与编号
+    fn device(&mut self) -> Result<(u8, u32), Error>;
+    /// 剩余数据按字读取
+    fn words(&mut self) -> Result<Vec<u16>, Error>;
+    /// 剩余数据按位读取，`points` 为空时读取全部
+    fn bits(&mut self, points: Option<usize>) -> Result<Vec<bool>, Error>;
+    fn remaining(&self) -> usize;
+}
+
+trait Sink {
+    fn u8(&mut self, value: u8);
+    fn u16(&mut self, value: u16);
+    fn device(&mut self, code: u8, number: u32) -> Result<(), Error>;
+    fn words(&mut self, words: &[u16]);
+    fn bits(&mut self, bits: &[bool]);
+    /// 写入数据长度的占位，之后的内容计入长度
+    fn begin_length(&mut self);
+    fn end_length(&mut self);
+}
+
+struct BinarySource<'a> {
+    frame: &'a [u8],
+    pos: usize,
+}
+
+impl BinarySource<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], Error> {
+        let bytes = self.frame.get(self.pos..self.pos + n).ok_or_else(|| {
+            Error::Protocol(ProtocolError::LengthMismatch {
+                expected: self.pos + n,
+                actual: self.frame.len(),
+            })
+        })?;
+        self.pos += n;
+        Ok(bytes)
+    }
+}
+
+impl Source for BinarySource<'_> {
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn device(&mut self) -> Result<(u8, u32), Error> {
+        let bytes = self.take(4)?;
+        let number = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]);
+        Ok((bytes[3], number))
+    }
+
+    fn words(&mut self) -> Result<Vec<u16>, Error> {
+        if !self.remaining().is_multiple_of(2) {
+            return Err(Error::Protocol(ProtocolError::OddByteCount(
+                self.remaining(),
+            )));
+        }
+        let bytes = self.take(self.remaining())?;
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect())
+    }
+
+    fn bits(&mut self, points: Option<usize>) -> Result<Vec<bool>, Error> {
+        let bytes = match points {
+            Some(points) => self.take(points.div_ceil(2))?,
+            None => self.take(self.remaining())?,
+        };
+        let mut bits = bytes_to_bools(bytes);
+        if let Some(points) = points {
+            bits.truncate(points);
+        }
+        Ok(bits)
+    }
+
+    fn remaining(&self) -> usize {
+        self.frame.len() - self.pos
+    }
+}
+
+struct AsciiSource<'a> {
+    frame: &'a [u8],
+    pos: usize,
+}
+
+impl AsciiSource<'_> {
+    fn take(&mut self, n: usize) -> Result<&str, Error> {
+        let chars = self.frame.get(self.pos..self.pos + n).ok_or_else(|| {
+            Error::Protocol(ProtocolError::LengthMismatch {
+                expected: self.pos + n,
+                actual: self.frame.len(),
+            })
+        })?;
+        let text = std::str::from_utf8(chars)
+            .ok()
+            .filter(|text| text.is_ascii())
+            .ok_or_else(|| invalid_data(format!("non-ASCII characters at offset {}", self.pos)))?;
+        self.pos += n;
+        Ok(text)
+    }
+
+    fn hex(&mut self, n: usize) -> Result<u32, Error> {
+        let pos = self.pos;
+        let text = self.take(n)?;
+        u32::from_str_radix(text, 16)
+            .map_err(|_| invalid_data(format!("invalid hex field {text:?} at offset {pos}")))
+    }
+}
+
+impl Source for AsciiSource<'_> {
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.hex(2)? as u8)
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(self.hex(4)? as u16)
+    }
+
+    fn device(&mut self) -> Result<(u8, u32), Error> {
+        let name = self.take(2)?.trim_end_matches('*').to_string();
+        let (code, number_base) = find_instruction_code(&name).ok_or_else(|| {
+            Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "unknown device code {name:?}"
+            )))
+        })?;
+        let text = self.take(6)?;
+        let radix = match number_base {
+            NumberBase::Decimal => 10,
+            NumberBase::Hexadecimal => 16,
+        };
+        let number = u32::from_str_radix(text, radix)
+            .map_err(|_| Error::Protocol(ProtocolError::InvalidAddress(format!("{name}{text}"))))?;
+        Ok((code, number))
+    }
+
+    fn words(&mut self) -> Result<Vec<u16>, Error> {
+        if !self.remaining().is_multiple_of(4) {
+            return Err(invalid_data(format!(
+                "{} characters are not whole words",
+                self.remaining()
+            )));
+        }
+        (0..self.remaining() / 4).map(|_| self.u16()).collect()
+    }
+
+    fn bits(&mut self, points: Option<usize>) -> Result<Vec<bool>, Error> {
+        let pos = self.pos;
+        let text = self.take(points.unwrap_or(self.remaining()))?;
+        text.chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(invalid_data(format!("invalid bit {c:?} at offset {pos}"))),
+            })
+            .collect()
+    }
+
+    fn remaining(&self) -> usize {
+        self.frame.len() - self.pos
+    }
+}
+
+#[derive(Default)]
+struct BinarySink {
+    buf: Vec<u8>,
+    length_at: usize,
+}
+
+impl Sink for BinarySink {
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn device(&mut self, code: u8, number: u32) -> Result<(), Error> {
+        self.buf.extend_from_slice(&number.to_le_bytes()[..3]);
+        self.buf.push(code);
+        Ok(())
+    }
+
+    fn words(&mut self, words: &[u16]) {
+        for &word in words {
+            self.u16(word);
+        }
+    }
+
+    fn bits(&mut self, bits: &[bool]) {
+        self.buf.extend_from_slice(&bools_to_bytes(bits));
+    }
+
+    fn begin_length(&mut self) {
+        self.length_at = self.buf.len();
+        self.u16(0);
+    }
+
+    fn end_length(&mut self) {
+        let length = (self.buf.len() - self.length_at - 2) as u16;
+        self.buf[self.length_at..self.length_at + 2].copy_from_slice(&length.to_le_bytes());
+    }
+}
+
+#[derive(Default)]
+struct AsciiSink {
+    buf: Vec<u8>,
+    length_at: usize,
+}
+
+impl Sink for AsciiSink {
+    fn u8(&mut self, value: u8) {
+        self.buf
+            .extend_from_slice(format!("{value:02X}").as_bytes());
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.buf
+            .extend_from_slice(format!("{value:04X}").as_bytes());
+    }
+
+    fn device(&mut self, code: u8, number: u32) -> Result<(), Error> {
+        let (prefix, number_base) = find_prefix_and_base_by_code(code).ok_or_else(|| {
+            Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "unknown device code {code:02X}"
+            )))
+        })?;
+        let number = match number_base {
+            NumberBase::Decimal => format!("{number:06}"),
+            NumberBase::Hexadecimal => format!("{number:06X}"),
+        };
+        self.buf
+            .extend_from_slice(format!("{prefix:*<2}{number}").as_bytes());
+        Ok(())
+    }
+
+    fn words(&mut self, words: &[u16]) {
+        for &word in words {
+            self.u16(word);
+        }
+    }
+
+    fn bits(&mut self, bits: &[bool]) {
+        self.buf
+            .extend(bits.iter().map(|&bit| if bit { b'1' } else { b'0' }));
+    }
+
+    fn begin_length(&mut self) {
+        self.length_at = self.buf.len();
+        self.u16(0);
+    }
+
+    fn end_length(&mut self) {
+        let length = format!("{:04X}", self.buf.len() - self.length_at - 4);
+        self.buf[self.length_at..self.length_at + 4].copy_from_slice(length.as_bytes());
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::Transport(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_read_request_round_trip() {
+        // D100 起 3 字的批量读取
+        let binary = [
+            0x50, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, 0x10, 0x00, 0x01, 0x04, 0x00,
+            0x00, 0x64, 0x00, 0x00, 0xA8, 0x03, 0x00,
+        ];
+        let ascii = b"500000FF03FF000018001004010000D*0001000003";
+        assert_eq!(binary_to_ascii(&binary, DataUnit::Words).unwrap(), ascii);
+        assert_eq!(ascii_to_binary(ascii, DataUnit::Words).unwrap(), binary);
+    }
+
+    #[test]
+    fn bit_write_request_round_trip() {
+        // Y1A 起 3 点的批量写入，Y 为十六进制编号
+        let ascii = b"500000FF03FF00001B001014010001Y*00001A0003101";
+        let binary = ascii_to_binary(ascii, DataUnit::Words).unwrap();
+        assert_eq!(
+            &binary[15..],
+            [0x1A, 0x00, 0x00, 0x9D, 0x03, 0x00, 0x10, 0x10]
+        );
+        assert_eq!(binary_to_ascii(&binary, DataUnit::Words).unwrap(), ascii);
+    }
+
+    #[test]
+    fn responses_follow_data_unit() {
+        let binary = [
+            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x04, 0x00, 0x00, 0x00, 0x10, 0x01,
+        ];
+        assert_eq!(
+            binary_to_ascii(&binary, DataUnit::Words).unwrap(),
+            b"D00000FF03FF00000800000110"
+        );
+        assert_eq!(
+            binary_to_ascii(&binary, DataUnit::Bits).unwrap(),
+            b"D00000FF03FF0000080000"
+                .iter()
+                .chain(b"1001")
+                .copied()
+                .collect::<Vec<_>>()
+        );
+
+        // 异常应答携带出错的指令
+        let ascii = b"D00000FF03FF000016C05900FF03FF0004010000";
+        let binary = ascii_to_binary(ascii, DataUnit::Words).unwrap();
+        assert_eq!(&binary[9..11], [0x59, 0xC0]);
+        assert_eq!(binary_to_ascii(&binary, DataUnit::Words).unwrap(), ascii);
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        let ascii = b"500000FF03FF000018001004010000D*0001000003";
+        // 长度字段与实际不符
+        assert!(ascii_to_binary(&ascii[..ascii.len() - 1], DataUnit::Words).is_err());
+        // 不支持的命令
+        let remote = b"500000FF03FF00000C001010010000";
+        assert!(matches!(
+            ascii_to_binary(remote, DataUnit::Words),
+            Err(Error::Protocol(ProtocolError::NotImplemented))
+        ));
+    }
+}
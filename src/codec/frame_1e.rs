@@ -0,0 +1,195 @@
+//! A 兼容 1E 帧
+//!
+//! FX3U、A 系列等较早的以太网模块只支持 1E 帧。与 3E 帧相比：
+//!
+//! - 请求以 1 字节子头部（即指令）开头，随后为 PC 编号与 ACPU 监视定时器，没有访问路径与数据长度；
+//! - 软元件代码为 2 个 ASCII 字符（如 `D` 为 `"D "`，即 0x4420），编号为 4 字节；
+//! - 点数为 1 字节，0 表示 256 点；
+//! - 应答以指令加 0x80 的子头部与 1 字节结束代码开头，同样没有长度字段，
+//!   正常应答的长度由请求决定，见 [`expected_response`]。
+//!
+//! 目前支持位单位与字单位的批量读写，每帧最多 [`MAX_BITS`] 点位或 [`MAX_WORDS`] 字，
+//! 更长的请求自动拆分；其它命令返回 [`ProtocolError::NotImplemented`]。
+//! 数据部分的格式与 3E 帧相同：字为小端序，位单位每字节 2 点，高 4 位在前。
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{
+    bytes::{BufMut, Bytes, BytesMut},
+    frame::{
+        convert_to_base, device_kind, find_instruction_code, off_spec, split_address, DeviceKind,
+        ProtocolError, Quantity, Request,
+    },
+    header::RequestHeader1E,
+    Error,
+};
+
+use super::bools_to_bytes;
+
+/// 位单位批量读取
+pub const BATCH_READ_BITS: u8 = 0x00;
+/// 字单位批量读取
+pub const BATCH_READ_WORDS: u8 = 0x01;
+/// 位单位批量写入
+pub const BATCH_WRITE_BITS: u8 = 0x02;
+/// 字单位批量写入
+pub const BATCH_WRITE_WORDS: u8 = 0x03;
+/// 单帧位单位读写的最大点数
+pub const MAX_BITS: Quantity = 256;
+/// 单帧字单位读写的最大字数，取 FX3U 以太网模块的上限
+pub const MAX_WORDS: Quantity = 64;
+/// 异常应答的结束代码，其后附 1 字节异常代码
+pub const ABNORMAL_END: u8 = 0x5B;
+
+const HEADER_LEN: usize = 4;
+
+/// 1E 帧的软元件代码
+const DEVICE_CODES: &[(&str, u16)] = &[
+    ("X", 0x5820),
+    ("Y", 0x5920),
+    ("M", 0x4D20),
+    ("F", 0x4620),
+    ("B", 0x4220),
+    ("D", 0x4420),
+    ("R", 0x5220),
+    ("W", 0x5720),
+    ("TN", 0x544E),
+    ("TS", 0x5453),
+    ("TC", 0x5443),
+    ("CN", 0x434E),
+    ("CS", 0x4353),
+    ("CC", 0x4343),
+];
+
+/// `prefix` 软元件的 1E 帧代码，1E 帧不支持的软元件返回 `None`
+pub fn device_code(prefix: &str) -> Option<u16> {
+    DEVICE_CODES
+        .iter()
+        .find(|(p, _)| *p == prefix)
+        .map(|(_, code)| *code)
+}
+
+/// 将批量读写请求编码为 1E 请求帧，超出单帧点数的请求拆分为多帧
+pub fn encode_request(req: &Request<'_>) -> Result<Vec<Bytes>, Error> {
+    let (address, quantity, command) = match req {
+        Request::ReadU8s(address, quantity) => (address, *quantity, BATCH_READ_WORDS),
+        Request::WriteU8s(address, u8s) => {
+            if u8s.len() % 2 != 0 {
+                off_spec(ProtocolError::OddByteCount(u8s.len()))?;
+            }
+            (
+                address,
+                u8s.len().div_ceil(2) as Quantity,
+                BATCH_WRITE_WORDS,
+            )
+        }
+        Request::ReadBits(address, quantity) => (address, *quantity, BATCH_READ_BITS),
+        Request::WriteBits(address, bits) => (address, bits.len() as Quantity, BATCH_WRITE_BITS),
+        Request::Command(_, _, _) => return Err(Error::Protocol(ProtocolError::NotImplemented)),
+    };
+    if quantity == 0 {
+        return Err(Error::Protocol(ProtocolError::OutOfRange));
+    }
+
+    let invalid = || Error::Protocol(ProtocolError::InvalidAddress(address.to_string()));
+    let (prefix, number) = split_address(address).ok_or_else(invalid)?;
+    let (_, base) = find_instruction_code(prefix).ok_or_else(invalid)?;
+    let start = convert_to_base(number, base).ok_or_else(invalid)?;
+    let code = device_code(prefix).ok_or_else(|| {
+        Error::Protocol(ProtocolError::InvalidAddress(format!(
+            "{prefix} is not available in the 1E frame"
+        )))
+    })?;
+
+    let words = matches!(command, BATCH_READ_WORDS | BATCH_WRITE_WORDS);
+    let (limit, step) = match (words, device_kind(prefix)) {
+        (false, _) => (MAX_BITS, 1),
+        // 按字访问位软元件时每个字覆盖 16 点
+        (true, Some(DeviceKind::Bit)) => (MAX_WORDS, 16),
+        (true, _) => (MAX_WORDS, 1),
+    };
+
+    let header = RequestHeader1E::new(command);
+    let mut frames = Vec::new();
+    for offset in (0..quantity).step_by(limit as usize) {
+        let points = (quantity - offset).min(limit);
+        let range = offset as usize..(offset + points) as usize;
+        let mut frame = BytesMut::with_capacity(HEADER_LEN + 7 + points as usize * 2);
+        frame.put_slice(header.bytes());
+        frame.put_u32_le(start + offset * step);
+        frame.put_u16_le(code);
+        // 256 点以 0 表示
+        frame.put_u8(points as u8);
+        match req {
+            Request::WriteU8s(_, u8s) => {
+                for i in range.start * 2..range.end * 2 {
+                    frame.put_u8(u8s.get(i).copied().unwrap_or(0));
+                }
+            }
+            Request::WriteBits(_, bits) => frame.put_slice(&bools_to_bytes(&bits[range])),
+            _ => {}
+        }
+        frames.push(frame.freeze());
+    }
+    Ok(frames)
+}
+
+/// 请求帧对应的应答子头部与正常应答中结束代码之后的数据字节数
+pub fn expected_response(frame: &[u8]) -> (u8, usize) {
+    let command = frame[0];
+    let points = match frame.get(HEADER_LEN + 6) {
+        Some(0) => 256,
+        Some(&points) => points as usize,
+        None => 0,
+    };
+    let data = match command {
+        BATCH_READ_BITS => points.div_ceil(2),
+        BATCH_READ_WORDS => points * 2,
+        _ => 0,
+    };
+    (command | 0x80, data)
+}
+
+/// 应答帧的结束代码，异常应答（0x5B）时高字节为 0x5B、低字节为异常代码
+pub fn end_code(frame: &[u8]) -> Option<u16> {
+    match *frame.get(1)? {
+        ABNORMAL_END => Some(LittleEndian::read_u16(&[*frame.get(2)?, ABNORMAL_END])),
+        code => Some(code as u16),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_batch_reads_and_writes() {
+        let frames = encode_request(&Request::ReadU8s("D100".into(), 3)).unwrap();
+        assert_eq!(
+            frames[0][..],
+            [0x01, 0xFF, 0x0A, 0x00, 0x64, 0x00, 0x00, 0x00, 0x20, 0x44, 0x03]
+        );
+        assert_eq!(expected_response(&frames[0]), (0x81, 6));
+
+        let bits = [true, false, true];
+        let frames = encode_request(&Request::WriteBits("Y1F".into(), bits[..].into())).unwrap();
+        assert_eq!(
+            frames[0][..],
+            [0x02, 0xFF, 0x0A, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x20, 0x59, 0x03, 0x10, 0x10]
+        );
+        assert_eq!(expected_response(&frames[0]), (0x82, 0));
+
+        // 超出单帧点数时拆分，按字访问位软元件时编号按 16 点递增
+        let frames = encode_request(&Request::ReadU8s("M0".into(), 100)).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1][4..11], [0x00, 0x04, 0x00, 0x00, 0x20, 0x4D, 36]);
+        let frames = encode_request(&Request::ReadBits("M0".into(), 256)).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(expected_response(&frames[0]), (0x80, 128));
+
+        assert!(encode_request(&Request::ReadU8s("SD0".into(), 1)).is_err());
+        assert!(encode_request(&Request::Command(0x0101, 0, (&[][..]).into())).is_err());
+        assert_eq!(end_code(&[0x81, 0x00]), Some(0));
+        assert_eq!(end_code(&[0x81, 0x5B, 0x10]), Some(0x5B10));
+    }
+}
@@ -0,0 +1,202 @@
+//! 带字段注释的十六进制转储
+//!
+//! [`HexDump`] 按 3E 二进制帧的字段逐行输出偏移、十六进制字节、可打印字符以及字段说明，
+//! 数据部分每行最多 16 字节。无法识别子头部的字节序列按每行 16 字节输出，不带注释。
+//!
+//! ```text
+//! 0000  50 00                                            |P.|               header: subheader
+//! 0002  00 FF FF 03 00                                   |.....|            header: route network 00 pc FF module 03FF station 00
+//! 0007  0C 00                                            |..|               header: data length 12
+//! 0009  10 00                                            |..|               header: monitoring timer
+//! 000B  01 04 00 00                                      |....|             command 0401/0000
+//! 000F  64 00 00 A8                                      |d...|             device D100
+//! 0013  03 00                                            |..|               points 3
+//! ```
+
+use std::fmt;
+
+use crate::frame::{find_prefix_and_base_by_code, NumberBase};
+
+const BYTES_PER_LINE: usize = 16;
+
+/// 以带注释的十六进制转储格式化帧，见[模块文档](self)
+#[derive(Debug, Clone, Copy)]
+pub struct HexDump<'a>(pub &'a [u8]);
+
+/// 将帧格式化为带注释的十六进制转储
+pub fn hexdump(frame: &[u8]) -> String {
+    HexDump(frame).to_string()
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut offset = 0;
+        for (len, note) in fields(self.0) {
+            let field = &self.0[offset..offset + len];
+            for (i, line) in field.chunks(BYTES_PER_LINE).enumerate() {
+                let note = if i == 0 { note.as_str() } else { "" };
+                write_line(f, offset + i * BYTES_PER_LINE, line, note)?;
+            }
+            offset += len;
+        }
+        Ok(())
+    }
+}
+
+fn write_line(f: &mut fmt::Formatter<'_>, offset: usize, line: &[u8], note: &str) -> fmt::Result {
+    let hex: Vec<String> = line.iter().map(|byte| format!("{byte:02X}")).collect();
+    let text: String = line
+        .iter()
+        .map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    let gutter = format!("|{text}|");
+    let line = format!(
+        "{offset:04X}  {:<width$} {gutter:<18} {note}",
+        hex.join(" "),
+        width = BYTES_PER_LINE * 3
+    );
+    writeln!(f, "{}", line.trim_end())
+}
+
+/// 按帧结构划分字段：各字段的字节数与注释，超出帧长度的字段被截断
+fn fields(frame: &[u8]) -> Vec<(usize, String)> {
+    let mut layout = Layout {
+        frame,
+        offset: 0,
+        fields: Vec::new(),
+    };
+    match frame.get(..2) {
+        Some([0x50, 0x00]) => {
+            layout.header();
+            layout.push(2, |_| "header: monitoring timer".to_string());
+            let command = layout.push(4, |b| {
+                format!(
+                    "command {:04X}/{:04X}",
+                    u16::from_le_bytes([b[0], b[1]]),
+                    u16::from_le_bytes([b[2], b[3]])
+                )
+            });
+            if matches!(command.get(..2), Some([0x01, 0x04] | [0x01, 0x14])) {
+                layout.push(4, |b| {
+                    device(b[3], u32::from_le_bytes([b[0], b[1], b[2], 0]))
+                });
+                layout.push(2, |b| {
+                    format!("points {}", u16::from_le_bytes([b[0], b[1]]))
+                });
+            }
+        }
+        Some([0xD0, 0x00]) => {
+            layout.header();
+            layout.push(2, |b| {
+                format!("end code {:04X}", u16::from_le_bytes([b[0], b[1]]))
+            });
+        }
+        _ => {}
+    }
+    let rest = frame.len() - layout.offset;
+    if rest > 0 {
+        let note = if layout.fields.is_empty() { "" } else { "data" };
+        layout.fields.push((rest, note.to_string()));
+    }
+    layout.fields
+}
+
+struct Layout<'a> {
+    frame: &'a [u8],
+    offset: usize,
+    fields: Vec<(usize, String)>,
+}
+
+impl<'a> Layout<'a> {
+    /// 添加一个字段并返回其字节；帧已不足该字段长度时只添加剩余字节，不带注释
+    fn push(&mut self, len: usize, note: impl FnOnce(&[u8]) -> String) -> &'a [u8] {
+        let frame = self.frame;
+        let rest = &frame[self.offset..];
+        if rest.is_empty() {
+            return &[];
+        }
+        if rest.len() < len {
+            self.fields.push((rest.len(), "truncated".to_string()));
+            self.offset = frame.len();
+            return &[];
+        }
+        let bytes = &rest[..len];
+        self.fields.push((len, note(bytes)));
+        self.offset += len;
+        bytes
+    }
+
+    /// 子头部、访问路径与数据长度
+    fn header(&mut self) {
+        self.push(2, |_| "header: subheader".to_string());
+        self.push(5, |b| {
+            format!(
+                "header: route network {:02X} pc {:02X} module {:04X} station {:02X}",
+                b[0],
+                b[1],
+                u16::from_le_bytes([b[2], b[3]]),
+                b[4]
+            )
+        });
+        self.push(2, |b| {
+            format!("header: data length {}", u16::from_le_bytes([b[0], b[1]]))
+        });
+    }
+}
+
+fn device(code: u8, number: u32) -> String {
+    match find_prefix_and_base_by_code(code) {
+        Some((prefix, NumberBase::Decimal)) => format!("device {prefix}{number}"),
+        Some((prefix, NumberBase::Hexadecimal)) => format!("device {prefix}{number:X}"),
+        None => format!("device code {code:02X} number {number}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotates_request_fields() {
+        let frame = [
+            0x50, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0E, 0x00, 0x10, 0x00, 0x01, 0x14, 0x00,
+            0x00, 0x64, 0x00, 0x00, 0xA8, 0x01, 0x00, 0x34, 0x12,
+        ];
+        let dump = hexdump(&frame);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].starts_with("0000  50 00 "));
+        assert!(lines[0].contains("|P.|"));
+        assert!(lines[0].ends_with("header: subheader"));
+        assert!(lines[1].ends_with("network 00 pc FF module 03FF station 00"));
+        assert!(lines[4].starts_with("000B  01 14 00 00"));
+        assert!(lines[4].ends_with("command 1401/0000"));
+        assert!(lines[5].ends_with("device D100"));
+        assert!(lines[7].starts_with("0015  34 12"));
+        assert!(lines[7].ends_with(&format!("|4.|{}data", " ".repeat(15))));
+    }
+
+    #[test]
+    fn dumps_responses_and_unknown_bytes() {
+        let mut frame = vec![
+            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x16, 0x00, 0x00, 0x00,
+        ];
+        frame.extend(0u8..20);
+        let dump = hexdump(&frame);
+        assert!(dump.contains("end code 0000"));
+        // 数据超过 16 字节时换行，续行不带注释
+        assert!(dump.ends_with("000B  00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F  |................| data\n001B  10 11 12 13                                      |....|\n"));
+
+        assert_eq!(
+            hexdump(b"AB"),
+            format!("0000  41 42{}|AB|\n", " ".repeat(44))
+        );
+        assert!(hexdump(&[0x50, 0x00, 0x00, 0xFF]).ends_with("truncated\n"));
+    }
+}
@@ -1,24 +1,32 @@
-use std::{
-    borrow::Cow,
-    convert::TryFrom,
-    io::{Cursor, Read},
-};
+use std::{convert::TryFrom, io::Cursor};
 
-use byteorder::{ByteOrder, LittleEndian, ReadBytesExt as _};
-use log;
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "server")]
+use {byteorder::ReadBytesExt as _, std::borrow::Cow, std::io::Read};
 
 use crate::{
     bytes::{BufMut, Bytes, BytesMut},
     frame::*,
     header::RequestHeader,
-    Error,
+    trace, Error,
 };
+pub mod ascii;
+pub mod frame_1e;
+pub mod hexdump;
+pub mod serial;
+pub mod split;
 pub mod tcp;
+pub mod transform;
+
+pub use split::Splitter;
+#[cfg(feature = "tcp")]
+pub(crate) use split::batch_points;
+pub use transform::FrameTransform;
 
 /// 优化的bool到字节转换，使用预分配和更高效的位操作
 #[inline]
 pub fn bools_to_bytes(bools: &[bool]) -> Vec<u8> {
-    let capacity = (bools.len() + 1) / 2;
+    let capacity = bools.len().div_ceil(2);
     let mut result = Vec::with_capacity(capacity);
 
     let chunks = bools.chunks_exact(2);
@@ -51,6 +59,7 @@ pub fn bytes_to_bools(bytes: &[u8]) -> Vec<bool> {
 pub struct ClientEncoder;
 
 /// 服务端解码器 - 将客户端发送的字节数据解码为 Request  
+#[cfg(feature = "server")]
 pub struct ServerDecoder;
 
 /// 客户端解码器 - 将服务端返回的字节数据解码为 Response
@@ -62,8 +71,33 @@ impl ClientEncoder {
         // 调用现有的 TryFrom 实现
         Vec::try_from(req)
     }
+
+    /// 按 `profile` 的点数限制拆分请求，并校验软元件和子指令
+    pub fn encode_with(req: Request<'_>, profile: &PlcProfile) -> Result<Vec<Bytes>, Error> {
+        encode_request(req, profile, &Splitter::new(), Route::LOCAL)
+    }
+
+    /// 同 [`Self::encode_with`]，但按 `splitter` 拆分请求
+    pub fn encode_split(
+        req: Request<'_>,
+        profile: &PlcProfile,
+        splitter: &Splitter,
+    ) -> Result<Vec<Bytes>, Error> {
+        encode_request(req, profile, splitter, Route::LOCAL)
+    }
+
+    /// 同 [`Self::encode_split`]，但以 `route` 为帧头中的访问路径
+    pub fn encode_routed(
+        req: Request<'_>,
+        profile: &PlcProfile,
+        splitter: &Splitter,
+        route: Route,
+    ) -> Result<Vec<Bytes>, Error> {
+        encode_request(req, profile, splitter, route)
+    }
 }
 
+#[cfg(feature = "server")]
 impl ServerDecoder {
     /// 将客户端发送的字节数据解码为 Request
     pub fn decode(bytes: Bytes) -> Result<Request<'static>, Error> {
@@ -85,97 +119,118 @@ impl<'a> TryFrom<Request<'a>> for Vec<Bytes> {
     type Error = Error;
 
     fn try_from(req: Request<'a>) -> Result<Vec<Bytes>, Error> {
-        use crate::frame::Request::*;
-
-        let (address, quantity_or_len, write_cursor) = match req {
-            ReadU8s(ref address, quantity) => (address.clone(), quantity, None),
-            WriteU8s(ref address, ref u8s) => {
-                let cursor = Cursor::new(Cow::Owned(u8s.to_vec()));
-                (
-                    address.clone(),
-                    ((u8s.len() as f32) / 2.0).round() as u32,
-                    Some(WriteCursor::U8s(cursor)),
-                )
+        encode_request(req, &PlcProfile::GENERIC, &Splitter::new(), Route::LOCAL)
+    }
+}
+
+fn encode_request(
+    req: Request<'_>,
+    profile: &PlcProfile,
+    splitter: &Splitter,
+    route: Route,
+) -> Result<Vec<Bytes>, Error> {
+    use crate::frame::Request::*;
+
+    if let Command(command, subcommand, data) = &req {
+        return Ok(vec![encode_routed_command(
+            route,
+            *command,
+            *subcommand,
+            data,
+        )]);
+    }
+
+    let function_code = req.function_code().value();
+    let subcommand = LittleEndian::read_u16(&function_code[2..4]);
+    if !profile.supports_subcommand(subcommand) {
+        let mut code = [0u8; 4];
+        code.copy_from_slice(&function_code);
+        return Err(Error::Protocol(ProtocolError::InvalidFunctionCode(code)));
+    }
+
+    let (address, quantity, bits) = match &req {
+        ReadU8s(address, quantity) => (address, *quantity, false),
+        WriteU8s(address, u8s) => {
+            if u8s.len() % 2 != 0 {
+                off_spec(ProtocolError::OddByteCount(u8s.len()))?;
             }
-            ReadBits(ref address, quantity) => (address.clone(), quantity, None),
-            WriteBits(ref address, ref bits) => {
-                let bytes = bools_to_bytes(bits);
-                let cursor = Cursor::new(Cow::Owned(bytes));
-                (
-                    address.clone(),
-                    bits.len() as u32,
-                    Some(WriteCursor::Bits(cursor)),
-                )
+            (address, u8s.len().div_ceil(2) as u32, false)
+        }
+        ReadBits(address, quantity) => (address, *quantity, true),
+        WriteBits(address, bits) => (address, bits.len() as u32, true),
+        Command(_, _, _) => unreachable!("commands are encoded above"),
+    };
+
+    let mut results = Vec::new();
+    let (u32_number, code) = parse_address_and_get_instruction_code(address, profile)?;
+    let header = RequestHeader::with_route(route);
+    let mut offset = 0;
+
+    for (current_address, len) in splitter.ranges(u32_number, quantity, bits, profile)? {
+        let range = offset..offset + len as usize;
+        offset = range.end;
+        let len = len as u16;
+
+        let mut data = match &req {
+            WriteU8s(_, _) => {
+                BytesMut::with_capacity(header.len() + REQUEST_BYTE_LAST_LEN + (len * 2) as usize)
+            }
+            WriteBits(_, _) => {
+                BytesMut::with_capacity(header.len() + REQUEST_BYTE_LAST_LEN + len as usize)
             }
+            _ => BytesMut::with_capacity(header.len() + REQUEST_BYTE_LAST_LEN),
         };
 
-        enum WriteCursor {
-            U8s(Cursor<Cow<'static, [u8]>>),
-            Bits(Cursor<Cow<'static, [u8]>>),
-        }
+        data.put_slice(header.bytes());
+        data.put_slice(&req.function_code().value());
+        request_command(&mut data, current_address, code, len);
 
-        let mut results = Vec::new();
-        let (u32_number, code) = parse_address_and_get_instruction_code(&address)?;
-        let mut current_len = quantity_or_len;
-        let mut current_address = u32_number;
-        let header = RequestHeader::new();
-
-        while current_len > 0 {
-            let len = current_len.min(LIMIT) as u16;
-
-            let mut data = match write_cursor {
-                Some(WriteCursor::U8s(_)) => BytesMut::with_capacity(
-                    header.len() + REQUEST_BYTE_LAST_LEN + (len * 2) as usize,
-                ),
-                Some(WriteCursor::Bits(_)) => {
-                    BytesMut::with_capacity(header.len() + REQUEST_BYTE_LAST_LEN + len as usize)
-                }
-                None => BytesMut::with_capacity(header.len() + REQUEST_BYTE_LAST_LEN),
-            };
-
-            data.put_slice(header.bytes());
-            data.put_slice(&req.function_code().value());
-            request_command(&mut data, current_address, code, len);
-
-            if let Some(write_cursor) = &write_cursor {
-                match write_cursor {
-                    WriteCursor::U8s(cursor) => {
-                        let mut write_iter = cursor.get_ref().iter().cloned();
-                        for _ in 0..len * 2 {
-                            if let Some(value) = write_iter.next() {
-                                data.put_u8(value);
-                            }
-                        }
-                    }
-                    WriteCursor::Bits(cursor) => {
-                        // bit写入时，每个字节包含实际数据
-                        let bytes_data = cursor.get_ref();
-                        for &byte_val in bytes_data.iter() {
-                            data.put_u8(byte_val);
-                        }
-                    }
+        match &req {
+            WriteU8s(_, u8s) => {
+                // 宽松模式下的奇数字节以 0 补足最后一个字
+                for i in range.start * 2..range.end * 2 {
+                    data.put_u8(u8s.get(i).copied().unwrap_or(0));
                 }
             }
-
-            let length = (data.len() - header.len() + 2) as u16;
-            LittleEndian::write_u16(&mut data[header.len() - 4..header.len() - 2], length);
-
-            current_address += len as u32;
-            current_len = current_len.saturating_sub(len as u32);
-            results.push(data.freeze());
+            // 位写入时每个字节包含两点
+            WriteBits(_, bits) => data.put_slice(&bools_to_bytes(&bits[range])),
+            _ => {}
         }
 
-        Ok(results)
+        let length = (data.len() - header.len() + 2) as u16;
+        LittleEndian::write_u16(&mut data[header.len() - 4..header.len() - 2], length);
+
+        results.push(data.freeze());
     }
+
+    Ok(results)
+}
+
+/// 编码软元件批量读写以外的命令，数据原样附在子指令之后，不做拆分
+pub(crate) fn encode_command(command: u16, subcommand: u16, payload: &[u8]) -> Bytes {
+    encode_routed_command(Route::LOCAL, command, subcommand, payload)
+}
+
+/// 同 [`encode_command`]，但以 `route` 为帧头中的访问路径
+fn encode_routed_command(route: Route, command: u16, subcommand: u16, payload: &[u8]) -> Bytes {
+    let header = RequestHeader::with_route(route);
+    let mut data = BytesMut::with_capacity(header.len() + 4 + payload.len());
+    data.put_slice(header.bytes());
+    data.put_u16_le(command);
+    data.put_u16_le(subcommand);
+    data.put_slice(payload);
+    let length = (data.len() - header.len() + 2) as u16;
+    LittleEndian::write_u16(&mut data[header.len() - 4..header.len() - 2], length);
+    data.freeze()
 }
 
 // 客户端解码: (Vec<Bytes>, Request) -> Response (客户端解析服务端响应时使用)
 impl TryFrom<(Vec<Bytes>, Request<'_>)> for Response {
     type Error = Error;
     fn try_from((bytes, req): (Vec<Bytes>, Request)) -> Result<Self, Error> {
-        log::debug!("=== Client received response from server ===");
+        trace::debug!("=== Client received response from server ===");
         for (i, byte_chunk) in bytes.iter().enumerate() {
-            log::debug!("Response chunk {}: {:02X?}", i, byte_chunk.as_ref());
+            trace::debug!(chunk = i, bytes = trace::Hex(byte_chunk); "Response chunk");
         }
 
         let mut data = Vec::new();
@@ -185,7 +240,7 @@ impl TryFrom<(Vec<Bytes>, Request<'_>)> for Response {
         //     data.extend_from_slice(&byte[2..]);
         // }
 
-        for (i, byte) in bytes.iter().enumerate() {
+        for byte in bytes.iter() {
             // // 确保至少有 2 字节结束码
             // if byte.len() < 2 {
             //     return Err(Error::Protocol(format!("Response too short: {:?}", byte)));
@@ -201,19 +256,38 @@ impl TryFrom<(Vec<Bytes>, Request<'_>)> for Response {
             data.extend_from_slice(&byte[2..]);
         }
 
-        log::debug!("Response data after processing: {:02X?}", data);
+        trace::debug!("Response data after processing: {:02X?}", data);
+
+        let expected = match req {
+            Request::ReadU8s(_, quantity) => Some(quantity as usize * 2),
+            Request::ReadBits(_, quantity) => Some((quantity as usize).div_ceil(2)),
+            Request::WriteU8s(_, _) | Request::WriteBits(_, _) | Request::Command(_, _, _) => None,
+        };
+        if let Some(expected) = expected.filter(|&expected| expected != data.len()) {
+            off_spec(ProtocolError::LengthMismatch {
+                expected,
+                actual: data.len(),
+            })?;
+        }
 
         let final_rdr = Cursor::new(data);
 
         match req {
             Request::ReadU8s(_, _) => Ok(Response::ReadU8s(final_rdr.get_ref().to_vec())),
             Request::WriteU8s(_, _) => Ok(Response::WriteU8s()),
-            Request::ReadBits(_, _) => {
+            Request::ReadBits(_, quantity) => {
                 let bytes = final_rdr.get_ref().to_vec();
-                let bits = bytes_to_bools(&bytes);
+                let mut bits = bytes_to_bools(&bytes);
+                // 奇数点时末尾的半字节为填充，按请求点数截取
+                bits.truncate(quantity as usize);
                 Ok(Response::ReadBits(bits))
             }
             Request::WriteBits(_, _) => Ok(Response::WriteBits()),
+            Request::Command(command, subcommand, _) => Ok(Response::Command(
+                command,
+                subcommand,
+                final_rdr.into_inner(),
+            )),
         }
     }
 }
@@ -227,6 +301,7 @@ impl TryFrom<(Vec<Bytes>, Request<'_>)> for Response {
 // }
 
 // 服务端解码: Bytes -> Request (服务端解析客户端请求时使用)
+#[cfg(feature = "server")]
 impl<'a> TryFrom<Bytes> for Request<'a> {
     type Error = Error;
 
@@ -239,16 +314,22 @@ impl<'a> TryFrom<Bytes> for Request<'a> {
         cursor.read_u16::<LittleEndian>()?; // 跳过 [10, 00]
 
         // 打印cursor的数据
-        log::debug!("Cursor data: {:?}", cursor.get_ref());
+        trace::debug!("Cursor data: {:?}", cursor.get_ref());
 
         let mut instruction_code = [0u8; 4];
 
         cursor.read_exact(&mut instruction_code)?;
-        let function_code = FunctionCode::new(BytesMut::from(&instruction_code[..]))
-            .ok_or_else(|| Error::Protocol(ProtocolError::InvalidFunctionCode(instruction_code)))?;
+        let function_code = FunctionCode::new(BytesMut::from(&instruction_code[..])).ok_or(
+            Error::Protocol(ProtocolError::InvalidFunctionCode(instruction_code)),
+        )?;
 
         let start_addr = cursor.read_u24::<LittleEndian>()?;
-        let (prefix, number_base) = find_prefix_and_base_by_code(cursor.read_u8()?).unwrap();
+        let device_code = cursor.read_u8()?;
+        let (prefix, number_base) = find_prefix_and_base_by_code(device_code).ok_or_else(|| {
+            Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "device code {device_code:02X}"
+            )))
+        })?;
         let quantity = cursor.read_u16::<LittleEndian>()? as u32;
 
         if quantity > LIMIT {
@@ -256,9 +337,9 @@ impl<'a> TryFrom<Bytes> for Request<'a> {
         }
 
         // 打印prefix
-        log::debug!("Prefix: {}", prefix);
+        trace::debug!("Prefix: {}", prefix);
         // 打印number_base
-        log::debug!("Number base: {:?}", number_base);
+        trace::debug!("Number base: {:?}", number_base);
 
         // start_addr根据number_base转换为对应string格式
         let start_addr: String = match number_base {
@@ -266,38 +347,50 @@ impl<'a> TryFrom<Bytes> for Request<'a> {
             NumberBase::Hexadecimal => format!("{:X}", start_addr),
         };
 
-        log::debug!("Start address (string): {}", start_addr);
+        trace::debug!("Start address (string): {}", start_addr);
 
         let address: Cow<'a, str> = format!("{}{}", prefix, start_addr).into();
 
-        log::debug!("Raw instruction code: {:02X?}", instruction_code);
-        log::debug!("Parsed function code: {:?}", function_code);
-        log::debug!("Start address: {}", address);
-        log::debug!("Raw quantity: {}", quantity);
+        trace::debug!("Raw instruction code: {:02X?}", instruction_code);
+        trace::debug!("Parsed function code: {:?}", function_code);
+        trace::debug!("Start address: {}", address);
+        trace::debug!("Raw quantity: {}", quantity);
 
         // 打印start_addr
-        log::debug!("Start address (u32): {}", start_addr);
+        trace::debug!("Start address (u32): {}", start_addr);
 
         match function_code {
             FunctionCode::ReadU8s => Ok(Request::ReadU8s(address, quantity)),
             FunctionCode::WriteU8s => {
                 let u8s = cursor.get_ref()[cursor.position() as usize..].to_vec();
-                log::debug!("Parsed U8s: {:?}", u8s);
+                trace::debug!("Parsed U8s: {:?}", u8s);
 
-                // if u8s.len() != quantity as usize {
-                //     return Err(Error::Protocol(ProtocolError::OutOfRange));
-                // }
+                if u8s.len() != quantity as usize * 2 {
+                    off_spec(ProtocolError::LengthMismatch {
+                        expected: quantity as usize * 2,
+                        actual: u8s.len(),
+                    })?;
+                }
                 Ok(Request::WriteU8s(address, u8s.into()))
             }
             FunctionCode::ReadBits => Ok(Request::ReadBits(address, quantity)),
             FunctionCode::WriteBits => {
                 let bytes = cursor.get_ref()[cursor.position() as usize..].to_vec();
+                if bytes.len() != (quantity as usize).div_ceil(2) {
+                    off_spec(ProtocolError::LengthMismatch {
+                        expected: (quantity as usize).div_ceil(2),
+                        actual: bytes.len(),
+                    })?;
+                }
                 let mut bits = bytes_to_bools(&bytes);
                 // 根据quantity截取正确数量的位
                 bits.truncate(quantity as usize);
-                log::debug!("Parsed {} bits: {:?}", quantity, bits);
+                trace::debug!("Parsed {} bits: {:?}", quantity, bits);
                 Ok(Request::WriteBits(address, bits.into()))
             }
+            FunctionCode::Command(_, _) => Err(Error::Protocol(
+                ProtocolError::InvalidFunctionCode(instruction_code),
+            )),
         }
     }
 }
@@ -310,14 +403,15 @@ fn request_command(data: &mut BytesMut, address: u32, code: u8, cnt: u16) {
     data.put_u16_le(cnt);
 }
 
-fn parse_address_and_get_instruction_code(address: &str) -> Result<(u32, u8), Error> {
-    let (prefix, number) = split_address(address).unwrap();
+fn parse_address_and_get_instruction_code(
+    address: &str,
+    profile: &PlcProfile,
+) -> Result<(u32, u8), Error> {
+    let invalid = || Error::Protocol(ProtocolError::InvalidAddress(address.to_string()));
+    let translated = profile.translate(address).ok_or_else(invalid)?;
+    let (device, u32_number) = profile.parse_address(&translated).ok_or_else(invalid)?;
 
-    let (code, number_base) = find_instruction_code(prefix).unwrap();
-
-    let u32_number = convert_to_base(number, number_base).unwrap();
-
-    Ok((u32_number, code))
+    Ok((u32_number, device.code))
 }
 
 // fn check_response(response_bytes: &[u8]) -> Result<(), Error> {
@@ -372,6 +466,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encode_with_profile_limits_and_devices() {
+        let request = Request::ReadBits("M0".into(), 2000);
+        assert_eq!(ClientEncoder::encode(request.clone()).unwrap().len(), 3);
+        assert_eq!(
+            ClientEncoder::encode_with(request, &PlcProfile::Q_SERIES)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let request = Request::ReadU8s("ZR0".into(), 1);
+        assert!(ClientEncoder::encode_with(request.clone(), &PlcProfile::IQ_R).is_ok());
+        assert!(matches!(
+            ClientEncoder::encode_with(request, &PlcProfile::FX_SERIES),
+            Err(Error::Protocol(ProtocolError::InvalidAddress(_)))
+        ));
+        assert!(ClientEncoder::encode(Request::ReadU8s("Q0".into(), 1)).is_err());
+    }
+
+    #[test]
+    fn encode_split_advances_write_data() {
+        let splitter = Splitter::new().max_points(2);
+        let request = Request::WriteU8s("D0".into(), vec![1, 2, 3, 4, 5, 6].into());
+        let frames = ClientEncoder::encode_split(request, &PlcProfile::GENERIC, &splitter).unwrap();
+        let data: Vec<&[u8]> = frames.iter().map(|frame| &frame[21..]).collect();
+        assert_eq!(data, [&[1, 2, 3, 4][..], &[5, 6]]);
+
+        let request = Request::WriteBits("M0".into(), vec![true, false, false, true, true].into());
+        let frames = ClientEncoder::encode_split(request, &PlcProfile::GENERIC, &splitter).unwrap();
+        let data: Vec<&[u8]> = frames.iter().map(|frame| &frame[21..]).collect();
+        assert_eq!(data, [&[0x10][..], &[0x01], &[0x10]]);
+    }
+
     #[test]
     fn test_write_u8s_to_bytes() {
         let data: Vec<u8> = vec![1, 2, 3, 4];
@@ -465,7 +593,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x90, 0x04, 0x00,
         ];
 
-        let len = (data.len() + 1) / 2 + 12;
+        let len = data.len().div_ceil(2) + 12;
 
         // 替换expected_odd_bytes的长度部分
         expected_bytes[7] = (len & 0xFF) as u8; // 低字节
@@ -493,7 +621,7 @@ mod tests {
         ];
 
         // 0x0E, 0x00的部分是指令长度
-        let len = (odd_data.len() + 1) / 2 + 12;
+        let len = odd_data.len().div_ceil(2) + 12;
 
         // 替换expected_odd_bytes的长度部分
         expected_odd_bytes[7] = (len & 0xFF) as u8; // 低字节
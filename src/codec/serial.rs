@@ -0,0 +1,85 @@
+//! 串行 1C/4C 格式 ASCII 帧的和校验
+//!
+//! 和校验码为帧内各字节之和的低 8 位，以两个大写十六进制 ASCII 字符附加在帧尾。
+//! 校验范围从控制代码（ENQ/STX）之后开始，到和校验码之前为止（STX 帧包含 ETX）；
+//! 格式 4 帧尾的 CR LF 不参与校验。
+
+use crate::{frame::ProtocolError, Error};
+
+/// 请求帧起始
+pub const ENQ: u8 = 0x05;
+/// 带数据的应答帧起始
+pub const STX: u8 = 0x02;
+/// 应答数据结束
+pub const ETX: u8 = 0x03;
+
+const CRLF: &[u8] = b"\r\n";
+
+/// `data` 各字节之和的低 8 位，以两个大写十六进制字符表示
+pub fn sum_check(data: &[u8]) -> [u8; 2] {
+    let sum = data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+    let hex = |nibble: u8| b"0123456789ABCDEF"[nibble as usize];
+    [hex(sum >> 4), hex(sum & 0x0F)]
+}
+
+/// 对控制代码之后的全部字节计算和校验码并附加到帧尾
+pub fn append_sum_check(frame: &mut Vec<u8>) {
+    let code = sum_check(frame.get(1..).unwrap_or_default());
+    frame.extend_from_slice(&code);
+}
+
+/// 校验帧尾的和校验码，返回去掉和校验码（及 CR LF）后的帧
+pub fn verify_sum_check(frame: &[u8]) -> Result<&[u8], Error> {
+    let frame = frame.strip_suffix(CRLF).unwrap_or(frame);
+    if frame.len() < 3 {
+        return Err(Error::Protocol(ProtocolError::LengthMismatch {
+            expected: 3,
+            actual: frame.len(),
+        }));
+    }
+    let (body, received) = frame.split_at(frame.len() - 2);
+    let expected = sum_check(&body[1..]);
+    if !received.eq_ignore_ascii_case(&expected) {
+        return Err(Error::Protocol(ProtocolError::SumCheck {
+            expected: String::from_utf8_lossy(&expected).into_owned(),
+            received: String::from_utf8_lossy(received).into_owned(),
+        }));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_check_round_trip() {
+        // 1C 格式 1：站号 00、PC 号 FF、BR 批量读取 X40 起 5 点、等待 0
+        let mut frame = vec![ENQ];
+        frame.extend_from_slice(b"00FFBR0X004005");
+        append_sum_check(&mut frame);
+        assert_eq!(&frame[frame.len() - 2..], b"31");
+        assert_eq!(verify_sum_check(&frame).unwrap(), &frame[..frame.len() - 2]);
+
+        // 应答帧的校验范围包含 ETX，格式 4 的 CR LF 不参与校验
+        let mut reply = vec![STX];
+        reply.extend_from_slice(b"00FF10110");
+        reply.push(ETX);
+        append_sum_check(&mut reply);
+        reply.extend_from_slice(CRLF);
+        assert_eq!(verify_sum_check(&reply).unwrap().last(), Some(&ETX));
+    }
+
+    #[test]
+    fn corrupted_frame_is_rejected() {
+        let mut frame = vec![ENQ];
+        frame.extend_from_slice(b"00FFBR0X004005");
+        append_sum_check(&mut frame);
+        frame[5] = b'W';
+        assert!(matches!(
+            verify_sum_check(&frame),
+            Err(Error::Protocol(ProtocolError::SumCheck { received, .. })) if received == "31"
+        ));
+        assert!(verify_sum_check(&[ENQ, b'4']).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! 请求拆分策略
+//!
+//! 单帧可读写的点数有限，超过限制的批量读写会被拆分为多个地址连续的请求依次发出。
+//! [`Splitter`] 决定拆分方式：默认按 [`PlcProfile`] 的点数上限拆分，
+//! 也可以进一步限制单帧点数或单帧字节数（适用于 MTU 受限的网关）、
+//! 让拆分边界落在偶数地址上，或者关闭拆分。
+//! [`Splitter::split`] 返回拆分后的请求，便于自行分批发送。
+//!
+//! 小型 CPU 或经由中继访问时，PLC 能处理的单帧点数可能小于系列的上限。
+//! 设置 [`Splitter::adaptive`] 后，`TcpClient` 从上限开始，
+//! PLC 以点数超出范围的结束代码（C051-C054）拒绝时把单帧点数减半后重发，
+//! 调用未完成就被取消（通常是调用方的超时）时在下一次调用前减半，
+//! 减小后的点数在该连接上一直沿用，不必手动调整。
+//!
+//! 位请求的应答按两点一字节拼接，因此拆分出的位请求除最后一帧外点数总为偶数。
+
+use crate::{
+    frame::{NumberBase, PlcProfile, ProtocolError, Quantity, Request, REQUEST_BYTE_LAST_LEN},
+    header::RequestHeader,
+    Error,
+};
+
+/// 批量读写请求的拆分策略，见[模块文档](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Splitter {
+    enabled: bool,
+    max_points: Option<Quantity>,
+    max_bytes: Option<usize>,
+    align_even: bool,
+    adaptive: bool,
+    learned_words: Option<Quantity>,
+    learned_bits: Option<Quantity>,
+}
+
+impl Default for Splitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Splitter {
+    /// 仅按 PLC 系列的点数上限拆分
+    pub const fn new() -> Self {
+        Self {
+            enabled: true,
+            max_points: None,
+            max_bytes: None,
+            align_even: false,
+            adaptive: false,
+            learned_words: None,
+            learned_bits: None,
+        }
+    }
+
+    /// 不拆分：无论点数多少都以单帧发出，超出 PLC 的限制时由 PLC 拒绝
+    pub const fn unsplit() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    /// 单帧点数上限，与 PLC 系列的上限取较小者
+    pub const fn max_points(mut self, points: Quantity) -> Self {
+        self.max_points = Some(points);
+        self
+    }
+
+    /// 单帧请求与应答（含帧头）的字节数上限
+    pub const fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// 字请求的拆分边界是否落在偶数地址上，避免双字数值被拆到两帧
+    pub const fn align_even(mut self, align_even: bool) -> Self {
+        self.align_even = align_even;
+        self
+    }
+
+    /// 是否按 PLC 的实际表现自动减小单帧点数，见[模块文档](self)
+    pub const fn adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// 是否自动减小单帧点数
+    pub const fn is_adaptive(&self) -> bool {
+        self.adaptive
+    }
+
+    /// 自动减小后的单帧点数，`bits` 为真时为位请求的点数，尚未减小时为 `None`
+    pub const fn learned_points(&self, bits: bool) -> Option<Quantity> {
+        if bits {
+            self.learned_bits
+        } else {
+            self.learned_words
+        }
+    }
+
+    /// 自动减小单帧点数：`quantity` 点的请求失败后，把单帧点数减为实际发出的最大帧的一半
+    ///
+    /// 未启用自动调整或已无法再减小时返回 false。
+    #[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+    pub(crate) fn shrink(&mut self, bits: bool, quantity: Quantity, profile: &PlcProfile) -> bool {
+        if !self.enabled || !self.adaptive {
+            return false;
+        }
+        let mut points = self.limit(bits, profile).min(quantity) / 2;
+        if bits {
+            points -= points % 2;
+        }
+        if points == 0 {
+            return false;
+        }
+        if bits {
+            self.learned_bits = Some(points);
+        } else {
+            self.learned_words = Some(points);
+        }
+        true
+    }
+
+    /// 按 `profile` 拆分批量读写请求，其它命令原样返回
+    ///
+    /// 拆分后的地址为经 `profile` 转换后的 MC 地址。
+    pub fn split(
+        &self,
+        request: &Request<'_>,
+        profile: &PlcProfile,
+    ) -> Result<Vec<Request<'static>>, Error> {
+        let Some((bits, quantity)) = batch_points(request) else {
+            return Ok(vec![request.clone().into_owned()]);
+        };
+        let address = request.address();
+        let invalid = || Error::Protocol(ProtocolError::InvalidAddress(address.to_string()));
+        let translated = profile.translate(address).ok_or_else(invalid)?;
+        let (device, number) = profile.parse_address(&translated).ok_or_else(invalid)?;
+
+        let mut offset = 0;
+        self.ranges(number, quantity, bits, profile)?
+            .into_iter()
+            .map(|(start, len)| {
+                let address = match device.number_base {
+                    NumberBase::Decimal => format!("{}{start}", device.prefix),
+                    NumberBase::Hexadecimal => format!("{}{start:X}", device.prefix),
+                };
+                let range = offset..offset + len as usize;
+                offset = range.end;
+                Ok(match request {
+                    Request::ReadU8s(_, _) => Request::ReadU8s(address.into(), len),
+                    Request::WriteU8s(_, u8s) => {
+                        let end = (range.end * 2).min(u8s.len());
+                        Request::WriteU8s(address.into(), u8s[range.start * 2..end].to_vec().into())
+                    }
+                    Request::ReadBits(_, _) => Request::ReadBits(address.into(), len),
+                    Request::WriteBits(_, bits) => {
+                        Request::WriteBits(address.into(), bits[range].to_vec().into())
+                    }
+                    Request::Command(_, _, _) => unreachable!("commands are not split"),
+                })
+            })
+            .collect()
+    }
+
+    /// 从 `start` 起共 `quantity` 点的各帧起始编号与点数
+    pub(crate) fn ranges(
+        &self,
+        start: u32,
+        quantity: Quantity,
+        bits: bool,
+        profile: &PlcProfile,
+    ) -> Result<Vec<(u32, Quantity)>, Error> {
+        let limit = if self.enabled {
+            self.limit(bits, profile)
+        } else {
+            quantity
+        };
+        let limit = limit.min(u16::MAX as Quantity);
+        if limit == 0 && quantity > 0 {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+
+        let mut ranges = Vec::new();
+        let (mut start, mut remaining) = (start, quantity);
+        while remaining > 0 {
+            let mut len = remaining.min(limit);
+            if self.enabled && self.align_even && !bits && len < remaining && len > 1 {
+                len -= (start + len) % 2;
+            }
+            ranges.push((start, len));
+            start += len;
+            remaining -= len;
+        }
+        Ok(ranges)
+    }
+
+    /// 单帧点数上限，位请求取偶数
+    fn limit(&self, bits: bool, profile: &PlcProfile) -> Quantity {
+        let mut limit = if bits {
+            profile.max_bit_points
+        } else {
+            profile.max_word_points
+        };
+        if let Some(points) = self.max_points {
+            limit = limit.min(points);
+        }
+        if let Some(points) = self.learned_points(bits) {
+            limit = limit.min(points);
+        }
+        if let Some(bytes) = self.max_bytes {
+            // 请求帧的固定部分比应答帧长，以此估算数据可用的字节数
+            let room = bytes.saturating_sub(RequestHeader::new().len() + REQUEST_BYTE_LAST_LEN);
+            let points = if bits { room * 2 } else { room / 2 };
+            limit = limit.min(points.min(Quantity::MAX as usize) as Quantity);
+        }
+        if bits {
+            limit -= limit % 2;
+        }
+        limit
+    }
+}
+
+/// 批量读写请求是否为位请求及其点数，其它命令返回 `None`
+pub(crate) fn batch_points(request: &Request<'_>) -> Option<(bool, Quantity)> {
+    match request {
+        Request::ReadU8s(_, quantity) => Some((false, *quantity)),
+        Request::WriteU8s(_, u8s) => Some((false, u8s.len().div_ceil(2) as Quantity)),
+        Request::ReadBits(_, quantity) => Some((true, *quantity)),
+        Request::WriteBits(_, bits) => Some((true, bits.len() as Quantity)),
+        Request::Command(_, _, _) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses(requests: &[Request<'_>]) -> Vec<(String, Quantity)> {
+        requests
+            .iter()
+            .map(|request| match request {
+                Request::ReadU8s(address, len) | Request::ReadBits(address, len) => {
+                    (address.to_string(), *len)
+                }
+                Request::WriteU8s(address, u8s) => (address.to_string(), u8s.len() as Quantity),
+                Request::WriteBits(address, bits) => (address.to_string(), bits.len() as Quantity),
+                Request::Command(_, _, _) => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_by_points_bytes_and_alignment() {
+        let profile = PlcProfile::GENERIC;
+        let read = Request::ReadU8s("D1".into(), 10);
+
+        let split = Splitter::new()
+            .max_points(4)
+            .split(&read, &profile)
+            .unwrap();
+        assert_eq!(
+            addresses(&split),
+            [("D1".into(), 4), ("D5".into(), 4), ("D9".into(), 2)]
+        );
+
+        let split = Splitter::new()
+            .max_points(4)
+            .align_even(true)
+            .split(&read, &profile)
+            .unwrap();
+        assert_eq!(
+            addresses(&split),
+            [("D1".into(), 3), ("D4".into(), 4), ("D8".into(), 3)]
+        );
+
+        // 21 字节的固定部分加 8 字节数据
+        let split = Splitter::new()
+            .max_bytes(29)
+            .split(&Request::ReadBits("B1A".into(), 40), &profile)
+            .unwrap();
+        assert_eq!(
+            addresses(&split),
+            [("B1A".into(), 16), ("B2A".into(), 16), ("B3A".into(), 8)]
+        );
+
+        let write = Request::WriteU8s("W0".into(), vec![1, 2, 3, 4, 5].into());
+        let split = Splitter::new()
+            .max_points(2)
+            .split(&write, &profile)
+            .unwrap();
+        assert_eq!(split[1], Request::WriteU8s("W2".into(), vec![5].into()));
+
+        let split = Splitter::unsplit()
+            .split(&Request::ReadU8s("D0".into(), 2000), &profile)
+            .unwrap();
+        assert_eq!(addresses(&split), [("D0".into(), 2000)]);
+
+        // 位请求的每帧点数取偶数
+        let split = Splitter::new()
+            .max_points(5)
+            .split(
+                &Request::WriteBits("M0".into(), vec![true; 9].into()),
+                &profile,
+            )
+            .unwrap();
+        assert_eq!(
+            addresses(&split),
+            [("M0".into(), 4), ("M4".into(), 4), ("M8".into(), 1)]
+        );
+
+        assert!(Splitter::new()
+            .max_bytes(21)
+            .split(&read, &profile)
+            .is_err());
+    }
+    #[test]
+    fn adaptive_splitter_halves_the_largest_frame() {
+        let profile = PlcProfile::GENERIC;
+        let mut splitter = Splitter::new().adaptive(true);
+        assert_eq!(splitter.learned_points(false), None);
+
+        // 10 字的请求被拒绝后以 5 字一帧发出
+        assert!(splitter.shrink(false, 10, &profile));
+        let split = splitter
+            .split(&Request::ReadU8s("D0".into(), 12), &profile)
+            .unwrap();
+        assert_eq!(
+            addresses(&split),
+            [("D0".into(), 5), ("D5".into(), 5), ("D10".into(), 2)]
+        );
+        // 位请求的上限单独记录，且取偶数
+        assert!(splitter.shrink(true, 7, &profile));
+        assert_eq!(splitter.learned_points(true), Some(2));
+        assert!(!splitter.shrink(true, 2, &profile));
+        assert_eq!(splitter.learned_points(false), Some(5));
+
+        assert!(!Splitter::new().shrink(false, 10, &profile));
+    }
+}
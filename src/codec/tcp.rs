@@ -2,31 +2,117 @@ use byteorder::{ByteOrder, LittleEndian};
 #[cfg(feature = "server")]
 use bytes::BufMut;
 use bytes::{Bytes, BytesMut};
-use log;
-use std::io::Result;
+use std::{io::Result, sync::Arc};
 use tokio_util::codec::{Decoder, Encoder};
 
-use crate::{frame::Request, header::ResponseHeader};
+use crate::{
+    frame::{EndCode, Request, ResponseInfo, Route},
+    header::ResponseHeader,
+    stats::Traffic,
+    trace,
+};
+
+use super::{
+    ascii::{self, DataUnit},
+    frame_1e,
+    hexdump::HexDump,
+    FrameTransform,
+};
 
 #[cfg(feature = "server")]
-use crate::{frame::Response, header::RequestHeader};
+use crate::{
+    frame::{FunctionCode, Response},
+    header::RequestHeader,
+};
 
 #[derive(Debug, Default)]
-pub(crate) struct McClientDecoder;
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+pub(crate) struct McClientDecoder {
+    /// 下一帧应答的格式，由客户端在发送每个请求帧后设置
+    pub(crate) format: ResponseFormat,
+    /// 解码前改写完整应答帧
+    pub(crate) transform: Option<Arc<dyn FrameTransform>>,
+    /// 记录收到的帧与读缓冲区容量
+    pub(crate) traffic: Option<Arc<Traffic>>,
+}
+
+/// 客户端期待的应答帧格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+pub(crate) enum ResponseFormat {
+    #[default]
+    Binary3E,
+    /// 1E 应答帧没有长度字段：应答子头部与正常应答的数据字节数
+    Binary1E { subheader: u8, data_len: usize },
+    /// ASCII 码的 3E 帧，转换为二进制码后解码
+    Ascii3E(DataUnit),
+}
 
 #[derive(Debug, Default)]
 #[cfg(feature = "server")]
 pub(crate) struct McServerDecoder;
 
+/// 客户端收到的一帧响应：帧头中的应答站信息与结束代码之后的 payload
+#[derive(Debug)]
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+pub(crate) struct ResponseFrame {
+    pub(crate) network_no: u8,
+    pub(crate) pc_no: u8,
+    pub(crate) dest_io: u16,
+    pub(crate) dest_station: u8,
+    /// 以 2 字节结束代码开头的响应数据
+    pub(crate) payload: Bytes,
+}
+
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+impl ResponseFrame {
+    /// 帧头中的应答站信息与结束代码
+    pub(crate) fn info(&self) -> ResponseInfo {
+        ResponseInfo {
+            route: Route {
+                network_no: self.network_no,
+                pc_no: self.pc_no,
+                dest_io: self.dest_io,
+                dest_station: self.dest_station,
+            },
+            end_code: self
+                .payload
+                .get(..2)
+                .map(LittleEndian::read_u16)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 结束代码非零时返回携带应答站信息的 `EndCode`
+    pub(crate) fn end_code(&self) -> Option<EndCode> {
+        let code = self.info().end_code;
+        (code != 0).then(|| {
+            EndCode::new(
+                code,
+                self.network_no,
+                self.pc_no,
+                self.dest_io,
+                self.dest_station,
+            )
+        })
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
 pub(crate) struct McClientCodec {
     pub(crate) decoder: McClientDecoder,
 }
 
 impl McClientCodec {
+    #[cfg_attr(not(feature = "tcp"), allow(dead_code))]
     pub(crate) const fn new() -> Self {
         Self {
-            decoder: McClientDecoder,
+            decoder: McClientDecoder {
+                format: ResponseFormat::Binary3E,
+                transform: None,
+                traffic: None,
+            },
         }
     }
 }
@@ -35,66 +121,284 @@ impl McClientCodec {
 #[cfg(feature = "server")]
 pub(crate) struct ServerCodec {
     pub(crate) decoder: McServerDecoder,
+    /// 记录收发的帧与读缓冲区容量
+    pub(crate) traffic: Option<Arc<Traffic>>,
+}
+
+#[cfg(feature = "server")]
+impl ServerCodec {
+    pub(crate) fn with_traffic(traffic: Arc<Traffic>) -> Self {
+        Self {
+            decoder: McServerDecoder,
+            traffic: Some(traffic),
+        }
+    }
+
+    fn record_sent(&self, len: usize) {
+        if let Some(traffic) = &self.traffic {
+            traffic.record_sent(len);
+        }
+    }
+}
+
+/// 服务端的异常应答：非零结束代码与出错请求的指令
+#[derive(Debug)]
+#[cfg(feature = "server")]
+pub(crate) struct ErrorResponse {
+    pub(crate) route: Route,
+    pub(crate) end_code: u16,
+    pub(crate) function: FunctionCode,
+}
+
+/// 帧头中子头部之后的 5 字节访问路径
+#[cfg(feature = "server")]
+fn read_route(bytes: &[u8]) -> Route {
+    Route {
+        network_no: bytes[0],
+        pc_no: bytes[1],
+        dest_io: LittleEndian::read_u16(&bytes[2..4]),
+        dest_station: bytes[4],
+    }
+}
+
+#[cfg(feature = "server")]
+fn put_route(buf: &mut BytesMut, route: Route) {
+    buf.put_u8(route.network_no);
+    buf.put_u8(route.pc_no);
+    buf.put_u16_le(route.dest_io);
+    buf.put_u8(route.dest_station);
+}
+
+/// 应答帧头：子头部、与请求相同的访问路径以及应答数据长度
+#[cfg(feature = "server")]
+fn put_response_header(buf: &mut BytesMut, route: Route, data_length: u16) {
+    buf.put_u16_le(0x00D0);
+    put_route(buf, route);
+    buf.put_u16_le(data_length);
 }
 
 impl Decoder for McClientDecoder {
-    type Item = Bytes;
+    type Item = ResponseFrame;
     type Error = std::io::Error;
 
-    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>> {
-        let response_header = ResponseHeader::new();
-        let header_len = response_header.len();
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<ResponseFrame>> {
+        let transform = self.transform.as_deref();
+        let (available, capacity) = (buf.len(), buf.capacity());
+        let frame = match self.format {
+            ResponseFormat::Binary3E => decode_3e(buf, transform),
+            ResponseFormat::Binary1E {
+                subheader,
+                data_len,
+            } => decode_1e(buf, subheader, data_len, transform),
+            ResponseFormat::Ascii3E(unit) => decode_ascii_3e(buf, unit, transform),
+        }?;
+        if let (Some(traffic), Some(_)) = (&self.traffic, &frame) {
+            traffic.record_received(available - buf.len(), capacity);
+        }
+        Ok(frame)
+    }
+}
 
-        if buf.len() < header_len {
-            return Ok(None); // Need more data
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// 以改写后的帧替换原帧，改写后的帧不能短于 `min_len`
+fn apply_transform(
+    frame: &mut BytesMut,
+    transform: Option<&dyn FrameTransform>,
+    min_len: usize,
+) -> Result<()> {
+    if let Some(transform) = transform {
+        transform.incoming(frame);
+        if frame.len() < min_len {
+            return Err(invalid_data(format!(
+                "Response frame truncated to {} bytes by transform",
+                frame.len()
+            )));
         }
+    }
+    Ok(())
+}
 
-        log::debug!("Client received buffer: {:02X?}", &buf[..]);
+fn check_3e_prefix(header: &[u8]) -> Result<()> {
+    // 客户端解析服务端响应 - 验证副帧头 (D0 00)，路由字段由应答站决定
+    if header[..2] != [0xD0, 0x00] {
+        return Err(invalid_data(format!(
+            "Invalid MC response prefix: {header:02X?}"
+        )));
+    }
+    Ok(())
+}
 
-        // 客户端解析服务端响应 - 验证响应前缀 (D0 00 00 FF FF 03 00)
-        let response_prefix = [0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
-        if buf[..response_prefix.len()] != response_prefix {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Invalid MC response prefix: {:02X?}", &buf[..header_len]),
-            ));
+fn decode_3e(
+    buf: &mut BytesMut,
+    transform: Option<&dyn FrameTransform>,
+) -> Result<Option<ResponseFrame>> {
+    let response_header = ResponseHeader::new();
+    let header_len = response_header.len();
+
+    if buf.len() < header_len {
+        return Ok(None); // Need more data
+    }
+
+    trace::debug!(bytes = trace::Hex(&buf[..]); "Client received buffer");
+
+    // 帧头可能由改写钩子修正，此时在改写后再验证
+    if transform.is_none() {
+        check_3e_prefix(&buf[..header_len])?;
+    }
+
+    // Extract data length from header
+    let len = usize::from(LittleEndian::read_u16(&buf[header_len - 2..header_len]));
+    let total_len = header_len + len;
+
+    if buf.len() < total_len {
+        return Ok(None); // Need more data
+    }
+
+    // Extract complete frame and keep the responder fields of the header
+    let mut complete_frame = buf.split_to(total_len);
+    if transform.is_some() {
+        apply_transform(&mut complete_frame, transform, header_len)?;
+        check_3e_prefix(&complete_frame[..header_len])?;
+    }
+
+    trace::event!(trace, "Response frame\n{}", HexDump(&complete_frame));
+
+    let payload = complete_frame.split_off(header_len).freeze();
+    Ok(Some(ResponseFrame {
+        network_no: complete_frame[2],
+        pc_no: complete_frame[3],
+        dest_io: LittleEndian::read_u16(&complete_frame[4..6]),
+        dest_station: complete_frame[6],
+        payload,
+    }))
+}
+
+/// 解码 ASCII 码的 3E 应答帧：`D000`、10 个字符的访问路径与 4 个字符的数据长度之后为应答数据
+fn decode_ascii_3e(
+    buf: &mut BytesMut,
+    unit: DataUnit,
+    transform: Option<&dyn FrameTransform>,
+) -> Result<Option<ResponseFrame>> {
+    const HEADER_LEN: usize = 18;
+    if buf.len() < HEADER_LEN {
+        return Ok(None);
+    }
+    let check_prefix = |header: &[u8]| {
+        if header.starts_with(b"D000") {
+            return Ok(());
         }
+        Err(invalid_data(format!(
+            "Invalid MC ASCII response prefix: {:?}",
+            String::from_utf8_lossy(header)
+        )))
+    };
+    if transform.is_none() {
+        check_prefix(&buf[..HEADER_LEN])?;
+    }
+    let len = std::str::from_utf8(&buf[HEADER_LEN - 4..HEADER_LEN])
+        .ok()
+        .and_then(|len| usize::from_str_radix(len, 16).ok())
+        .ok_or_else(|| invalid_data("Invalid MC ASCII data length".to_string()))?;
+    if buf.len() < HEADER_LEN + len {
+        return Ok(None);
+    }
 
-        // Extract data length from header
-        let len = usize::from(LittleEndian::read_u16(&buf[header_len - 2..header_len]));
-        let total_len = header_len + len;
+    let mut frame = buf.split_to(HEADER_LEN + len);
+    if transform.is_some() {
+        apply_transform(&mut frame, transform, HEADER_LEN)?;
+        check_prefix(&frame[..HEADER_LEN])?;
+    }
+    trace::event!(trace, "Response frame\n{}", HexDump(&frame));
+    let binary =
+        ascii::ascii_to_binary(&frame, unit).map_err(|err| invalid_data(err.to_string()))?;
+    decode_3e(&mut BytesMut::from(&binary[..]), None)?
+        .ok_or_else(|| invalid_data("Incomplete MC ASCII response".to_string()))
+        .map(Some)
+}
 
-        if buf.len() < total_len {
-            return Ok(None); // Need more data
+/// 解码 1E 应答帧：正常应答附 `data_len` 字节数据，异常应答（0x5B）附 1 字节异常代码
+fn decode_1e(
+    buf: &mut BytesMut,
+    subheader: u8,
+    data_len: usize,
+    transform: Option<&dyn FrameTransform>,
+) -> Result<Option<ResponseFrame>> {
+    let Some(&code) = buf.get(1) else {
+        return Ok(None);
+    };
+    let check_subheader = |frame: &[u8]| {
+        if frame[0] == subheader {
+            return Ok(());
         }
+        Err(invalid_data(format!(
+            "Invalid 1E response subheader: {:02X}",
+            frame[0]
+        )))
+    };
+    if transform.is_none() {
+        check_subheader(buf)?;
+    }
+    let total_len = match code {
+        0x00 => 2 + data_len,
+        frame_1e::ABNORMAL_END => 3,
+        _ => 2,
+    };
+    if buf.len() < total_len {
+        return Ok(None);
+    }
 
-        // Extract complete frame and return payload only
-        let mut complete_frame = buf.split_to(total_len);
-        let payload = complete_frame.split_off(header_len).freeze();
-        Ok(Some(payload))
+    // 与 3E 帧一致，payload 以 2 字节结束代码开头
+    let mut frame = buf.split_to(total_len);
+    if transform.is_some() {
+        apply_transform(&mut frame, transform, 2)?;
+        check_subheader(&frame)?;
     }
+    trace::event!(trace, "Response frame\n{}", HexDump(&frame));
+
+    let end_code = frame_1e::end_code(&frame).unwrap_or_default();
+    let mut payload = BytesMut::with_capacity(2 + data_len);
+    payload.extend_from_slice(&end_code.to_le_bytes());
+    if frame[1] == 0x00 {
+        payload.extend_from_slice(&frame[2..]);
+    }
+    let Route {
+        network_no,
+        pc_no,
+        dest_io,
+        dest_station,
+    } = Route::LOCAL;
+    Ok(Some(ResponseFrame {
+        network_no,
+        pc_no,
+        dest_io,
+        dest_station,
+        payload: payload.freeze(),
+    }))
 }
 
 #[cfg(feature = "server")]
 impl Decoder for McServerDecoder {
-    type Item = Bytes;
+    type Item = (Route, Bytes);
     type Error = std::io::Error;
 
-    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>> {
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<(Route, Bytes)>> {
         let request_header = RequestHeader::new();
         let header_len = request_header.len();
 
         // let response_header = ResponseHeader::new();
         // let header_len = response_header.len();
 
-        log::debug!("Server received buffer: {:02X?}", &buf[..]);
+        trace::debug!(bytes = trace::Hex(&buf[..]); "Server received buffer");
 
         if buf.len() < header_len {
             return Ok(None); // Need more data
         }
 
-        // 服务端解析客户端请求 - 验证请求前缀 (50 00 00 FF FF 03 00)
-        let request_prefix = [0x50, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+        // 服务端解析客户端请求 - 验证子头部 (50 00)，访问路径随请求一并交给服务
+        let request_prefix = [0x50, 0x00];
         if buf[..request_prefix.len()] != request_prefix {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -105,39 +409,55 @@ impl Decoder for McServerDecoder {
         // Extract data length from header
         let len = usize::from(LittleEndian::read_u16(&buf[header_len - 4..header_len - 2]));
 
-        log::debug!("Data length: {}", len);
+        trace::debug!("Data length: {}", len);
 
         // 检查是否有足够的数据来读取完整的包
         let total_len = header_len - 4 + len + 2;
         if buf.len() < total_len {
-            log::debug!("Need more data: buf.len()={}, total_len={}", buf.len(), total_len);
+            trace::debug!(
+                "Need more data: buf.len()={}, total_len={}",
+                buf.len(),
+                total_len
+            );
             return Ok(None); // Need more data
         }
 
-        log::debug!("Server2 received buffer: {:02X?}", &buf[..]);
+        trace::debug!("Server2 received buffer: {:02X?}", &buf[..]);
+        trace::event!(trace, "Request frame\n{}", HexDump(&buf[..total_len]));
 
         let _header = buf.split_to(header_len - 4);
+        let route = read_route(&_header[2..7]);
 
         // 打印头部信息
-        log::debug!("Header: {:02X?}", &_header[..]);
+        trace::debug!("Header: {:02X?}", &_header[..]);
 
         // 2. 获取 payload 数据部分
         let payload = buf.split_to(len + 2);
-        log::debug!("Payload: {:02X?}", &payload[..]);
+        trace::debug!("Payload: {:02X?}", &payload[..]);
 
-        Ok(Some(payload.into()))
+        Ok(Some((route, payload.into())))
     }
 }
 
 impl Decoder for McClientCodec {
-    type Item = Bytes;
+    type Item = ResponseFrame;
     type Error = std::io::Error;
 
-    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>> {
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<ResponseFrame>> {
         self.decoder.decode(buf)
     }
 }
 
+impl Encoder<Bytes> for McClientCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, frame: Bytes, buf: &mut BytesMut) -> Result<()> {
+        // 已由 ClientEncoder 编码好的单个请求帧
+        buf.extend_from_slice(&frame);
+        Ok(())
+    }
+}
+
 impl Encoder<Request<'_>> for McClientCodec {
     type Error = std::io::Error;
 
@@ -156,11 +476,15 @@ impl Encoder<Request<'_>> for McClientCodec {
 
 #[cfg(feature = "server")]
 impl Decoder for ServerCodec {
-    type Item = Bytes;
+    type Item = (Route, Bytes);
     type Error = std::io::Error;
 
-    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>> {
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<(Route, Bytes)>> {
+        let (available, capacity) = (buf.len(), buf.capacity());
         if let Some(payload) = self.decoder.decode(buf)? {
+            if let Some(traffic) = &self.traffic {
+                traffic.record_received(available - buf.len(), capacity);
+            }
             Ok(Some(payload))
         } else {
             Ok(None)
@@ -172,49 +496,55 @@ impl Decoder for ServerCodec {
 impl Encoder<Response> for ServerCodec {
     type Error = std::io::Error;
 
+    /// 以本站访问路径应答
     fn encode(&mut self, item: Response, buf: &mut BytesMut) -> std::io::Result<()> {
-        let response_header = ResponseHeader::new();
-        let response_header_len = response_header.len();
+        self.encode((Route::LOCAL, item), buf)
+    }
+}
+
+#[cfg(feature = "server")]
+impl Encoder<(Route, Response)> for ServerCodec {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        (route, item): (Route, Response),
+        buf: &mut BytesMut,
+    ) -> std::io::Result<()> {
+        let response_header_len = ResponseHeader::new().len();
+        let start = buf.len();
 
         // 添加调试打印
-        log::debug!("=== ServerCodec::encode Debug ===");
-        log::debug!("Response item: {:?}", item);
-        log::debug!("Item length: {}", item.len());
+        trace::debug!("=== ServerCodec::encode Debug ===");
+        trace::debug!("Response item: {:?}", item);
+        trace::debug!("Item length: {}", item.len());
 
         buf.reserve(response_header_len + item.len() + 2);
 
-        let mut header_bytes = BytesMut::from(&response_header.0[..]);
-
         // 计算数据长度
         let data_length = match &item {
             Response::ReadU8s(_) => (item.len() * 2 + 2) as u16,
             Response::WriteU8s() => 2,
-            Response::ReadBits(values) => ((values.len() + 1) / 2 + 2) as u16,
+            Response::ReadBits(values) => (values.len().div_ceil(2) + 2) as u16,
             Response::WriteBits() => 2,
+            Response::Command(_, _, data) => (data.len() + 2) as u16,
         };
-        log::debug!("Calculated data length: {}", data_length);
-
-        LittleEndian::write_u16(
-            &mut header_bytes[response_header_len - 2..response_header_len],
-            data_length,
-        );
-
-        log::debug!("Header after length update: {:02X?}", &header_bytes[..]);
+        trace::debug!("Calculated data length: {}", data_length);
 
-        buf.put_slice(&header_bytes);
+        put_response_header(buf, route, data_length);
         buf.put_u16_le(0x0000);
 
-        log::debug!("Buffer after header + end code: {:02X?}", &buf[..]);
+        trace::debug!("Buffer after header + end code: {:02X?}", &buf[..]);
 
         match item {
             Response::ReadU8s(values) => {
-                log::debug!("Adding ReadU8s data: {:02X?}", values);
+                trace::debug!("Adding ReadU8s data: {:02X?}", values);
                 for &value in &values {
                     buf.put_u8(value);
                 }
             }
             Response::WriteU8s() => {
-                log::debug!("WriteU8s response - no additional data");
+                trace::debug!("WriteU8s response - no additional data");
             }
             Response::ReadBits(values) => {
                 let bytes = crate::codec::bools_to_bytes(&values);
@@ -223,23 +553,43 @@ impl Encoder<Response> for ServerCodec {
                 }
             }
             Response::WriteBits() => {
-                log::debug!("WriteBits response - no additional data");
+                trace::debug!("WriteBits response - no additional data");
             }
+            Response::Command(_, _, data) => buf.put_slice(&data),
         }
 
-        log::debug!("Final encoded buffer: {:02X?}", &buf[..]);
-        log::debug!("Final buffer length: {}", buf.len());
-        log::debug!("================================");
+        trace::debug!("Final encoded buffer: {:02X?}", &buf[..]);
+        trace::debug!("Final buffer length: {}", buf.len());
+        trace::debug!("================================");
 
+        self.record_sent(buf.len() - start);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+impl Encoder<ErrorResponse> for ServerCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: ErrorResponse, buf: &mut BytesMut) -> std::io::Result<()> {
+        // 结束代码之后是应答站的访问路径（与帧头相同）以及出错请求的指令与子指令
+        buf.reserve(ResponseHeader::new().len() + 11);
+        let start = buf.len();
+        put_response_header(buf, item.route, 11);
+        buf.put_u16_le(item.end_code);
+        put_route(buf, item.route);
+        buf.put_slice(&item.function.value());
+        self.record_sent(buf.len() - start);
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "server")]
     use super::*;
     #[cfg(feature = "server")]
-    use crate::{frame::Response, header::RequestHeader};
+    use crate::frame::Response;
     #[cfg(feature = "server")]
     use bytes::{Buf, BytesMut};
 
@@ -307,7 +657,7 @@ mod tests {
 
         // 验证数据长度计算是否正确
         let data_length = LittleEndian::read_u16(&header_bytes[7..9]);
-        let expected_length = ((bits.len() + 1) / 2 + 2) as u16; // bit数据 + 结束码
+        let expected_length = (bits.len().div_ceil(2) + 2) as u16; // bit数据 + 结束码
         assert_eq!(data_length, expected_length);
 
         // 验证结束代码 (0x0000)
@@ -387,7 +737,7 @@ mod tests {
 
         // 验证数据长度 - 奇数长度应该向上取整
         let data_length = LittleEndian::read_u16(&header_bytes[7..9]);
-        let expected_length = ((bits.len() + 1) / 2 + 2) as u16; // (3+1)/2 + 2 = 4
+        let expected_length = (bits.len().div_ceil(2) + 2) as u16; // ceil(3/2) + 2 = 4
         assert_eq!(data_length, expected_length);
 
         // 跳过结束代码
@@ -447,7 +797,7 @@ mod tests {
 
         // 验证数据长度
         let data_length = LittleEndian::read_u16(&header_bytes[7..9]);
-        let expected_length = ((bits.len() + 1) / 2 + 2) as u16;
+        let expected_length = (bits.len().div_ceil(2) + 2) as u16;
         assert_eq!(data_length, expected_length);
 
         // 跳过结束代码
@@ -503,9 +853,7 @@ mod tests {
         buffer.extend_from_slice(&bytes);
 
         // 创建ServerCodec实例
-        let mut codec = ServerCodec {
-            decoder: McServerDecoder {},
-        };
+        let mut codec = ServerCodec::default();
 
         // 调用decode方法
         let result = codec.decode(&mut buffer);
@@ -539,13 +887,13 @@ mod tests {
         assert_eq!(test_byte, 0b10000100);
 
         // 测试读取位值
-        let bit_0 = (test_byte >> 0) & 0x01 != 0;
+        let bit_0 = test_byte & 0x01 != 0;
         let bit_2 = (test_byte >> 2) & 0x01 != 0;
         let bit_7 = (test_byte >> 7) & 0x01 != 0;
 
-        assert_eq!(bit_0, false); // 第0位已清除
-        assert_eq!(bit_2, true); // 第2位为1
-        assert_eq!(bit_7, true); // 第7位为1
+        assert!(!bit_0); // 第0位已清除
+        assert!(bit_2); // 第2位为1
+        assert!(bit_7); // 第7位为1
     }
 
     #[test]
@@ -578,7 +926,7 @@ mod tests {
         // 验证编码
         let header_bytes = buf.split_to(9);
         let data_length = LittleEndian::read_u16(&header_bytes[7..9]);
-        let expected_length = ((simulated_bits.len() + 1) / 2 + 2) as u16;
+        let expected_length = (simulated_bits.len().div_ceil(2) + 2) as u16;
         assert_eq!(data_length, expected_length);
 
         // 跳过结束代码
@@ -645,22 +993,10 @@ mod tests {
         assert_eq!(result_bits, expected_bits);
 
         // 特别验证第0位应该是 true (因为11的LSB是1)
-        assert_eq!(
-            result_bits[0], true,
-            "M100 位0应该是 true，因为值11的第0位是1"
-        );
-        assert_eq!(
-            result_bits[1], true,
-            "M100 位1应该是 true，因为值11的第1位是1"
-        );
-        assert_eq!(
-            result_bits[2], false,
-            "M100 位2应该是 false，因为值11的第2位是0"
-        );
-        assert_eq!(
-            result_bits[3], true,
-            "M100 位3应该是 true，因为值11的第3位是1"
-        );
+        assert!(result_bits[0], "M100 位0应该是 true，因为值11的第0位是1");
+        assert!(result_bits[1], "M100 位1应该是 true，因为值11的第1位是1");
+        assert!(!result_bits[2], "M100 位2应该是 false，因为值11的第2位是0");
+        assert!(result_bits[3], "M100 位3应该是 true，因为值11的第3位是1");
     }
 
     #[test]
@@ -697,7 +1033,7 @@ mod tests {
 
         let byte_value = memory[final_byte_offset]; // 0x34
         let x0_bit_value = (byte_value >> bit_in_byte) & 0x01 != 0; // (0x34 >> 0) & 0x01 = 0
-        assert_eq!(x0_bit_value, false, "X0位应该是false，因为0x34的第0位是0");
+        assert!(!x0_bit_value, "X0位应该是false，因为0x34的第0位是0");
 
         // 测试 X16 位（应该读取X10字的第0位）
         // bit_addr = 16, word_register = 1*10 = 10 (X10), bit_in_word = 0
@@ -712,7 +1048,7 @@ mod tests {
 
         let byte_value = memory[final_byte_offset]; // 0x78
         let x16_bit_value = (byte_value >> bit_in_byte) & 0x01 != 0; // (0x78 >> 0) & 0x01 = 0
-        assert_eq!(x16_bit_value, false, "X16位应该是false，因为0x78的第0位是0");
+        assert!(!x16_bit_value, "X16位应该是false，因为0x78的第0位是0");
 
         // 测试连续内存模型的u8读取
         // X1 u8读取 = X0的字节1 + X10的字节0
@@ -0,0 +1,38 @@
+//! 收发帧的改写钩子
+//!
+//! 部分第三方 SLMP 实现与规格不完全一致，例如要求保留字节为特定值、应答子头部有误。
+//! [`FrameTransform`] 在编码完成、发送之前改写请求帧，在切分出完整应答帧、解码之前改写应答帧，
+//! 现场的兼容处理不必修改编解码器：
+//!
+//! ```text
+//! #[derive(Debug)]
+//! struct FixSubheader;
+//!
+//! impl FrameTransform for FixSubheader {
+//!     fn incoming(&self, frame: &mut BytesMut) {
+//!         frame[..2].copy_from_slice(&[0xD0, 0x00]);
+//!     }
+//! }
+//!
+//! context.set_frame_transform(FixSubheader);
+//! ```
+//!
+//! 应答帧按改写前的长度字段切分，改写可以修改帧头与数据，但不能补足缺少的字节。
+//! 帧内容为所用帧格式的原始字节，ASCII 码帧即为 ASCII 字符。
+
+use std::fmt;
+
+use crate::bytes::BytesMut;
+
+/// 发送前与接收后改写原始帧，见[模块文档](self)
+pub trait FrameTransform: fmt::Debug + Send + Sync {
+    /// 改写即将发送的请求帧，默认不改写
+    fn outgoing(&self, frame: &mut BytesMut) {
+        let _ = frame;
+    }
+
+    /// 改写收到的完整应答帧，默认不改写
+    fn incoming(&self, frame: &mut BytesMut) {
+        let _ = frame;
+    }
+}
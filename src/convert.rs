@@ -0,0 +1,160 @@
+//! 字与数值之间的转换
+//!
+//! PLC 的字数据以小端字节序传输，32/64 位数值占用多个连续的字。
+//! 三菱 PLC 默认低位字在前，部分设备或程序则按高位字在前存放，
+//! 可通过 [`WordOrder`] 指定。直接使用 `call()` 或编写自定义 `Service`
+//! 时可用这些函数完成转换。
+
+use std::mem::size_of;
+
+/// 多字数值中各字的排列顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WordOrder {
+    /// 低位字在前（三菱 PLC 的默认格式）
+    #[default]
+    LowFirst,
+    /// 高位字在前
+    HighFirst,
+}
+
+/// 可由若干个字构成的数值类型
+pub trait WordValue: Copy {
+    /// 占用的字数
+    const WORDS: usize;
+
+    /// 由 `WORDS` 个字构造数值
+    fn from_words(words: &[u16], order: WordOrder) -> Self;
+
+    /// 将数值按字追加到 `out`
+    fn push_words(self, order: WordOrder, out: &mut Vec<u16>);
+}
+
+macro_rules! impl_word_value {
+    ($($ty:ty),+) => {
+        $(
+            impl WordValue for $ty {
+                const WORDS: usize = size_of::<$ty>() / 2;
+
+                fn from_words(words: &[u16], order: WordOrder) -> Self {
+                    let mut bytes = [0u8; size_of::<$ty>()];
+                    for (i, chunk) in bytes.chunks_exact_mut(2).enumerate() {
+                        let word = match order {
+                            WordOrder::LowFirst => words[i],
+                            WordOrder::HighFirst => words[Self::WORDS - 1 - i],
+                        };
+                        chunk.copy_from_slice(&word.to_le_bytes());
+                    }
+                    <$ty>::from_le_bytes(bytes)
+                }
+
+                fn push_words(self, order: WordOrder, out: &mut Vec<u16>) {
+                    let words = self
+                        .to_le_bytes()
+                        .chunks_exact(2)
+                        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                        .collect::<Vec<_>>();
+                    match order {
+                        WordOrder::LowFirst => out.extend(words),
+                        WordOrder::HighFirst => out.extend(words.into_iter().rev()),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_word_value!(u16, i16, u32, i32, f32, u64, i64, f64);
+
+/// 将字序列转换为数值序列，末尾不足一个数值的字被忽略
+pub fn words_to_values<T: WordValue>(words: &[u16], order: WordOrder) -> Vec<T> {
+    words
+        .chunks_exact(T::WORDS)
+        .map(|chunk| T::from_words(chunk, order))
+        .collect()
+}
+
+/// 将数值序列转换为字序列
+pub fn values_to_words<T: WordValue>(values: &[T], order: WordOrder) -> Vec<u16> {
+    let mut words = Vec::with_capacity(values.len() * T::WORDS);
+    for &value in values {
+        value.push_words(order, &mut words);
+    }
+    words
+}
+
+/// 将小端字节序数据转换为字序列，末尾的奇数字节被忽略
+pub fn bytes_to_words(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
+
+/// 将字序列转换为小端字节序数据，即 `Request::WriteU8s` 的数据格式
+pub fn words_to_bytes(words: &[u16]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+macro_rules! typed_conversions {
+    ($($ty:ty => $from_words:ident, $to_words:ident;)+) => {
+        $(
+            #[doc = concat!("将字序列转换为 `", stringify!($ty), "` 序列")]
+            pub fn $from_words(words: &[u16], order: WordOrder) -> Vec<$ty> {
+                words_to_values(words, order)
+            }
+
+            #[doc = concat!("将 `", stringify!($ty), "` 序列转换为字序列")]
+            pub fn $to_words(values: &[$ty], order: WordOrder) -> Vec<u16> {
+                values_to_words(values, order)
+            }
+        )+
+    };
+}
+
+typed_conversions! {
+    u32 => words_to_u32s, u32s_to_words;
+    i32 => words_to_i32s, i32s_to_words;
+    f32 => words_to_f32s, f32s_to_words;
+    u64 => words_to_u64s, u64s_to_words;
+    i64 => words_to_i64s, i64s_to_words;
+    f64 => words_to_f64s, f64s_to_words;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_order_conversions() {
+        // 1.0f32 = 0x3F80_0000
+        assert_eq!(f32s_to_words(&[1.0], WordOrder::LowFirst), [0x0000, 0x3F80]);
+        assert_eq!(
+            f32s_to_words(&[1.0], WordOrder::HighFirst),
+            [0x3F80, 0x0000]
+        );
+        assert_eq!(
+            words_to_f32s(&[0x3F80, 0x0000, 0x4000], WordOrder::HighFirst),
+            [1.0]
+        );
+
+        let words = [0x0004, 0x0003, 0x0002, 0x0001];
+        assert_eq!(
+            words_to_u64s(&words, WordOrder::HighFirst),
+            [0x0004_0003_0002_0001]
+        );
+        assert_eq!(
+            words_to_u64s(&words, WordOrder::LowFirst),
+            [0x0001_0002_0003_0004]
+        );
+        for order in [WordOrder::LowFirst, WordOrder::HighFirst] {
+            let values = [-2i32, i32::MAX];
+            assert_eq!(words_to_i32s(&i32s_to_words(&values, order), order), values);
+        }
+    }
+
+    #[test]
+    fn bytes_and_words() {
+        assert_eq!(bytes_to_words(&[0x34, 0x12, 0xFF]), [0x1234]);
+        assert_eq!(words_to_bytes(&[0x1234, 0xABCD]), [0x34, 0x12, 0xCD, 0xAB]);
+    }
+}
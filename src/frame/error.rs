@@ -1,5 +1,9 @@
+use std::fmt;
+
 use thiserror::Error;
 
+use super::Route;
+
 #[derive(Error, Debug)]
 pub enum ProtocolError {
     #[error("The number of points to read or write is out of the allowed range.")]
@@ -13,6 +17,120 @@ pub enum ProtocolError {
 
     #[error("This functionality is not yet implemented.")]
     NotImplemented,
+
+    #[error("{0}")]
+    EndCode(EndCode),
+
+    #[error("Invalid PLC profile: {0}")]
+    InvalidProfile(String),
+
+    #[error("Odd byte count {0} cannot be written as whole words")]
+    OddByteCount(usize),
+
+    #[error("Data length mismatch: expected {expected} bytes, got {actual}")]
+    LengthMismatch { expected: usize, actual: usize },
+
+    #[error("Invalid connection URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Access denied by client policy: {0}")]
+    AccessDenied(String),
+
+    #[error("Sum check mismatch: calculated {expected}, frame carries {received}")]
+    SumCheck { expected: String, received: String },
+}
+
+/// PLC 返回的非零结束代码，以及应答站的路由信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndCode {
+    code: u16,
+    network_no: u8,
+    pc_no: u8,
+    dest_io: u16,
+    dest_station: u8,
+}
+
+impl EndCode {
+    pub const fn new(code: u16, network_no: u8, pc_no: u8, dest_io: u16, dest_station: u8) -> Self {
+        Self {
+            code,
+            network_no,
+            pc_no,
+            dest_io,
+            dest_station,
+        }
+    }
+
+    /// 原始结束代码，可直接对照厂商手册
+    pub const fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// 应答站的网络编号
+    pub const fn network_no(&self) -> u8 {
+        self.network_no
+    }
+
+    /// 应答站的 PLC 编号
+    pub const fn pc_no(&self) -> u8 {
+        self.pc_no
+    }
+
+    /// 应答站的请求目标模块 I/O 编号
+    pub const fn dest_io(&self) -> u16 {
+        self.dest_io
+    }
+
+    /// 应答站的请求目标模块站号
+    pub const fn dest_station(&self) -> u8 {
+        self.dest_station
+    }
+
+    /// 应答站的访问路径
+    pub const fn route(&self) -> Route {
+        Route {
+            network_no: self.network_no,
+            pc_no: self.pc_no,
+            dest_io: self.dest_io,
+            dest_station: self.dest_station,
+        }
+    }
+
+    /// 结束代码的说明，未收录的代码返回通用说明
+    pub fn description(&self) -> &'static str {
+        match self.code {
+            0xC050 => "ASCII data that cannot be converted to binary was received",
+            0xC051..=0xC054 => "The number of points to read or write is out of range",
+            0xC056 => "The request exceeds the maximum device address",
+            0xC058 => "The request data length does not match the character count",
+            0xC059 => "The command or subcommand is not supported",
+            0xC05B => "The CPU cannot read or write the specified device",
+            0xC05C => "The request content is invalid",
+            0xC05F => "The request cannot be executed on the target CPU",
+            0xC060 => "The request content is invalid for the specified device",
+            0xC061 => "The request data length does not match the number of points",
+            0xC06F => "The communication data code does not match the setting",
+            0xC0D8 => "The number of monitor-registered blocks exceeds the limit",
+            0xCEE0..=0xCEFF => "An error occurred on the relay network",
+            0x4000..=0x4FFF => "An error was detected by the CPU module",
+            _ => "Unknown end code",
+        }
+    }
+}
+
+impl fmt::Display for EndCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PLC returned end code 0x{:04X} ({}) from network {:02X}, PC {:02X}, module {:04X}, station {:02X}",
+            self.code,
+            self.description(),
+            self.network_no,
+            self.pc_no,
+            self.dest_io,
+            self.dest_station
+        )
+    }
 }
 
 pub fn map_error_code(error_code: u16) -> Option<ProtocolError> {
@@ -22,3 +140,24 @@ pub fn map_error_code(error_code: u16) -> Option<ProtocolError> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_code_accessors_and_display() {
+        let end_code = EndCode::new(0xC059, 0x00, 0xFF, 0x03FF, 0x00);
+        assert_eq!(end_code.code(), 0xC059);
+        assert_eq!(end_code.pc_no(), 0xFF);
+        assert_eq!(end_code.dest_io(), 0x03FF);
+        assert_eq!(
+            end_code.to_string(),
+            "PLC returned end code 0xC059 (The command or subcommand is not supported) \
+             from network 00, PC FF, module 03FF, station 00"
+        );
+
+        let error = ProtocolError::EndCode(EndCode::new(0x1234, 0x01, 0x02, 0x03E0, 0x05));
+        assert!(error.to_string().contains("0x1234 (Unknown end code)"));
+    }
+}
@@ -8,12 +8,21 @@ const KV_INSTRUCTIONS: &[(&str, &str, DataOProcess)] = &[
     ("DM", "D", DataOProcess::None),
     ("FM", "R", DataOProcess::None),
     ("B", "B", DataOProcess::None),
+    ("W", "W", DataOProcess::None),
     ("ZF", "ZR", DataOProcess::DecimalToHex),
     // XYM markers
     ("M", "M", DataOProcess::None),
     ("D", "D", DataOProcess::None),
     ("F", "R", DataOProcess::None),
     ("L", "L", DataOProcess::None),
+    // Timers and counters: a bare T/C is the current value, S/C suffixes
+    // select the contact and coil
+    ("T", "TN", DataOProcess::None),
+    ("TS", "TS", DataOProcess::None),
+    ("TC", "TC", DataOProcess::None),
+    ("C", "CN", DataOProcess::None),
+    ("CS", "CS", DataOProcess::None),
+    ("CC", "CC", DataOProcess::None),
     // Special
     ("X", "X", DataOProcess::XYToHex),
     ("Y", "Y", DataOProcess::XYToHex),
@@ -42,6 +51,34 @@ pub fn find(prefix: &str) -> Option<(&'static str, DataOProcess)> {
     None
 }
 
+// 三菱地址转回基恩士写法时使用的前缀，与上表互逆；
+// 有多种写法时优先 KV 原生写法，Y 没有对应的 R 写法，沿用 XYM 写法
+const MC_INSTRUCTIONS: &[(&str, &str, DataOProcess)] = &[
+    ("X", "R", DataOProcess::Hex),
+    ("Y", "Y", DataOProcess::XYToHex),
+    ("M", "MR", DataOProcess::Decimal),
+    ("L", "LR", DataOProcess::Decimal),
+    ("D", "DM", DataOProcess::None),
+    ("R", "FM", DataOProcess::None),
+    ("B", "B", DataOProcess::None),
+    ("W", "W", DataOProcess::None),
+    ("ZR", "ZF", DataOProcess::DecimalToHex),
+    ("TN", "T", DataOProcess::None),
+    ("TS", "TS", DataOProcess::None),
+    ("TC", "TC", DataOProcess::None),
+    ("CN", "C", DataOProcess::None),
+    ("CS", "CS", DataOProcess::None),
+    ("CC", "CC", DataOProcess::None),
+];
+
+/// 按三菱前缀查找基恩士前缀及正向转换时的处理方式
+pub fn find_mitsubishi(prefix: &str) -> Option<(&'static str, DataOProcess)> {
+    MC_INSTRUCTIONS
+        .iter()
+        .find(|&&(key, _, _)| key == prefix)
+        .map(|&(_, value, process)| (value, process))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,6 +90,8 @@ mod tests {
         assert_eq!(find("D"), Some(("D", DataOProcess::None)));
         assert_eq!(find("X"), Some(("X", DataOProcess::XYToHex)));
         assert_eq!(find("Y"), Some(("Y", DataOProcess::XYToHex)));
+        assert_eq!(find("T"), Some(("TN", DataOProcess::None)));
+        assert_eq!(find("C"), Some(("CN", DataOProcess::None)));
     }
 
     #[test]
@@ -62,6 +101,17 @@ mod tests {
         assert_eq!(find("DM"), Some(("D", DataOProcess::None)));
         assert_eq!(find("FM"), Some(("R", DataOProcess::None)));
         assert_eq!(find("ZF"), Some(("ZR", DataOProcess::DecimalToHex)));
+        assert_eq!(find("TS"), Some(("TS", DataOProcess::None)));
+        assert_eq!(find("CC"), Some(("CC", DataOProcess::None)));
+    }
+
+    #[test]
+    fn test_find_mitsubishi_inverts_find() {
+        for &(mitsubishi, keyence, process) in MC_INSTRUCTIONS {
+            assert_eq!(find_mitsubishi(mitsubishi), Some((keyence, process)));
+            assert_eq!(find(keyence), Some((mitsubishi, process)));
+        }
+        assert_eq!(find_mitsubishi("SW"), None);
     }
 
     #[test]
@@ -1,6 +1,6 @@
 use convert::convert_xy_number;
 pub use error::KVError;
-use map::find;
+use map::{find, find_mitsubishi};
 use regex::split_address;
 use types::DataOProcess;
 
@@ -57,6 +57,39 @@ pub fn convert_keyence_to_mitsubishi_address(address: &str) -> Result<String, KV
     }
 }
 
+/// 将三菱地址转换为基恩士地址，是 [`convert_keyence_to_mitsubishi_address`] 的逆转换
+///
+/// 同一软元件有多种基恩士写法时优先 KV 原生写法（`D100` → `DM100`、`X1A` → `R110`），
+/// 输出 `Y` 沿用 XYM 写法。
+pub fn convert_mitsubishi_to_keyence_address(address: &str) -> Result<String, KVError> {
+    let (prefix, number) = super::split_address(address).ok_or(KVError::PaseError)?;
+    let (instruction, process) = find_mitsubishi(prefix).ok_or(KVError::MapNotFound)?;
+    let parse = |radix| u32::from_str_radix(number, radix).map_err(|_| KVError::ParseNumberError);
+
+    Ok(match process {
+        // 每 16 点为一个通道，基恩士写作通道号 × 100 + 点号
+        DataOProcess::Hex => {
+            let value = parse(16)?;
+            format!("{instruction}{}", value / 16 * 100 + value % 16)
+        }
+        DataOProcess::Decimal => {
+            let value = parse(10)?;
+            format!("{instruction}{}", value / 16 * 100 + value % 16)
+        }
+        DataOProcess::DecimalToHex => format!("{instruction}{}", parse(16)?),
+        // XYM 写法以十进制通道号加一位十六进制点号表示
+        DataOProcess::XYToHex => {
+            let value = parse(16)?;
+            if value < 16 {
+                format!("{instruction}{value:X}")
+            } else {
+                format!("{instruction}{}{:X}", value / 16, value % 16)
+            }
+        }
+        DataOProcess::None => format!("{instruction}{number}"),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*; // 引入当前模块的所有项，假设 `convert_keyence_to_mitsubishi_address` 在当前模块内
@@ -74,4 +107,57 @@ mod tests {
         // 你可以根据需要添加断言
         assert!(result.is_ok()); // 只是一个示例，实际断言内容要根据函数的预期行为来定
     }
+
+    #[test]
+    fn test_convert_mitsubishi_to_keyence_round_trips() {
+        for (mitsubishi, keyence) in [
+            ("X1A", "R110"),
+            ("X5", "R5"),
+            ("Y14F", "Y20F"),
+            ("YA", "YA"),
+            ("M20", "MR104"),
+            ("L3", "LR3"),
+            ("D100", "DM100"),
+            ("R10", "FM10"),
+            ("ZR1F", "ZF31"),
+            ("B1F", "B1F"),
+            ("W1F", "W1F"),
+            ("TN10", "T10"),
+            ("CC5", "CC5"),
+        ] {
+            assert_eq!(
+                convert_mitsubishi_to_keyence_address(mitsubishi).unwrap(),
+                keyence
+            );
+            assert_eq!(
+                convert_keyence_to_mitsubishi_address(keyence).unwrap(),
+                mitsubishi
+            );
+        }
+        assert_eq!(
+            convert_mitsubishi_to_keyence_address("SW10"),
+            Err(KVError::MapNotFound)
+        );
+        assert_eq!(
+            convert_mitsubishi_to_keyence_address("M1F"),
+            Err(KVError::ParseNumberError)
+        );
+    }
+
+    #[test]
+    fn test_convert_timers_and_counters() {
+        for (keyence, mitsubishi) in [
+            ("T10", "TN10"),
+            ("TS10", "TS10"),
+            ("TC10", "TC10"),
+            ("C5", "CN5"),
+            ("CS5", "CS5"),
+            ("CC5", "CC5"),
+        ] {
+            assert_eq!(
+                convert_keyence_to_mitsubishi_address(keyence).unwrap(),
+                mitsubishi
+            );
+        }
+    }
 }
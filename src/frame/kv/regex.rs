@@ -9,7 +9,7 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
     let bytes = address.as_bytes();
 
     // 优化：处理双字符前缀的特殊情况
-    let prefix_len = match (bytes.get(0), bytes.get(1), bytes.get(2)) {
+    let prefix_len = match (bytes.first(), bytes.get(1), bytes.get(2)) {
         // 双字符前缀检查
         (Some(&b'D'), Some(&b'M'), Some(third))
             if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
@@ -51,6 +51,26 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
         {
             2
         }
+        (Some(&b'T'), Some(&b'C'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
+        (Some(&b'T'), Some(&b'S'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
+        (Some(&b'C'), Some(&b'C'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
+        (Some(&b'C'), Some(&b'S'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
         // 单字符前缀检查
         (Some(&b'R'), Some(second), _)
             if second.is_ascii_digit() || second.is_ascii_alphanumeric() =>
@@ -72,6 +92,7 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
         {
             1
         }
+        (Some(&b'W'), Some(second), _) if second.is_ascii_alphanumeric() => 1,
         (Some(&b'T'), Some(second), _)
             if second.is_ascii_digit() || second.is_ascii_alphanumeric() =>
         {
@@ -143,6 +164,9 @@ mod tests {
         assert_eq!(split_address("CM70"), Some(("CM", "70")));
         assert_eq!(split_address("EM80"), Some(("EM", "80")));
         assert_eq!(split_address("ZF90"), Some(("ZF", "90")));
+        assert_eq!(split_address("TC10"), Some(("TC", "10")));
+        assert_eq!(split_address("CS5"), Some(("CS", "5")));
+        assert_eq!(split_address("T10"), Some(("T", "10")));
 
         // 测试带小数点的地址
         assert_eq!(split_address("R100.01"), Some(("R", "100.01")));
@@ -1,7 +1,7 @@
 use super::NumberBase;
 
 // 优化：使用静态数组代替HashMap，提高查找性能
-const PLC_INSTRUCTIONS: &[(&str, u8, NumberBase)] = &[
+pub(crate) const PLC_INSTRUCTIONS: &[(&str, u8, NumberBase)] = &[
     ("X", 0x9c, NumberBase::Hexadecimal),
     ("Y", 0x9d, NumberBase::Hexadecimal),
     ("F", 0x93, NumberBase::Decimal),
@@ -14,12 +14,41 @@ const PLC_INSTRUCTIONS: &[(&str, u8, NumberBase)] = &[
     ("SD", 0xA9, NumberBase::Decimal),     // 特殊存储器
     ("ZR", 0xB0, NumberBase::Hexadecimal), // 文件寄存器
     ("W", 0xB4, NumberBase::Hexadecimal),  // 链接寄存器
+    ("SB", 0xA1, NumberBase::Hexadecimal), // 链接特殊继电器
+    ("SW", 0xB5, NumberBase::Hexadecimal), // 链接特殊寄存器
     ("TN", 0xC2, NumberBase::Decimal),     // 定时器当前值
     ("TS", 0xC1, NumberBase::Decimal),     // 定时器接点
+    ("TC", 0xC0, NumberBase::Decimal),     // 定时器线圈
     ("CN", 0xC5, NumberBase::Decimal),     // 计数器当前值
     ("CS", 0xC4, NumberBase::Decimal),     // 计数器接点
+    ("CC", 0xC3, NumberBase::Decimal),     // 计数器线圈
 ];
 
+/// 软元件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// 位软元件，可按位访问，也可按字访问（每个字 16 点）
+    Bit,
+    /// 字软元件，只能按字访问
+    Word,
+}
+
+/// 内置软元件中的位软元件
+const BIT_DEVICES: &[&str] = &[
+    "X", "Y", "F", "M", "L", "B", "SB", "SM", "TS", "TC", "CS", "CC",
+];
+
+/// 内置软元件前缀的类别，未知前缀返回 `None`
+pub fn device_kind(prefix: &str) -> Option<DeviceKind> {
+    if BIT_DEVICES.contains(&prefix) {
+        Some(DeviceKind::Bit)
+    } else if find_instruction_code(prefix).is_some() {
+        Some(DeviceKind::Word)
+    } else {
+        None
+    }
+}
+
 // 优化的查找函数，使用线性搜索（对于小数组更快）
 #[inline]
 pub fn find_instruction_code(prefix: &str) -> Option<(u8, NumberBase)> {
@@ -96,9 +125,29 @@ mod tests {
             find_instruction_code("ZR"),
             Some((0xB0, NumberBase::Hexadecimal))
         );
+        assert_eq!(
+            find_instruction_code("SB"),
+            Some((0xA1, NumberBase::Hexadecimal))
+        );
+        assert_eq!(
+            find_instruction_code("SW"),
+            Some((0xB5, NumberBase::Hexadecimal))
+        );
         assert_eq!(find_instruction_code("INVALID"), None);
     }
 
+    #[test]
+    fn test_device_kind() {
+        assert_eq!(device_kind("M"), Some(DeviceKind::Bit));
+        assert_eq!(device_kind("TS"), Some(DeviceKind::Bit));
+        assert_eq!(device_kind("CC"), Some(DeviceKind::Bit));
+        assert_eq!(device_kind("SB"), Some(DeviceKind::Bit));
+        assert_eq!(device_kind("SW"), Some(DeviceKind::Word));
+        assert_eq!(device_kind("D"), Some(DeviceKind::Word));
+        assert_eq!(device_kind("TN"), Some(DeviceKind::Word));
+        assert_eq!(device_kind("INVALID"), None);
+    }
+
     #[test]
     fn test_convert_to_base() {
         // 十进制测试
@@ -10,15 +10,24 @@ use crate::bytes::BytesMut;
 mod error;
 mod kv;
 mod map;
+mod profile;
 mod regex;
 mod types;
+mod validation;
 
-pub use error::{map_error_code, ProtocolError};
+pub use error::{map_error_code, EndCode, ProtocolError};
 
-pub use map::{convert_to_base, find_instruction_code, find_prefix_and_base_by_code};
+#[cfg(any(feature = "client", feature = "test-util"))]
+pub(crate) use map::PLC_INSTRUCTIONS;
+pub use map::{
+    convert_to_base, device_kind, find_instruction_code, find_prefix_and_base_by_code, DeviceKind,
+};
+pub use profile::{AddressTranslator, DeviceSpec, PlcProfile, ProfileConfig};
 pub use regex::split_address;
+pub(crate) use validation::off_spec;
+pub use validation::{set_validation_mode, validation_mode, ValidationMode};
 
-pub use kv::convert_keyence_to_mitsubishi_address;
+pub use kv::{convert_keyence_to_mitsubishi_address, convert_mitsubishi_to_keyence_address};
 
 pub use kv::KVError;
 
@@ -28,6 +37,8 @@ pub enum FunctionCode {
     WriteU8s,
     ReadBits,
     WriteBits,
+    /// 其他命令：指令代码与子指令代码
+    Command(u16, u16),
 }
 
 impl FunctionCode {
@@ -61,6 +72,10 @@ impl FunctionCode {
             FunctionCode::WriteBits => {
                 buf.extend_from_slice(&[0x01, 0x14, 0x01, 0x00]);
             }
+            FunctionCode::Command(command, subcommand) => {
+                buf.extend_from_slice(&command.to_le_bytes());
+                buf.extend_from_slice(&subcommand.to_le_bytes());
+            }
         }
         buf
     }
@@ -75,11 +90,14 @@ impl Display for FunctionCode {
 
 // 请求的枚举，类似你给出的Modbus请求设计
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Request<'a> {
     ReadU8s(Cow<'a, str>, Quantity),
     WriteU8s(Cow<'a, str>, Cow<'a, [u8]>),
     ReadBits(Cow<'a, str>, Quantity),
     WriteBits(Cow<'a, str>, Cow<'a, [bool]>),
+    /// 软元件批量读写以外的命令：指令代码、子指令代码与请求数据
+    Command(u16, u16, Cow<'a, [u8]>),
 }
 
 // 实现辅助功能，比如将请求转换为'owned'版本或获取功能码
@@ -97,6 +115,19 @@ impl<'a> Request<'a> {
             WriteBits(addr, bits) => {
                 WriteBits(Cow::Owned(addr.into_owned()), Cow::Owned(bits.into_owned()))
             }
+            Command(command, subcommand, data) => {
+                Command(command, subcommand, Cow::Owned(data.into_owned()))
+            }
+        }
+    }
+
+    /// 请求的起始软元件地址，`Command` 没有地址时为空字符串
+    #[must_use]
+    pub fn address(&self) -> &str {
+        use Request::*;
+        match self {
+            ReadU8s(addr, _) | WriteU8s(addr, _) | ReadBits(addr, _) | WriteBits(addr, _) => addr,
+            Command(_, _, _) => "",
         }
     }
 
@@ -108,16 +139,20 @@ impl<'a> Request<'a> {
             WriteU8s(_, _) => FunctionCode::WriteU8s,
             ReadBits(_, _) => FunctionCode::ReadBits,
             WriteBits(_, _) => FunctionCode::WriteBits,
+            Command(command, subcommand, _) => FunctionCode::Command(*command, *subcommand),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Response {
     ReadU8s(Vec<u8>),
     WriteU8s(),
     ReadBits(Vec<bool>),
     WriteBits(),
+    /// `Request::Command` 的响应：指令代码、子指令代码与结束代码之后的数据
+    Command(u16, u16, Vec<u8>),
 }
 
 pub struct ResponseIterator {
@@ -156,6 +191,7 @@ impl Response {
             WriteU8s() => FunctionCode::WriteU8s,
             ReadBits(_) => FunctionCode::ReadBits,
             WriteBits() => FunctionCode::WriteBits,
+            Command(command, subcommand, _) => FunctionCode::Command(*command, *subcommand),
         }
     }
 
@@ -166,8 +202,13 @@ impl Response {
             Response::WriteU8s() => 0,
             Response::ReadBits(values) => values.len(),
             Response::WriteBits() => 0,
+            Response::Command(_, _, data) => data.len(),
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,318 @@
+//! PLC 系列参数
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use super::{convert_to_base, map::PLC_INSTRUCTIONS, NumberBase, ProtocolError, Quantity, LIMIT};
+
+/// 软元件定义：前缀、软元件代码与地址进制
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceSpec {
+    pub prefix: Cow<'static, str>,
+    pub code: u8,
+    pub number_base: NumberBase,
+}
+
+impl DeviceSpec {
+    pub const fn new(prefix: &'static str, code: u8, number_base: NumberBase) -> Self {
+        Self {
+            prefix: Cow::Borrowed(prefix),
+            code,
+            number_base,
+        }
+    }
+
+    /// 运行时构造的软元件定义
+    pub fn owned(prefix: impl Into<String>, code: u8, number_base: NumberBase) -> Self {
+        Self {
+            prefix: Cow::Owned(prefix.into()),
+            code,
+            number_base,
+        }
+    }
+}
+
+type TranslateFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// 地址转换函数，在编码前将设备自有的地址写法转换为 MC 地址
+#[derive(Clone)]
+pub struct AddressTranslator(Arc<TranslateFn>);
+
+impl AddressTranslator {
+    pub fn new(translate: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(translate))
+    }
+
+    pub fn translate(&self, address: &str) -> Option<String> {
+        (self.0)(address)
+    }
+}
+
+impl fmt::Debug for AddressTranslator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AddressTranslator")
+    }
+}
+
+impl PartialEq for AddressTranslator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+macro_rules! devices {
+    ($($prefix:literal),+ $(,)?) => {
+        &[$(device($prefix)),+]
+    };
+}
+
+/// 从内置软元件表中取出指定前缀的定义
+const fn device(prefix: &'static str) -> DeviceSpec {
+    let mut i = 0;
+    while i < PLC_INSTRUCTIONS.len() {
+        let (p, code, number_base) = PLC_INSTRUCTIONS[i];
+        if const_str_eq(p, prefix) {
+            return DeviceSpec::new(p, code, number_base);
+        }
+        i += 1;
+    }
+    panic!("unknown device prefix");
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const ALL_DEVICES: &[DeviceSpec] = devices![
+    "X", "Y", "F", "M", "L", "D", "R", "B", "SM", "SD", "ZR", "W", "SB", "SW", "TN", "TS", "TC",
+    "CN", "CS", "CC",
+];
+
+/// FX5 不支持文件寄存器 ZR
+const FX_DEVICES: &[DeviceSpec] = devices![
+    "X", "Y", "F", "M", "L", "D", "R", "B", "SM", "SD", "W", "SB", "SW", "TN", "TS", "TC", "CN",
+    "CS", "CC",
+];
+
+/// PLC 系列参数
+///
+/// 决定单条命令的最大点数、可用的软元件和支持的子指令，
+/// 编码器据此拆分请求并在发送前校验地址。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlcProfile {
+    pub name: Cow<'static, str>,
+    /// 字单位批量读写的最大点数
+    pub max_word_points: Quantity,
+    /// 位单位批量读写的最大点数
+    pub max_bit_points: Quantity,
+    /// 可用的软元件
+    pub devices: Cow<'static, [DeviceSpec]>,
+    /// 支持的子指令
+    pub subcommands: Cow<'static, [u16]>,
+    /// 可选的地址转换
+    pub translator: Option<AddressTranslator>,
+}
+
+/// 自定义系列参数的配置
+///
+/// 默认值与 `PlcProfile::GENERIC` 相同，可在此基础上修改后调用 `PlcProfile::custom`。
+#[derive(Debug, Clone)]
+pub struct ProfileConfig {
+    pub name: String,
+    pub max_word_points: Quantity,
+    pub max_bit_points: Quantity,
+    pub devices: Vec<DeviceSpec>,
+    pub subcommands: Vec<u16>,
+    pub translator: Option<AddressTranslator>,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        let generic = PlcProfile::GENERIC;
+        Self {
+            name: generic.name.into_owned(),
+            max_word_points: generic.max_word_points,
+            max_bit_points: generic.max_bit_points,
+            devices: generic.devices.into_owned(),
+            subcommands: generic.subcommands.into_owned(),
+            translator: None,
+        }
+    }
+}
+
+impl PlcProfile {
+    /// 通用参数，与早期版本的固定限制一致
+    pub const GENERIC: Self = Self {
+        name: Cow::Borrowed("Generic"),
+        max_word_points: LIMIT,
+        max_bit_points: LIMIT,
+        devices: Cow::Borrowed(ALL_DEVICES),
+        subcommands: Cow::Borrowed(&[0x0000, 0x0001]),
+        translator: None,
+    };
+
+    /// Q/L 系列
+    pub const Q_SERIES: Self = Self {
+        name: Cow::Borrowed("Q Series"),
+        max_word_points: 960,
+        max_bit_points: 7168,
+        devices: Cow::Borrowed(ALL_DEVICES),
+        subcommands: Cow::Borrowed(&[0x0000, 0x0001]),
+        translator: None,
+    };
+
+    /// iQ-R 系列，额外支持扩展软元件子指令 0002/0003
+    pub const IQ_R: Self = Self {
+        name: Cow::Borrowed("iQ-R"),
+        max_word_points: 960,
+        max_bit_points: 7168,
+        devices: Cow::Borrowed(ALL_DEVICES),
+        subcommands: Cow::Borrowed(&[0x0000, 0x0001, 0x0002, 0x0003]),
+        translator: None,
+    };
+
+    /// FX5 系列（SLMP）
+    pub const FX_SERIES: Self = Self {
+        name: Cow::Borrowed("FX Series"),
+        max_word_points: 960,
+        max_bit_points: 7904,
+        devices: Cow::Borrowed(FX_DEVICES),
+        subcommands: Cow::Borrowed(&[0x0000, 0x0001]),
+        translator: None,
+    };
+
+    /// 由配置构造自定义系列参数，并校验设备表与点数限制
+    pub fn custom(config: ProfileConfig) -> Result<Self, ProtocolError> {
+        let invalid = |reason: String| Err(ProtocolError::InvalidProfile(reason));
+
+        for limit in [config.max_word_points, config.max_bit_points] {
+            if limit == 0 || limit > Quantity::from(u16::MAX) {
+                return invalid(format!("point limit {limit} is out of range"));
+            }
+        }
+        if config.devices.is_empty() {
+            return invalid("device table is empty".to_string());
+        }
+        for (i, device) in config.devices.iter().enumerate() {
+            if device.prefix.is_empty() || !device.prefix.bytes().all(|b| b.is_ascii_uppercase()) {
+                return invalid(format!("invalid device prefix {:?}", device.prefix));
+            }
+            if config.devices[..i]
+                .iter()
+                .any(|d| d.prefix == device.prefix)
+            {
+                return invalid(format!("duplicate device prefix {:?}", device.prefix));
+            }
+        }
+
+        Ok(Self {
+            name: config.name.into(),
+            max_word_points: config.max_word_points,
+            max_bit_points: config.max_bit_points,
+            devices: config.devices.into(),
+            subcommands: config.subcommands.into(),
+            translator: config.translator,
+        })
+    }
+
+    /// 经地址转换（若有）后的地址
+    pub fn translate<'a>(&self, address: &'a str) -> Option<Cow<'a, str>> {
+        match &self.translator {
+            Some(translator) => translator.translate(address).map(Cow::Owned),
+            None => Some(Cow::Borrowed(address)),
+        }
+    }
+
+    /// 解析地址，返回软元件定义与起始编号；软元件不可用时返回 `None`
+    pub fn parse_address<'a>(&'a self, address: &str) -> Option<(&'a DeviceSpec, u32)> {
+        self.devices
+            .iter()
+            .filter(|device| address.len() > device.prefix.len())
+            .filter(|device| address.starts_with(device.prefix.as_ref()))
+            // 最长前缀优先，如 SM 优先于 S
+            .max_by_key(|device| device.prefix.len())
+            .and_then(|device| {
+                convert_to_base(&address[device.prefix.len()..], device.number_base)
+                    .map(|number| (device, number))
+            })
+    }
+
+    pub fn supports_subcommand(&self, subcommand: u16) -> bool {
+        self.subcommands.contains(&subcommand)
+    }
+}
+
+impl Default for PlcProfile {
+    fn default() -> Self {
+        Self::GENERIC
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_address_uses_profile_devices() {
+        let profile = PlcProfile::Q_SERIES;
+        let (device, number) = profile.parse_address("SM400").unwrap();
+        assert_eq!(
+            (device.prefix.as_ref(), device.code, number),
+            ("SM", 0x91, 400)
+        );
+        let (device, number) = profile.parse_address("X1F").unwrap();
+        assert_eq!((device.code, number), (0x9C, 0x1F));
+        assert!(profile.parse_address("ZR10").is_some());
+        assert!(profile.parse_address("D").is_none());
+        assert!(profile.parse_address("D1A").is_none());
+
+        // FX5 没有 ZR
+        assert!(PlcProfile::FX_SERIES.parse_address("ZR10").is_none());
+        assert!(PlcProfile::IQ_R.supports_subcommand(0x0002));
+        assert!(!PlcProfile::Q_SERIES.supports_subcommand(0x0002));
+    }
+
+    #[test]
+    fn custom_profile_from_config() {
+        // 仅有 D 与自定义 P 软元件，地址以小写书写的设备
+        let profile = PlcProfile::custom(ProfileConfig {
+            name: "OEM".to_string(),
+            max_word_points: 64,
+            devices: vec![
+                DeviceSpec::new("D", 0xA8, NumberBase::Decimal),
+                DeviceSpec::owned("P", 0xF0, NumberBase::Hexadecimal),
+            ],
+            translator: Some(AddressTranslator::new(|address| {
+                Some(address.to_ascii_uppercase())
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let address = profile.translate("p1f").unwrap();
+        let (device, number) = profile.parse_address(&address).unwrap();
+        assert_eq!((device.code, number), (0xF0, 0x1F));
+        assert!(profile.parse_address("M0").is_none());
+
+        let duplicate = ProfileConfig {
+            devices: vec![DeviceSpec::new("D", 0xA8, NumberBase::Decimal); 2],
+            ..Default::default()
+        };
+        assert!(PlcProfile::custom(duplicate).is_err());
+        let zero_limit = ProfileConfig {
+            max_bit_points: 0,
+            ..Default::default()
+        };
+        assert!(PlcProfile::custom(zero_limit).is_err());
+    }
+}
@@ -9,7 +9,7 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
     let bytes = address.as_bytes();
 
     // 优化：处理双字符前缀的特殊情况
-    let prefix_len = match (bytes.get(0), bytes.get(1), bytes.get(2)) {
+    let prefix_len = match (bytes.first(), bytes.get(1), bytes.get(2)) {
         // 双字符前缀检查（必须先检查，否则会被单字符匹配）
         (Some(&b'S'), Some(&b'M'), Some(third))
             if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
@@ -26,6 +26,8 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
         {
             2
         }
+        (Some(&b'S'), Some(&b'B'), Some(third)) if third.is_ascii_alphanumeric() => 2,
+        (Some(&b'S'), Some(&b'W'), Some(third)) if third.is_ascii_alphanumeric() => 2,
         (Some(&b'T'), Some(&b'N'), Some(third))
             if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
         {
@@ -36,6 +38,11 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
         {
             2
         }
+        (Some(&b'T'), Some(&b'C'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
         (Some(&b'C'), Some(&b'N'), Some(third))
             if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
         {
@@ -46,6 +53,11 @@ pub fn split_address(address: &str) -> Option<(&str, &str)> {
         {
             2
         }
+        (Some(&b'C'), Some(&b'C'), Some(third))
+            if third.is_ascii_digit() || third.is_ascii_alphanumeric() =>
+        {
+            2
+        }
         // 单字符前缀检查
         (Some(&b'X'), Some(second), _) if second.is_ascii_alphanumeric() => 1,
         (Some(&b'Y'), Some(second), _) if second.is_ascii_alphanumeric() => 1,
@@ -17,14 +17,79 @@ pub enum NumberBase {
     Hexadecimal,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Model {
+    #[default]
     Mitsubishi,
     Keyence,
 }
 
-impl Default for Model {
+/// 奇数字节写入的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OddLengthPolicy {
+    /// 返回 `ProtocolError::OddByteCount`（默认）
+    #[default]
+    Reject,
+    /// 末尾补 0 凑成整字写入，并记录警告
+    ZeroPad,
+}
+
+/// 帧格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FrameType {
+    /// 3E 帧，二进制码
+    #[default]
+    Binary3E,
+    /// A 兼容 1E 帧，二进制码，用于 FX3U、A 系列等只支持 1E 帧的以太网模块
+    Binary1E,
+    /// 3E 帧，ASCII 码
+    Ascii3E,
+}
+
+/// 3E 帧的访问路径：网络编号、PLC 编号、请求目标模块 I/O 编号与站号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Route {
+    pub network_no: u8,
+    pub pc_no: u8,
+    pub dest_io: u16,
+    pub dest_station: u8,
+}
+
+impl Route {
+    /// 直接连接的本站 CPU：网络 00、PLC FF、I/O 03FF、站号 00
+    pub const LOCAL: Self = Self {
+        network_no: 0x00,
+        pc_no: 0xFF,
+        dest_io: 0x03FF,
+        dest_station: 0x00,
+    };
+
+    /// 多 CPU 系统中第 `number` 号 CPU（1-4），I/O 编号为 03E0-03E3
+    pub const fn multi_cpu(number: u8) -> Self {
+        Self {
+            dest_io: 0x03E0 + number.saturating_sub(1) as u16,
+            ..Self::LOCAL
+        }
+    }
+}
+
+impl Default for Route {
     fn default() -> Self {
-        Model::Mitsubishi
+        Self::LOCAL
     }
 }
+
+/// 应答帧头中的应答站信息与结束代码，用于确认多站系统中实际应答的站
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseInfo {
+    /// 应答站的访问路径，1E 帧没有访问路径，固定为 [`Route::LOCAL`]
+    pub route: Route,
+    /// 结束代码，正常结束时为 0
+    pub end_code: u16,
+}
+
+/// 客户端请求帧头中的访问路径，经由 CC-Link IE、MELSECNET 中继访问其它站时设置
+///
+/// 与 [`Route`] 相同，默认访问直接连接的本站 CPU（00/FF/03FF/00）。
+pub type HeaderConfig = Route;
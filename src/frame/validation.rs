@@ -0,0 +1,63 @@
+//! 协议校验模式
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::ProtocolError;
+use crate::{trace, Error};
+
+/// 协议校验模式
+///
+/// 严格模式拒绝一切不符合规范的数据（如奇数字节写入、长度不一致）；
+/// 宽松模式仅记录警告并继续处理，与早期版本的行为一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    Strict,
+    #[default]
+    Permissive,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(ValidationMode::Permissive as u8);
+
+/// 设置全局校验模式
+pub fn set_validation_mode(mode: ValidationMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// 当前全局校验模式
+pub fn validation_mode() -> ValidationMode {
+    if MODE.load(Ordering::Relaxed) == ValidationMode::Strict as u8 {
+        ValidationMode::Strict
+    } else {
+        ValidationMode::Permissive
+    }
+}
+
+/// 按全局模式处理不符合规范的情况
+pub(crate) fn off_spec(error: ProtocolError) -> Result<(), Error> {
+    off_spec_with(validation_mode(), error)
+}
+
+fn off_spec_with(mode: ValidationMode, error: ProtocolError) -> Result<(), Error> {
+    match mode {
+        ValidationMode::Strict => Err(Error::Protocol(error)),
+        ValidationMode::Permissive => {
+            trace::warning!("{error}, continuing in permissive mode");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn off_spec_depends_on_mode() {
+        assert_eq!(validation_mode(), ValidationMode::Permissive);
+        assert!(off_spec_with(ValidationMode::Permissive, ProtocolError::OddByteCount(3)).is_ok());
+        assert!(matches!(
+            off_spec_with(ValidationMode::Strict, ProtocolError::OddByteCount(3)),
+            Err(Error::Protocol(ProtocolError::OddByteCount(3)))
+        ));
+    }
+}
@@ -1,21 +1,28 @@
 use bytes::{BufMut, Bytes, BytesMut};
 
+use crate::frame::Route;
+
 pub type HeaderByte = Bytes;
 
 pub struct RequestHeader(pub HeaderByte);
 
 impl RequestHeader {
-    /// 构造三菱 MC 3E 协议头部
+    /// 构造三菱 MC 3E 协议头部，访问本站 CPU
     pub fn new() -> Self {
+        Self::with_route(Route::LOCAL)
+    }
+
+    /// 构造访问路径为 `route` 的 3E 协议头部
+    pub fn with_route(route: Route) -> Self {
         // 使用 BytesMut 动态缓冲区
         let mut buf = BytesMut::new();
 
         // 写入固定的头部
         buf.put_u16_le(0x0050); // 3E 协议头
-        buf.put_u8(0x00); // 网络编号，固定 00
-        buf.put_u8(0xFF); // PLC 编号，固定 FF
-        buf.put_u16_le(0x03FF); // 目标模块 IO 编号
-        buf.put_u8(0x00); // 目标模块站号，固定 00
+        buf.put_u8(route.network_no); // 网络编号
+        buf.put_u8(route.pc_no); // PLC 编号
+        buf.put_u16_le(route.dest_io); // 目标模块 IO 编号
+        buf.put_u8(route.dest_station); // 目标模块站号
         buf.put_u16_le(0x000C); // 请求数据的长度（根据实际情况调整）
         buf.put_u16_le(0x0010); // 监视定时器
 
@@ -34,8 +41,31 @@ impl RequestHeader {
     }
 }
 
+/// A 兼容 1E 帧的请求头：子头部（即指令）、PC 编号与 ACPU 监视定时器
+pub struct RequestHeader1E(pub HeaderByte);
+
+impl RequestHeader1E {
+    /// 构造 1E 协议头部，`command` 为子头部中的指令
+    pub fn new(command: u8) -> Self {
+        let mut buf = BytesMut::new();
+
+        buf.put_u8(command); // 子头部即指令
+        buf.put_u8(0xFF); // PC 编号，固定 FF
+        buf.put_u16_le(0x000A); // ACPU 监视定时器，单位 250ms
+
+        RequestHeader1E(buf.freeze())
+    }
+
+    /// 获取请求头的字节数组
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg_attr(not(any(feature = "tcp", feature = "server")), allow(dead_code))]
 pub struct ResponseHeader(pub HeaderByte);
 
+#[cfg_attr(not(any(feature = "tcp", feature = "server")), allow(dead_code))]
 impl ResponseHeader {
     pub fn new() -> Self {
         // 使用 BytesMut 动态缓冲区
@@ -1,5 +1,7 @@
 pub use bytes;
 pub use log;
+#[cfg(feature = "tracing")]
+pub use tracing;
 
 pub mod error;
 pub use self::error::Error;
@@ -7,11 +9,30 @@ pub use self::error::Error;
 pub mod frame;
 
 pub mod codec;
-pub use codec::{ClientEncoder, ServerDecoder, ClientDecoder};
+pub use codec::{ClientDecoder, ClientEncoder};
+#[cfg(feature = "server")]
+pub use codec::ServerDecoder;
 
+#[cfg(feature = "client")]
 pub mod client;
 
+pub mod address_book;
+
+pub mod convert;
+
+pub mod slmp;
+
+pub mod stats;
+
+#[cfg(feature = "compression")]
+pub mod compress;
+
 mod header;
 
+mod trace;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
 #[cfg(feature = "server")]
 pub mod server;
@@ -0,0 +1,370 @@
+//! 网关的读取缓存
+//!
+//! 作为网关或代理把下游请求转发给上游 PLC 时，多个下游客户端往往以相同的周期读取相同的地址。
+//! [`ReadCache`] 包装转发用的 [`Service`]，把批量读取（`ReadU8s`、`ReadBits`）的应答按访问路径、
+//! 地址与点数缓存一段时间：
+//!
+//! - 缓存有效期内的相同读取直接返回缓存的应答；
+//! - 同一读取正在等待上游应答时，后到的请求等待这一次的结果，不再重复发出；
+//! - 写请求不经过缓存，并清除同一软元件的缓存；`0x1000` 及以上的命令（批量/随机写入、
+//!   远程操作等）清除全部缓存；
+//! - 有效期可按软元件单独设置，设为零时该软元件不缓存。
+//!
+//! 上游返回错误时不缓存，等待同一读取的请求改为各自转发。
+//!
+//! ```text
+//! let service = ReadCache::new(gateway, Duration::from_millis(200))
+//!     .device_ttl("SD", Duration::ZERO);
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio::{sync::watch, time::Instant};
+
+use crate::frame::{split_address, Quantity, Request, Response, Route};
+
+use super::{
+    service::{BoxedFuture, Upstream},
+    Service, Session,
+};
+
+/// 此编号及以上的命令可能改写软元件，执行时清除全部缓存
+const MODIFYING_COMMANDS: u16 = 0x1000;
+
+/// 缓存键：访问路径、是否为位读取、地址与点数
+type Key = (Route, bool, String, Quantity);
+
+#[derive(Debug)]
+enum Slot {
+    Ready {
+        response: Response,
+        expires: Instant,
+    },
+    /// 正在等待上游应答，`id` 区分先后发出的读取
+    Pending {
+        id: u64,
+        result: watch::Receiver<Option<Response>>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct State {
+    slots: HashMap<Key, Slot>,
+    /// 每次写入递增，写入期间发出的读取不写入缓存
+    generation: u64,
+    next_id: u64,
+}
+
+impl State {
+    /// 清除 `device` 软元件的缓存，`None` 表示全部
+    fn invalidate(&mut self, device: Option<&str>) {
+        self.generation += 1;
+        match device {
+            Some(device) => self
+                .slots
+                .retain(|(_, _, address, _), _| device_of(address) != Some(device)),
+            None => self.slots.clear(),
+        }
+    }
+}
+
+/// 缓存批量读取应答的 [`Service`] 包装，见[模块文档](self)
+pub struct ReadCache<S> {
+    inner: Arc<S>,
+    ttl: Duration,
+    device_ttls: HashMap<String, Duration>,
+    state: Arc<Mutex<State>>,
+    hits: Arc<AtomicU64>,
+}
+
+impl<S> ReadCache<S> {
+    /// 读取应答缓存 `ttl`
+    pub fn new(inner: S, ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            ttl,
+            device_ttls: HashMap::new(),
+            state: Arc::default(),
+            hits: Arc::default(),
+        }
+    }
+
+    /// 单独设置 `device` 软元件（如 `"SD"`）的有效期，为零时不缓存
+    pub fn device_ttl(mut self, device: &str, ttl: Duration) -> Self {
+        self.device_ttls.insert(device.to_string(), ttl);
+        self
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// 由缓存或合并的读取应答、未转发给上游的请求数
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// 清除全部缓存
+    pub fn clear(&self) {
+        self.state.lock().unwrap().invalidate(None);
+    }
+
+    fn ttl(&self, address: &str) -> Duration {
+        device_of(address)
+            .and_then(|device| self.device_ttls.get(device))
+            .copied()
+            .unwrap_or(self.ttl)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for ReadCache<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadCache")
+            .field("inner", &self.inner)
+            .field("ttl", &self.ttl)
+            .field("device_ttls", &self.device_ttls)
+            .field("hits", &self.hits)
+            .finish_non_exhaustive()
+    }
+}
+
+fn device_of(address: &str) -> Option<&str> {
+    split_address(address).map(|(device, _)| device)
+}
+
+impl<S> ReadCache<S>
+where
+    S: Service<Request = Request<'static>, Response = Response> + Send + Sync + 'static,
+    S::Future: 'static,
+    S::Exception: Send + 'static,
+{
+    fn serve(
+        &self,
+        session: Option<&Arc<Session>>,
+        route: Route,
+        request: Request<'static>,
+    ) -> BoxedFuture<S::Exception> {
+        let upstream = Upstream::new(&self.inner, session, route);
+        let (key, ttl) = match &request {
+            Request::ReadU8s(address, quantity) | Request::ReadBits(address, quantity) => {
+                let bits = matches!(request, Request::ReadBits(_, _));
+                let ttl = self.ttl(address);
+                if ttl.is_zero() {
+                    return Box::pin(upstream.call(request));
+                }
+                ((route, bits, address.to_string(), *quantity), ttl)
+            }
+            Request::WriteU8s(address, _) | Request::WriteBits(address, _) => {
+                let device = device_of(address).map(str::to_string);
+                return self.write_through(upstream, request, device);
+            }
+            Request::Command(command, _, _) if *command >= MODIFYING_COMMANDS => {
+                return self.write_through(upstream, request, None);
+            }
+            Request::Command(_, _, _) => return Box::pin(upstream.call(request)),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        match state.slots.get(&key) {
+            Some(Slot::Ready { response, expires }) if *expires > Instant::now() => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                let response = response.clone();
+                return Box::pin(async move { Ok(response) });
+            }
+            // 发出读取的请求被取消时发送端已关闭，改由本次读取重新发出
+            Some(Slot::Pending { result, .. }) if result.has_changed().is_ok() => {
+                let mut result = result.clone();
+                let hits = self.hits.clone();
+                return Box::pin(async move {
+                    loop {
+                        if let Some(response) = result.borrow_and_update().clone() {
+                            hits.fetch_add(1, Ordering::Relaxed);
+                            return Ok(response);
+                        }
+                        if result.changed().await.is_err() {
+                            return upstream.call(request).await;
+                        }
+                    }
+                });
+            }
+            _ => {}
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        let id = state.next_id;
+        state.next_id += 1;
+        let generation = state.generation;
+        state.slots.insert(
+            key.clone(),
+            Slot::Pending {
+                id,
+                result: receiver,
+            },
+        );
+        drop(state);
+
+        let shared = self.state.clone();
+        let future = upstream.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            let mut state = shared.lock().unwrap();
+            let current = matches!(
+                state.slots.get(&key),
+                Some(Slot::Pending { id: pending, .. }) if *pending == id
+            );
+            if current {
+                match &result {
+                    Ok(response) if state.generation == generation => {
+                        let expires = Instant::now() + ttl;
+                        let response = response.clone();
+                        state.slots.insert(key, Slot::Ready { response, expires });
+                    }
+                    _ => {
+                        state.slots.remove(&key);
+                    }
+                }
+            }
+            drop(state);
+            // 失败时关闭通道，等待者各自转发
+            if let Ok(response) = &result {
+                let _ = sender.send(Some(response.clone()));
+            }
+            result
+        })
+    }
+
+    /// 转发写请求，转发前后都清除 `device` 的缓存（`None` 表示全部）
+    fn write_through(
+        &self,
+        upstream: Upstream<S>,
+        request: Request<'static>,
+        device: Option<String>,
+    ) -> BoxedFuture<S::Exception> {
+        self.state.lock().unwrap().invalidate(device.as_deref());
+        let shared = self.state.clone();
+        let future = upstream.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            // 写入期间读取到的旧值可能已经写入缓存
+            shared.lock().unwrap().invalidate(device.as_deref());
+            result
+        })
+    }
+}
+
+impl<S> Service for ReadCache<S>
+where
+    S: Service<Request = Request<'static>, Response = Response> + Send + Sync + 'static,
+    S::Future: 'static,
+    S::Exception: Send + 'static,
+{
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = S::Exception;
+    type Future = BoxedFuture<S::Exception>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        self.serve(None, Route::LOCAL, req)
+    }
+
+    fn call_routed(&self, route: Route, req: Self::Request) -> Self::Future {
+        self.serve(None, route, req)
+    }
+
+    fn call_with_session(
+        &self,
+        session: &Arc<Session>,
+        route: Route,
+        req: Self::Request,
+    ) -> Self::Future {
+        self.serve(Some(session), route, req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ProtocolError;
+    use std::sync::atomic::AtomicUsize;
+
+    /// 每次读取耗时 10ms 并返回调用次数，记录转发的请求数
+    #[derive(Debug, Default)]
+    struct Plc {
+        calls: AtomicUsize,
+    }
+
+    impl Service for Plc {
+        type Request = Request<'static>;
+        type Response = Response;
+        type Exception = ProtocolError;
+        type Future = BoxedFuture<ProtocolError>;
+
+        fn call(&self, req: Self::Request) -> Self::Future {
+            let count = self.calls.fetch_add(1, Ordering::SeqCst) as u8 + 1;
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                match req {
+                    Request::ReadU8s(_, _) => Ok(Response::ReadU8s(vec![count, 0])),
+                    Request::WriteU8s(_, _) => Ok(Response::WriteU8s()),
+                    _ => Err(ProtocolError::NotImplemented),
+                }
+            })
+        }
+    }
+
+    fn read(address: &'static str) -> Request<'static> {
+        Request::ReadU8s(address.into(), 1)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn collapses_identical_reads_until_written() {
+        let cache = Arc::new(
+            ReadCache::new(Plc::default(), Duration::from_secs(1)).device_ttl("SD", Duration::ZERO),
+        );
+        let calls = |cache: &ReadCache<Plc>| cache.get_ref().calls.load(Ordering::SeqCst);
+
+        // 同时到达的相同读取只转发一次
+        let burst: Vec<_> = (0..5).map(|_| cache.call(read("D100"))).collect();
+        for response in burst {
+            assert_eq!(response.await.unwrap(), Response::ReadU8s(vec![1, 0]));
+        }
+        assert_eq!(calls(&cache), 1);
+
+        assert_eq!(
+            cache.call(read("D100")).await.unwrap(),
+            Response::ReadU8s(vec![1, 0])
+        );
+        assert_eq!(cache.hits(), 5);
+
+        // 其它软元件的写入不影响缓存，同一软元件的写入清除缓存
+        cache
+            .call(Request::WriteU8s("M0".into(), vec![1, 0].into()))
+            .await
+            .unwrap();
+        cache.call(read("D100")).await.unwrap();
+        assert_eq!(calls(&cache), 2);
+        cache
+            .call(Request::WriteU8s("D200".into(), vec![1, 0].into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.call(read("D100")).await.unwrap(),
+            Response::ReadU8s(vec![4, 0])
+        );
+
+        // 过期后重新读取；有效期为零的软元件总是转发
+        tokio::time::advance(Duration::from_secs(2)).await;
+        cache.call(read("D100")).await.unwrap();
+        cache.call(read("SD0")).await.unwrap();
+        cache.call(read("SD0")).await.unwrap();
+        assert_eq!(calls(&cache), 7);
+    }
+}
@@ -1,5 +1,25 @@
+pub mod cache;
+pub mod priority;
+#[cfg(feature = "client")]
+pub mod replica;
+pub mod seed;
 mod service;
+pub mod session;
+pub mod simulator;
+pub mod tap;
 pub mod tcp;
+#[cfg(feature = "web")]
+pub mod web;
 
+pub use self::cache::ReadCache;
+pub use self::priority::PriorityLane;
+#[cfg(feature = "client")]
+pub use self::replica::Replica;
+pub use self::seed::Seed;
 pub use self::service::Service;
-pub use self::tcp::{accept_tcp_connection, Server, Terminated};
+pub use self::session::{FrameType, Session};
+pub use self::simulator::{JournalData, MultiCpu, Simulator, WriteRecord};
+pub use self::tap::{TapEvent, TapReply};
+pub use self::tcp::{accept_tcp_connection, Server, ServerBuilder, Terminated};
+#[cfg(feature = "web")]
+pub use self::web::serve_web;
@@ -0,0 +1,313 @@
+//! 网关的优先通道
+//!
+//! 网关把大量下游请求转发给同一台上游 PLC 时，报警等关键软元件的读取可能排在批量数据之后，
+//! 延迟随负载增长。[`PriorityLane`] 包装转发用的 [`Service`]，限制同时转发给上游的请求数，
+//! 并把排队的请求分为两条通道：访问了登记的高优先级软元件范围的请求先于排队的批量请求转发。
+//! 已经转发的请求不会被打断，高优先级请求最多等待正在处理的请求完成。
+//!
+//! ```text
+//! let service = PriorityLane::new(gateway)
+//!     .high_priority("M1000-M1999")?
+//!     .high_priority("D8000-D8099")?;
+//! ```
+//!
+//! 范围为 `D100-D199`、单个地址 `M10`，或仅前缀 `X` 表示全部编号；命令总是走批量通道。
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    frame::{
+        convert_to_base, device_kind, find_instruction_code, split_address, DeviceKind,
+        ProtocolError, Request, Response, Route,
+    },
+    Error,
+};
+
+use super::{
+    service::{BoxedFuture, Upstream},
+    Service, Session,
+};
+
+/// 高优先级的软元件范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Range {
+    prefix: String,
+    numbers: RangeInclusive<u32>,
+}
+
+impl Range {
+    fn parse(range: &str) -> Result<Self, Error> {
+        let range = range.trim();
+        if find_instruction_code(range).is_some() {
+            return Ok(Self {
+                prefix: range.to_string(),
+                numbers: 0..=u32::MAX,
+            });
+        }
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let invalid = || Error::Protocol(ProtocolError::InvalidAddress(range.to_string()));
+        let (prefix, start) = parse_address(first).ok_or_else(invalid)?;
+        let (last_prefix, end) = parse_address(last).ok_or_else(invalid)?;
+        if prefix != last_prefix || start > end {
+            return Err(invalid());
+        }
+        Ok(Self {
+            prefix: prefix.to_string(),
+            numbers: start..=end,
+        })
+    }
+}
+
+fn parse_address(address: &str) -> Option<(&str, u32)> {
+    let (prefix, number) = split_address(address.trim())?;
+    let (_, base) = find_instruction_code(prefix)?;
+    Some((prefix, convert_to_base(number, base)?))
+}
+
+/// 请求访问的软元件与编号范围，命令返回 `None`
+fn accessed<'a>(request: &'a Request<'_>) -> Option<(&'a str, RangeInclusive<u32>)> {
+    let (address, points, words) = match request {
+        Request::ReadU8s(address, quantity) => (address, *quantity, true),
+        Request::WriteU8s(address, u8s) => (address, u8s.len().div_ceil(2) as u32, true),
+        Request::ReadBits(address, quantity) => (address, *quantity, false),
+        Request::WriteBits(address, bits) => (address, bits.len() as u32, false),
+        Request::Command(_, _, _) => return None,
+    };
+    let (prefix, start) = parse_address(address)?;
+    // 按字访问位软元件时每个字覆盖 16 点
+    let points = if words && device_kind(prefix) == Some(DeviceKind::Bit) {
+        points.saturating_mul(16)
+    } else {
+        points
+    };
+    Some((prefix, start..=start.saturating_add(points.max(1) - 1)))
+}
+
+/// 两条通道的排队状态
+#[derive(Debug, Default)]
+struct Lanes {
+    in_flight: usize,
+    high: VecDeque<oneshot::Sender<Permit>>,
+    bulk: VecDeque<oneshot::Sender<Permit>>,
+}
+
+/// 转发许可，释放时交给下一个排队的请求
+#[derive(Debug)]
+struct Permit {
+    lanes: Arc<Mutex<Lanes>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut lanes = self.lanes.lock().unwrap();
+        let Some(next) = lanes.high.pop_front().or_else(|| lanes.bulk.pop_front()) else {
+            lanes.in_flight -= 1;
+            return;
+        };
+        drop(lanes);
+        // 等待者已取消时许可随发送失败被丢弃，继续交给下一个
+        let _ = next.send(Permit {
+            lanes: self.lanes.clone(),
+        });
+    }
+}
+
+async fn acquire(lanes: &Arc<Mutex<Lanes>>, high: bool, limit: usize) -> Permit {
+    let receiver = {
+        let mut state = lanes.lock().unwrap();
+        if state.in_flight < limit {
+            state.in_flight += 1;
+            return Permit {
+                lanes: lanes.clone(),
+            };
+        }
+        let (sender, receiver) = oneshot::channel();
+        if high {
+            state.high.push_back(sender);
+        } else {
+            state.bulk.push_back(sender);
+        }
+        receiver
+    };
+    receiver
+        .await
+        .expect("queued senders are only dropped by sending")
+}
+
+/// 为高优先级软元件提供优先通道的 [`Service`] 包装，见[模块文档](self)
+pub struct PriorityLane<S> {
+    inner: Arc<S>,
+    ranges: Vec<Range>,
+    max_in_flight: usize,
+    lanes: Arc<Mutex<Lanes>>,
+}
+
+impl<S> PriorityLane<S> {
+    /// 同时只转发一个请求，尚未登记高优先级范围
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            ranges: Vec::new(),
+            max_in_flight: 1,
+            lanes: Arc::default(),
+        }
+    }
+
+    /// 登记高优先级的软元件范围，访问与其重叠的请求走优先通道
+    pub fn high_priority(mut self, range: &str) -> Result<Self, Error> {
+        self.ranges.push(Range::parse(range)?);
+        Ok(self)
+    }
+
+    /// 同时转发给上游的最大请求数，默认为 1
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// 请求是否访问了高优先级范围
+    pub fn is_high_priority(&self, request: &Request<'_>) -> bool {
+        accessed(request).is_some_and(|(prefix, numbers)| {
+            self.ranges.iter().any(|range| {
+                range.prefix == prefix
+                    && range.numbers.start() <= numbers.end()
+                    && numbers.start() <= range.numbers.end()
+            })
+        })
+    }
+
+    /// 当前排队等待的高优先级与批量请求数
+    pub fn queued(&self) -> (usize, usize) {
+        let lanes = self.lanes.lock().unwrap();
+        (lanes.high.len(), lanes.bulk.len())
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for PriorityLane<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriorityLane")
+            .field("inner", &self.inner)
+            .field("ranges", &self.ranges)
+            .field("max_in_flight", &self.max_in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> PriorityLane<S>
+where
+    S: Service<Request = Request<'static>, Response = Response> + Send + Sync + 'static,
+    S::Future: 'static,
+    S::Exception: Send + 'static,
+{
+    fn serve(
+        &self,
+        session: Option<&Arc<Session>>,
+        route: Route,
+        request: Request<'static>,
+    ) -> BoxedFuture<S::Exception> {
+        let upstream = Upstream::new(&self.inner, session, route);
+        let high = self.is_high_priority(&request);
+        let lanes = self.lanes.clone();
+        let limit = self.max_in_flight;
+        Box::pin(async move {
+            let _permit = acquire(&lanes, high, limit).await;
+            upstream.call(request).await
+        })
+    }
+}
+
+impl<S> Service for PriorityLane<S>
+where
+    S: Service<Request = Request<'static>, Response = Response> + Send + Sync + 'static,
+    S::Future: 'static,
+    S::Exception: Send + 'static,
+{
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = S::Exception;
+    type Future = BoxedFuture<S::Exception>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        self.serve(None, Route::LOCAL, req)
+    }
+
+    fn call_routed(&self, route: Route, req: Self::Request) -> Self::Future {
+        self.serve(None, route, req)
+    }
+
+    fn call_with_session(
+        &self,
+        session: &Arc<Session>,
+        route: Route,
+        req: Self::Request,
+    ) -> Self::Future {
+        self.serve(Some(session), route, req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 每个请求耗时 10ms，按处理顺序记录地址
+    #[derive(Debug, Default)]
+    struct Plc(Mutex<Vec<String>>);
+
+    impl Service for Plc {
+        type Request = Request<'static>;
+        type Response = Response;
+        type Exception = ProtocolError;
+        type Future = BoxedFuture<ProtocolError>;
+
+        fn call(&self, req: Self::Request) -> Self::Future {
+            self.0.lock().unwrap().push(req.address().to_string());
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(Response::ReadU8s(vec![0, 0]))
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn alarm_reads_overtake_queued_bulk_reads() {
+        let lane = Arc::new(
+            PriorityLane::new(Plc::default())
+                .high_priority("M1000-M1099")
+                .unwrap(),
+        );
+        let mut tasks = Vec::new();
+        for address in ["D0", "D1000", "D2000", "M1050"] {
+            let lane = lane.clone();
+            let request = Request::ReadBits(address.into(), 1);
+            tasks.push(tokio::spawn(lane.call(request)));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(lane.queued(), (1, 2));
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(
+            *lane.get_ref().0.lock().unwrap(),
+            ["D0", "M1050", "D1000", "D2000"]
+        );
+
+        // 按字读取位软元件时每个字覆盖 16 点
+        assert!(lane.is_high_priority(&Request::ReadU8s("M992".into(), 1)));
+        assert!(!lane.is_high_priority(&Request::ReadU8s("M976".into(), 1)));
+        assert!(PriorityLane::new(Plc::default())
+            .high_priority("M10-D20")
+            .is_err());
+    }
+}
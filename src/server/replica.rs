@@ -0,0 +1,205 @@
+//! 只读副本
+//!
+//! 看板与分析程序频繁读取生产 PLC 会占用其通信处理时间。[`Replica`] 经由客户端 [`Context`]
+//! 定期把登记的区域从 PLC 复制到本地的 [`Simulator`]，看板改为访问以该模拟器运行的服务端：
+//!
+//! ```text
+//! let simulator = Arc::new(Simulator::new());
+//! let mut replica = Replica::new(context, simulator.clone())
+//!     .area("D", 0..1000)
+//!     .area("M", 0..512)
+//!     .trigger("D9000");
+//! // 另以 simulator 运行 Server（如 mc-sim），看板连接到该服务端
+//! replica.run(Duration::from_secs(1)).await;
+//! ```
+//!
+//! 复制是单向的：副本收到的写请求照常写入模拟器，但会在下一次复制时被 PLC 的值覆盖。
+//!
+//! - 周期复制：每次 [`Replica::refresh`] 读取全部区域；
+//! - 变化触发：以 [`Replica::trigger`] 指定 PLC 程序在数据变化时递增的计数字，
+//!   每次只读取该字，值变化时才读取全部区域。
+//!
+//! 全部区域读取成功后才一起写入模拟器，读取失败时副本保持上一次的完整数据。
+//! 区域的地址规则与 `Context::dump_area` 相同。
+
+use std::{io, ops::Range, sync::Arc, time::Duration};
+
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+use crate::{
+    client::{Client, Context, Reader as _},
+    trace, Error,
+};
+
+use super::Simulator;
+
+/// 把 PLC 的区域复制到本地模拟器的只读副本，见[模块文档](self)
+#[derive(Debug)]
+pub struct Replica<T: Client> {
+    context: Context<T>,
+    simulator: Arc<Simulator>,
+    areas: Vec<(String, Range<u32>)>,
+    /// 变化触发的计数字及上一次复制时的值
+    trigger: Option<(String, Option<u16>)>,
+    synced: u64,
+    last_synced: Option<Instant>,
+}
+
+impl<T: Client> Replica<T> {
+    pub fn new(context: Context<T>, simulator: Arc<Simulator>) -> Self {
+        Self {
+            context,
+            simulator,
+            areas: Vec::new(),
+            trigger: None,
+            synced: 0,
+            last_synced: None,
+        }
+    }
+
+    /// 登记 `prefix` 软元件的 `range` 范围，位软元件的范围长度须为 16 的倍数
+    pub fn area(mut self, prefix: &str, range: Range<u32>) -> Self {
+        self.areas.push((prefix.to_string(), range));
+        self
+    }
+
+    /// 仅在 `address` 的字变化时复制，地址按 PLC 型号转换
+    pub fn trigger(mut self, address: &str) -> Self {
+        self.trigger = Some((address.to_string(), None));
+        self
+    }
+
+    /// 复制一次，返回是否读取并写入了区域
+    ///
+    /// 设置了计数字且其值与上一次复制时相同时不读取区域，返回 `false`。
+    pub async fn refresh(&mut self) -> Result<bool, Error> {
+        let counter = match &self.trigger {
+            Some((address, last)) => {
+                let counter = self.context.read_u16(address).await?;
+                if *last == Some(counter) {
+                    return Ok(false);
+                }
+                Some(counter)
+            }
+            None => None,
+        };
+
+        let mut dump = Vec::new();
+        for (prefix, range) in &self.areas {
+            self.context
+                .dump_area(prefix, range.clone(), &mut dump)
+                .await?;
+        }
+        self.simulator.load_dump(&dump[..]).map_err(|err| {
+            Error::Transport(io::Error::new(
+                err.kind(),
+                format!("replica does not fit the simulator: {err}"),
+            ))
+        })?;
+
+        if let Some((_, last)) = &mut self.trigger {
+            *last = counter;
+        }
+        self.synced += 1;
+        self.last_synced = Some(Instant::now());
+        Ok(true)
+    }
+
+    /// 每隔 `interval` 复制一次，不会返回
+    ///
+    /// 复制失败时记录警告并在下一个周期重试；需要停止时丢弃返回的 future 即可。
+    pub async fn run(&mut self, interval: Duration) {
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Err(err) = self.refresh().await {
+                trace::warning!("Replica refresh failed: {err}");
+            }
+        }
+    }
+
+    /// 成功写入模拟器的次数
+    pub fn synced(&self) -> u64 {
+        self.synced
+    }
+
+    /// 上一次写入模拟器的时间，用于判断副本是否过旧
+    pub fn last_synced(&self) -> Option<Instant> {
+        self.last_synced
+    }
+
+    pub fn simulator(&self) -> &Arc<Simulator> {
+        &self.simulator
+    }
+
+    pub fn context(&self) -> &Context<T> {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context<T> {
+        &mut self.context
+    }
+
+    pub fn into_inner(self) -> Context<T> {
+        self.context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{Request, Response};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// 以另一台模拟器充当生产 PLC，记录读取的地址
+    #[derive(Debug, Clone)]
+    struct Plc(Arc<Simulator>, Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Client for Plc {
+        async fn call(&mut self, request: Request<'_>) -> Result<Response, Error> {
+            self.1.lock().unwrap().push(request.address().to_string());
+            self.0.handle(&request).map_err(Error::Protocol)
+        }
+    }
+
+    #[tokio::test]
+    async fn copies_areas_when_the_trigger_changes() {
+        let plc = Arc::new(Simulator::new());
+        plc.write_words("D10", &[1, 2, 3]).unwrap();
+        plc.write_bits("M17", &[true]).unwrap();
+        let reads = Arc::default();
+        let replica = Arc::new(Simulator::new());
+        let source = Plc(plc.clone(), Arc::clone(&reads));
+        let mut sync = Replica::new(Context::new(source), replica.clone())
+            .area("D", 0..100)
+            .area("M", 0..32)
+            .trigger("D1999");
+
+        assert!(sync.refresh().await.unwrap());
+        assert_eq!(replica.read_words("D10", 3).unwrap(), [1, 2, 3]);
+        assert_eq!(replica.read_bits("M16", 2).unwrap(), [false, true]);
+
+        // 计数字未变化时只读取计数字
+        plc.write_words("D10", &[9]).unwrap();
+        assert!(!sync.refresh().await.unwrap());
+        assert_eq!(replica.read_words("D10", 1).unwrap(), [1]);
+        assert_eq!(*reads.lock().unwrap(), ["D1999", "D0", "M0", "D1999"]);
+
+        plc.write_words("D1999", &[1]).unwrap();
+        assert!(sync.refresh().await.unwrap());
+        assert_eq!(replica.read_words("D10", 1).unwrap(), [9]);
+        assert_eq!(sync.synced(), 2);
+
+        // 读取失败时不写入任何区域
+        plc.write_words("D10", &[5]).unwrap();
+        let mut broken = Replica::new(Context::new(Plc(plc, reads)), replica.clone())
+            .area("D", 0..100)
+            .area("W", 0..16);
+        assert!(broken.refresh().await.is_err());
+        assert_eq!(broken.synced(), 0);
+        assert_eq!(replica.read_words("D10", 1).unwrap(), [9]);
+    }
+}
@@ -0,0 +1,262 @@
+//! 模拟器的种子文件
+//!
+//! 测试场景通常需要从接近现场的机器状态开始。[`Seed`] 以「地址 → 带类型的值」描述初始数据，
+//! 由 [`Simulator::load_seed`] 写入模拟器，可在启动时加载（`mc-sim --seed machine.csv`），
+//! 也可在测试中随时调用以切换场景。
+//!
+//! CSV 每行为 `地址,类型,值[,字序]`，首行为 `address,type,value` 开头的表头时跳过：
+//!
+//! ```text
+//! # 以 # 开头的行与空行被忽略
+//! M10,bool,1
+//! D100,u16,1200
+//! D102,f32,3.25,high
+//! D110,string,ABC-123
+//! ```
+//!
+//! JSON（需要 `seed-json` 特性）为对象数组，字段与 CSV 的列相同，数值可以写为数字或字符串：
+//!
+//! ```text
+//! [
+//!   { "address": "D100", "type": "u16", "value": 1200 },
+//!   { "address": "D102", "type": "f32", "value": 3.25, "order": "high" }
+//! ]
+//! ```
+//!
+//! 类型为 `bool`、`u16`、`i16`、`u32`、`i32`、`f32`、`u64`、`i64`、`f64`、`string`、`wstring`：
+//!
+//! - 多字数值默认低位字在前，字序写为 `high` 时高位字在前；
+//! - `string` 每个字存放 2 个字节，低字节在前，奇数长度以 0 补足，与 `read_strings` 的格式一致；
+//!   CSV 中字符串为第二个逗号之后的全部内容，可以包含逗号；
+//! - `wstring` 为 UTF-16，末尾追加 0x0000，与 `write_wstring` 的格式一致。
+//!
+//! 写入不记入写入日志；任一条目无效时不写入任何数据。
+
+use std::io;
+
+use crate::convert::{WordOrder, WordValue};
+
+use super::Simulator;
+
+/// 种子文件中一个条目的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeedValue {
+    /// 位
+    Bit(bool),
+    /// 按字写入的数据，数值与字符串均已转换为字
+    Words(Vec<u16>),
+}
+
+/// 一个条目：起始地址与值
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedEntry {
+    pub address: String,
+    pub value: SeedValue,
+}
+
+/// 模拟器的初始数据，见[模块文档](self)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Seed {
+    pub entries: Vec<SeedEntry>,
+}
+
+impl Seed {
+    /// 解析 CSV 种子文件
+    pub fn parse_csv(text: &str) -> io::Result<Self> {
+        let mut seed = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let at_line = |message: String| invalid_data(format!("line {}: {message}", index + 1));
+            let mut fields = line.splitn(3, ',').map(str::trim);
+            let (Some(address), Some(kind), Some(rest)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(at_line("expected address,type,value[,order]".to_string()));
+            };
+            if seed.entries.is_empty()
+                && address.eq_ignore_ascii_case("address")
+                && kind.eq_ignore_ascii_case("type")
+            {
+                continue;
+            }
+            let (value, order) = match kind {
+                "string" | "wstring" => (rest, None),
+                _ => match rest.split_once(',') {
+                    Some((value, order)) => (value.trim(), Some(order.trim())),
+                    None => (rest, None),
+                },
+            };
+            seed.entries
+                .push(SeedEntry::parse(address, kind, value, order).map_err(at_line)?);
+        }
+        Ok(seed)
+    }
+
+    /// 解析 JSON 种子文件
+    #[cfg(feature = "seed-json")]
+    pub fn parse_json(text: &str) -> io::Result<Self> {
+        use serde_json::Value;
+
+        let items: Vec<serde_json::Map<String, Value>> = serde_json::from_str(text)
+            .map_err(|err| invalid_data(format!("invalid seed JSON: {err}")))?;
+        let mut seed = Self::default();
+        for (index, item) in items.iter().enumerate() {
+            let at_item = |message: String| invalid_data(format!("entry {}: {message}", index + 1));
+            let field = |name: &str| -> io::Result<Option<String>> {
+                match item.get(name) {
+                    None | Some(Value::Null) => Ok(None),
+                    Some(Value::String(text)) => Ok(Some(text.clone())),
+                    Some(value @ (Value::Number(_) | Value::Bool(_))) => {
+                        Ok(Some(value.to_string()))
+                    }
+                    Some(_) => Err(at_item(format!("{name} must be a string or a number"))),
+                }
+            };
+            let required =
+                |name: &str| field(name)?.ok_or_else(|| at_item(format!("missing {name}")));
+            let order = field("order")?;
+            seed.entries.push(
+                SeedEntry::parse(
+                    &required("address")?,
+                    &required("type")?,
+                    &required("value")?,
+                    order.as_deref(),
+                )
+                .map_err(at_item)?,
+            );
+        }
+        Ok(seed)
+    }
+}
+
+impl SeedEntry {
+    fn parse(address: &str, kind: &str, value: &str, order: Option<&str>) -> Result<Self, String> {
+        fn number<T: WordValue + std::str::FromStr>(
+            value: &str,
+            order: WordOrder,
+        ) -> Result<Vec<u16>, String> {
+            let value: T = value
+                .parse()
+                .map_err(|_| format!("invalid {} value {value:?}", std::any::type_name::<T>()))?;
+            let mut words = Vec::with_capacity(T::WORDS);
+            value.push_words(order, &mut words);
+            Ok(words)
+        }
+
+        if address.is_empty() {
+            return Err("missing address".to_string());
+        }
+        let order = match order {
+            None | Some("" | "low") => WordOrder::LowFirst,
+            Some("high") => WordOrder::HighFirst,
+            Some(other) => return Err(format!("unknown word order {other:?}")),
+        };
+        let value = match kind {
+            "bool" => SeedValue::Bit(match value {
+                "0" | "false" => false,
+                "1" | "true" => true,
+                _ => return Err(format!("invalid bool value {value:?}")),
+            }),
+            "u16" => SeedValue::Words(number::<u16>(value, order)?),
+            "i16" => SeedValue::Words(number::<i16>(value, order)?),
+            "u32" => SeedValue::Words(number::<u32>(value, order)?),
+            "i32" => SeedValue::Words(number::<i32>(value, order)?),
+            "f32" => SeedValue::Words(number::<f32>(value, order)?),
+            "u64" => SeedValue::Words(number::<u64>(value, order)?),
+            "i64" => SeedValue::Words(number::<i64>(value, order)?),
+            "f64" => SeedValue::Words(number::<f64>(value, order)?),
+            "string" => SeedValue::Words(
+                value
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+                    .collect(),
+            ),
+            "wstring" => SeedValue::Words(value.encode_utf16().chain([0]).collect()),
+            other => return Err(format!("unknown type {other:?}")),
+        };
+        Ok(Self {
+            address: address.to_string(),
+            value,
+        })
+    }
+}
+
+impl Simulator {
+    /// 把 `seed` 的全部条目写入模拟器，返回写入的条目数，见 [`seed`](self) 模块
+    ///
+    /// 先检查全部地址可以写入，任一条目超出范围时不写入任何数据。
+    pub fn load_seed(&self, seed: &Seed) -> io::Result<usize> {
+        for entry in &seed.entries {
+            let checked = match &entry.value {
+                SeedValue::Bit(_) => self.read_bits(&entry.address, 1).map(drop),
+                SeedValue::Words(words) => self.read_words(&entry.address, words.len()).map(drop),
+            };
+            checked.map_err(|err| invalid_data(format!("{}: {err}", entry.address)))?;
+        }
+        for entry in &seed.entries {
+            let written = match &entry.value {
+                SeedValue::Bit(bit) => self.write_bits(&entry.address, &[*bit]),
+                SeedValue::Words(words) => self.write_words(&entry.address, words),
+            };
+            written.map_err(|err| invalid_data(format!("{}: {err}", entry.address)))?;
+        }
+        Ok(seed.entries.len())
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_typed_values_from_csv() {
+        let csv = "address,type,value\n\
+                   # 主轴\n\
+                   M10,bool,1\n\
+                   D100,i16,-2\n\
+                   D102,f32,3.25,high\n\
+                   D104,u32,65536\n\
+                   D110,string,AB,C\n";
+        let seed = Seed::parse_csv(csv).unwrap();
+        let simulator = Simulator::new();
+        assert_eq!(simulator.load_seed(&seed).unwrap(), 5);
+        assert_eq!(simulator.read_bits("M10", 1).unwrap(), [true]);
+        assert_eq!(
+            simulator.read_words("D100", 6).unwrap(),
+            [0xFFFE, 0, 0x4050, 0x0000, 0x0000, 0x0001]
+        );
+        assert_eq!(simulator.read_words("D110", 2).unwrap(), [0x4241, 0x432C]);
+
+        assert!(Seed::parse_csv("D0,u16,70000").is_err());
+        assert!(Seed::parse_csv("D0,f32,1,middle").is_err());
+        // 超出范围的条目使整个种子不被写入
+        let seed = Seed::parse_csv("D0,u16,7\nD999999,u16,1").unwrap();
+        assert!(simulator.load_seed(&seed).is_err());
+        assert_eq!(simulator.read_words("D0", 1).unwrap(), [0]);
+    }
+
+    #[cfg(feature = "seed-json")]
+    #[test]
+    fn seeds_typed_values_from_json() {
+        let json = r#"[
+            { "address": "D0", "type": "f64", "value": 1.5 },
+            { "address": "D4", "type": "u32", "value": "1", "order": "high" },
+            { "address": "D6", "type": "wstring", "value": "温" }
+        ]"#;
+        let simulator = Simulator::new();
+        simulator
+            .load_seed(&Seed::parse_json(json).unwrap())
+            .unwrap();
+        assert_eq!(simulator.read_words("D3", 1).unwrap(), [0x3FF8]);
+        assert_eq!(simulator.read_words("D4", 4).unwrap(), [0, 1, 0x6E29, 0]);
+        assert!(Seed::parse_json(r#"[{ "address": "D0", "value": 1 }]"#).is_err());
+    }
+}
@@ -1,4 +1,8 @@
-use std::{future::Future, sync::Arc};
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use crate::frame::{Request, Response, Route};
+
+use super::Session;
 
 /// `Service` trait
 pub trait Service {
@@ -8,6 +12,59 @@ pub trait Service {
     type Future: Future<Output = Result<Self::Response, Self::Exception>> + Send;
 
     fn call(&self, req: Self::Request) -> Self::Future;
+
+    /// 处理发往 `route` 的请求
+    ///
+    /// 服务端对每个请求调用此方法，默认忽略访问路径直接调用 [`Self::call`]；
+    /// 模拟多 CPU 或多站点的服务可按请求目标模块 I/O 编号等分派。
+    fn call_routed(&self, route: Route, req: Self::Request) -> Self::Future {
+        let _ = route;
+        self.call(req)
+    }
+
+    /// 在连接会话 `session` 中处理发往 `route` 的请求
+    ///
+    /// 服务端实际调用的入口，默认忽略会话并调用 [`Self::call_routed`]；
+    /// 需要区分对端或在同一连接的请求之间保存状态的服务可重写此方法。
+    fn call_with_session(
+        &self,
+        session: &Arc<Session>,
+        route: Route,
+        req: Self::Request,
+    ) -> Self::Future {
+        let _ = session;
+        self.call_routed(route, req)
+    }
+}
+
+/// 包装服务使用的装箱 future
+pub(crate) type BoxedFuture<E> = Pin<Box<dyn Future<Output = Result<Response, E>> + Send>>;
+
+/// 包装服务稍后转发请求所需的上下文：内部服务、会话与访问路径
+pub(crate) struct Upstream<S> {
+    inner: Arc<S>,
+    session: Option<Arc<Session>>,
+    route: Route,
+}
+
+impl<S> Upstream<S>
+where
+    S: Service<Request = Request<'static>, Response = Response>,
+{
+    pub(crate) fn new(inner: &Arc<S>, session: Option<&Arc<Session>>, route: Route) -> Self {
+        Self {
+            inner: inner.clone(),
+            session: session.cloned(),
+            route,
+        }
+    }
+
+    pub(crate) fn call(&self, request: Request<'static>) -> S::Future {
+        match &self.session {
+            Some(session) => self.inner.call_with_session(session, self.route, request),
+            None => self.inner.call_routed(self.route, request),
+        }
+    }
 }
 
 // Arc<T>的Service实现，允许在Arc中使用Service
@@ -23,6 +80,19 @@ where
     fn call(&self, req: Self::Request) -> Self::Future {
         (**self).call(req)
     }
+
+    fn call_routed(&self, route: Route, req: Self::Request) -> Self::Future {
+        (**self).call_routed(route, req)
+    }
+
+    fn call_with_session(
+        &self,
+        session: &Arc<Session>,
+        route: Route,
+        req: Self::Request,
+    ) -> Self::Future {
+        (**self).call_with_session(session, route, req)
+    }
 }
 
 #[cfg(test)]
@@ -80,6 +150,9 @@ mod tests {
                     registers.insert(addr.parse::<u16>().unwrap_or(0), value);
                     Ok(Response::WriteU8s())
                 }
+                Request::ReadBits(_, _) | Request::WriteBits(_, _) | Request::Command(..) => {
+                    Err(ProtocolError::NotImplemented)
+                }
             };
             future::ready(res)
         }
@@ -0,0 +1,74 @@
+//! 连接会话
+//!
+//! 服务端为每个 TCP 连接创建一个 [`Session`]，并随每个请求通过
+//! [`Service::call_with_session`](super::Service::call_with_session) 交给服务，
+//! 服务可借此识别对端，并在同一连接的请求之间保存认证身份、监视登记等状态。
+
+use std::{
+    net::SocketAddr,
+    sync::{Mutex, MutexGuard},
+};
+
+pub use crate::frame::FrameType;
+
+/// 单个连接的会话状态，见[模块文档](self)
+///
+/// 同一连接上并发处理的请求共享同一个会话，可变状态以内部锁保护。
+#[derive(Debug)]
+pub struct Session {
+    peer: SocketAddr,
+    frame_type: FrameType,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    identity: Option<String>,
+    monitor: Option<Vec<u8>>,
+}
+
+impl Session {
+    pub fn new(peer: SocketAddr, frame_type: FrameType) -> Self {
+        Self {
+            peer,
+            frame_type,
+            state: Mutex::default(),
+        }
+    }
+
+    /// 对端地址
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// 连接使用的帧格式
+    pub fn frame_type(&self) -> FrameType {
+        self.frame_type
+    }
+
+    /// 认证后的身份，未认证时为 `None`
+    pub fn identity(&self) -> Option<String> {
+        self.lock().identity.clone()
+    }
+
+    /// 设置（或以 `None` 清除）认证身份
+    pub fn set_identity(&self, identity: Option<String>) {
+        self.lock().identity = identity;
+    }
+
+    /// 监视登记（0801）的请求数据，尚未登记时为 `None`
+    pub fn monitor_registration(&self) -> Option<Vec<u8>> {
+        self.lock().monitor.clone()
+    }
+
+    /// 保存（或以 `None` 清除）监视登记的请求数据，供之后的监视（0802）请求使用
+    pub fn set_monitor_registration(&self, data: Option<Vec<u8>>) {
+        self.lock().monitor = data;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,1011 @@
+//! 内存 PLC 模拟器
+//!
+//! [`Simulator`] 实现了 [`Service`]，可直接交给 [`Server`](super::Server) 使用，
+//! 也可以在测试中直接读写其中的数据。每种软元件是一块独立的连续内存：
+//!
+//! - 字软元件（默认 D）按字存放，位访问时第 `n` 点对应起始字之后第 `n / 16` 个字的第 `n % 16` 位；
+//! - 位软元件（默认 X、Y、M、L）按点存放，字访问时每个字打包 16 点，低位在前。
+//!
+//! 地址编号的进制与客户端一致（X、Y 为十六进制）。访问超出内存范围或未配置的软元件时返回错误，
+//! 不会截断或补零。
+//!
+//! 经由 [`Service`] 处理的写请求会记入环形的写入日志（[`Simulator::journal`]），
+//! 记录地址、写入前后的值、时间与来源连接，供集成测试核对应用实际执行的写入顺序。
+//! 以 [`Simulator::connection`] 为每个连接创建服务即可记录对端地址。
+//!
+//! 与实际 PLC 的停电保持范围一样，可以用 [`Simulator::with_latched`] 指定各软元件的保持范围；
+//! [`Simulator::power_cycle`] 模拟断电重启，保持范围以外的数据清零，用于测试上电后的行为。
+//!
+//! 多 CPU 系统由 [`MultiCpu`] 模拟：按请求目标模块 I/O 编号把请求分派给各自独立的 [`Simulator`]。
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, fs, future,
+    io::{self, BufRead, Write as _},
+    net::SocketAddr,
+    ops::{Bound, Range, RangeBounds},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    frame::{
+        convert_to_base, find_instruction_code, find_prefix_and_base_by_code, split_address,
+        NumberBase, PlcProfile, ProtocolError, Request, Response, Route,
+    },
+    trace,
+};
+
+use super::{Service, Session};
+
+/// 默认字软元件的字数
+const DEFAULT_WORDS: usize = 2000;
+/// 默认位软元件的点数
+const DEFAULT_BITS: usize = 4000;
+/// 随机读取命令
+const RANDOM_READ: u16 = 0x0403;
+/// 多块批量读取命令
+const BLOCK_READ: u16 = 0x0406;
+/// 多块批量写入命令
+const BLOCK_WRITE: u16 = 0x1406;
+/// 写入日志默认保留的条数
+const DEFAULT_JOURNAL_CAPACITY: usize = 1024;
+/// 按点存放的位软元件
+const BIT_DEVICES: &[&str] = &[
+    "X", "Y", "F", "M", "L", "B", "SB", "SM", "TS", "TC", "CS", "CC",
+];
+
+#[derive(Debug)]
+enum Zone {
+    Words(Vec<u16>),
+    Bits(Vec<bool>),
+}
+
+impl Zone {
+    fn read_words(&self, start: usize, count: usize) -> Result<Vec<u16>, ProtocolError> {
+        match self {
+            Zone::Words(words) => Ok(range(words, start, count)?.to_vec()),
+            Zone::Bits(bits) => Ok(range(bits, start, count * 16)?
+                .chunks(16)
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .fold(0, |word, (i, &bit)| word | (u16::from(bit) << i))
+                })
+                .collect()),
+        }
+    }
+
+    fn write_words(&mut self, start: usize, values: &[u16]) -> Result<(), ProtocolError> {
+        match self {
+            Zone::Words(words) => {
+                range_mut(words, start, values.len())?.copy_from_slice(values);
+            }
+            Zone::Bits(bits) => {
+                let target = range_mut(bits, start, values.len() * 16)?;
+                for (i, bit) in target.iter_mut().enumerate() {
+                    *bit = values[i / 16] >> (i % 16) & 1 != 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_bits(&self, start: usize, count: usize) -> Result<Vec<bool>, ProtocolError> {
+        match self {
+            Zone::Bits(bits) => Ok(range(bits, start, count)?.to_vec()),
+            Zone::Words(words) => {
+                let source = range(words, start, count.div_ceil(16))?;
+                Ok((0..count)
+                    .map(|i| source[i / 16] >> (i % 16) & 1 != 0)
+                    .collect())
+            }
+        }
+    }
+
+    fn write_bits(&mut self, start: usize, values: &[bool]) -> Result<(), ProtocolError> {
+        match self {
+            Zone::Bits(bits) => {
+                range_mut(bits, start, values.len())?.copy_from_slice(values);
+            }
+            Zone::Words(words) => {
+                let target = range_mut(words, start, values.len().div_ceil(16))?;
+                for (i, &value) in values.iter().enumerate() {
+                    let mask = 1 << (i % 16);
+                    if value {
+                        target[i / 16] |= mask;
+                    } else {
+                        target[i / 16] &= !mask;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 将不在 `latched` 范围内的字或点清零
+    fn clear_volatile(&mut self, latched: &[Range<usize>]) {
+        let is_latched = |i: usize| latched.iter().any(|range| range.contains(&i));
+        match self {
+            Zone::Words(words) => {
+                for (i, word) in words.iter_mut().enumerate() {
+                    if !is_latched(i) {
+                        *word = 0;
+                    }
+                }
+            }
+            Zone::Bits(bits) => {
+                for (i, bit) in bits.iter_mut().enumerate() {
+                    if !is_latched(i) {
+                        *bit = false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 写入日志中的数据，与写请求的类型一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalData {
+    /// 按字写入（`WriteU8s`）
+    Words(Vec<u16>),
+    /// 按位写入（`WriteBits`）
+    Bits(Vec<bool>),
+}
+
+impl fmt::Display for JournalData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalData::Words(words) => {
+                for (i, word) in words.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { " " };
+                    write!(f, "{sep}{word:04X}")?;
+                }
+            }
+            JournalData::Bits(bits) => {
+                for &bit in bits {
+                    f.write_str(if bit { "1" } else { "0" })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一次写入的日志记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteRecord {
+    /// 写请求中的起始地址
+    pub address: String,
+    /// 写入前的值
+    pub old: JournalData,
+    /// 写入的值
+    pub new: JournalData,
+    /// 写入时间
+    pub timestamp: SystemTime,
+    /// 发出请求的连接，直接调用 [`Simulator`] 的 [`Service`] 时为 `None`
+    pub peer: Option<SocketAddr>,
+}
+
+/// 以制表符分隔：Unix 时间（秒）、对端地址、起始地址、写入前、写入后
+impl fmt::Display for WriteRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(f, "{}.{:06}\t", time.as_secs(), time.subsec_micros())?;
+        match self.peer {
+            Some(peer) => write!(f, "{peer}\t")?,
+            None => f.write_str("-\t")?,
+        }
+        write!(f, "{}\t{}\t{}", self.address, self.old, self.new)
+    }
+}
+
+#[derive(Debug)]
+struct Journal {
+    records: VecDeque<WriteRecord>,
+    capacity: usize,
+}
+
+impl Journal {
+    fn push(&mut self, record: WriteRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+}
+
+/// 按软元件分区存放数据的模拟 PLC
+#[derive(Debug)]
+pub struct Simulator {
+    zones: Mutex<HashMap<String, Zone>>,
+    /// 各软元件的停电保持范围
+    latched: Mutex<HashMap<String, Vec<Range<usize>>>>,
+    journal: Mutex<Journal>,
+}
+
+impl Simulator {
+    /// 默认配置：D0-D1999，X、Y、M、L 各 4000 点
+    pub fn new() -> Self {
+        let simulator = Self::empty().with_word_zone("D", DEFAULT_WORDS);
+        ["X", "Y", "M", "L"]
+            .into_iter()
+            .fold(simulator, |simulator, prefix| {
+                simulator.with_bit_zone(prefix, DEFAULT_BITS)
+            })
+    }
+
+    /// 为 `profile` 支持的每种软元件创建区域：字软元件 2000 字，位软元件 4000 点
+    pub fn from_profile(profile: &PlcProfile) -> Self {
+        profile
+            .devices
+            .iter()
+            .fold(Self::empty(), |simulator, device| {
+                if BIT_DEVICES.contains(&&*device.prefix) {
+                    simulator.with_bit_zone(&device.prefix, DEFAULT_BITS)
+                } else {
+                    simulator.with_word_zone(&device.prefix, DEFAULT_WORDS)
+                }
+            })
+    }
+
+    /// 不含任何软元件的模拟器
+    pub fn empty() -> Self {
+        Self {
+            zones: Mutex::new(HashMap::new()),
+            latched: Mutex::new(HashMap::new()),
+            journal: Mutex::new(Journal {
+                records: VecDeque::new(),
+                capacity: DEFAULT_JOURNAL_CAPACITY,
+            }),
+        }
+    }
+
+    /// 写入日志保留的条数，超出时丢弃最早的记录，默认 1024，为 0 时不记录
+    #[must_use]
+    pub fn with_journal_capacity(self, capacity: usize) -> Self {
+        {
+            let mut journal = self.lock_journal();
+            journal.capacity = capacity;
+            let excess = journal.records.len().saturating_sub(capacity);
+            journal.records.drain(..excess);
+        }
+        self
+    }
+
+    /// 为来自 `peer` 的连接创建服务，其写入在日志中记录对端地址
+    pub fn connection(self: &Arc<Self>, peer: SocketAddr) -> Connection {
+        Connection {
+            simulator: Arc::clone(self),
+            peer,
+        }
+    }
+
+    /// 添加（或替换）一个字软元件区域，初始值全为 0
+    #[must_use]
+    pub fn with_word_zone(self, prefix: &str, words: usize) -> Self {
+        self.lock()
+            .insert(prefix.to_string(), Zone::Words(vec![0; words]));
+        self
+    }
+
+    /// 添加（或替换）一个位软元件区域，初始值全为 OFF
+    #[must_use]
+    pub fn with_bit_zone(self, prefix: &str, bits: usize) -> Self {
+        self.lock()
+            .insert(prefix.to_string(), Zone::Bits(vec![false; bits]));
+        self
+    }
+
+    /// 将 `prefix` 软元件的 `numbers` 范围设为停电保持，可多次调用添加多个范围
+    ///
+    /// 范围按软元件编号计，字软元件为字、位软元件为点（X、Y 等十六进制编号同样以数值表示），
+    /// `..` 表示整个区域。未设置保持范围的软元件在 [`Self::power_cycle`] 时全部清零。
+    #[must_use]
+    pub fn with_latched(self, prefix: &str, numbers: impl RangeBounds<usize>) -> Self {
+        let start = match numbers.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match numbers.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => usize::MAX,
+        };
+        self.latched
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(prefix.to_string())
+            .or_default()
+            .push(start..end);
+        self
+    }
+
+    /// 模拟断电重启：保持范围以外的数据清零，写入日志不受影响
+    pub fn power_cycle(&self) {
+        let latched = self
+            .latched
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (prefix, zone) in self.lock().iter_mut() {
+            zone.clear_volatile(latched.get(prefix).map_or(&[], Vec::as_slice));
+        }
+    }
+
+    /// 从 `addr` 开始读取 `count` 个字
+    pub fn read_words(&self, addr: &str, count: usize) -> Result<Vec<u16>, ProtocolError> {
+        let (prefix, start) = parse_address(addr)?;
+        let zones = self.lock();
+        zones
+            .get(prefix)
+            .ok_or_else(|| unknown_device(addr))?
+            .read_words(start, count)
+    }
+
+    /// 从 `addr` 开始写入字数据，不记入写入日志
+    pub fn write_words(&self, addr: &str, values: &[u16]) -> Result<(), ProtocolError> {
+        let (prefix, start) = parse_address(addr)?;
+        let mut zones = self.lock();
+        zones
+            .get_mut(prefix)
+            .ok_or_else(|| unknown_device(addr))?
+            .write_words(start, values)
+    }
+
+    /// 以 `Context::dump_area` 导出的文本设置初始数据，返回写入的字数
+    ///
+    /// 每个区域以 `@<起始地址> <字数>` 开头，随后为以空白分隔的十六进制字；
+    /// 位软元件按字写入，每个字覆盖 16 点。写入不记入写入日志。
+    pub fn load_dump(&self, reader: impl BufRead) -> io::Result<usize> {
+        let mut section: Option<(String, usize, Vec<u16>)> = None;
+        let mut loaded = 0;
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('@') {
+                if let Some(section) = section.take() {
+                    loaded += self.finish_dump_section(section)?;
+                }
+                let (address, count) = header
+                    .split_once(' ')
+                    .and_then(|(address, count)| Some((address, count.trim().parse().ok()?)))
+                    .ok_or_else(|| invalid_data(format!("invalid area header {line:?}")))?;
+                section = Some((address.to_string(), count, Vec::with_capacity(count)));
+                continue;
+            }
+            let (_, _, words) = section
+                .as_mut()
+                .ok_or_else(|| invalid_data("data line before area header".to_string()))?;
+            for word in line.split_whitespace() {
+                words.push(
+                    u16::from_str_radix(word, 16)
+                        .map_err(|_| invalid_data(format!("invalid word {word:?}")))?,
+                );
+            }
+        }
+        if let Some(section) = section {
+            loaded += self.finish_dump_section(section)?;
+        }
+        Ok(loaded)
+    }
+
+    fn finish_dump_section(
+        &self,
+        (address, count, words): (String, usize, Vec<u16>),
+    ) -> io::Result<usize> {
+        if words.len() != count {
+            return Err(invalid_data(format!(
+                "area {address} declares {count} words but has {}",
+                words.len()
+            )));
+        }
+        self.write_words(&address, &words)
+            .map_err(|err| invalid_data(format!("area {address}: {err}")))?;
+        Ok(count)
+    }
+
+    /// 从 `addr` 开始读取 `count` 点
+    pub fn read_bits(&self, addr: &str, count: usize) -> Result<Vec<bool>, ProtocolError> {
+        let (prefix, start) = parse_address(addr)?;
+        let zones = self.lock();
+        zones
+            .get(prefix)
+            .ok_or_else(|| unknown_device(addr))?
+            .read_bits(start, count)
+    }
+
+    /// 从 `addr` 开始写入位数据，不记入写入日志
+    pub fn write_bits(&self, addr: &str, values: &[bool]) -> Result<(), ProtocolError> {
+        let (prefix, start) = parse_address(addr)?;
+        let mut zones = self.lock();
+        zones
+            .get_mut(prefix)
+            .ok_or_else(|| unknown_device(addr))?
+            .write_bits(start, values)
+    }
+
+    /// 写入日志中的全部记录，按写入顺序排列
+    pub fn journal(&self) -> Vec<WriteRecord> {
+        self.lock_journal().records.iter().cloned().collect()
+    }
+
+    /// 取出并清空写入日志
+    pub fn take_journal(&self) -> Vec<WriteRecord> {
+        self.lock_journal().records.drain(..).collect()
+    }
+
+    /// 清空写入日志
+    pub fn clear_journal(&self) {
+        self.lock_journal().records.clear();
+    }
+
+    /// 将写入日志按行写入文件，格式见 [`WriteRecord`] 的 `Display`
+    pub fn dump_journal(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        for record in self.journal() {
+            writeln!(file, "{record}")?;
+        }
+        file.flush()
+    }
+
+    /// 处理一个请求，与 [`Service::call`] 相同但同步返回
+    pub fn handle(&self, request: &Request<'_>) -> Result<Response, ProtocolError> {
+        self.handle_from(request, None)
+    }
+
+    fn handle_from(
+        &self,
+        request: &Request<'_>,
+        peer: Option<SocketAddr>,
+    ) -> Result<Response, ProtocolError> {
+        match request {
+            Request::ReadU8s(addr, count) => {
+                let words = self.read_words(addr, *count as usize)?;
+                Ok(Response::ReadU8s(
+                    words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+                ))
+            }
+            Request::WriteU8s(addr, bytes) => {
+                if bytes.len() % 2 != 0 {
+                    return Err(ProtocolError::OddByteCount(bytes.len()));
+                }
+                let words: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                    .collect();
+                self.write_journaled(addr, JournalData::Words(words), peer)?;
+                Ok(Response::WriteU8s())
+            }
+            Request::ReadBits(addr, count) => {
+                Ok(Response::ReadBits(self.read_bits(addr, *count as usize)?))
+            }
+            Request::WriteBits(addr, bits) => {
+                self.write_journaled(addr, JournalData::Bits(bits.to_vec()), peer)?;
+                Ok(Response::WriteBits())
+            }
+            Request::Command(RANDOM_READ, 0x0000, data) => Ok(Response::Command(
+                RANDOM_READ,
+                0x0000,
+                self.read_random(data)?,
+            )),
+            Request::Command(BLOCK_READ, 0x0000, data) => Ok(Response::Command(
+                BLOCK_READ,
+                0x0000,
+                self.read_blocks(data)?,
+            )),
+            Request::Command(BLOCK_WRITE, 0x0000, data) => {
+                self.write_blocks(data, peer)?;
+                Ok(Response::Command(BLOCK_WRITE, 0x0000, Vec::new()))
+            }
+            Request::Command(_, _, _) => Err(ProtocolError::NotImplemented),
+        }
+    }
+
+    /// 多块批量读取（0406/0000）：先为字软元件块数与位软元件块数，随后每块 3 字节编号、
+    /// 1 字节软元件代码与 2 字节字数；位软元件块的每个字覆盖 16 点
+    fn read_blocks(&self, data: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let blocks = match data {
+            [words, bits, ..] => *words as usize + *bits as usize,
+            _ => return Err(ProtocolError::OutOfRange),
+        };
+        let entries = &data[2..];
+        if entries.len() != blocks * 6 {
+            return Err(ProtocolError::OutOfRange);
+        }
+        let zones = self.lock();
+        let mut out = Vec::new();
+        for entry in entries.chunks_exact(6) {
+            let number = u32::from_le_bytes([entry[0], entry[1], entry[2], 0]) as usize;
+            let (prefix, _) = find_prefix_and_base_by_code(entry[3]).ok_or_else(|| {
+                ProtocolError::InvalidAddress(format!("device code {:#04X}", entry[3]))
+            })?;
+            let words = u16::from_le_bytes([entry[4], entry[5]]) as usize;
+            let zone = zones.get(prefix).ok_or_else(|| unknown_device(prefix))?;
+            for word in zone.read_words(number, words)? {
+                out.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        Ok(out)
+    }
+
+    /// 多块批量写入（1406/0000）：格式同多块批量读取，每块的 6 字节之后紧跟该块的写入数据
+    ///
+    /// 先检查全部块可以写入，任一块无效时不写入任何数据；每块在写入日志中记为一条。
+    fn write_blocks(&self, data: &[u8], peer: Option<SocketAddr>) -> Result<(), ProtocolError> {
+        let blocks = match data {
+            [words, bits, ..] => *words as usize + *bits as usize,
+            _ => return Err(ProtocolError::OutOfRange),
+        };
+        let mut rest = &data[2..];
+        let mut writes = Vec::with_capacity(blocks);
+        for _ in 0..blocks {
+            let [n0, n1, n2, code, c0, c1, tail @ ..] = rest else {
+                return Err(ProtocolError::OutOfRange);
+            };
+            let words = u16::from_le_bytes([*c0, *c1]) as usize;
+            if tail.len() < words * 2 {
+                return Err(ProtocolError::OutOfRange);
+            }
+            let (values, remaining) = tail.split_at(words * 2);
+            rest = remaining;
+
+            let number = u32::from_le_bytes([*n0, *n1, *n2, 0]);
+            let (prefix, base) = find_prefix_and_base_by_code(*code)
+                .ok_or_else(|| ProtocolError::InvalidAddress(format!("device code {code:#04X}")))?;
+            let address = match base {
+                NumberBase::Decimal => format!("{prefix}{number}"),
+                NumberBase::Hexadecimal => format!("{prefix}{number:X}"),
+            };
+            self.read_words(&address, words)?;
+            let values = values
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            writes.push((address, values));
+        }
+        if !rest.is_empty() {
+            return Err(ProtocolError::OutOfRange);
+        }
+        for (address, values) in writes {
+            self.write_journaled(&address, JournalData::Words(values), peer)?;
+        }
+        Ok(())
+    }
+
+    /// 字单位随机读取（0403/0000）：先为字访问点数与双字访问点数，随后每点 3 字节编号与 1 字节软元件代码
+    fn read_random(&self, data: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let (words, dwords) = match data {
+            [words, dwords, ..] => (*words as usize, *dwords as usize),
+            _ => return Err(ProtocolError::OutOfRange),
+        };
+        let entries = &data[2..];
+        if entries.len() != (words + dwords) * 4 {
+            return Err(ProtocolError::OutOfRange);
+        }
+        let zones = self.lock();
+        let mut out = Vec::with_capacity(words * 2 + dwords * 4);
+        for (index, entry) in entries.chunks_exact(4).enumerate() {
+            let number = u32::from_le_bytes([entry[0], entry[1], entry[2], 0]) as usize;
+            let (prefix, _) = find_prefix_and_base_by_code(entry[3]).ok_or_else(|| {
+                ProtocolError::InvalidAddress(format!("device code {:#04X}", entry[3]))
+            })?;
+            let width = if index < words { 1 } else { 2 };
+            let zone = zones.get(prefix).ok_or_else(|| unknown_device(prefix))?;
+            for word in zone.read_words(number, width)? {
+                out.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        Ok(out)
+    }
+
+    /// 写入并记录日志，读取旧值与写入在同一次加锁内完成
+    fn write_journaled(
+        &self,
+        addr: &str,
+        new: JournalData,
+        peer: Option<SocketAddr>,
+    ) -> Result<(), ProtocolError> {
+        let (prefix, start) = parse_address(addr)?;
+        let mut zones = self.lock();
+        let zone = zones.get_mut(prefix).ok_or_else(|| unknown_device(addr))?;
+        let old = match &new {
+            JournalData::Words(words) => {
+                let old = zone.read_words(start, words.len())?;
+                zone.write_words(start, words)?;
+                JournalData::Words(old)
+            }
+            JournalData::Bits(bits) => {
+                let old = zone.read_bits(start, bits.len())?;
+                zone.write_bits(start, bits)?;
+                JournalData::Bits(old)
+            }
+        };
+        self.lock_journal().push(WriteRecord {
+            address: addr.to_string(),
+            old,
+            new,
+            timestamp: SystemTime::now(),
+            peer,
+        });
+        Ok(())
+    }
+
+    fn lock_journal(&self) -> std::sync::MutexGuard<'_, Journal> {
+        self.journal
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Zone>> {
+        // 持有锁时不会 panic，锁中毒时数据仍然一致
+        self.zones
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Service for Simulator {
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = ProtocolError;
+    type Future = future::Ready<Result<Self::Response, Self::Exception>>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        future::ready(serve(self, &req, None))
+    }
+
+    /// 写入日志中记录会话的对端地址
+    fn call_with_session(
+        &self,
+        session: &Arc<Session>,
+        _route: Route,
+        req: Self::Request,
+    ) -> Self::Future {
+        future::ready(serve(self, &req, Some(session.peer())))
+    }
+}
+
+/// 绑定了对端地址的 [`Simulator`] 服务，由 [`Simulator::connection`] 创建
+#[derive(Debug, Clone)]
+pub struct Connection {
+    simulator: Arc<Simulator>,
+    peer: SocketAddr,
+}
+
+impl Connection {
+    /// 对端地址
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+}
+
+impl Service for Connection {
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = ProtocolError;
+    type Future = future::Ready<Result<Self::Response, Self::Exception>>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        future::ready(serve(&self.simulator, &req, Some(self.peer)))
+    }
+}
+
+/// 多 CPU 系统：按请求目标模块 I/O 编号分派到各 CPU 的 [`Simulator`]
+///
+/// 本站（03FF）与 1 号 CPU（03E0）默认指向控制 CPU，其他 CPU（如运动 CPU）
+/// 以 [`Self::with_cpu`] 添加。发往未配置 I/O 编号的请求返回 `InvalidAddress` 错误。
+#[derive(Debug, Clone)]
+pub struct MultiCpu {
+    cpus: HashMap<u16, Arc<Simulator>>,
+}
+
+impl MultiCpu {
+    pub fn new(control: Arc<Simulator>) -> Self {
+        let cpus = [Route::LOCAL.dest_io, Route::multi_cpu(1).dest_io]
+            .into_iter()
+            .map(|dest_io| (dest_io, Arc::clone(&control)))
+            .collect();
+        Self { cpus }
+    }
+
+    /// 添加（或替换）请求目标模块 I/O 编号为 `dest_io` 的 CPU，
+    /// 多 CPU 系统中 2-4 号 CPU 为 03E1-03E3，见 [`Route::multi_cpu`]
+    #[must_use]
+    pub fn with_cpu(mut self, dest_io: u16, cpu: Arc<Simulator>) -> Self {
+        self.cpus.insert(dest_io, cpu);
+        self
+    }
+
+    /// 请求目标模块 I/O 编号为 `dest_io` 的 CPU
+    pub fn cpu(&self, dest_io: u16) -> Option<&Arc<Simulator>> {
+        self.cpus.get(&dest_io)
+    }
+
+    /// 处理发往 `route` 的请求
+    pub fn handle(&self, route: Route, request: &Request<'_>) -> Result<Response, ProtocolError> {
+        self.cpu(route.dest_io)
+            .ok_or_else(|| {
+                ProtocolError::InvalidAddress(format!("no CPU at I/O number {:04X}", route.dest_io))
+            })?
+            .handle(request)
+    }
+}
+
+impl Service for MultiCpu {
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = ProtocolError;
+    type Future = future::Ready<Result<Self::Response, Self::Exception>>;
+
+    /// 发往本站
+    fn call(&self, req: Self::Request) -> Self::Future {
+        self.call_routed(Route::LOCAL, req)
+    }
+
+    fn call_routed(&self, route: Route, req: Self::Request) -> Self::Future {
+        let result = self.handle(route, &req);
+        if let Err(err) = &result {
+            trace::warning!(dest_io = route.dest_io; "Simulator rejected {req:?}: {err}");
+        }
+        future::ready(result)
+    }
+}
+
+fn serve(
+    simulator: &Simulator,
+    req: &Request<'_>,
+    peer: Option<SocketAddr>,
+) -> Result<Response, ProtocolError> {
+    let result = simulator.handle_from(req, peer);
+    if let Err(err) = &result {
+        trace::warning!("Simulator rejected {req:?}: {err}");
+    }
+    result
+}
+
+/// 解析地址，返回软元件前缀与编号
+fn parse_address(addr: &str) -> Result<(&str, usize), ProtocolError> {
+    let invalid = || ProtocolError::InvalidAddress(addr.to_string());
+    let (prefix, number) = split_address(addr).ok_or_else(invalid)?;
+    let (_, number_base) = find_instruction_code(prefix).ok_or_else(invalid)?;
+    let number = convert_to_base(number, number_base).ok_or_else(invalid)?;
+    Ok((prefix, number as usize))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn unknown_device(addr: &str) -> ProtocolError {
+    ProtocolError::InvalidAddress(format!("{addr} (device not simulated)"))
+}
+
+fn range<T>(data: &[T], start: usize, count: usize) -> Result<&[T], ProtocolError> {
+    let end = start.checked_add(count).ok_or(ProtocolError::OutOfRange)?;
+    data.get(start..end).ok_or(ProtocolError::OutOfRange)
+}
+
+fn range_mut<T>(data: &mut [T], start: usize, count: usize) -> Result<&mut [T], ProtocolError> {
+    let end = start.checked_add(count).ok_or(ProtocolError::OutOfRange)?;
+    data.get_mut(start..end).ok_or(ProtocolError::OutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_and_bit_aliasing() {
+        let simulator = Simulator::new();
+
+        // 位软元件按字写入：X1 开始的 16 点，X 为十六进制编号
+        simulator.write_words("X1", &[0xFFFF]).unwrap();
+        assert_eq!(simulator.read_bits("XF", 1).unwrap(), [true]);
+        assert_eq!(simulator.read_bits("X10", 2).unwrap(), [true, false]);
+        assert_eq!(simulator.read_bits("X0", 1).unwrap(), [false]);
+        assert_eq!(simulator.read_words("X0", 1).unwrap(), [0xFFFE]);
+
+        // 字软元件按位访问
+        simulator.write_bits("D10", &[true, false, true]).unwrap();
+        assert_eq!(simulator.read_words("D10", 1).unwrap(), [0b101]);
+        simulator.write_words("D11", &[0x8000]).unwrap();
+        assert!(simulator.read_bits("D11", 16).unwrap()[15]);
+
+        assert!(matches!(
+            simulator.read_words("D1999", 2),
+            Err(ProtocolError::OutOfRange)
+        ));
+        assert!(simulator.read_words("R0", 1).is_err());
+        assert!(simulator.read_bits("M4000", 1).is_err());
+    }
+
+    #[test]
+    fn random_and_block_commands() {
+        let simulator = Simulator::new();
+        simulator.write_words("D100", &[1, 2, 3]).unwrap();
+        simulator.write_bits("M17", &[true]).unwrap();
+        // 字访问 D100、M16，双字访问 D101
+        let data = [2, 1, 100, 0, 0, 0xA8, 16, 0, 0, 0x90, 101, 0, 0, 0xA8];
+        let request = Request::Command(RANDOM_READ, 0, data[..].into());
+        assert_eq!(
+            simulator.handle(&request).unwrap(),
+            Response::Command(RANDOM_READ, 0, vec![1, 0, 2, 0, 2, 0, 3, 0])
+        );
+        let request = Request::Command(RANDOM_READ, 0, data[..10].into());
+        assert!(simulator.handle(&request).is_err());
+
+        // 字软元件块 D101 起 2 字，位软元件块 M16 起 1 字
+        let data = [1, 1, 101, 0, 0, 0xA8, 2, 0, 16, 0, 0, 0x90, 1, 0];
+        let request = Request::Command(BLOCK_READ, 0, data[..].into());
+        assert_eq!(
+            simulator.handle(&request).unwrap(),
+            Response::Command(BLOCK_READ, 0, vec![2, 0, 3, 0, 2, 0])
+        );
+
+        // 多块批量写入：D200 起 2 字，M32 起 1 字；数据与块数不符时不写入
+        let data = [
+            1, 1, 200, 0, 0, 0xA8, 2, 0, 7, 0, 8, 0, 32, 0, 0, 0x90, 1, 0, 5, 0,
+        ];
+        let request = Request::Command(BLOCK_WRITE, 0, data[..].into());
+        assert!(simulator.handle(&request).is_ok());
+        assert_eq!(simulator.read_words("D200", 2).unwrap(), [7, 8]);
+        assert_eq!(simulator.read_bits("M32", 3).unwrap(), [true, false, true]);
+        let request = Request::Command(BLOCK_WRITE, 0, data[..18].into());
+        assert!(simulator.handle(&request).is_err());
+    }
+
+    #[test]
+    fn profile_zones_and_dump_seed() {
+        let simulator = Simulator::from_profile(&PlcProfile::FX_SERIES);
+        assert!(simulator.read_words("W0", 1).is_ok());
+        assert!(simulator.read_words("ZR0", 1).is_err());
+        assert_eq!(simulator.read_bits("B0", 1).unwrap(), [false]);
+
+        let dump = "@D100 3\n0001 0002\n0003\n@M16 1\n8001\n";
+        assert_eq!(simulator.load_dump(dump.as_bytes()).unwrap(), 4);
+        assert_eq!(simulator.read_words("D100", 3).unwrap(), [1, 2, 3]);
+        assert_eq!(simulator.read_bits("M31", 1).unwrap(), [true]);
+        assert!(simulator.journal().is_empty());
+
+        assert!(simulator.load_dump(&b"@D0 2\n0001\n"[..]).is_err());
+        assert!(simulator.load_dump(&b"0001\n"[..]).is_err());
+        assert!(simulator.load_dump(&b"@Q0 1\n0001\n"[..]).is_err());
+    }
+
+    #[test]
+    fn power_cycle_keeps_latched_ranges() {
+        let simulator = Simulator::new()
+            .with_latched("D", 100..200)
+            .with_latched("D", 1000..=1000)
+            .with_latched("L", ..);
+        simulator.write_words("D99", &[1, 2]).unwrap();
+        simulator.write_words("D199", &[3, 4]).unwrap();
+        simulator.write_words("D1000", &[5, 6]).unwrap();
+        simulator.write_bits("L5", &[true]).unwrap();
+        simulator.write_bits("M5", &[true]).unwrap();
+
+        simulator.power_cycle();
+        assert_eq!(simulator.read_words("D99", 2).unwrap(), [0, 2]);
+        assert_eq!(simulator.read_words("D199", 2).unwrap(), [3, 0]);
+        assert_eq!(simulator.read_words("D1000", 2).unwrap(), [5, 0]);
+        assert_eq!(simulator.read_bits("L5", 1).unwrap(), [true]);
+        assert_eq!(simulator.read_bits("M5", 1).unwrap(), [false]);
+    }
+
+    #[tokio::test]
+    async fn serves_requests() {
+        let simulator = Simulator::empty().with_word_zone("D", 10);
+        let response = simulator
+            .call(Request::WriteU8s("D2".into(), vec![0x34, 0x12].into()))
+            .await
+            .unwrap();
+        assert_eq!(response, Response::WriteU8s());
+
+        let response = simulator
+            .call(Request::ReadU8s("D1".into(), 2))
+            .await
+            .unwrap();
+        assert_eq!(response, Response::ReadU8s(vec![0x00, 0x00, 0x34, 0x12]));
+
+        assert!(simulator
+            .call(Request::WriteU8s("D2".into(), vec![0x34].into()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn multi_cpu_routes_by_io_number() {
+        let control = Arc::new(Simulator::empty().with_word_zone("D", 10));
+        let motion = Arc::new(Simulator::empty().with_word_zone("D", 10));
+        let system = MultiCpu::new(Arc::clone(&control)).with_cpu(0x03E1, Arc::clone(&motion));
+
+        let write = |value: u8| Request::WriteU8s("D0".into(), vec![value, 0].into());
+        system.call(write(1)).await.unwrap();
+        system
+            .call_routed(Route::multi_cpu(2), write(2))
+            .await
+            .unwrap();
+        assert_eq!(control.read_words("D0", 1).unwrap(), [1]);
+        assert_eq!(motion.read_words("D0", 1).unwrap(), [2]);
+
+        let read = Request::ReadU8s("D0".into(), 1);
+        let response = system.call_routed(Route::multi_cpu(1), read.clone());
+        assert_eq!(response.await.unwrap(), Response::ReadU8s(vec![1, 0]));
+        assert!(matches!(
+            system.call_routed(Route::multi_cpu(3), read).await,
+            Err(ProtocolError::InvalidAddress(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn journal_records_writes() {
+        let simulator = Arc::new(
+            Simulator::empty()
+                .with_word_zone("D", 10)
+                .with_bit_zone("M", 16)
+                .with_journal_capacity(2),
+        );
+        // 直接写入不记录
+        simulator.write_words("D0", &[0x1111]).unwrap();
+        let peer: SocketAddr = "192.0.2.1:5000".parse().unwrap();
+        let connection = simulator.connection(peer);
+
+        for request in [
+            Request::WriteU8s("D0".into(), vec![0x22, 0x22].into()),
+            Request::WriteU8s("D1".into(), vec![0x33, 0x33].into()),
+            Request::WriteBits("M3".into(), vec![true, false].into()),
+        ] {
+            connection.call(request).await.unwrap();
+        }
+        // 失败的写入不记录
+        assert!(connection
+            .call(Request::WriteU8s("D10".into(), vec![0, 0].into()))
+            .await
+            .is_err());
+
+        // 容量为 2，最早的 D0 已被丢弃
+        let journal = simulator.journal();
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal[0].address, "D1");
+        assert_eq!(journal[0].old, JournalData::Words(vec![0]));
+        assert_eq!(journal[0].new, JournalData::Words(vec![0x3333]));
+        assert_eq!(journal[1].old, JournalData::Bits(vec![false, false]));
+        assert_eq!(journal[1].peer, Some(peer));
+        assert!(journal[0].timestamp <= journal[1].timestamp);
+
+        let path =
+            std::env::temp_dir().join(format!("tokio-mc-journal-{}.log", std::process::id()));
+        simulator.dump_journal(&path).unwrap();
+        let dump = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with("\t192.0.2.1:5000\tM3\t00\t10"));
+
+        simulator
+            .call(Request::WriteBits("M0".into(), vec![true].into()))
+            .await
+            .unwrap();
+        assert_eq!(simulator.take_journal()[1].peer, None);
+        assert!(simulator.journal().is_empty());
+    }
+}
@@ -0,0 +1,56 @@
+//! 协议事件监听
+//!
+//! 通过 [`Server::set_tap`](super::Server::set_tap) 或
+//! [`ServerBuilder::tap`](super::ServerBuilder::tap) 传入 `broadcast` 通道的发送端后，
+//! 服务端把每个解码后的请求与发出的应答以 [`TapEvent`] 发送到该通道，
+//! 无需包装 `Service` 即可做协议统计或在集成测试中断言交互过程：
+//!
+//! ```text
+//! let (tap, mut events) = tokio::sync::broadcast::channel(1024);
+//! let server = Server::builder(addr).tap(tap).build()?;
+//! ```
+//!
+//! 没有接收端时不复制请求；接收端落后超过通道容量时丢弃最早的事件，不影响服务端处理。
+
+use std::net::SocketAddr;
+
+use tokio::sync::broadcast;
+
+use crate::frame::{FunctionCode, Request, Response, Route};
+
+/// 服务端发出的协议事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum TapEvent {
+    /// 解码后、交给 `Service` 之前的请求
+    Request {
+        peer: SocketAddr,
+        route: Route,
+        request: Request<'static>,
+    },
+    /// 已发送给客户端的应答
+    Response {
+        peer: SocketAddr,
+        route: Route,
+        /// 所应答请求的功能
+        function: FunctionCode,
+        reply: TapReply,
+    },
+}
+
+/// 应答的内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum TapReply {
+    /// `Service` 的正常应答
+    Response(Response),
+    /// `Service` 返回错误，内容为错误的调试输出
+    ServiceError(String),
+    /// 过载时的忙应答，携带结束代码
+    Busy(u16),
+}
+
+/// 存在接收端时返回发送端，避免无人监听时复制请求与应答
+pub(crate) fn active(
+    tap: Option<&broadcast::Sender<TapEvent>>,
+) -> Option<&broadcast::Sender<TapEvent>> {
+    tap.filter(|tap| tap.receiver_count() > 0)
+}
@@ -1,20 +1,35 @@
-use std::{future::Future, io, net::SocketAddr};
+use std::{
+    collections::HashSet,
+    future::{self, Future},
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use futures_util::{FutureExt as _, SinkExt as _, StreamExt as _};
+use futures_util::{
+    future::Either, stream::FuturesOrdered, FutureExt as _, SinkExt as _, StreamExt as _,
+};
 use socket2::{Domain, Socket, Type};
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     net::{TcpListener, TcpStream},
+    sync::broadcast,
 };
 use tokio_util::codec::Framed;
 
 use crate::{
-    codec::tcp::ServerCodec,
-    frame::{Request, Response},
+    codec::tcp::{ErrorResponse, ServerCodec},
+    frame::{FunctionCode, Request, Response, Route},
+    stats::Traffic,
+    trace,
 };
 
-use super::Service;
+use super::{
+    tap::{self, TapEvent, TapReply},
+    FrameType, Service, Session,
+};
 
 #[async_trait]
 pub trait BindSocket {
@@ -50,11 +65,71 @@ where
 #[derive(Debug)]
 pub struct Server {
     listener: TcpListener,
+    config: ConnectionConfig,
+    /// 连接过的对端地址，用于统计重连
+    peers: Mutex<HashSet<IpAddr>>,
 }
 
 impl Server {
     pub fn new(listener: TcpListener) -> Self {
-        Self { listener }
+        Self {
+            listener,
+            config: ConnectionConfig::default(),
+            peers: Mutex::default(),
+        }
+    }
+
+    /// 设置连接空闲超时
+    ///
+    /// 连接在该时长内没有收到完整请求时被关闭，`on_process_error` 会收到
+    /// `TimedOut` 错误，避免半开连接一直占用资源。`None` 表示不超时（默认）。
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.config.idle_timeout = idle_timeout;
+    }
+
+    /// 设置每个连接同时处理的请求上限，默认为 1（逐个处理）
+    ///
+    /// 大于 1 时，客户端连续发送的请求会在前一个请求处理期间被读取并交给
+    /// `Service`，响应仍按请求顺序返回；达到上限后不再读取新请求，
+    /// 由 TCP 流控让客户端等待，避免慢速的后端被请求淹没。
+    pub fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        self.config.max_in_flight = max_in_flight.max(1);
+    }
+
+    /// 设置过载时的忙应答，`None` 表示关闭（默认）
+    ///
+    /// 开启后，`Service` 正在处理的请求数达到 [`Self::set_max_in_flight`] 的上限时，
+    /// 服务端继续读取新请求并立即以 `end_code` 作为结束代码应答，而不是等待排队，
+    /// 便于测试客户端的重试逻辑。忙应答同样按请求顺序发送，
+    /// 已读取但尚未应答的请求最多 [`MAX_BUSY_BACKLOG`] 个。
+    /// 忙状态的结束代码因 PLC 系列而异，需按所模拟的机型指定。
+    pub fn set_busy_rejection(&mut self, end_code: Option<u16>) {
+        self.config.busy_end_code = end_code;
+    }
+
+    /// 设置协议事件的发送端，`None` 表示关闭（默认），见 [`tap`]
+    ///
+    /// 之后接受的连接把每个解码后的请求与发出的应答发送到该通道。
+    pub fn set_tap(&mut self, tap: Option<broadcast::Sender<TapEvent>>) {
+        self.config.tap = tap;
+    }
+
+    /// 全部连接的流量统计，见 [`stats`](crate::stats)
+    ///
+    /// 返回的句柄在 [`Self::serve_until`] 取得服务端的所有权之后仍然有效；
+    /// 由同一个 [`ServerBuilder`] 构建的各 worker 共用同一份统计。
+    pub fn traffic(&self) -> Arc<Traffic> {
+        Arc::clone(&self.config.traffic)
+    }
+
+    /// 以 [`ServerBuilder`] 配置监听地址与套接字选项
+    pub fn builder(addr: SocketAddr) -> ServerBuilder {
+        ServerBuilder::new(addr)
+    }
+
+    /// 实际监听的地址，绑定端口 0 时可由此获得分配的端口
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
     }
 
     /// Listens for incoming connections and starts a MC TCP server task for
@@ -80,22 +155,39 @@ impl Server {
     {
         loop {
             let (stream, socket_addr) = self.listener.accept().await?;
-            log::debug!("Accepted connection from {socket_addr}");
+            trace::debug!(peer = socket_addr; "Accepted connection");
+            let seen = !self
+                .peers
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(socket_addr.ip());
+            self.config.traffic.record_connection(seen);
 
             let Some((service, transport)) = on_connected(stream, socket_addr).await? else {
-                log::debug!("No service for connection from {socket_addr}");
+                trace::debug!(peer = socket_addr; "No service for connection");
                 continue;
             };
             let on_process_error = on_process_error.clone();
+            let config = self.config.clone();
 
-            let framed = Framed::new(transport, ServerCodec::default());
+            let framed = Framed::new(
+                transport,
+                ServerCodec::with_traffic(Arc::clone(&config.traffic)),
+            );
 
-            tokio::spawn(async move {
-                log::debug!("Processing requests from {socket_addr}");
-                if let Err(err) = process(framed, service).await {
+            let task = async move {
+                trace::debug!(peer = socket_addr; "Processing requests");
+                if let Err(err) = process(framed, socket_addr, service, config).await {
                     on_process_error(err);
                 }
-            });
+            };
+            // 连接内的所有事件都挂在带 peer 字段的 span 下
+            #[cfg(feature = "tracing")]
+            let task = tracing::Instrument::instrument(
+                task,
+                tracing::debug_span!("mc_connection", peer = %socket_addr),
+            );
+            tokio::spawn(task);
         }
     }
 
@@ -130,76 +222,325 @@ impl Server {
     }
 }
 
+/// 单个连接的处理参数
+#[derive(Debug, Clone)]
+struct ConnectionConfig {
+    idle_timeout: Option<Duration>,
+    max_in_flight: usize,
+    busy_end_code: Option<u16>,
+    tap: Option<broadcast::Sender<TapEvent>>,
+    traffic: Arc<Traffic>,
+}
+
+/// 开启忙应答时，除正在处理的请求外最多积压的待发送应答数
+pub const MAX_BUSY_BACKLOG: usize = 64;
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: None,
+            max_in_flight: 1,
+            busy_end_code: None,
+            tap: None,
+            traffic: Arc::default(),
+        }
+    }
+}
+
+/// 按请求顺序发送的应答
+enum Reply<E> {
+    /// `Service` 的处理结果
+    Service(Result<Response, E>),
+    /// 过载时的忙应答，携带结束代码
+    Busy(u16),
+}
+
 /// The request-response loop spawned by [`Server::serve`] for each client
-async fn process<S, T>(mut framed: Framed<T, ServerCodec>, service: S) -> io::Result<()>
+///
+/// 最多同时处理 `max_in_flight` 个请求，响应按请求顺序发送；
+/// 达到上限后暂停读取新请求，由 TCP 流控向客户端施加背压；
+/// 开启忙应答时则继续读取，并对超出上限的请求直接返回忙应答。
+async fn process<S, T>(
+    mut framed: Framed<T, ServerCodec>,
+    peer: SocketAddr,
+    service: S,
+    config: ConnectionConfig,
+) -> io::Result<()>
 where
     S: Service<Request = Request<'static>, Response = Response> + Send + Sync + 'static,
     S::Exception: Send + std::fmt::Debug,
     T: AsyncRead + AsyncWrite + Unpin,
 {
+    let max_in_flight = config.max_in_flight.max(1);
+    let max_pending = match config.busy_end_code {
+        Some(_) => max_in_flight + MAX_BUSY_BACKLOG,
+        None => max_in_flight,
+    };
+    let session = Arc::new(Session::new(peer, FrameType::Binary3E));
+    let mut in_flight = FuturesOrdered::new();
+    // 交给 Service 且尚未完成的请求数，不含忙应答
+    let mut calls = 0;
+    let mut reading = true;
+
     loop {
-        let Some(request_bytes) = framed.next().await.transpose().inspect_err(|err| {
-            log::debug!("Failed to receive and decode request: {err}");
-        })?
-        else {
-            log::debug!("TCP socket has been closed");
-            break;
+        // 仅在没有待处理请求时计算空闲时间
+        let idle = async {
+            match config.idle_timeout {
+                Some(idle_timeout) => tokio::time::sleep(idle_timeout).await,
+                None => future::pending().await,
+            }
         };
 
-        log::debug!("Received request: {:02X?}", request_bytes);
+        tokio::select! {
+            Some((fc, route, reply)) = in_flight.next(), if !in_flight.is_empty() => {
+                if let Reply::Service(_) = reply {
+                    calls -= 1;
+                }
+                let on_sent = tap::active(config.tap.as_ref()).map(|tap| {
+                    move |reply| {
+                        // 发送失败仅说明接收端已全部关闭
+                        let _ = tap.send(TapEvent::Response {
+                            peer,
+                            route,
+                            function: fc,
+                            reply,
+                        });
+                    }
+                });
+                send_reply(&mut framed, fc, route, reply, on_sent).await?;
+            }
+            next = framed.next(), if reading && in_flight.len() < max_pending => {
+                let Some((route, request_bytes)) = next.transpose().inspect_err(|err| {
+                    trace::debug!("Failed to receive and decode request: {err}");
+                })?
+                else {
+                    trace::debug!("TCP socket has been closed");
+                    // 继续发送已接收请求的响应
+                    reading = false;
+                    continue;
+                };
+
+                trace::debug!(bytes = trace::Hex(&request_bytes); "Received request");
+
+                let req = crate::codec::ServerDecoder::decode(request_bytes).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("Parse error: {e}"))
+                })?;
 
-        let req = crate::codec::ServerDecoder::decode(request_bytes)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Parse error: {e}")))?;
+                let fc = req.function_code();
+                trace::debug!(
+                    function = fc,
+                    address = req.address(),
+                    dest_io = route.dest_io;
+                    "Decoded request"
+                );
+                if let Some(tap) = tap::active(config.tap.as_ref()) {
+                    let _ = tap.send(TapEvent::Request {
+                        peer,
+                        route,
+                        request: req.clone(),
+                    });
+                }
+                match config.busy_end_code {
+                    Some(end_code) if calls >= max_in_flight => {
+                        trace::warning!(function = fc; "Service busy, rejecting request");
+                        in_flight.push_back(Either::Right(future::ready((fc, route, Reply::Busy(end_code)))));
+                    }
+                    _ => {
+                        calls += 1;
+                        in_flight.push_back(Either::Left(
+                            service
+                                .call_with_session(&session, route, req)
+                                .map(move |result| (fc, route, Reply::Service(result))),
+                        ));
+                    }
+                }
+            }
+            () = idle, if reading && in_flight.is_empty() => {
+                trace::debug!(idle_timeout = config.idle_timeout; "Closing idle connection");
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "connection idle timeout"));
+            }
+            else => break,
+        }
+    }
 
-        let fc = req.function_code();
-        let result: Result<Response, <S as Service>::Exception> = service.call(req).await;
+    Ok(())
+}
 
-        match result {
-            Ok(resp) => {
-                framed.send(resp).await.inspect_err(|err| {
-                    log::debug!("Failed to send response (function = {fc}): {err}");
-                })?;
+/// 发送应答，成功后以应答内容调用 `on_sent`（若有）
+async fn send_reply<T, E>(
+    framed: &mut Framed<T, ServerCodec>,
+    fc: FunctionCode,
+    route: Route,
+    reply: Reply<E>,
+    on_sent: Option<impl FnOnce(TapReply)>,
+) -> io::Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    E: std::fmt::Debug,
+{
+    match reply {
+        Reply::Busy(end_code) => {
+            let busy = ErrorResponse {
+                route,
+                end_code,
+                function: fc,
+            };
+            framed.send(busy).await.inspect_err(|err| {
+                trace::debug!(function = fc; "Failed to send busy response: {err}");
+            })?;
+            if let Some(on_sent) = on_sent {
+                on_sent(TapReply::Busy(end_code));
             }
-            Err(exc) => {
-                log::warn!("Service error for function {fc}: {exc:?}");
-                // For error cases, send an appropriate error response
-                // This could be enhanced to return proper error codes based on the exception type
-                let error_response = Response::WriteU8s();
-                framed.send(error_response).await.inspect_err(|err| {
-                    log::debug!("Failed to send error response (function = {fc}): {err}");
+        }
+        Reply::Service(Ok(resp)) => {
+            // 仅在需要通知时复制应答
+            let copy = on_sent.as_ref().map(|_| resp.clone());
+            framed.send((route, resp)).await.inspect_err(|err| {
+                trace::debug!(function = fc; "Failed to send response: {err}");
+            })?;
+            if let (Some(on_sent), Some(resp)) = (on_sent, copy) {
+                on_sent(TapReply::Response(resp));
+            }
+        }
+        Reply::Service(Err(exc)) => {
+            trace::warning!(function = fc; "Service error: {exc:?}");
+            // For error cases, send an appropriate error response
+            // This could be enhanced to return proper error codes based on the exception type
+            let error_response = Response::WriteU8s();
+            framed
+                .send((route, error_response))
+                .await
+                .inspect_err(|err| {
+                    trace::debug!(function = fc; "Failed to send error response: {err}");
                 })?;
+            if let Some(on_sent) = on_sent {
+                on_sent(TapReply::ServiceError(format!("{exc:?}")));
             }
         }
     }
-
     Ok(())
 }
 
-/// Start TCP listener - configure and open TCP socket
-#[allow(unused)]
-fn listener(addr: SocketAddr, workers: usize) -> io::Result<TcpListener> {
-    let listener = match addr {
-        SocketAddr::V4(_) => Socket::new(Domain::IPV4, Type::STREAM, None)?,
-        SocketAddr::V6(_) => Socket::new(Domain::IPV6, Type::STREAM, None)?,
-    };
-    configure_tcp(workers, &listener)?;
-    listener.reuse_address()?;
-    listener.bind(&addr.into())?;
-    listener.listen(1024)?;
-    TcpListener::from_std(listener.into())
+/// 监听套接字的构建器
+///
+/// 支持 IPv4/IPv6 双栈、多 worker 共享端口（`SO_REUSEPORT`）以及绑定到指定网卡。
+/// 每次调用 [`Self::build`] 都会打开一个新的监听套接字，
+/// 多 worker 时各自调用一次即可。
+#[derive(Debug, Clone)]
+pub struct ServerBuilder {
+    addr: SocketAddr,
+    only_v6: Option<bool>,
+    workers: usize,
+    #[cfg_attr(
+        not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")),
+        allow(dead_code)
+    )]
+    device: Option<String>,
+    backlog: i32,
+    config: ConnectionConfig,
+}
+
+impl ServerBuilder {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            only_v6: None,
+            workers: 1,
+            device: None,
+            backlog: 1024,
+            config: ConnectionConfig::default(),
+        }
+    }
+
+    /// 对 IPv6 地址启用或关闭双栈，启用后同时接受 IPv4 连接；未设置时沿用系统默认
+    pub fn dual_stack(mut self, dual_stack: bool) -> Self {
+        self.only_v6 = Some(!dual_stack);
+        self
+    }
+
+    /// 共享同一端口的 worker 数量，大于 1 时在 Unix 上启用 `SO_REUSEPORT`
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// 仅在指定网卡（如 `eth1`）上监听
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn bind_device(mut self, interface: impl Into<String>) -> Self {
+        self.device = Some(interface.into());
+        self
+    }
+
+    /// 监听队列长度，默认为 1024
+    pub fn backlog(mut self, backlog: i32) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    /// 连接空闲超时，见 [`Server::set_idle_timeout`]
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.config.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// 每个连接同时处理的请求上限，见 [`Server::set_max_in_flight`]
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.config.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// 过载时以 `end_code` 作为忙应答，见 [`Server::set_busy_rejection`]
+    pub fn reject_when_busy(mut self, end_code: u16) -> Self {
+        self.config.busy_end_code = Some(end_code);
+        self
+    }
+
+    /// 协议事件的发送端，见 [`Server::set_tap`]
+    pub fn tap(mut self, tap: broadcast::Sender<TapEvent>) -> Self {
+        self.config.tap = Some(tap);
+        self
+    }
+
+    /// 打开监听套接字并创建 [`Server`]，需在 tokio 运行时内调用
+    pub fn build(&self) -> io::Result<Server> {
+        Ok(Server {
+            listener: self.listener()?,
+            config: self.config.clone(),
+            peers: Mutex::default(),
+        })
+    }
+
+    /// Start TCP listener - configure and open TCP socket
+    fn listener(&self) -> io::Result<TcpListener> {
+        let listener = match self.addr {
+            SocketAddr::V4(_) => Socket::new(Domain::IPV4, Type::STREAM, None)?,
+            SocketAddr::V6(_) => Socket::new(Domain::IPV6, Type::STREAM, None)?,
+        };
+        if let (SocketAddr::V6(_), Some(only_v6)) = (self.addr, self.only_v6) {
+            listener.set_only_v6(only_v6)?;
+        }
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        if let Some(device) = &self.device {
+            listener.bind_device(Some(device.as_bytes()))?;
+        }
+        configure_tcp(self.workers, &listener)?;
+        listener.set_reuse_address(true)?;
+        listener.set_nonblocking(true)?;
+        listener.bind(&self.addr.into())?;
+        listener.listen(self.backlog)?;
+        TcpListener::from_std(listener.into())
+    }
 }
 
 #[cfg(unix)]
-#[allow(unused)]
 fn configure_tcp(workers: usize, tcp: &Socket) -> io::Result<()> {
     if workers > 1 {
-        tcp.reuse_port()?;
+        tcp.set_reuse_port(true)?;
     }
     Ok(())
 }
 
 #[cfg(windows)]
-#[allow(unused)]
 fn configure_tcp(_workers: usize, _tcp: &Socket) -> io::Result<()> {
     Ok(())
 }
@@ -213,6 +554,16 @@ mod tests {
 
     use crate::server::service::Service;
 
+    const PEER: SocketAddr =
+        SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 50000);
+
+    /// 以客户端编码器生成单帧请求
+    fn request_frame(request: Request<'_>) -> Vec<u8> {
+        let mut frames = crate::codec::ClientEncoder::encode(request).unwrap();
+        assert_eq!(frames.len(), 1);
+        frames.remove(0).to_vec()
+    }
+
     #[derive(Clone)]
     struct DummyService {
         response: Response,
@@ -247,9 +598,14 @@ mod tests {
                 }
                 Request::WriteU8s(_, data) => {
                     // 模拟写操作成功
-                    log::debug!("Writing {} bytes", data.len());
+                    trace::debug!("Writing {} bytes", data.len());
                     Response::WriteU8s()
                 }
+                Request::ReadBits(_, qty) => Response::ReadBits(vec![false; qty as usize]),
+                Request::WriteBits(_, _) => Response::WriteBits(),
+                Request::Command(command, subcommand, data) => {
+                    Response::Command(command, subcommand, data.into_owned())
+                }
             };
             future::ready(Ok(response))
         }
@@ -277,14 +633,7 @@ mod tests {
         let (mut client, server) = duplex(1024);
         let framed = Framed::new(server, ServerCodec::default());
 
-        // 使用正确的MC协议格式
-        let bytes = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0E, 0x00, // 头部
-            0x10, 0x00, 0x01, 0x14, 0x00, 0x00, // 写U8s命令
-            0x00, 0x00, 0x00, // D0地址
-            0xA8, 0x02, 0x00, // 数量2
-            0x58, 0x1B, // 要写入的数据
-        ];
+        let bytes = request_frame(Request::WriteU8s("D0".into(), vec![0x58, 0x1B].into()));
 
         client.write_all(&bytes).await.unwrap();
         client.shutdown().await.unwrap();
@@ -292,7 +641,7 @@ mod tests {
         let svc = DummyService {
             response: Response::ReadU8s(vec![42, 43]),
         };
-        let result = process(framed, svc).await;
+        let result = process(framed, PEER, svc, ConnectionConfig::default()).await;
 
         assert!(result.is_ok());
     }
@@ -303,25 +652,17 @@ mod tests {
         let framed = Framed::new(server, ServerCodec::default());
 
         // 第一个读请求
-        let read_request1 = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, // 头部
-            0x10, 0x00, 0x01, 0x04, 0x00, 0x00, // 读U8s命令
-            0x44, 0x30, 0x00, 0x00, // D0地址
-            0x02, 0x00, // 数量2
-        ];
+        let read_request1 = request_frame(Request::ReadU8s("D0".into(), 2));
 
         // 第二个读请求
-        let read_request2 = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, // 头部
-            0x10, 0x00, 0x01, 0x04, 0x00, 0x00, // 读U8s命令
-            0x44, 0x31, 0x00, 0x00, // D1地址
-            0x04, 0x00, // 数量4
-        ];
+        let read_request2 = request_frame(Request::ReadU8s("D1".into(), 4));
 
         let service = EchoService;
 
         // 启动处理任务
-        let process_task = tokio::spawn(async move { process(framed, service).await });
+        let process_task = tokio::spawn(async move {
+            process(framed, PEER, service, ConnectionConfig::default()).await
+        });
 
         // 发送第一个请求
         client.write_all(&read_request1).await.unwrap();
@@ -353,18 +694,14 @@ mod tests {
         let framed = Framed::new(server, ServerCodec::default());
 
         // 写请求数据
-        let write_request = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0E, 0x00, // 头部
-            0x10, 0x00, 0x01, 0x14, 0x00, 0x00, // 写U8s命令
-            0x44, 0x30, 0x00, 0x00, // D0地址
-            0x02, 0x00, // 数量2
-            0xAA, 0xBB, // 要写入的数据
-        ];
+        let write_request = request_frame(Request::WriteU8s("D0".into(), vec![0xAA, 0xBB].into()));
 
         let service = EchoService;
 
         // 启动处理任务
-        let process_task = tokio::spawn(async move { process(framed, service).await });
+        let process_task = tokio::spawn(async move {
+            process(framed, PEER, service, ConnectionConfig::default()).await
+        });
 
         // 发送写请求
         client.write_all(&write_request).await.unwrap();
@@ -390,13 +727,15 @@ mod tests {
         let service = EchoService;
 
         // 启动处理任务
-        let process_task = tokio::spawn(async move { process(framed, service).await });
+        let process_task = tokio::spawn(async move {
+            process(framed, PEER, service, ConnectionConfig::default()).await
+        });
 
         // 1. 先发送写请求
-        let write_request = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0E, 0x00, 0x10, 0x00, 0x01, 0x14, 0x00,
-            0x00, 0x44, 0x30, 0x00, 0x00, 0x03, 0x00, 0x11, 0x22, 0x33,
-        ];
+        let write_request = request_frame(Request::WriteU8s(
+            "D0".into(),
+            vec![0x11, 0x22, 0x33, 0x44].into(),
+        ));
 
         client.write_all(&write_request).await.unwrap();
 
@@ -406,10 +745,7 @@ mod tests {
         assert!(n > 0, "Should receive write response");
 
         // 2. 然后发送读请求
-        let read_request = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, 0x10, 0x00, 0x01, 0x04, 0x00,
-            0x00, 0x44, 0x30, 0x00, 0x00, 0x03, 0x00,
-        ];
+        let read_request = request_frame(Request::ReadU8s("D0".into(), 3));
 
         client.write_all(&read_request).await.unwrap();
 
@@ -435,13 +771,12 @@ mod tests {
 
         let service = ErrorService;
 
-        let process_task = tokio::spawn(async move { process(framed, service).await });
+        let process_task = tokio::spawn(async move {
+            process(framed, PEER, service, ConnectionConfig::default()).await
+        });
 
         // 发送一个请求，服务会返回错误
-        let request = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, 0x10, 0x00, 0x01, 0x04, 0x00,
-            0x00, 0x44, 0x30, 0x00, 0x00, 0x01, 0x00,
-        ];
+        let request = request_frame(Request::ReadU8s("D0".into(), 1));
 
         client.write_all(&request).await.unwrap();
 
@@ -478,6 +813,7 @@ mod tests {
         };
 
         let server = Server::new(listener);
+        let traffic = server.traffic();
 
         // 启动服务器
         let server_task = tokio::spawn(async move {
@@ -495,10 +831,7 @@ mod tests {
         let mut stream = TcpStream::connect(addr).await.unwrap();
 
         // 发送读请求
-        let read_request = [
-            0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x0C, 0x00, 0x10, 0x00, 0x01, 0x04, 0x00,
-            0x00, 0x44, 0x30, 0x00, 0x00, 0x05, 0x00, // 请求5个字节
-        ];
+        let read_request = request_frame(Request::ReadU8s("D0".into(), 5));
 
         stream.write_all(&read_request).await.unwrap();
 
@@ -507,6 +840,18 @@ mod tests {
         let n = stream.read(&mut response).await.unwrap();
         assert!(n > 0, "Should receive response from server");
 
+        // 同一地址再次连接计为重连
+        drop(stream);
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(&read_request).await.unwrap();
+        assert_eq!(stream.read(&mut response).await.unwrap(), n);
+        let stats = traffic.snapshot();
+        assert_eq!((stats.connections, stats.reconnects), (2, 1));
+        assert_eq!((stats.frames_received, stats.frames_sent), (2, 2));
+        assert_eq!(stats.bytes_received, 2 * read_request.len() as u64);
+        assert_eq!(stats.bytes_sent, 2 * n as u64);
+        assert_eq!(stats.max_frame_len, read_request.len().max(n));
+
         // 关闭连接会触发服务器任务退出
         drop(stream);
 
@@ -514,6 +859,195 @@ mod tests {
         let _result = server_task.await;
     }
 
+    #[tokio::test]
+    async fn builder_binds_dual_stack_and_shared_port() {
+        let server = Server::builder("127.0.0.1:0".parse().unwrap())
+            .workers(2)
+            .build()
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // 多 worker 时同一端口可以再次绑定
+        #[cfg(unix)]
+        {
+            let second = Server::builder(addr).workers(2).build().unwrap();
+            assert_eq!(second.local_addr().unwrap(), addr);
+        }
+        assert!(Server::builder(addr).build().is_err());
+
+        // 主机未启用 IPv6 时跳过双栈部分
+        let Ok(dual) = Server::builder("[::]:0".parse().unwrap())
+            .dual_stack(true)
+            .build()
+        else {
+            return;
+        };
+        let port = dual.local_addr().unwrap().port();
+        let accept =
+            tokio::spawn(async move { dual.listener.accept().await.map(|(_, peer)| peer) });
+        TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let peer = accept.await.unwrap().unwrap();
+        assert_eq!(peer.ip().to_canonical(), std::net::Ipv4Addr::LOCALHOST);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_connection_is_closed_after_timeout() {
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let process_task = tokio::spawn(process(
+            framed,
+            PEER,
+            EchoService,
+            ConnectionConfig {
+                idle_timeout: Some(Duration::from_secs(5)),
+                ..Default::default()
+            },
+        ));
+
+        // 活动的连接在超时前收到请求，计时重新开始
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        let request = request_frame(Request::ReadU8s("D0".into(), 1));
+        client.write_all(&request).await.unwrap();
+        let mut buf = vec![0u8; 32];
+        assert!(client.read(&mut buf).await.unwrap() > 0);
+
+        let err = process_task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        // 服务端关闭后客户端读到 EOF
+        assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+    }
+
+    /// 按请求点数延迟应答，并记录同时处理的请求数峰值
+    #[derive(Clone, Default)]
+    struct SlowService {
+        active: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Service for SlowService {
+        type Request = Request<'static>;
+        type Response = Response;
+        type Exception = std::io::Error;
+        type Future =
+            std::pin::Pin<Box<dyn Future<Output = Result<Self::Response, Self::Exception>> + Send>>;
+
+        fn call(&self, req: Self::Request) -> Self::Future {
+            use std::sync::atomic::Ordering;
+            let Request::ReadU8s(_, qty) = req else {
+                unreachable!()
+            };
+            let (active, peak) = (Arc::clone(&self.active), Arc::clone(&self.peak));
+            Box::pin(async move {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // 点数越少越慢，使后到的请求先处理完
+                tokio::time::sleep(Duration::from_millis(100 / u64::from(qty))).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok(Response::ReadU8s(vec![qty as u8; qty as usize * 2]))
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_flight_requests_are_bounded_and_answered_in_order() {
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let service = SlowService::default();
+        let peak = Arc::clone(&service.peak);
+        let config = ConnectionConfig {
+            max_in_flight: 2,
+            ..Default::default()
+        };
+        let process_task = tokio::spawn(process(framed, PEER, service, config));
+
+        for qty in 1..=4 {
+            let request = request_frame(Request::ReadU8s("D0".into(), qty));
+            client.write_all(&request).await.unwrap();
+        }
+        for qty in 1..=4u8 {
+            let mut header = [0u8; 9];
+            client.read_exact(&mut header).await.unwrap();
+            let len = u16::from_le_bytes([header[7], header[8]]) as usize;
+            let mut body = vec![0u8; len];
+            client.read_exact(&mut body).await.unwrap();
+            assert_eq!(body[2..], vec![qty; qty as usize * 2]);
+        }
+        client.shutdown().await.unwrap();
+
+        assert!(process_task.await.unwrap().is_ok());
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn saturated_service_answers_busy() {
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let service = SlowService::default();
+        let peak = Arc::clone(&service.peak);
+        let config = ConnectionConfig {
+            busy_end_code: Some(0xCEE0),
+            ..Default::default()
+        };
+        let process_task = tokio::spawn(process(framed, PEER, service, config));
+
+        for qty in 1..=3 {
+            let request = request_frame(Request::ReadU8s("D0".into(), qty));
+            client.write_all(&request).await.unwrap();
+        }
+        let mut end_codes = Vec::new();
+        for _ in 0..3 {
+            let mut header = [0u8; 9];
+            client.read_exact(&mut header).await.unwrap();
+            let len = u16::from_le_bytes([header[7], header[8]]) as usize;
+            let mut body = vec![0u8; len];
+            client.read_exact(&mut body).await.unwrap();
+            end_codes.push(u16::from_le_bytes([body[0], body[1]]));
+            if end_codes.last() == Some(&0xCEE0) {
+                // 忙应答带有出错请求的指令与子指令
+                assert_eq!(body.len(), 11);
+                assert_eq!(body[7..], [0x01, 0x04, 0x00, 0x00]);
+            }
+        }
+        assert_eq!(end_codes, [0x0000, 0xCEE0, 0xCEE0]);
+
+        // 后端空闲后恢复正常处理
+        let request = request_frame(Request::ReadU8s("D0".into(), 2));
+        client.write_all(&request).await.unwrap();
+        let mut response = [0u8; 15];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(response[9..11], [0x00, 0x00]);
+        client.shutdown().await.unwrap();
+
+        assert!(process_task.await.unwrap().is_ok());
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn requests_are_routed_by_destination_io() {
+        use crate::server::{MultiCpu, Simulator};
+
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let motion = Arc::new(Simulator::empty().with_word_zone("D", 10));
+        motion.write_words("D0", &[0x1234]).unwrap();
+        let system = MultiCpu::new(Arc::new(Simulator::empty().with_word_zone("D", 10)))
+            .with_cpu(0x03E1, motion);
+        let process_task = tokio::spawn(process(framed, PEER, system, ConnectionConfig::default()));
+
+        // 请求目标模块 I/O 编号改为 03E1（2 号 CPU）
+        let mut request = request_frame(Request::ReadU8s("D0".into(), 1));
+        request[4..6].copy_from_slice(&0x03E1u16.to_le_bytes());
+        client.write_all(&request).await.unwrap();
+
+        let mut response = [0u8; 13];
+        client.read_exact(&mut response).await.unwrap();
+        // 应答帧头沿用请求的访问路径
+        assert_eq!(response[..7], [0xD0, 0x00, 0x00, 0xFF, 0xE1, 0x03, 0x00]);
+        assert_eq!(response[9..], [0x00, 0x00, 0x34, 0x12]);
+        client.shutdown().await.unwrap();
+        assert!(process_task.await.unwrap().is_ok());
+    }
+
     #[tokio::test]
     async fn test_invalid_request_data() {
         let (mut client, server) = duplex(1024);
@@ -521,7 +1055,9 @@ mod tests {
 
         let service = EchoService;
 
-        let process_task = tokio::spawn(async move { process(framed, service).await });
+        let process_task = tokio::spawn(async move {
+            process(framed, PEER, service, ConnectionConfig::default()).await
+        });
 
         // 发送无效的请求数据（头部正确但payload无效）
         let invalid_request = [
@@ -553,4 +1089,104 @@ mod tests {
         // passes type-check is the goal here
         std::mem::drop(server.serve(&on_connected, |_err| {}));
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn tap_emits_decoded_requests_and_replies() {
+        let (tap, mut events) = broadcast::channel(16);
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let config = ConnectionConfig {
+            busy_end_code: Some(0xCEE0),
+            tap: Some(tap),
+            ..Default::default()
+        };
+        let process_task = tokio::spawn(process(framed, PEER, SlowService::default(), config));
+
+        for qty in 1..=2 {
+            let request = request_frame(Request::ReadU8s("D0".into(), qty));
+            client.write_all(&request).await.unwrap();
+        }
+        let mut response = [0u8; 13 + 20];
+        client.read_exact(&mut response).await.unwrap();
+        client.shutdown().await.unwrap();
+        assert!(process_task.await.unwrap().is_ok());
+
+        let route = Route::default();
+        let request = |qty| TapEvent::Request {
+            peer: PEER,
+            route,
+            request: Request::ReadU8s("D0".into(), qty),
+        };
+        let reply = |reply| TapEvent::Response {
+            peer: PEER,
+            route,
+            function: FunctionCode::ReadU8s,
+            reply,
+        };
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(
+            received,
+            [
+                request(1),
+                request(2),
+                reply(TapReply::Response(Response::ReadU8s(vec![1, 1]))),
+                reply(TapReply::Busy(0xCEE0)),
+            ]
+        );
+    }
+
+    /// 首个请求在会话中记录身份，之后的请求按身份应答
+    struct SessionService;
+
+    impl Service for SessionService {
+        type Request = Request<'static>;
+        type Response = Response;
+        type Exception = std::io::Error;
+        type Future = future::Ready<Result<Self::Response, Self::Exception>>;
+
+        fn call(&self, _req: Self::Request) -> Self::Future {
+            unreachable!("the server passes the session")
+        }
+
+        fn call_with_session(
+            &self,
+            session: &Arc<Session>,
+            _route: Route,
+            _req: Self::Request,
+        ) -> Self::Future {
+            assert_eq!(session.peer(), PEER);
+            assert_eq!(session.frame_type(), FrameType::Binary3E);
+            let known = session.identity().is_some();
+            session.set_identity(Some("operator".to_string()));
+            future::ready(Ok(Response::ReadU8s(vec![known as u8, 0])))
+        }
+    }
+
+    #[tokio::test]
+    async fn session_state_persists_across_requests() {
+        let (mut client, server) = duplex(1024);
+        let framed = Framed::new(server, ServerCodec::default());
+        let process_task = tokio::spawn(process(
+            framed,
+            PEER,
+            SessionService,
+            ConnectionConfig::default(),
+        ));
+
+        let mut reply = [0u8; 13];
+        for expected in [0, 1] {
+            client
+                .write_all(&request_frame(Request::ReadU8s("D0".into(), 1)))
+                .await
+                .unwrap();
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply[11..], [expected, 0]);
+        }
+
+        client.shutdown().await.unwrap();
+        assert!(process_task.await.unwrap().is_ok());
+    }
 }
@@ -0,0 +1,287 @@
+//! 模拟器的网页界面
+//!
+//! 测试人员查看、修改模拟器中的值时不必另写 MC 客户端：[`serve_web`] 在单独的端口上提供一个网页，
+//! 每 0.5 秒刷新所选区域的当前值，点击数值即可修改。
+//!
+//! ```text
+//! let listener = TcpListener::bind("127.0.0.1:8080").await?;
+//! tokio::spawn(serve_web(listener, simulator.clone()));
+//! ```
+//!
+//! 网页经由以下接口读写 [`Simulator`]，也可以直接用 `curl` 调用：
+//!
+//! - `GET /api/read?address=D100&count=16`：返回 `{"address":"D100","values":[...]}`，
+//!   位软元件的值为 0/1，每次最多 [`MAX_POINTS`] 点；
+//! - `POST /api/write?address=D100&value=1234`：写入一个值，字为 -32768 至 65535，位为 0/1。
+//!
+//! 出错时返回 400 与纯文本的原因。接口没有认证，只应绑定在本机或测试网络上。
+
+use std::{io, sync::Arc};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{
+    frame::{device_kind, split_address, DeviceKind},
+    trace,
+};
+
+use super::Simulator;
+
+/// 单次读取的最大点数
+pub const MAX_POINTS: usize = 1024;
+
+/// 请求头的最大字节数
+const MAX_HEAD: usize = 8 * 1024;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>mc-sim</title>
+<style>
+body { font-family: monospace; margin: 1em; }
+td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: right; }
+td.value { cursor: pointer; }
+#error { color: #c00; }
+</style>
+</head>
+<body>
+<form id="area">
+Address <input id="address" value="D0" size="8">
+Count <input id="count" value="64" size="5">
+<button>Show</button> <span id="error"></span>
+</form>
+<table id="values"></table>
+<script>
+let area = { address: "D0", count: 64 };
+const prefix = a => a.match(/^[A-Z]+/i)[0].toUpperCase();
+const start = a => a.slice(prefix(a).length);
+const hex = p => p === "X" || p === "Y" || p === "B" || p === "W" || p === "SB" || p === "SW";
+function address(i) {
+  const p = prefix(area.address), base = hex(p) ? 16 : 10;
+  return p + (parseInt(start(area.address), base) + i).toString(base).toUpperCase();
+}
+async function refresh() {
+  const response = await fetch(`/api/read?address=${area.address}&count=${area.count}`);
+  if (!response.ok) { document.getElementById("error").textContent = await response.text(); return; }
+  document.getElementById("error").textContent = "";
+  const { values } = await response.json();
+  const rows = [];
+  for (let i = 0; i < values.length; i += 8) {
+    const cells = values.slice(i, i + 8)
+      .map((v, j) => `<td class="value" data-i="${i + j}">${v}</td>`).join("");
+    rows.push(`<tr><th>${address(i)}</th>${cells}</tr>`);
+  }
+  document.getElementById("values").innerHTML = rows.join("");
+}
+document.getElementById("area").onsubmit = e => {
+  e.preventDefault();
+  area = { address: document.getElementById("address").value, count: document.getElementById("count").value };
+  refresh();
+};
+document.getElementById("values").onclick = async e => {
+  if (!e.target.dataset.i) return;
+  const target = address(Number(e.target.dataset.i));
+  const value = prompt(target, e.target.textContent);
+  if (value === null) return;
+  const response = await fetch(`/api/write?address=${target}&value=${encodeURIComponent(value)}`, { method: "POST" });
+  if (!response.ok) alert(await response.text());
+  refresh();
+};
+refresh();
+setInterval(refresh, 500);
+</script>
+</body>
+</html>
+"#;
+
+/// 在 `listener` 上提供 `simulator` 的网页界面，见[模块文档](self)
+///
+/// 只在接受连接失败时返回；每个连接处理一个请求。
+pub async fn serve_web(listener: TcpListener, simulator: Arc<Simulator>) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let simulator = Arc::clone(&simulator);
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, &simulator).await {
+                trace::debug!("Web request from {peer} failed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle_connection<S>(mut stream: S, simulator: &Simulator) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 || head.len() + n > MAX_HEAD {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete or oversized request head",
+            ));
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (status, content_type, body) = match respond(simulator, method, path, query) {
+        Ok((content_type, body)) => ("200 OK", content_type, body),
+        Err(Reply::NotFound) => ("404 Not Found", "text/plain", "not found".to_string()),
+        Err(Reply::BadRequest(message)) => ("400 Bad Request", "text/plain", message),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}; charset=utf-8\r\n\
+         Content-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// 无法正常应答的请求
+enum Reply {
+    NotFound,
+    BadRequest(String),
+}
+
+/// 处理一个请求，返回内容类型与正文
+fn respond(
+    simulator: &Simulator,
+    method: &str,
+    path: &str,
+    query: &str,
+) -> Result<(&'static str, String), Reply> {
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    };
+    let bad = |message: String| Reply::BadRequest(message);
+    let address = || {
+        let address = param("address").ok_or_else(|| bad("missing address".to_string()))?;
+        let address = address.to_ascii_uppercase();
+        let (prefix, _) =
+            split_address(&address).ok_or_else(|| bad(format!("invalid address {address}")))?;
+        let bits = device_kind(prefix) == Some(DeviceKind::Bit);
+        Ok((address, bits))
+    };
+
+    match (method, path) {
+        ("GET", "/") => Ok(("text/html", PAGE.to_string())),
+        ("GET", "/api/read") => {
+            let (address, bits) = address()?;
+            let count = match param("count") {
+                Some(count) => count
+                    .parse()
+                    .ok()
+                    .filter(|count| (1..=MAX_POINTS).contains(count))
+                    .ok_or_else(|| bad(format!("count must be 1-{MAX_POINTS}")))?,
+                None => 16,
+            };
+            let values: Vec<String> = if bits {
+                simulator
+                    .read_bits(&address, count)
+                    .map(|bits| bits.iter().map(|&bit| u8::from(bit).to_string()).collect())
+            } else {
+                simulator
+                    .read_words(&address, count)
+                    .map(|words| words.iter().map(u16::to_string).collect())
+            }
+            .map_err(|err| bad(format!("{err:?}")))?;
+            Ok((
+                "application/json",
+                format!(
+                    "{{\"address\":\"{address}\",\"values\":[{}]}}",
+                    values.join(",")
+                ),
+            ))
+        }
+        ("POST", "/api/write") => {
+            let (address, bits) = address()?;
+            let value = param("value").ok_or_else(|| bad("missing value".to_string()))?;
+            let invalid = || bad(format!("invalid value {value}"));
+            if bits {
+                let bit = match value {
+                    "0" | "false" => false,
+                    "1" | "true" => true,
+                    _ => return Err(invalid()),
+                };
+                simulator.write_bits(&address, &[bit])
+            } else {
+                let word: i32 = value.parse().map_err(|_| invalid())?;
+                if !(i16::MIN as i32..=u16::MAX as i32).contains(&word) {
+                    return Err(invalid());
+                }
+                simulator.write_words(&address, &[word as u16])
+            }
+            .map_err(|err| bad(format!("{err:?}")))?;
+            Ok(("text/plain", String::new()))
+        }
+        _ => Err(Reply::NotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    async fn request(simulator: &Simulator, head: &str) -> String {
+        let (mut client, server) = duplex(64 * 1024);
+        client.write_all(head.as_bytes()).await.unwrap();
+        handle_connection(server, simulator).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_through_the_api() {
+        let simulator = Simulator::new();
+        simulator.write_words("D100", &[1, 2]).unwrap();
+
+        let response = request(
+            &simulator,
+            "GET /api/read?address=d100&count=3 HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\n{\"address\":\"D100\",\"values\":[1,2,0]}"));
+
+        let write = "POST /api/write?address=D101&value=-1 HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        assert!(request(&simulator, write)
+            .await
+            .starts_with("HTTP/1.1 200 OK"));
+        let write = "POST /api/write?address=M5&value=1 HTTP/1.1\r\n\r\n";
+        assert!(request(&simulator, write)
+            .await
+            .starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(simulator.read_words("D100", 2).unwrap(), [1, 0xFFFF]);
+        assert_eq!(simulator.read_bits("M4", 2).unwrap(), [false, true]);
+
+        let response = request(
+            &simulator,
+            "GET /api/read?address=M0&count=6 HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        assert!(response.ends_with("[0,0,0,0,0,1]}"));
+        let write = "POST /api/write?address=D0&value=70000 HTTP/1.1\r\n\r\n";
+        assert!(request(&simulator, write).await.starts_with("HTTP/1.1 400"));
+        let page = request(&simulator, "GET / HTTP/1.1\r\n\r\n").await;
+        assert!(page.contains("text/html") && page.contains("/api/read"));
+    }
+}
@@ -0,0 +1,491 @@
+//! SLMP 命名层
+//!
+//! MC 协议 3E 帧在三菱以外的设备（伺服、变频器、视觉传感器等）上以 SLMP
+//! 的名义提供。本模块以 SLMP 术语重新导出客户端类型，并实现 SLMP 特有的
+//! 节点搜索（0E30）与 IP 地址设定（0E31）命令帧，以及通过 UDP 发送这两个命令的
+//! `discover` 与 `set_ip_address`（`tcp` 特性）。
+
+use std::net::Ipv4Addr;
+#[cfg(feature = "tcp")]
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{
+    bytes::{BufMut, Bytes, BytesMut},
+    codec::encode_command,
+    frame::{EndCode, PlcProfile, ProtocolError, Request, Response},
+    Error,
+};
+
+pub type SlmpRequest<'a> = Request<'a>;
+pub type SlmpResponse = Response;
+pub type SlmpProfile = PlcProfile;
+pub type SlmpEndCode = EndCode;
+
+#[cfg(feature = "tcp")]
+pub type SlmpClient<T = tokio::net::TcpStream> =
+    crate::client::Context<crate::client::tcp::TcpClient<T>>;
+
+#[cfg(feature = "tcp")]
+pub use crate::client::tcp::{attach, connect, connect_url, connect_with_timeout};
+
+/// 节点搜索命令
+pub const NODE_SEARCH: u16 = 0x0E30;
+/// IP 地址设定命令
+pub const IP_ADDRESS_SET: u16 = 0x0E31;
+
+/// 以太网 MAC 地址
+pub type MacAddr = [u8; 6];
+
+/// 节点搜索请求，由客户端以 UDP 广播发送
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSearch {
+    pub client_mac: MacAddr,
+    pub client_ip: Ipv4Addr,
+}
+
+impl NodeSearch {
+    /// 编码为完整的请求帧
+    pub fn encode(&self) -> Bytes {
+        let mut data = BytesMut::new();
+        put_mac(&mut data, self.client_mac);
+        put_ip(&mut data, self.client_ip);
+        encode_command(NODE_SEARCH, 0x0000, &data)
+    }
+}
+
+/// 节点搜索的应答站信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub client_mac: MacAddr,
+    pub client_ip: Ipv4Addr,
+    pub mac: MacAddr,
+    pub ip: Ipv4Addr,
+    pub subnet_mask: Ipv4Addr,
+    pub default_gateway: Ipv4Addr,
+    pub hostname: String,
+    pub vendor_code: u16,
+    pub model_code: u32,
+    pub equipment_version: u16,
+    pub target_ip: Ipv4Addr,
+    pub target_port: u16,
+    pub status: u16,
+    pub port: u16,
+    pub protocol: u8,
+}
+
+impl NodeInfo {
+    /// 解析完整的节点搜索响应帧（含帧头与结束代码）
+    pub fn from_response_frame(frame: &[u8]) -> Result<Self, Error> {
+        Self::decode(response_data(frame)?)
+    }
+
+    /// 解析结束代码之后的响应数据
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader(data);
+        let info = Self {
+            client_mac: reader.mac()?,
+            client_ip: reader.sized_ip()?,
+            mac: reader.mac()?,
+            ip: reader.sized_ip()?,
+            subnet_mask: reader.ip()?,
+            default_gateway: reader.ip()?,
+            hostname: reader.hostname()?,
+            vendor_code: LittleEndian::read_u16(reader.take(2)?),
+            model_code: LittleEndian::read_u32(reader.take(4)?),
+            equipment_version: LittleEndian::read_u16(reader.take(2)?),
+            target_ip: reader.sized_ip()?,
+            target_port: LittleEndian::read_u16(reader.take(2)?),
+            status: LittleEndian::read_u16(reader.take(2)?),
+            port: LittleEndian::read_u16(reader.take(2)?),
+            protocol: reader.take(1)?[0],
+        };
+        Ok(info)
+    }
+
+    /// 编码为结束代码之后的响应数据，供模拟器应答节点搜索；主机名超过 255 字节时返回错误
+    pub fn encode(&self) -> Result<Bytes, Error> {
+        if self.hostname.len() > usize::from(u8::MAX) {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        let mut data = BytesMut::new();
+        put_mac(&mut data, self.client_mac);
+        put_ip(&mut data, self.client_ip);
+        put_mac(&mut data, self.mac);
+        put_ip(&mut data, self.ip);
+        data.put_u32_le(self.subnet_mask.into());
+        data.put_u32_le(self.default_gateway.into());
+        data.put_u8(self.hostname.len() as u8);
+        data.put_slice(self.hostname.as_bytes());
+        data.put_u16_le(self.vendor_code);
+        data.put_u32_le(self.model_code);
+        data.put_u16_le(self.equipment_version);
+        put_ip(&mut data, self.target_ip);
+        data.put_u16_le(self.target_port);
+        data.put_u16_le(self.status);
+        data.put_u16_le(self.port);
+        data.put_u8(self.protocol);
+        Ok(data.freeze())
+    }
+}
+
+/// IP 地址设定请求，按 MAC 地址指定要修改的站点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpAddressSet {
+    pub client_mac: MacAddr,
+    pub client_ip: Ipv4Addr,
+    pub mac: MacAddr,
+    pub ip: Ipv4Addr,
+    pub subnet_mask: Ipv4Addr,
+    pub default_gateway: Ipv4Addr,
+    pub hostname: String,
+    pub target_ip: Ipv4Addr,
+    pub target_port: u16,
+    pub protocol: u8,
+}
+
+impl IpAddressSet {
+    /// 编码为完整的请求帧；主机名超过 255 字节时返回错误
+    pub fn encode(&self) -> Result<Bytes, Error> {
+        if self.hostname.len() > usize::from(u8::MAX) {
+            return Err(Error::Protocol(ProtocolError::OutOfRange));
+        }
+        let mut data = BytesMut::new();
+        put_mac(&mut data, self.client_mac);
+        put_ip(&mut data, self.client_ip);
+        put_mac(&mut data, self.mac);
+        put_ip(&mut data, self.ip);
+        data.put_u32_le(self.subnet_mask.into());
+        data.put_u32_le(self.default_gateway.into());
+        data.put_u8(self.hostname.len() as u8);
+        data.put_slice(self.hostname.as_bytes());
+        put_ip(&mut data, self.target_ip);
+        data.put_u16_le(self.target_port);
+        data.put_u8(self.protocol);
+        Ok(encode_command(IP_ADDRESS_SET, 0x0000, &data))
+    }
+}
+
+/// [`discover`] 与 [`set_ip_address`] 的 UDP 参数
+#[cfg(feature = "tcp")]
+#[derive(Debug, Clone)]
+pub struct DiscoverOptions {
+    /// 命令发往的地址，通常为子网广播地址与设备的 SLMP UDP 端口，如 `192.168.3.255:5000`
+    pub target: SocketAddr,
+    /// 本地绑定地址，默认 `0.0.0.0:0`；多网卡时可指定网卡地址
+    pub bind: SocketAddr,
+    /// 等待应答的时间，默认 1 秒
+    pub timeout: Duration,
+    /// 写入请求的客户端 MAC 地址，应答中原样返回
+    pub client_mac: MacAddr,
+    /// 写入请求的客户端 IP 地址，应答中原样返回
+    pub client_ip: Ipv4Addr,
+}
+
+#[cfg(feature = "tcp")]
+impl DiscoverOptions {
+    pub fn new(target: SocketAddr) -> Self {
+        Self {
+            target,
+            bind: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            timeout: Duration::from_secs(1),
+            client_mac: [0; 6],
+            client_ip: Ipv4Addr::UNSPECIFIED,
+        }
+    }
+
+    async fn socket(&self) -> Result<tokio::net::UdpSocket, Error> {
+        let socket = tokio::net::UdpSocket::bind(self.bind).await?;
+        socket.set_broadcast(true)?;
+        Ok(socket)
+    }
+}
+
+/// 接收一个 UDP 应答，超时返回 `None`
+#[cfg(feature = "tcp")]
+async fn receive(
+    socket: &tokio::net::UdpSocket,
+    deadline: tokio::time::Instant,
+) -> Result<Option<(Vec<u8>, SocketAddr)>, Error> {
+    let mut buf = vec![0u8; 2048];
+    match tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        Ok(received) => {
+            let (len, peer) = received?;
+            buf.truncate(len);
+            Ok(Some((buf, peer)))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+/// 发送节点搜索并收集超时前应答的站点，按 MAC 地址去重
+///
+/// 无法解析的应答会被忽略。没有站点应答时返回空列表。
+#[cfg(feature = "tcp")]
+pub async fn discover(options: &DiscoverOptions) -> Result<Vec<NodeInfo>, Error> {
+    let socket = options.socket().await?;
+    let request = NodeSearch {
+        client_mac: options.client_mac,
+        client_ip: options.client_ip,
+    };
+    socket.send_to(&request.encode(), options.target).await?;
+
+    let deadline = tokio::time::Instant::now() + options.timeout;
+    let mut seen = HashSet::new();
+    let mut nodes = Vec::new();
+    while let Some((frame, peer)) = receive(&socket, deadline).await? {
+        match NodeInfo::from_response_frame(&frame) {
+            Ok(node) => {
+                if seen.insert(node.mac) {
+                    nodes.push(node);
+                }
+            }
+            Err(err) => {
+                crate::trace::debug!(peer = peer; "Ignoring node search reply: {err}");
+            }
+        }
+    }
+    Ok(nodes)
+}
+
+/// 发送 IP 地址设定，等待第一个应答
+///
+/// 目标站点以 [`IpAddressSet::mac`] 指定，因此可经广播修改不在本网段的站点。
+/// 站点以非零结束代码拒绝时返回 `EndCode` 错误，超时未应答返回 `TimedOut`。
+#[cfg(feature = "tcp")]
+pub async fn set_ip_address(
+    request: &IpAddressSet,
+    options: &DiscoverOptions,
+) -> Result<(), Error> {
+    let socket = options.socket().await?;
+    socket.send_to(&request.encode()?, options.target).await?;
+
+    let deadline = tokio::time::Instant::now() + options.timeout;
+    while let Some((frame, peer)) = receive(&socket, deadline).await? {
+        match response_data(&frame) {
+            Ok(_) => return Ok(()),
+            Err(err @ Error::Protocol(ProtocolError::EndCode(_))) => return Err(err),
+            Err(err) => {
+                crate::trace::debug!(peer = peer; "Ignoring IP address set reply: {err}");
+            }
+        }
+    }
+    Err(Error::Transport(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "no reply to IP address set",
+    )))
+}
+
+/// 校验响应帧并返回结束代码之后的数据，结束代码非零时返回 `EndCode` 错误
+pub fn response_data(frame: &[u8]) -> Result<&[u8], Error> {
+    let too_short = || ProtocolError::LengthMismatch {
+        expected: 11,
+        actual: frame.len(),
+    };
+    if frame.len() < 11 {
+        return Err(Error::Protocol(too_short()));
+    }
+    if frame[..2] != [0xD0, 0x00] {
+        return Err(Error::Transport(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid MC response prefix: {:02X?}", &frame[..2]),
+        )));
+    }
+    let len = usize::from(LittleEndian::read_u16(&frame[7..9]));
+    if frame.len() != 9 + len {
+        return Err(Error::Protocol(ProtocolError::LengthMismatch {
+            expected: 9 + len,
+            actual: frame.len(),
+        }));
+    }
+    let code = LittleEndian::read_u16(&frame[9..11]);
+    if code != 0 {
+        let end_code = EndCode::new(
+            code,
+            frame[2],
+            frame[3],
+            LittleEndian::read_u16(&frame[4..6]),
+            frame[6],
+        );
+        return Err(Error::Protocol(ProtocolError::EndCode(end_code)));
+    }
+    Ok(&frame[11..])
+}
+
+/// MAC 与 IP 地址均以低位字节在前的顺序传输
+fn put_mac(buf: &mut BytesMut, mac: MacAddr) {
+    buf.extend(mac.iter().rev());
+}
+
+fn put_ip(buf: &mut BytesMut, ip: Ipv4Addr) {
+    buf.put_u8(4);
+    buf.put_u32_le(ip.into());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < len {
+            return Err(Error::Protocol(ProtocolError::LengthMismatch {
+                expected: len,
+                actual: self.0.len(),
+            }));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn mac(&mut self) -> Result<MacAddr, Error> {
+        let mut mac: MacAddr = self.take(6)?.try_into().expect("6 bytes");
+        mac.reverse();
+        Ok(mac)
+    }
+
+    fn ip(&mut self) -> Result<Ipv4Addr, Error> {
+        Ok(LittleEndian::read_u32(self.take(4)?).into())
+    }
+
+    /// 带 1 字节长度前缀的 IP 地址，目前仅支持 IPv4
+    fn sized_ip(&mut self) -> Result<Ipv4Addr, Error> {
+        match self.take(1)?[0] {
+            4 => self.ip(),
+            size => Err(Error::Protocol(ProtocolError::InvalidAddress(format!(
+                "unsupported IP address size {size}"
+            )))),
+        }
+    }
+
+    fn hostname(&mut self) -> Result<String, Error> {
+        let len = usize::from(self.take(1)?[0]);
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| Error::Utf8Error(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_info() -> NodeInfo {
+        NodeInfo {
+            client_mac: [0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+            client_ip: Ipv4Addr::new(192, 168, 3, 100),
+            mac: [0x08, 0x00, 0x70, 0xAA, 0xBB, 0xCC],
+            ip: Ipv4Addr::new(192, 168, 3, 39),
+            subnet_mask: Ipv4Addr::new(255, 255, 255, 0),
+            default_gateway: Ipv4Addr::new(192, 168, 3, 1),
+            hostname: "FX5U".to_string(),
+            vendor_code: 0x0000,
+            model_code: 0x4A21_0000,
+            equipment_version: 0x0001,
+            target_ip: Ipv4Addr::new(192, 168, 3, 100),
+            target_port: 5000,
+            status: 0x0000,
+            port: 5000,
+            protocol: 0x01,
+        }
+    }
+
+    #[test]
+    fn node_search_request_frame() {
+        let frame = NodeSearch {
+            client_mac: [0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+            client_ip: Ipv4Addr::new(192, 168, 3, 100),
+        }
+        .encode();
+
+        assert_eq!(
+            frame.to_vec(),
+            [
+                0x50, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00, 0x11, 0x00, 0x10, 0x00, 0x30, 0x0E, 0x00,
+                0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x04, 0x64, 0x03, 0xA8, 0xC0,
+            ]
+        );
+    }
+
+    #[test]
+    fn node_info_round_trip() {
+        let info = node_info();
+        let data = info.encode().unwrap();
+
+        let mut frame = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+        frame.extend_from_slice(&((data.len() + 2) as u16).to_le_bytes());
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.extend_from_slice(&data);
+        assert_eq!(NodeInfo::from_response_frame(&frame).unwrap(), info);
+
+        // 截断的数据与错误结束代码
+        assert!(NodeInfo::decode(&data[..data.len() - 1]).is_err());
+        frame[9] = 0x59;
+        frame[10] = 0xC0;
+        assert!(matches!(
+            NodeInfo::from_response_frame(&frame),
+            Err(Error::Protocol(ProtocolError::EndCode(_)))
+        ));
+        // 请求帧的副帧头不是应答
+        frame[0] = 0x50;
+        assert!(matches!(
+            response_data(&frame),
+            Err(Error::Transport(err)) if err.kind() == std::io::ErrorKind::InvalidData
+        ));
+
+        let long = NodeInfo {
+            hostname: "x".repeat(256),
+            ..info
+        };
+        assert!(long.encode().is_err());
+    }
+
+    #[cfg(feature = "tcp")]
+    #[tokio::test]
+    async fn discover_collects_replies() {
+        let device = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut options = DiscoverOptions::new(device.local_addr().unwrap());
+        options.timeout = Duration::from_millis(200);
+
+        let responder = tokio::spawn(async move {
+            let mut buf = [0u8; 256];
+            let (len, client) = device.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[11..13], [0x30, 0x0E]);
+            assert!(len > 15);
+            let data = node_info().encode().unwrap();
+            let mut frame = vec![0xD0, 0x00, 0x00, 0xFF, 0xFF, 0x03, 0x00];
+            frame.extend_from_slice(&((data.len() + 2) as u16).to_le_bytes());
+            frame.extend_from_slice(&[0x00, 0x00]);
+            frame.extend_from_slice(&data);
+            // 重复的应答与无效数据均被忽略
+            device.send_to(&frame, client).await.unwrap();
+            device.send_to(&frame, client).await.unwrap();
+            device.send_to(b"garbage", client).await.unwrap();
+        });
+
+        let nodes = discover(&options).await.unwrap();
+        responder.await.unwrap();
+        assert_eq!(nodes, [node_info()]);
+    }
+
+    #[test]
+    fn ip_address_set_rejects_long_hostname() {
+        let info = node_info();
+        let mut request = IpAddressSet {
+            client_mac: info.client_mac,
+            client_ip: info.client_ip,
+            mac: info.mac,
+            ip: Ipv4Addr::new(192, 168, 3, 40),
+            subnet_mask: info.subnet_mask,
+            default_gateway: info.default_gateway,
+            hostname: info.hostname,
+            target_ip: info.target_ip,
+            target_port: info.target_port,
+            protocol: info.protocol,
+        };
+        let frame = request.encode().unwrap();
+        assert_eq!(&frame[11..15], [0x31, 0x0E, 0x00, 0x00]);
+
+        request.hostname = "x".repeat(256);
+        assert!(request.encode().is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! 长时间运行的流量统计
+//!
+//! 网关上线前通常要经过 7×24 小时的浸泡测试，需要确认帧数持续增长而缓冲区与内存不随时间增长。
+//! [`Traffic`] 以原子计数器记录收发的帧数与字节数、连接与重连次数以及出现过的最长帧与最大读缓冲区，
+//! 客户端由 `TcpClient::traffic` 取得，
+//! 服务端由 `Server::traffic` 取得，可在运行中随时读取快照：
+//!
+//! ```text
+//! let traffic = server.traffic();
+//! tokio::spawn(server.serve_until(&on_connected, on_error, shutdown));
+//! loop {
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!     log::info!("{:?}", traffic.snapshot());
+//! }
+//! ```
+//!
+//! 启用 `alloc-stats` 特性后，可将 `CountingAllocator` 设为全局分配器，
+//! 快照中同时包含整个进程的分配次数与占用字节数。
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// 流量统计的快照，见[模块文档](self)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    /// 发出的帧数
+    pub frames_sent: u64,
+    /// 收到的完整帧数
+    pub frames_received: u64,
+    /// 发出的字节数
+    pub bytes_sent: u64,
+    /// 收到的完整帧的字节数
+    pub bytes_received: u64,
+    /// 建立的连接数，服务端为接受的连接数
+    pub connections: u64,
+    /// 重连次数：客户端为替换连接的次数，服务端为来自已连接过的对端地址的连接数
+    pub reconnects: u64,
+    /// 出现过的最长帧的字节数
+    pub max_frame_len: usize,
+    /// 读缓冲区出现过的最大容量
+    pub max_buffer_capacity: usize,
+    /// 整个进程的内存分配统计
+    #[cfg(feature = "alloc-stats")]
+    pub allocations: AllocationStats,
+}
+
+/// 可在多个连接间共享的流量计数器
+#[derive(Debug, Default)]
+pub struct Traffic {
+    frames_sent: AtomicU64,
+    frames_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    connections: AtomicU64,
+    reconnects: AtomicU64,
+    max_frame_len: AtomicUsize,
+    max_buffer_capacity: AtomicUsize,
+}
+
+impl Traffic {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前计数的快照
+    pub fn snapshot(&self) -> TrafficStats {
+        TrafficStats {
+            frames_sent: self.frames_sent.load(Ordering::Relaxed),
+            frames_received: self.frames_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            connections: self.connections.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            max_frame_len: self.max_frame_len.load(Ordering::Relaxed),
+            max_buffer_capacity: self.max_buffer_capacity.load(Ordering::Relaxed),
+            #[cfg(feature = "alloc-stats")]
+            allocations: allocations(),
+        }
+    }
+
+    /// 清零全部计数，分配统计不受影响
+    pub fn reset(&self) {
+        for counter in [
+            &self.frames_sent,
+            &self.frames_received,
+            &self.bytes_sent,
+            &self.bytes_received,
+            &self.connections,
+            &self.reconnects,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.max_frame_len.store(0, Ordering::Relaxed);
+        self.max_buffer_capacity.store(0, Ordering::Relaxed);
+    }
+
+    #[cfg_attr(not(any(feature = "tcp", feature = "server")), allow(dead_code))]
+    pub(crate) fn record_sent(&self, len: usize) {
+        self.frames_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+        self.max_frame_len.fetch_max(len, Ordering::Relaxed);
+    }
+
+    /// 记录收到的一帧及解码时读缓冲区的容量
+    pub(crate) fn record_received(&self, len: usize, buffer_capacity: usize) {
+        self.frames_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+        self.max_frame_len.fetch_max(len, Ordering::Relaxed);
+        self.max_buffer_capacity
+            .fetch_max(buffer_capacity, Ordering::Relaxed);
+    }
+
+    #[cfg_attr(not(any(feature = "tcp", feature = "server")), allow(dead_code))]
+    pub(crate) fn record_connection(&self, reconnect: bool) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        if reconnect {
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+pub use self::alloc::{allocations, AllocationStats, CountingAllocator};
+
+#[cfg(feature = "alloc-stats")]
+mod alloc {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    };
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    /// 进程的内存分配统计（需启用 `alloc-stats` 特性并安装 [`CountingAllocator`]）
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct AllocationStats {
+        /// 分配次数，重新分配计为一次
+        pub allocations: u64,
+        /// 释放次数
+        pub deallocations: u64,
+        /// 当前占用的字节数
+        pub live_bytes: usize,
+        /// 占用字节数的峰值
+        pub peak_bytes: usize,
+    }
+
+    /// 当前的分配统计，未安装 [`CountingAllocator`] 时全部为 0
+    pub fn allocations() -> AllocationStats {
+        AllocationStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 统计分配次数与占用字节数的全局分配器包装
+    ///
+    /// ```text
+    /// #[global_allocator]
+    /// static ALLOCATOR: CountingAllocator = CountingAllocator::new(System);
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct CountingAllocator<A = System>(A);
+
+    impl<A> CountingAllocator<A> {
+        pub const fn new(inner: A) -> Self {
+            Self(inner)
+        }
+    }
+
+    fn grow(size: usize) {
+        let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+    }
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.0.alloc(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.0.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.0.dealloc(ptr, layout);
+            DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = self.0.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+                grow(new_size);
+            }
+            new_ptr
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_frames_and_peaks() {
+        let traffic = Traffic::new();
+        traffic.record_connection(false);
+        traffic.record_sent(21);
+        traffic.record_received(17, 8192);
+        traffic.record_sent(30);
+        traffic.record_connection(true);
+
+        let stats = traffic.snapshot();
+        assert_eq!(
+            (stats.frames_sent, stats.bytes_sent, stats.frames_received),
+            (2, 51, 1)
+        );
+        assert_eq!((stats.connections, stats.reconnects), (2, 1));
+        assert_eq!((stats.max_frame_len, stats.max_buffer_capacity), (30, 8192));
+
+        traffic.reset();
+        assert_eq!(traffic.snapshot().frames_sent, 0);
+        assert_eq!(traffic.snapshot().max_frame_len, 0);
+    }
+}
@@ -0,0 +1,373 @@
+//! 供下游测试使用的辅助工具（需启用 `test-util` feature）
+
+use std::{fmt, io};
+
+use proptest::{arbitrary::Arbitrary, collection::vec, prelude::*, strategy::BoxedStrategy};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    bytes::Bytes,
+    codec::{hexdump::HexDump, ClientEncoder},
+    frame::{NumberBase, Request, Response, LIMIT, PLC_INSTRUCTIONS},
+    Error,
+};
+
+/// 创建时间处于暂停状态的单线程运行时
+///
+/// 传给同步 `Context` 后，超时等待由 tokio 自动推进，测试无需真实等待。
+pub fn paused_runtime() -> io::Result<Runtime> {
+    Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+}
+
+/// 合法的软元件地址，如 `D100`、`X1F`
+///
+/// 编号按软元件的进制书写（十六进制为大写、无前导零），与服务端解码结果一致。
+pub fn device_address() -> impl Strategy<Value = String> {
+    (prop::sample::select(PLC_INSTRUCTIONS), 0u32..=0xFFFF).prop_map(
+        |((prefix, _, number_base), number)| match number_base {
+            NumberBase::Decimal => format!("{prefix}{number}"),
+            NumberBase::Hexadecimal => format!("{prefix}{number:X}"),
+        },
+    )
+}
+
+/// 可编码为单个请求帧的 `Request`
+pub fn request() -> impl Strategy<Value = Request<'static>> {
+    let limit = LIMIT as usize;
+    prop_oneof![
+        (device_address(), 1..=LIMIT)
+            .prop_map(|(address, quantity)| Request::ReadU8s(address.into(), quantity)),
+        (device_address(), vec(any::<[u8; 2]>(), 1..=limit)).prop_map(|(address, words)| {
+            Request::WriteU8s(address.into(), words.concat().into())
+        }),
+        (device_address(), 1..=LIMIT)
+            .prop_map(|(address, quantity)| Request::ReadBits(address.into(), quantity)),
+        (device_address(), vec(any::<bool>(), 1..=limit))
+            .prop_map(|(address, bits)| Request::WriteBits(address.into(), bits.into())),
+    ]
+}
+
+/// 任意 `Response`
+pub fn response() -> impl Strategy<Value = Response> {
+    let limit = LIMIT as usize;
+    prop_oneof![
+        vec(any::<[u8; 2]>(), 1..=limit).prop_map(|words| Response::ReadU8s(words.concat())),
+        Just(Response::WriteU8s()),
+        vec(any::<bool>(), 1..=limit).prop_map(Response::ReadBits),
+        Just(Response::WriteBits()),
+        (any::<u16>(), any::<u16>(), vec(any::<u8>(), 0..=limit))
+            .prop_map(|(command, subcommand, data)| Response::Command(command, subcommand, data)),
+    ]
+}
+
+/// 请求及其编码后的完整请求帧（含帧头）
+pub fn request_frame() -> impl Strategy<Value = (Request<'static>, Bytes)> {
+    request().prop_map(|request| {
+        let mut frames = ClientEncoder::encode(request.clone()).expect("valid request");
+        debug_assert_eq!(frames.len(), 1);
+        (request, frames.remove(0))
+    })
+}
+
+impl Arbitrary for Request<'static> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        request().boxed()
+    }
+}
+
+impl Arbitrary for Response {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        response().boxed()
+    }
+}
+
+/// 随本库附带的参考帧夹具，格式见 [`parse_fixtures`]
+pub const REFERENCE_FIXTURES: &str = include_str!("test_util/reference_3e.txt");
+
+/// 参考帧夹具中的一条用例
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    /// 用例名，即操作行原文
+    pub name: String,
+    /// 操作对应的请求
+    pub request: Request<'static>,
+    /// 参考实现产生的帧，`None` 为通配字节
+    pub expected: Vec<Option<u8>>,
+}
+
+/// 解析参考帧夹具
+///
+/// 用例之间以空行分隔，`#` 开头的行为注释。每个用例的第一行为操作，其余行为参考实现
+/// （GX Works、pymcprotocol 等）发出的帧，以空白分隔的十六进制字节书写，`??` 表示不比较的字节，
+/// 如各实现取值不同的监视定时器。支持的操作：
+///
+/// ```text
+/// read_words D100 3
+/// read_bits M100 8
+/// write_words D100 0x1995 0x1202 4400
+/// write_bits M100 1 1 0 0
+/// command 0101 0000 [数据字节...]
+/// ```
+pub fn parse_fixtures(text: &str) -> Result<Vec<Fixture>, String> {
+    let mut fixtures = Vec::new();
+    let mut current: Option<Fixture> = None;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            fixtures.extend(current.take());
+            continue;
+        }
+        let error = |err: String| format!("line {}: {err}", index + 1);
+        match &mut current {
+            None => {
+                current = Some(Fixture {
+                    name: line.to_string(),
+                    request: parse_operation(line).map_err(error)?,
+                    expected: Vec::new(),
+                });
+            }
+            Some(fixture) => {
+                for byte in line.split_whitespace() {
+                    fixture.expected.push(match byte {
+                        "??" => None,
+                        _ => Some(
+                            u8::from_str_radix(byte, 16)
+                                .map_err(|_| error(format!("invalid byte {byte:?}")))?,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    fixtures.extend(current);
+
+    match fixtures.iter().find(|fixture| fixture.expected.is_empty()) {
+        Some(fixture) => Err(format!("{}: missing frame", fixture.name)),
+        None => Ok(fixtures),
+    }
+}
+
+fn parse_operation(line: &str) -> Result<Request<'static>, String> {
+    let mut words = line.split_whitespace();
+    let operation = words.next().unwrap_or_default();
+    let mut address = || {
+        words
+            .next()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{operation}: missing address"))
+    };
+    let request = match operation {
+        "read_words" | "read_bits" => {
+            let address = address()?;
+            let count = words
+                .next()
+                .and_then(|count| count.parse().ok())
+                .ok_or_else(|| format!("{operation}: invalid count"))?;
+            if operation == "read_words" {
+                Request::ReadU8s(address.into(), count)
+            } else {
+                Request::ReadBits(address.into(), count)
+            }
+        }
+        "write_words" => {
+            let address = address()?;
+            let mut u8s = Vec::new();
+            for word in words.by_ref() {
+                u8s.extend(parse_u16(word)?.to_le_bytes());
+            }
+            Request::WriteU8s(address.into(), u8s.into())
+        }
+        "write_bits" => {
+            let address = address()?;
+            let bits = words
+                .by_ref()
+                .map(|bit| match bit {
+                    "0" => Ok(false),
+                    "1" => Ok(true),
+                    _ => Err(format!("invalid bit {bit:?}")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Request::WriteBits(address.into(), bits.into())
+        }
+        "command" => {
+            let mut code = || {
+                let word = words.next().unwrap_or_default();
+                u16::from_str_radix(word, 16).map_err(|_| format!("invalid command {word:?}"))
+            };
+            let (command, subcommand) = (code()?, code()?);
+            let data = words
+                .by_ref()
+                .map(|byte| {
+                    u8::from_str_radix(byte, 16).map_err(|_| format!("invalid byte {byte:?}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Request::Command(command, subcommand, data.into())
+        }
+        _ => return Err(format!("unknown operation {operation:?}")),
+    };
+    match words.next() {
+        Some(extra) => Err(format!("unexpected {extra:?}")),
+        None => Ok(request),
+    }
+}
+
+fn parse_u16(word: &str) -> Result<u16, String> {
+    match word.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => word.parse(),
+    }
+    .map_err(|_| format!("invalid word {word:?}"))
+}
+
+/// 本库编码结果与参考帧的差异
+#[derive(Debug)]
+pub struct FrameDiff {
+    /// 用例名
+    pub name: String,
+    /// 参考帧
+    pub expected: Vec<Option<u8>>,
+    /// 本库编码的帧（请求拆分为多帧时首尾相接），或编码错误
+    pub actual: Result<Vec<u8>, Error>,
+}
+
+impl FrameDiff {
+    /// 不一致的字节偏移；长度不同时，较短一侧缺少的偏移也计入
+    pub fn offsets(&self) -> Vec<usize> {
+        let actual = self.actual.as_deref().unwrap_or_default();
+        (0..self.expected.len().max(actual.len()))
+            .filter(|&i| match (self.expected.get(i), actual.get(i)) {
+                (Some(None), Some(_)) => false,
+                (Some(Some(expected)), Some(actual)) => expected != actual,
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+/// 列出不一致的偏移、参考帧，以及本库编码帧的带注释转储
+impl fmt::Display for FrameDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<String> = self
+            .expected
+            .iter()
+            .map(|byte| byte.map_or("??".to_string(), |byte| format!("{byte:02X}")))
+            .collect();
+        match &self.actual {
+            Ok(actual) => {
+                let offsets: Vec<String> = self
+                    .offsets()
+                    .iter()
+                    .map(|offset| format!("{offset:04X}"))
+                    .collect();
+                writeln!(f, "{}: frame differs at {}", self.name, offsets.join(", "))?;
+                writeln!(f, "expected: {}", expected.join(" "))?;
+                write!(f, "actual:\n{}", HexDump(actual))
+            }
+            Err(err) => {
+                writeln!(f, "{}: encoding failed: {err}", self.name)?;
+                writeln!(f, "expected: {}", expected.join(" "))
+            }
+        }
+    }
+}
+
+/// 以本库编码用例的请求并与参考帧比较
+pub fn compare_fixture(fixture: &Fixture) -> Result<(), FrameDiff> {
+    let actual = ClientEncoder::encode(fixture.request.clone()).map(|frames| frames.concat());
+    let diff = FrameDiff {
+        name: fixture.name.clone(),
+        expected: fixture.expected.clone(),
+        actual,
+    };
+    match &diff.actual {
+        Ok(_) if diff.offsets().is_empty() => Ok(()),
+        _ => Err(diff),
+    }
+}
+
+/// 解析夹具并逐条比较，返回所有存在差异的用例
+pub fn compare_fixtures(text: &str) -> Result<Vec<FrameDiff>, String> {
+    Ok(parse_fixtures(text)?
+        .iter()
+        .filter_map(|fixture| compare_fixture(fixture).err())
+        .collect())
+}
+
+/// 断言夹具中的所有用例与本库编码一致，否则列出全部差异后 panic
+#[track_caller]
+pub fn assert_fixtures(text: &str) {
+    let diffs = compare_fixtures(text).unwrap_or_else(|err| panic!("invalid fixtures: {err}"));
+    if !diffs.is_empty() {
+        let report: Vec<String> = diffs.iter().map(ToString::to_string).collect();
+        panic!(
+            "{} fixture(s) differ from the reference frames\n\n{}",
+            diffs.len(),
+            report.join("\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{bools_to_bytes, ClientDecoder};
+
+    #[test]
+    fn matches_reference_fixtures() {
+        assert_fixtures(REFERENCE_FIXTURES);
+    }
+
+    #[test]
+    fn reports_frame_differences() {
+        let text =
+            "read_words D100 3\n50 00 00 FF FF 03 00 0C 00 ?? ?? 01 04 00 00 65 00 00 A8 03\n";
+        let diffs = compare_fixtures(text).unwrap();
+        assert_eq!(diffs.len(), 1);
+        // 设备编号与缺少的最后一个字节
+        assert_eq!(diffs[0].offsets(), [15, 20]);
+        let report = diffs[0].to_string();
+        assert!(report.starts_with("read_words D100 3: frame differs at 000F, 0014\n"));
+        assert!(report.contains("device D100"));
+
+        assert!(parse_fixtures("read_words D100\n50 00").is_err());
+        assert!(parse_fixtures("# no frame\nwrite_bits M0 1\n").is_err());
+    }
+
+    proptest! {
+        #[cfg(feature = "server")]
+        #[test]
+        fn request_frames_round_trip((request, frame) in request_frame()) {
+            // 服务端解码器从帧头的请求数据长度字段开始解析
+            let decoded = crate::codec::ServerDecoder::decode(frame.slice(7..)).unwrap();
+            prop_assert_eq!(decoded, request);
+        }
+
+        #[test]
+        fn responses_round_trip(response in any::<Response>()) {
+            // 客户端解码器的输入以 2 字节结束代码开头
+            let (request, data) = match &response {
+                Response::ReadU8s(u8s) => (Request::ReadU8s("D0".into(), response.len() as u32), u8s.clone()),
+                Response::WriteU8s() => (Request::WriteU8s("D0".into(), vec![0; 2].into()), vec![]),
+                Response::ReadBits(bits) => (Request::ReadBits("M0".into(), bits.len() as u32), bools_to_bytes(bits)),
+                Response::WriteBits() => (Request::WriteBits("M0".into(), vec![true].into()), vec![]),
+                Response::Command(command, subcommand, data) => (Request::Command(*command, *subcommand, vec![].into()), data.clone()),
+            };
+            let payload = Bytes::from([&[0x00, 0x00][..], &data].concat());
+
+            let decoded = ClientDecoder::decode(vec![payload], request).unwrap();
+            prop_assert_eq!(decoded, response);
+        }
+    }
+}
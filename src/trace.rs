@@ -0,0 +1,63 @@
+//! 内部日志门面
+//!
+//! 启用 `tracing` feature 时，事件通过 `tracing` 发出，并携带结构化字段
+//! （peer、功能码、地址、字节等）；否则回退到 `log`，字段以 `key=value`
+//! 的形式追加在消息末尾。
+
+use std::fmt;
+
+/// 以十六进制输出字节序列，用于日志字段
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02X?}", self.0)
+    }
+}
+
+/// `log` 后端下的结构化字段
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Fields<'a>(pub(crate) &'a [(&'static str, &'a dyn fmt::Debug)]);
+
+#[cfg(not(feature = "tracing"))]
+impl fmt::Display for Fields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.0 {
+            write!(f, " {key}={value:?}")?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! event {
+    ($level:ident, $($key:ident = $value:expr),+ ; $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::$level!($($key = ?$value,)+ $($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        ::log::$level!(
+            "{}{}",
+            format_args!($($arg)+),
+            $crate::trace::Fields(&[$((stringify!($key), &$value as &dyn ::std::fmt::Debug)),+])
+        );
+    }};
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::$level!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        ::log::$level!($($arg)+);
+    }};
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::trace::event!(debug, $($arg)+)
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)+) => {
+        $crate::trace::event!(warn, $($arg)+)
+    };
+}
+
+pub(crate) use {debug, event, warning};